@@ -0,0 +1,184 @@
+use chrono::{Local, TimeZone, Timelike};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::font::Font;
+use crate::stats::Stats;
+use crate::ui::{self, View, ViewAction};
+use crate::GamepadAction;
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    unlocked: fn(&Stats, &[String]) -> bool,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_session",
+        name: "First steps",
+        description: "Play any game",
+        unlocked: |stats, _| !stats.sessions.is_empty(),
+    },
+    Achievement {
+        id: "every_game",
+        name: "Played every game",
+        description: "Play every game in the library at least once",
+        unlocked: |stats, library| {
+            let played: HashSet<&str> = stats.sessions.iter().map(|s| s.rom.as_str()).collect();
+            !library.is_empty() && library.iter().all(|rom| played.contains(rom.as_str()))
+        },
+    },
+    Achievement {
+        id: "smw_10h",
+        name: "10 hours in SMW",
+        description: "Spend 10 hours in Super Mario World",
+        unlocked: |stats, _| {
+            stats.sessions.iter().filter(|s| s.rom == "smw.sfc").map(|s| s.secs).sum::<u64>() >= 10 * 3600
+        },
+    },
+    Achievement {
+        id: "night_owl",
+        name: "Night owl",
+        description: "Start a session between midnight and 4 am",
+        unlocked: |stats, _| {
+            stats.sessions.iter().any(|s| {
+                Local.timestamp_opt(s.start, 0).single().map(|t| t.hour() < 4).unwrap_or(false)
+            })
+        },
+    },
+    Achievement {
+        id: "marathon",
+        name: "Marathon",
+        description: "Play for 3 hours in one session",
+        unlocked: |stats, _| stats.sessions.iter().any(|s| s.secs >= 3 * 3600),
+    },
+    Achievement {
+        id: "regular",
+        name: "Regular",
+        description: "Play on 7 different days",
+        unlocked: |stats, _| {
+            let days: HashSet<_> = stats
+                .sessions
+                .iter()
+                .filter_map(|s| Local.timestamp_opt(s.start, 0).single().map(|t| t.date_naive()))
+                .collect();
+            days.len() >= 7
+        },
+    },
+];
+
+/// Unlock times by achievement id, stored as `launcher/achievements.json` next to stats.json.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Unlocked {
+    pub unlocked: HashMap<String, i64>,
+}
+
+impl Unlocked {
+    pub fn load(launcher_dir: &Path) -> Self {
+        fs::read_to_string(launcher_dir.join("achievements.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, launcher_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(launcher_dir.join("achievements.json"), content).map_err(|e| e.to_string())
+    }
+
+    /// Unlocks everything the history now qualifies for and returns what is new.
+    pub fn check(&mut self, stats: &Stats, library: &[String]) -> Vec<&'static Achievement> {
+        let now = Local::now().timestamp();
+        let mut new = Vec::new();
+        for achievement in ACHIEVEMENTS {
+            if !self.unlocked.contains_key(achievement.id) && (achievement.unlocked)(stats, library) {
+                self.unlocked.insert(achievement.id.to_string(), now);
+                new.push(achievement);
+            }
+        }
+        new
+    }
+}
+
+/// Trophy screen listing every achievement, unlocked ones with their date.
+pub struct TrophyScreen {
+    rows: Vec<(String, String, bool)>, // name line, description, unlocked
+    selected: usize,
+}
+
+impl TrophyScreen {
+    pub fn new(unlocked: &Unlocked) -> Self {
+        let rows = ACHIEVEMENTS
+            .iter()
+            .map(|a| match unlocked.unlocked.get(a.id) {
+                Some(time) => {
+                    let date = Local
+                        .timestamp_opt(*time, 0)
+                        .single()
+                        .map(|t| t.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default();
+                    (format!("{} ({})", a.name, date), a.description.to_string(), true)
+                }
+                None => (format!("{} (locked)", a.name), a.description.to_string(), false),
+            })
+            .collect();
+        TrophyScreen { rows, selected: 0 }
+    }
+}
+
+impl View for TrophyScreen {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.selected = self.selected.saturating_sub(1),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
+                self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.selected = self.selected.saturating_sub(1),
+            GamepadAction::Down => self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1)),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        let count = self.rows.iter().filter(|(_, _, unlocked)| *unlocked).count();
+        let title = format!("Trophies {}/{}", count, self.rows.len());
+        ui::draw_screen_frame(canvas, texture_creator, font, &title)?;
+
+        let names: Vec<String> = self.rows.iter().map(|(name, _, _)| name.clone()).collect();
+        ui::draw_list(canvas, texture_creator, font, &names, self.selected, 80, 14)?;
+
+        if let Some(f) = font {
+            if let Some((_, description, unlocked)) = self.rows.get(self.selected) {
+                let color = if *unlocked { ui::accent_color() } else { Color::RGB(160, 160, 160) };
+                ui::draw_text(canvas, texture_creator, f, description, 30, 580, color)?;
+            }
+            ui::draw_text(canvas, texture_creator, f, "Esc: back", 30, 620, ui::text_color())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Shared timing for the launcher's animations: card color fades and hover zoom, the launch and
+//! return fades, the blurred backdrop crossfade, the quick settings slide and card flips all run
+//! at the speed and with the easing picked in the launcher options.
+
+use sdl2::rect::Rect;
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
+pub const EASE_LINEAR: u8 = 0;
+pub const EASE_OUT: u8 = 1;
+pub const EASE_SPRING: u8 = 2;
+pub const EASINGS: [&str; 3] = ["Linear", "Ease-out", "Spring"];
+/// Speeds the options menu cycles through, 1.0 being the launcher's own timing.
+pub const SPEEDS: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Motion {
+    pub speed: f32, // multiplies every animation's speed
+    pub easing: u8,
+}
+
+impl Default for Motion {
+    fn default() -> Self {
+        Motion { speed: 1.0, easing: EASE_OUT }
+    }
+}
+
+impl Motion {
+    pub fn new(speed: f32, easing: u8) -> Self {
+        Motion { speed: speed.max(0.1), easing }
+    }
+
+    /// A per-frame step (share of an animation covered each frame) at this speed.
+    pub fn step(&self, per_frame: f32) -> f32 {
+        per_frame * self.speed
+    }
+
+    /// How long an animation that normally takes `base` runs at this speed.
+    pub fn duration(&self, base: Duration) -> Duration {
+        base.div_f32(self.speed)
+    }
+
+    /// Linear progress from 0 to 1 of an animation started at `since`, normally `base` long.
+    pub fn progress(&self, since: Instant, base: Duration) -> f32 {
+        (since.elapsed().as_secs_f32() / self.duration(base).as_secs_f32().max(f32::EPSILON)).min(1.0)
+    }
+
+    /// Shapes linear progress `t`. Spring overshoots 1 on the way, so callers using the
+    /// result as an alpha clamp it.
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self.easing {
+            EASE_LINEAR => t,
+            EASE_SPRING if t < 1.0 => 1.0 - (t * PI * 2.5).cos() * (-6.0 * t).exp(),
+            EASE_SPRING => 1.0,
+            _ => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+
+    /// `ease` clamped to 0..1, for fades.
+    pub fn fade(&self, t: f32) -> f32 {
+        self.ease(t).clamp(0.0, 1.0)
+    }
+}
+
+/// `rect` scaled by `scale` around its center.
+pub fn grow(rect: Rect, scale: f32) -> Rect {
+    let width = (rect.width() as f32 * scale).max(1.0) as u32;
+    let height = (rect.height() as f32 * scale).max(1.0) as u32;
+    Rect::from_center(rect.center(), width, height)
+}
@@ -0,0 +1,312 @@
+//! Whole-install backups: launcher/, smw.ini and the game's saves zipped into one archive. The
+//! saves are stored as `game/saves` wherever they live, so archives restore into any saves folder.
+
+use chrono::Local;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use zip::write::FileOptions;
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::{streamer, GamepadAction, SCREEN_WIDTH};
+
+const AUTO_PREFIX: &str = "auto_";
+const AUTO_INTERVAL: Duration = Duration::from_secs(7 * 24 * 3600);
+const AUTO_KEEP: usize = 4;
+
+/// Where the data lives; archive entries are stored under `launcher/` and `game/`.
+#[derive(Debug, Clone)]
+pub struct DataDirs {
+    pub launcher_dir: PathBuf,
+    pub game_dir: PathBuf,
+    pub saves_dir: PathBuf,
+}
+
+/// Archives in `dir`, newest first.
+pub fn list(dir: &Path) -> Vec<PathBuf> {
+    let mut archives: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "zip").unwrap_or(false))
+        .map(|path| (fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+        .collect();
+    archives.sort_by(|a, b| b.0.cmp(&a.0));
+    archives.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Zips everything into `dir/<prefix>smas_<timestamp>.zip`. `progress` gets each entry's name
+/// and the fraction done as it goes.
+pub fn create(dir: &Path, data: &DataDirs, prefix: &str, progress: &mut dyn FnMut(&str, f32)) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}smas_{}.zip", prefix, Local::now().format("%Y%m%d-%H%M%S")));
+
+    let mut files = Vec::new();
+    collect_dir(&data.launcher_dir, "launcher", &mut files)?;
+    files.push((data.game_dir.join("smw.ini"), "game/smw.ini".to_string()));
+    collect_dir(&data.saves_dir, "game/saves", &mut files)?;
+
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    for (idx, (file_path, name)) in files.iter().enumerate() {
+        progress(name, idx as f32 / files.len() as f32);
+        add_file(&mut zip, file_path, name)?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// The files under `dir` with their entry names, for `create`.
+fn collect_dir(dir: &Path, name: &str, files: &mut Vec<(PathBuf, String)>) -> Result<(), String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(()),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let entry_name = format!("{}/{}", name, entry.file_name().to_string_lossy());
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+        // Sockets and other special files (control.sock) are skipped, and so is the download
+        // cache, which can be fetched again; saves kept in launcher/ are added as game/saves
+        if entry_name == "launcher/cache" || entry_name == "launcher/saves" {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_dir(&path, &entry_name, files)?;
+        } else if file_type.is_file() {
+            files.push((path, entry_name));
+        }
+    }
+    Ok(())
+}
+
+fn add_file(zip: &mut zip::ZipWriter<File>, path: &Path, name: &str) -> Result<(), String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Ok(()),
+    };
+    zip.start_file(name, FileOptions::default()).map_err(|e| e.to_string())?;
+    io::copy(&mut file, zip).map_err(|e| format!("{}: {}", name, e))?;
+    Ok(())
+}
+
+/// Extracts an archive over the current data and returns how many files were written.
+/// The current state is archived first so a restore can itself be undone; `progress` is
+/// called as for `create`, for both steps.
+pub fn restore(dir: &Path, archive: &Path, data: &DataDirs, progress: &mut dyn FnMut(&str, f32)) -> Result<usize, String> {
+    create(dir, data, "before_restore_", &mut |name, fraction| progress(name, fraction / 2.0))?;
+
+    let file = File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a backup archive: {}", e))?;
+    let mut restored = 0;
+    let count = zip.len();
+    for idx in 0..count {
+        let mut entry = zip.by_index(idx).map_err(|e| e.to_string())?;
+        progress(entry.name(), 0.5 + idx as f32 / count as f32 / 2.0);
+        // enclosed_name rejects entries that would escape the data folders
+        let name = match entry.enclosed_name() {
+            Some(name) if !entry.is_dir() => name.to_path_buf(),
+            _ => continue,
+        };
+        let target = if let Ok(relative) = name.strip_prefix("launcher") {
+            data.launcher_dir.join(relative)
+        } else if let Ok(relative) = name.strip_prefix("game/saves") {
+            data.saves_dir.join(relative)
+        } else if let Ok(relative) = name.strip_prefix("game") {
+            data.game_dir.join(relative)
+        } else {
+            continue;
+        };
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = File::create(&target).map_err(|e| e.to_string())?;
+        io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+/// Makes the weekly automatic backup when the last one is older than a week, keeping the newest few.
+/// Returns the new archive, or None when no backup was due.
+pub fn auto_backup(dir: &Path, data: &DataDirs) -> Result<Option<PathBuf>, String> {
+    let autos = auto_archives(dir);
+    let due = match autos.first().and_then(|p| fs::metadata(p).and_then(|m| m.modified()).ok()) {
+        Some(modified) => modified.elapsed().map(|age| age >= AUTO_INTERVAL).unwrap_or(false),
+        None => true,
+    };
+    if !due {
+        return Ok(None);
+    }
+
+    let path = create(dir, data, AUTO_PREFIX, &mut |_, _| {})?;
+    for old in autos.iter().skip(AUTO_KEEP - 1) {
+        if let Err(e) = fs::remove_file(old) {
+            eprintln!("Failed to remove old backup {}: {}", streamer::path(old), e);
+        }
+    }
+    Ok(Some(path))
+}
+
+/// The weekly automatic archives in `dir`, newest first.
+pub fn auto_archives(dir: &Path) -> Vec<PathBuf> {
+    list(dir).into_iter().filter(|p| is_auto(p)).collect()
+}
+
+/// One file from an archive, by its entry name (e.g. `game/saves/smw.srm`); None when it isn't in there.
+pub fn read_entry(archive: &Path, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let file = File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a backup archive: {}", e))?;
+    let mut entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut data = Vec::new();
+    entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    Ok(Some(data))
+}
+
+fn is_auto(path: &Path) -> bool {
+    path.file_name().map(|n| n.to_string_lossy().starts_with(AUTO_PREFIX)).unwrap_or(false)
+}
+
+enum TaskMsg {
+    Progress(String, f32),
+    Done(Result<String, String>), // what to tell the player either way
+}
+
+/// Runs a backup or restore on a worker thread and shows how far it got. Stays open until the
+/// work is done, a restore left halfway would mix two states.
+pub struct ArchiveTask {
+    title: &'static str,
+    status: String,
+    progress: f32,
+    rx: Option<Receiver<TaskMsg>>,
+    restoring: bool,
+}
+
+impl ArchiveTask {
+    pub fn backup(dir: PathBuf, data: DataDirs) -> Self {
+        Self::start("Backup everything", false, move |progress| {
+            create(&dir, &data, "", progress).map(|path| {
+                println!("Saved backup {}", streamer::path(&path));
+                format!("Backup saved as {}", path.file_name().unwrap_or_default().to_string_lossy())
+            })
+        })
+    }
+
+    pub fn restore(dir: PathBuf, archive: PathBuf, data: DataDirs) -> Self {
+        Self::start("Restore backup", true, move |progress| {
+            restore(&dir, &archive, &data, progress).map(|count| {
+                println!("Restored {} files from {}", count, streamer::path(&archive));
+                format!("Restored {} file(s) from {}", count, archive.file_name().unwrap_or_default().to_string_lossy())
+            })
+        })
+    }
+
+    fn start(
+        title: &'static str,
+        restoring: bool,
+        work: impl FnOnce(&mut dyn FnMut(&str, f32)) -> Result<String, String> + Send + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let progress_tx = tx.clone();
+            let result = work(&mut |name, fraction| {
+                let _ = progress_tx.send(TaskMsg::Progress(name.to_string(), fraction));
+            });
+            let _ = tx.send(TaskMsg::Done(result));
+        });
+        ArchiveTask {
+            title,
+            status: "Starting...".to_string(),
+            progress: 0.0,
+            rx: Some(rx),
+            restoring,
+        }
+    }
+
+    fn close(&self) -> ViewAction {
+        if self.rx.is_some() {
+            ViewAction::None
+        } else {
+            ViewAction::Close
+        }
+    }
+}
+
+impl View for ArchiveTask {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape | Keycode::Return), .. } => self.close(),
+            _ => ViewAction::None,
+        }
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Back | GamepadAction::Confirm => self.close(),
+            _ => ViewAction::None,
+        }
+    }
+
+    fn update(&mut self) -> ViewAction {
+        let mut action = ViewAction::None;
+        while let Some(msg) = self.rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            match msg {
+                TaskMsg::Progress(name, fraction) => {
+                    self.status = streamer::scrub(&name);
+                    self.progress = fraction;
+                }
+                TaskMsg::Done(Ok(message)) => {
+                    self.rx = None;
+                    self.progress = 1.0;
+                    self.status = message.clone();
+                    action = if self.restoring { ViewAction::Restored(message) } else { ViewAction::TaskFinished(message) };
+                }
+                TaskMsg::Done(Err(e)) => {
+                    eprintln!("{} failed: {}", self.title, e);
+                    self.rx = None;
+                    self.status = format!("Failed: {}", streamer::scrub(&e));
+                    action = ViewAction::TaskFinished(format!("{} failed: {}", self.title, e));
+                }
+            }
+        }
+        action
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, self.title)?;
+
+        let bar = Rect::new(30, 500, SCREEN_WIDTH - 60, 16);
+        canvas.set_draw_color(Color::RGB(60, 60, 90));
+        canvas.fill_rect(bar)?;
+        canvas.set_draw_color(ui::accent_color());
+        canvas.fill_rect(Rect::new(bar.x(), bar.y(), (bar.width() as f32 * self.progress) as u32, bar.height()))?;
+
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            ui::draw_text(canvas, texture_creator, f, &self.status, 30, 540, text_color)?;
+            let help = if self.rx.is_some() { "Please wait..." } else { "Esc: back" };
+            ui::draw_text(canvas, texture_creator, f, help, 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! Default UI assets compiled into the binary, used whenever the launcher/ folder is missing
+//! or has a broken copy, so a fresh checkout still gets a usable (if plain) launcher.
+
+use sdl2::image::LoadTexture;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::rwops::RWops;
+use sdl2::ttf;
+use sdl2::video::WindowContext;
+use std::path::Path;
+
+use crate::streamer;
+
+pub const CURSOR: &[u8] = include_bytes!("../release/launcher/UI/Cursor.png");
+pub const POINTER: &[u8] = include_bytes!("../release/launcher/pointer.png");
+pub const BACKGROUND: &[u8] = include_bytes!("../release/launcher/MBG.png");
+pub const FONT: &[u8] = include_bytes!("../release/launcher/smw.ttf");
+
+/// Loads the texture at `path`, or the embedded copy when the file is missing or unreadable, also
+/// saying why the file wasn't used when it wasn't.
+pub fn load_texture_reporting<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    path: &Path,
+    embedded: &[u8],
+    name: &str,
+) -> (Option<Texture<'a>>, Option<String>) {
+    let problem = if path.exists() {
+        match texture_creator.load_texture(path) {
+            Ok(t) => {
+                println!("Loaded {}: {}", name, streamer::path(path));
+                return (Some(t), None);
+            }
+            Err(e) => {
+                eprintln!("Failed to load {}: {}", name, e);
+                format!("{} is unreadable ({})", streamer::path(path), e)
+            }
+        }
+    } else {
+        eprintln!("No {} at {}, using the built-in one", name, streamer::path(path));
+        format!("{} is missing", streamer::path(path))
+    };
+    match texture_creator.load_texture_bytes(embedded) {
+        Ok(t) => (Some(t), Some(format!("{}, using the built-in one", problem))),
+        Err(e) => {
+            eprintln!("Failed to load the built-in {}: {}", name, e);
+            (None, Some(format!("{}, and the built-in one failed too ({})", problem, e)))
+        }
+    }
+}
+
+/// The embedded smw.ttf at `point_size`.
+pub fn load_font(ttf_context: &ttf::Sdl2TtfContext, point_size: u16) -> Result<ttf::Font<'_, 'static>, String> {
+    ttf_context.load_font_from_rwops(RWops::from_bytes(FONT)?, point_size)
+}
+
+/// A short two-note chime as an in-memory WAV, standing in for a missing `pg.wav`.
+pub fn launch_sound_wav() -> Vec<u8> {
+    tone_wav(&[(988.0, 0.08), (1319.0, 0.35)], 6_000.0)
+}
+
+/// A quiet low thud for when the selection can't move any further.
+pub fn bump_sound_wav() -> Vec<u8> {
+    tone_wav(&[(110.0, 0.06)], 3_000.0)
+}
+
+/// A short high blip for moving the selection; `AudioSystem::nav_effect` varies its pitch.
+pub fn nav_sound_wav() -> Vec<u8> {
+    tone_wav(&[(1568.0, 0.035)], 2_500.0)
+}
+
+/// A rising scale that runs long enough for buffer underruns to be heard as crackles.
+pub fn test_sound_wav() -> Vec<u8> {
+    let notes: Vec<(f32, f32)> = [523.0, 587.0, 659.0, 698.0, 784.0, 880.0, 988.0, 1047.0]
+        .iter()
+        .map(|&freq| (freq, 0.2))
+        .collect();
+    tone_wav(&notes, 5_000.0)
+}
+
+/// Square wave `notes` (frequency in Hz, length in seconds), each fading out, as a mono WAV.
+fn tone_wav(notes: &[(f32, f32)], amplitude: f32) -> Vec<u8> {
+    const RATE: u32 = 22_050;
+
+    let mut samples: Vec<i16> = Vec::new();
+    for &(freq, secs) in notes {
+        let count = (RATE as f32 * secs) as usize;
+        for i in 0..count {
+            let t = i as f32 / RATE as f32;
+            let square = if (t * freq).fract() < 0.5 { 1.0 } else { -1.0 };
+            let fade = 1.0 - i as f32 / count as f32;
+            samples.push((square * fade * amplitude) as i16);
+        }
+    }
+
+    let data_len = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&RATE.to_le_bytes());
+    wav.extend_from_slice(&(RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
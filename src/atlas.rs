@@ -0,0 +1,179 @@
+//! UI chrome packed into one texture: the cursor, the pointer, button images and the theme's badge
+//! icons, so drawing them doesn't switch textures for each one. A theme can ship all of these as a
+//! single sheet, `atlas.png` next to an `atlas.json` of `{"<sprite>": [x, y, width, height]}`, e.g.
+//! `{"button": [0, 0, 150, 40], "badge/flags/US.png": [150, 0, 24, 16]}`; sprites missing from the
+//! sheet are packed in from their loose files. Badge sprites are named `badge/<icon>` after the
+//! `icon` in theme.json with `{label}` filled in.
+
+use sdl2::image::{ImageRWops, LoadSurface};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::video::{Window, WindowContext};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::assets;
+use crate::streamer;
+use crate::theme::{self, Theme};
+
+pub const CURSOR: &str = "cursor";
+pub const POINTER: &str = "pointer";
+/// Button images in UI/, by state; a state without one falls back to the plain button, then to the drawn look.
+pub const BUTTON: &str = "button";
+pub const BUTTON_HOVER: &str = "button_hover";
+pub const BUTTON_PRESSED: &str = "button_pressed";
+pub const BUTTON_FOCUSED: &str = "button_focused";
+const BUTTON_STATES: [&str; 4] = [BUTTON, BUTTON_HOVER, BUTTON_PRESSED, BUTTON_FOCUSED];
+
+const WIDTH: u32 = 1024; // rows of sprites wrap at this width and the atlas grows downward
+const PADDING: i32 = 1; // keeps scaled sprites from picking up their neighbours' edges
+
+pub struct Atlas<'a> {
+    texture: Option<Texture<'a>>,
+    sprites: HashMap<String, Rect>,
+}
+
+impl<'a> Atlas<'a> {
+    pub fn empty() -> Self {
+        Atlas { texture: None, sprites: HashMap::new() }
+    }
+
+    /// Packs the images into rows, tallest first, and uploads them as one texture.
+    fn pack(texture_creator: &'a TextureCreator<WindowContext>, mut images: Vec<(String, Surface<'static>)>) -> Result<Self, String> {
+        images.retain(|(name, image)| {
+            let fits = image.width() <= WIDTH;
+            if !fits {
+                eprintln!("Skipping UI sprite {}: wider than {}px", name, WIDTH);
+            }
+            fits
+        });
+        if images.is_empty() {
+            return Ok(Atlas::empty());
+        }
+        images.sort_by(|a, b| b.1.height().cmp(&a.1.height()));
+
+        let mut sprites = HashMap::new();
+        let (mut x, mut y, mut row_height) = (0, 0, 0);
+        for (name, image) in &images {
+            if x + image.width() as i32 > WIDTH as i32 {
+                x = 0;
+                y += row_height + PADDING;
+                row_height = 0;
+            }
+            sprites.insert(name.clone(), Rect::new(x, y, image.width(), image.height()));
+            x += image.width() as i32 + PADDING;
+            row_height = row_height.max(image.height() as i32);
+        }
+
+        let mut sheet = Surface::new(WIDTH, (y + row_height).max(1) as u32, PixelFormatEnum::RGBA8888)?;
+        for (name, image) in &mut images {
+            // Copied as-is so transparent pixels stay transparent in the sheet
+            image.set_blend_mode(BlendMode::None)?;
+            image.blit(None, &mut sheet, sprites[name.as_str()])?;
+        }
+        let mut texture = texture_creator.create_texture_from_surface(&sheet).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(Atlas { texture: Some(texture), sprites })
+    }
+
+    /// Where `name` sits in the atlas, None when it wasn't packed.
+    pub fn sprite(&self, name: &str) -> Option<Rect> {
+        self.sprites.get(name).copied()
+    }
+
+    /// Draws `name` stretched over `dst`; false when there is no such sprite.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, name: &str, dst: Rect) -> Result<bool, String> {
+        match (&self.texture, self.sprite(name)) {
+            (Some(texture), Some(src)) => {
+                canvas.copy(texture, src, dst)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+/// Builds the atlas for `theme`, leaving out whatever fails to load.
+pub fn load<'a>(texture_creator: &'a TextureCreator<WindowContext>, launcher_dir: &Path, theme: &Theme, with_pointer: bool) -> Atlas<'a> {
+    let mut images = sheet_sprites(&theme::theme_dir(launcher_dir, &theme.name));
+    let mut add = |name: String, image: Option<Surface<'static>>| {
+        if let Some(image) = image.filter(|_| !images.iter().any(|(packed, _)| *packed == name)) {
+            images.push((name, image));
+        }
+    };
+    add(CURSOR.to_string(), load_image(&launcher_dir.join("UI").join("Cursor.png"), Some(assets::CURSOR)));
+    if with_pointer {
+        add(POINTER.to_string(), load_image(&launcher_dir.join("pointer.png"), Some(assets::POINTER)));
+    }
+    for state in BUTTON_STATES {
+        let path = theme::themed_file(launcher_dir, theme, &format!("UI/{}.png", state));
+        if path.exists() {
+            add(state.to_string(), load_image(&path, None));
+        }
+    }
+    // Icons naming a {label} have one file per label, those load on demand in BadgeRenderer
+    for style in theme.badges.values().filter(|s| !s.icon.is_empty() && !s.icon.contains("{label}")) {
+        add(format!("badge/{}", style.icon), load_image(&theme::themed_file(launcher_dir, theme, &style.icon), None));
+    }
+    match Atlas::pack(texture_creator, images) {
+        Ok(atlas) => atlas,
+        Err(e) => {
+            eprintln!("Failed to build the UI atlas: {}", e);
+            Atlas::empty()
+        }
+    }
+}
+
+/// The file at `path`, else the embedded copy.
+fn load_image(path: &Path, embedded: Option<&'static [u8]>) -> Option<Surface<'static>> {
+    match Surface::from_file(path) {
+        Ok(image) => return Some(image),
+        Err(e) if path.exists() => eprintln!("Failed to load {}: {}", streamer::path(path), e),
+        Err(_) => {}
+    }
+    let embedded = embedded?;
+    match RWops::from_bytes(embedded).and_then(|rw| rw.load()) {
+        Ok(image) => Some(image),
+        Err(e) => {
+            eprintln!("Failed to load the built-in copy of {}: {}", streamer::path(path), e);
+            None
+        }
+    }
+}
+
+/// Sprites cut from a theme's `atlas.png` by its `atlas.json`.
+fn sheet_sprites(theme_dir: &Path) -> Vec<(String, Surface<'static>)> {
+    let json_path = theme_dir.join("atlas.json");
+    let Ok(text) = fs::read_to_string(&json_path) else {
+        return Vec::new();
+    };
+    let rects: HashMap<String, [u32; 4]> = match serde_json::from_str(&text) {
+        Ok(rects) => rects,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", streamer::path(&json_path), e);
+            return Vec::new();
+        }
+    };
+    let Some(mut sheet) = load_image(&theme_dir.join("atlas.png"), None) else {
+        return Vec::new();
+    };
+    if let Err(e) = sheet.set_blend_mode(BlendMode::None) {
+        eprintln!("Failed to prepare the theme atlas: {}", e);
+    }
+    let mut sprites = Vec::new();
+    for (name, [x, y, width, height]) in rects {
+        let cut = Surface::new(width.max(1), height.max(1), PixelFormatEnum::RGBA8888).and_then(|mut sprite| {
+            sheet.blit(Rect::new(x as i32, y as i32, width.max(1), height.max(1)), &mut sprite, None)?;
+            Ok(sprite)
+        });
+        match cut {
+            Ok(sprite) => sprites.push((name, sprite)),
+            Err(e) => eprintln!("Failed to cut sprite {} from the theme atlas: {}", name, e),
+        }
+    }
+    sprites
+}
@@ -0,0 +1,413 @@
+//! Launcher music and sound effects. The whole mixer is optional so machines without
+//! an audio device (headless boxes, some VMs and containers) still get a working launcher.
+//! The navigation sound has a reserved channel with a mixer effect that replays it faster, so
+//! moving quickly climbs in pitch a step at a time like the SMAS menus.
+//! Music always streams from disk and is only opened once the first frame is up, since some
+//! decoders scan the whole file first. Sound effects are decoded into memory up front, except a
+//! theme's oversized launch jingle, which streams on the music channel the launch has just freed.
+
+use sdl2::mixer::{self, Chunk, LoaderRWops, Music, Sdl2MixerContext, AUDIO_S16LSB, DEFAULT_CHANNELS};
+use sdl2::rwops::RWops;
+use std::cell::{Cell, RefCell};
+use std::os::raw::{c_int, c_void};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{assets, streamer};
+
+/// Files the mixer plays, picked by the theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundFiles {
+    pub music: PathBuf,
+    pub launch: PathBuf,
+    pub bump: Option<PathBuf>, // None = the built-in thud
+    pub nav: Option<PathBuf>, // None = the built-in blip
+}
+
+/// Where the background music stands; it opens after `init` so startup doesn't wait on it.
+enum MusicState {
+    Pending(Option<Duration>), // where to start playing once open, None = not asked to play yet
+    Ready(Music<'static>),
+    Missing,
+}
+
+/// The launch sound, held in memory or, past `PRELOAD_MAX_BYTES`, streamed when played.
+enum LaunchSound {
+    Chunk(Chunk),
+    Streamed(PathBuf),
+}
+
+pub struct AudioSystem {
+    _context: Sdl2MixerContext,
+    files: SoundFiles,
+    music: MusicState,
+    launch_sound: Option<LaunchSound>,
+    jingle: RefCell<Option<Music<'static>>>, // streamed launch sound while it plays
+    bump_sound: Option<Chunk>,
+    nav_sound: Option<Chunk>,
+    nav_step: Cell<(Option<Instant>, usize)>, // last navigation sound and its index in NAV_PITCHES
+    test_sound: Option<Chunk>,
+    // SDL_mixer can't report the music position here, so playing time is tracked instead
+    music_played: Cell<Duration>,
+    music_since: Cell<Option<Instant>>, // None while stopped or paused
+}
+
+/// How long the music takes to come back after a game exits.
+const RETURN_FADE_MS: i32 = 800;
+const NAV_CHANNEL: i32 = 0; // reserved, so other sounds never get the pitch effect
+/// Semitones above the base sample for each navigation step in a quick run, then around again.
+const NAV_PITCHES: [f32; 5] = [0.0, 1.0, 2.0, 3.0, 4.0];
+/// Moves closer together than this count as one run and keep climbing.
+const RAPID_NAV: Duration = Duration::from_millis(250);
+/// Sound effect files larger than this aren't decoded into memory.
+const PRELOAD_MAX_BYTES: u64 = 2 * 1024 * 1024;
+
+/// The navigation sample as the mixer holds it, and how the effect is stepping through it.
+struct Voice {
+    samples: Vec<i16>, // interleaved, `channels` per frame
+    channels: usize,
+    position: f32, // in frames
+    rate: f32, // frames of the sample per output frame
+}
+
+static VOICE: Mutex<Voice> = Mutex::new(Voice { samples: Vec::new(), channels: 2, position: 0.0, rate: 1.0 });
+
+/// Runs on the audio thread in place of the chunk's own data: the sample resampled at `rate`,
+/// so a higher pitch also ends sooner and the chunk's remaining length is silence.
+unsafe extern "C" fn pitch_effect(_channel: c_int, stream: *mut c_void, len: c_int, _udata: *mut c_void) {
+    if stream.is_null() || len <= 0 {
+        return;
+    }
+    let out = std::slice::from_raw_parts_mut(stream as *mut i16, len as usize / 2);
+    let mut voice = match VOICE.lock() {
+        Ok(voice) => voice,
+        Err(_) => return,
+    };
+    let channels = voice.channels.max(1);
+    let frames = voice.samples.len() / channels;
+    for frame in out.chunks_exact_mut(channels) {
+        let idx = voice.position as usize;
+        let t = voice.position.fract();
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            *sample = if idx + 1 < frames {
+                let a = voice.samples[idx * channels + ch] as f32;
+                let b = voice.samples[(idx + 1) * channels + ch] as f32;
+                (a + (b - a) * t) as i16
+            } else {
+                0
+            };
+        }
+        voice.position += voice.rate;
+    }
+}
+
+/// Copies a loaded chunk's data for `pitch_effect`, which has to read ahead of the mixer.
+fn set_voice(sound: &Chunk) {
+    let Ok((_, _, channels)) = mixer::query_spec() else {
+        return;
+    };
+    // Chunks are converted to the device format when loaded, 16-bit little endian here
+    let raw = unsafe { &*sound.raw };
+    let bytes = unsafe { std::slice::from_raw_parts(raw.abuf, raw.alen as usize) };
+    if let Ok(mut voice) = VOICE.lock() {
+        voice.samples = bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        voice.channels = channels.max(1) as usize;
+    }
+}
+
+impl AudioSystem {
+    /// Opens the audio device with `chunk_size` samples per buffer and loads the sounds; the music
+    /// follows in `load_music`. Missing sounds are replaced by built-in ones.
+    pub fn init(files: &SoundFiles, chunk_size: i32) -> Result<Self, String> {
+        mixer::open_audio(44_100, AUDIO_S16LSB, DEFAULT_CHANNELS, chunk_size)?;
+        let context = mixer::init(mixer::InitFlag::MP3 | mixer::InitFlag::OGG)?;
+        mixer::allocate_channels(4);
+        mixer::reserve_channels(1);
+
+        let launch_sound = if oversized(&files.launch) {
+            println!("Streaming the launch sound: {}", streamer::path(&files.launch));
+            Some(LaunchSound::Streamed(files.launch.clone()))
+        } else {
+            load_file(&files.launch, "launch sound")
+                .or_else(|| load_builtin(&assets::launch_sound_wav(), "launch sound"))
+                .map(LaunchSound::Chunk)
+        };
+        let bump_sound = files
+            .bump
+            .as_deref()
+            .and_then(|path| load_file(path, "bump sound"))
+            .or_else(|| load_builtin(&assets::bump_sound_wav(), "bump sound"));
+        let nav_sound = files
+            .nav
+            .as_deref()
+            .and_then(|path| load_file(path, "navigation sound"))
+            .or_else(|| load_builtin(&assets::nav_sound_wav(), "navigation sound"));
+        if let Some(sound) = &nav_sound {
+            set_voice(sound);
+        }
+        let test_sound = load_builtin(&assets::test_sound_wav(), "test sound");
+
+        Ok(AudioSystem {
+            _context: context,
+            files: files.clone(),
+            music: MusicState::Pending(None),
+            launch_sound,
+            jingle: RefCell::new(None),
+            bump_sound,
+            nav_sound,
+            nav_step: Cell::new((None, 0)),
+            test_sound,
+            music_played: Cell::new(Duration::ZERO),
+            music_since: Cell::new(None),
+        })
+    }
+
+    fn stop_clock(&self) {
+        if let Some(since) = self.music_since.take() {
+            self.music_played.set(self.music_played.get() + since.elapsed());
+        }
+    }
+
+    /// What the mixer was opened with, to tell whether a theme change needs it reopened.
+    pub fn files(&self) -> &SoundFiles {
+        &self.files
+    }
+
+    pub fn has_music(&self) -> bool {
+        matches!(self.music, MusicState::Ready(_))
+    }
+    
+    /// True until `load_music` has opened the track (or found there is none).
+    pub fn music_loading(&self) -> bool {
+        matches!(self.music, MusicState::Pending(_))
+    }
+    
+    /// Opens the background music for streaming and starts it if it was asked to play meanwhile.
+    /// Called each frame; only the first call does anything.
+    pub fn load_music(&mut self) {
+        let MusicState::Pending(start) = self.music else {
+            return;
+        };
+        let path = &self.files.music;
+        if !path.exists() {
+            eprintln!("Background music not found at: {}", streamer::path(path));
+            self.music = MusicState::Missing;
+            return;
+        }
+        match Music::from_file(path) {
+            Ok(m) => {
+                println!("Loaded background music: {}", streamer::path(path));
+                self.music = MusicState::Ready(m);
+            }
+            Err(e) => {
+                eprintln!("Failed to load background music: {}", e);
+                self.music = MusicState::Missing;
+                return;
+            }
+        }
+        match start {
+            Some(position) if position.is_zero() => self.play_music(),
+            Some(position) => self.resume_music_from(position),
+            None => {}
+        }
+    }
+
+    /// How far into the track the music has played, for picking it up on a reopened device.
+    pub fn music_position(&self) -> Duration {
+        self.music_played.get() + self.music_since.get().map(|since| since.elapsed()).unwrap_or_default()
+    }
+
+    pub fn play_music(&mut self) {
+        if let MusicState::Pending(start) = &mut self.music {
+            *start = Some(Duration::ZERO);
+            return;
+        }
+        if let MusicState::Ready(m) = &self.music {
+            if let Err(e) = m.play(-1) {
+                eprintln!("Failed to play background music: {}", e);
+                return;
+            }
+            self.music_played.set(Duration::ZERO);
+            self.music_since.set(Some(Instant::now()));
+        }
+    }
+
+    pub fn pause_music(&self) {
+        Music::pause();
+        self.stop_clock();
+    }
+
+    pub fn resume_music(&self) {
+        Music::resume();
+        if self.music_since.get().is_none() {
+            self.music_since.set(Some(Instant::now()));
+        }
+    }
+
+    /// Sets music and sound effects to `percent` of full volume.
+    pub fn set_volume(&self, percent: u8) {
+        let volume = percent.min(100) as i32 * mixer::MAX_VOLUME / 100;
+        Music::set_volume(volume);
+        mixer::Channel::all().set_volume(volume);
+    }
+
+    /// Plays the built-in test scale, e.g. to check a latency preset for crackling.
+    pub fn test_effect(&self) {
+        if let Some(sound) = &self.test_sound {
+            if let Err(e) = mixer::Channel::all().play(sound, 0) {
+                eprintln!("Failed to play test sound: {}", e);
+            }
+        }
+    }
+
+    pub fn bump_effect(&self) {
+        if let Some(sound) = &self.bump_sound {
+            if let Err(e) = mixer::Channel::all().play(sound, 0) {
+                eprintln!("Failed to play bump sound: {}", e);
+            }
+        }
+    }
+
+    /// The selection moved: the navigation sound, a step higher than last time while moves
+    /// keep coming quickly and back at the theme's pitch after a pause.
+    pub fn nav_effect(&self) {
+        let Some(sound) = &self.nav_sound else {
+            return;
+        };
+        let step = match self.nav_step.get() {
+            (Some(at), step) if at.elapsed() < RAPID_NAV => (step + 1) % NAV_PITCHES.len(),
+            _ => 0,
+        };
+        self.nav_step.set((Some(Instant::now()), step));
+
+        // Halting drops the channel's effects, so the new one is in place before anything plays
+        let channel = mixer::Channel(NAV_CHANNEL);
+        channel.halt();
+        if let Ok(mut voice) = VOICE.lock() {
+            voice.position = 0.0;
+            voice.rate = 2f32.powf(NAV_PITCHES[step] / 12.0);
+        }
+        let registered = unsafe {
+            sdl2::sys::mixer::Mix_RegisterEffect(NAV_CHANNEL, Some(pitch_effect), None, std::ptr::null_mut())
+        };
+        if registered == 0 {
+            // Still worth hearing at the base pitch
+            eprintln!("Failed to add the navigation pitch effect: {}", sdl2::get_error());
+        }
+        if let Err(e) = channel.play(sound, 0) {
+            eprintln!("Failed to play navigation sound: {}", e);
+        }
+    }
+
+    /// First step of a launch: fades the music out over `ms`.
+    pub fn fade_out_music(&mut self, ms: i32) {
+        if let MusicState::Pending(start) = &mut self.music {
+            *start = None;
+        }
+        if let Err(e) = Music::fade_out(ms) {
+            eprintln!("Failed to fade out music: {}", e);
+        }
+        self.stop_clock();
+    }
+
+    /// The launch jingle, played once the music has faded.
+    pub fn launch_sound(&self) {
+        match &self.launch_sound {
+            Some(LaunchSound::Chunk(sound)) => {
+                if let Err(e) = mixer::Channel::all().play(sound, 0) {
+                    eprintln!("Failed to play launch sound: {}", e);
+                }
+            }
+            // The background music has faded out by now, so the music channel is free
+            Some(LaunchSound::Streamed(path)) => match Music::from_file(path).and_then(|m| m.play(0).map(|_| m)) {
+                Ok(m) => *self.jingle.borrow_mut() = Some(m),
+                Err(e) => eprintln!("Failed to play launch sound: {}", e),
+            },
+            None => {}
+        }
+    }
+
+    /// `return_effect` on a freshly opened device, continuing from `position`.
+    pub fn resume_music_from(&mut self, position: Duration) {
+        self.music_played.set(position);
+        self.return_effect();
+    }
+
+    /// Fades the music back in where `fade_out_music` cut it off, or from the start when
+    /// the track can't seek that far (its length isn't known, so loops aren't accounted for).
+    pub fn return_effect(&mut self) {
+        let m = match &mut self.music {
+            MusicState::Ready(m) => m,
+            MusicState::Pending(start) => {
+                *start = Some(self.music_played.get());
+                return;
+            }
+            MusicState::Missing => return,
+        };
+        let position = self.music_played.get().as_secs_f64();
+        if let Err(e) = m.fade_in_from_pos(-1, RETURN_FADE_MS, position) {
+            eprintln!("Failed to resume music at {:.0}s, restarting it: {}", position, e);
+            if let Err(e) = m.fade_in(-1, RETURN_FADE_MS) {
+                eprintln!("Failed to play background music: {}", e);
+                return;
+            }
+            self.music_played.set(Duration::ZERO);
+        }
+        self.music_since.set(Some(Instant::now()));
+        // Starting the music stopped the jingle, if it was still going
+        self.jingle.borrow_mut().take();
+    }
+}
+
+impl Drop for AudioSystem {
+    fn drop(&mut self) {
+        // Music and chunks must be freed before the device closes
+        self.music = MusicState::Missing;
+        self.jingle.borrow_mut().take();
+        self.launch_sound = None;
+        self.bump_sound = None;
+        mixer::Channel(NAV_CHANNEL).halt();
+        self.nav_sound = None;
+        self.test_sound = None;
+        mixer::close_audio();
+    }
+}
+
+/// Too big to be worth decoding into memory ahead of time.
+fn oversized(path: &Path) -> bool {
+    fs::metadata(path).map(|meta| meta.len() > PRELOAD_MAX_BYTES).unwrap_or(false)
+}
+
+fn load_file(path: &Path, name: &str) -> Option<Chunk> {
+    if !path.exists() {
+        eprintln!("{} not found at: {}", name, streamer::path(path));
+        return None;
+    }
+    // Effects replayed on every move stay short; an oversized file gets the built-in one
+    if oversized(path) {
+        eprintln!("{} is over {} MB, skipping: {}", name, PRELOAD_MAX_BYTES / (1024 * 1024), streamer::path(path));
+        return None;
+    }
+    match Chunk::from_file(path) {
+        Ok(sound) => {
+            println!("Loaded {}: {}", name, streamer::path(path));
+            Some(sound)
+        }
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", name, e);
+            None
+        }
+    }
+}
+
+fn load_builtin(wav: &[u8], name: &str) -> Option<Chunk> {
+    match RWops::from_bytes(wav).and_then(|rw| rw.load_wav()) {
+        Ok(sound) => Some(sound),
+        Err(e) => {
+            eprintln!("Failed to load the built-in {}: {}", name, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Generated cover art for ROMs without one: the game is run hidden until it saves a title-screen
+//! frame, which is cached as `launcher/pngs/auto/<rom>.png`. This needs an smw build with a
+//! screenshot hook, asked for through `SMW_SCREENSHOT` (where to save the PNG) and
+//! `SMW_SCREENSHOT_FRAME` (which frame). When a capture produces nothing the build is taken not
+//! to have the hook and no more captures are started this session. The hidden game belongs to the
+//! `CoverCapture`, which ends it when a real launch needs the game or the launcher quits.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Frame saved as the cover: a few seconds in, past the boot logos and onto the title screen.
+const TITLE_FRAME: u32 = 300;
+/// How long a hidden run gets to save its frame before it is killed.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(20);
+
+pub struct CoverCapture {
+    auto_dir: PathBuf,
+    running: Option<Receiver<(String, Result<(), String>)>>,
+    child: Arc<Mutex<Option<Child>>>, // the hidden game, taken by whoever ends it first
+    tried: HashSet<String>,
+    unsupported: bool, // a capture came back empty, so the smw build can't do it
+}
+
+impl CoverCapture {
+    pub fn new(pngs_dir: &Path) -> Self {
+        CoverCapture {
+            auto_dir: pngs_dir.join("auto"),
+            running: None,
+            child: Arc::new(Mutex::new(None)),
+            tried: HashSet::new(),
+            unsupported: false,
+        }
+    }
+
+    pub fn output_path(&self, rom: &str) -> PathBuf {
+        self.auto_dir.join(format!("{}.png", rom.trim_end_matches(".sfc")))
+    }
+
+    /// Variables that make the game run headless and save its title screen to `output`.
+    pub fn env(output: &Path) -> [(String, String); 4] {
+        [
+            ("SDL_VIDEODRIVER".to_string(), "dummy".to_string()),
+            ("SDL_AUDIODRIVER".to_string(), "dummy".to_string()),
+            ("SMW_SCREENSHOT".to_string(), output.display().to_string()),
+            ("SMW_SCREENSHOT_FRAME".to_string(), TITLE_FRAME.to_string()),
+        ]
+    }
+
+    /// The first of `uncovered` to capture next, or None while a capture runs or captures don't work.
+    pub fn next<'r>(&self, mut uncovered: impl Iterator<Item = &'r str>) -> Option<&'r str> {
+        if self.running.is_some() || self.unsupported {
+            return None;
+        }
+        uncovered.find(|rom| !self.tried.contains(*rom))
+    }
+
+    /// Starts `command` (the game with `env` set) and watches for the frame on a worker.
+    pub fn start(&mut self, rom: &str, mut command: Command) {
+        self.tried.insert(rom.to_string());
+        if let Err(e) = fs::create_dir_all(&self.auto_dir) {
+            eprintln!("Failed to create {}: {}", self.auto_dir.display(), e);
+            self.unsupported = true;
+            return;
+        }
+        let output = self.output_path(rom);
+        let _ = fs::remove_file(&output);
+        let spawned = command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("No cover captured for {} ({}), this smw build can't save frames", rom, e);
+                self.unsupported = true;
+                return;
+            }
+        };
+        if let Ok(mut slot) = self.child.lock() {
+            *slot = Some(child);
+        }
+        let (tx, rx) = mpsc::channel();
+        let (rom, shared) = (rom.to_string(), Arc::clone(&self.child));
+        println!("Capturing a cover for {}", rom);
+        thread::spawn(move || {
+            let result = watch(&shared, &output);
+            stop_child(&shared);
+            let _ = tx.send((rom, result));
+        });
+        self.running = Some(rx);
+    }
+
+    /// Ends a running capture; its ROM is tried again next session.
+    pub fn stop(&mut self) {
+        if self.running.take().is_some() {
+            println!("Stopping the cover capture");
+        }
+        stop_child(&self.child);
+    }
+
+    /// The ROM whose cover was just captured, once one is.
+    pub fn poll(&mut self) -> Option<String> {
+        let (rom, result) = self.running.as_ref()?.try_recv().ok()?;
+        self.running = None;
+        match result {
+            Ok(()) => Some(rom),
+            Err(e) => {
+                eprintln!("No cover captured for {} ({}), this smw build can't save frames", rom, e);
+                self.unsupported = true;
+                None
+            }
+        }
+    }
+}
+
+impl Drop for CoverCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Waits for the frame, the game to exit or the timeout, whichever comes first.
+fn watch(child: &Mutex<Option<Child>>, output: &Path) -> Result<(), String> {
+    let started = Instant::now();
+    loop {
+        if output.is_file() {
+            // Give the game a moment to finish writing the file
+            thread::sleep(Duration::from_millis(500));
+            return Ok(());
+        }
+        match child.lock().map_err(|e| e.to_string())?.as_mut() {
+            Some(child) => {
+                if let Ok(Some(status)) = child.try_wait() {
+                    return Err(format!("smw exited with {}", status));
+                }
+            }
+            None => return Err("stopped".to_string()),
+        }
+        if started.elapsed() >= CAPTURE_TIMEOUT {
+            return Err("timed out".to_string());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn stop_child(child: &Mutex<Option<Child>>) {
+    let taken = child.lock().ok().and_then(|mut slot| slot.take());
+    if let Some(mut child) = taken {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One file saved to `launcher/backups/` before it was rewritten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub id: String,
+    pub original: PathBuf,
+    pub operation: String,
+    pub created: u64,
+    #[serde(default)]
+    pub renamed_to: Option<PathBuf>, // where a rename put the file, restoring moves it back
+}
+
+fn manifest_path(backup_dir: &Path) -> PathBuf {
+    backup_dir.join("manifest.json")
+}
+
+pub fn load_manifest(backup_dir: &Path) -> Vec<BackupEntry> {
+    fs::read_to_string(manifest_path(backup_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(backup_dir: &Path, entries: &[BackupEntry]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(manifest_path(backup_dir), content).map_err(|e| e.to_string())
+}
+
+/// Copies `path` into the backup folder before a destructive operation.
+/// Does nothing when the file doesn't exist yet (nothing to lose).
+pub fn backup_file(backup_dir: &Path, path: &Path, operation: &str) -> Result<(), String> {
+    record(backup_dir, path, operation, None)
+}
+
+/// Backs up `path` before it's renamed to `to`, so restoring it renames the file back.
+pub fn backup_rename(backup_dir: &Path, path: &Path, to: &Path) -> Result<(), String> {
+    record(backup_dir, path, "renaming", Some(to.to_path_buf()))
+}
+
+fn record(backup_dir: &Path, path: &Path, operation: &str, renamed_to: Option<PathBuf>) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(backup_dir).map_err(|e| e.to_string())?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let created = now.as_secs();
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    // Several backups can land in the same millisecond (e.g. a batch rename), a counter keeps them apart
+    let mut entries = load_manifest(backup_dir);
+    let taken = |id: &str| backup_dir.join(id).exists() || entries.iter().any(|e| e.id == id);
+    let mut id = format!("{}{:03}_{}", created, now.subsec_millis(), file_name);
+    let mut n = 1;
+    while taken(&id) {
+        n += 1;
+        id = format!("{}{:03}-{}_{}", created, now.subsec_millis(), n, file_name);
+    }
+
+    fs::copy(path, backup_dir.join(&id)).map_err(|e| format!("Backup of {} failed: {}", file_name, e))?;
+
+    entries.push(BackupEntry {
+        id,
+        original: path.to_path_buf(),
+        operation: operation.to_string(),
+        created,
+        renamed_to,
+    });
+    save_manifest(backup_dir, &entries)?;
+    println!("Backed up {} before {}", file_name, operation);
+    Ok(())
+}
+
+/// Puts a backup back in place and drops it from the manifest. A renamed file is renamed back
+/// while it's still there, otherwise the copy is restored under the old name.
+pub fn restore(backup_dir: &Path, id: &str) -> Result<BackupEntry, String> {
+    let mut entries = load_manifest(backup_dir);
+    let pos = entries.iter().position(|e| e.id == id).ok_or("Backup not found")?;
+    let entry = entries.remove(pos);
+
+    match entry.renamed_to.as_ref().filter(|renamed| renamed.exists()) {
+        Some(renamed) => {
+            // A case-only rename still finds the file under its old name on case-insensitive file systems
+            let same_file = renamed.to_string_lossy().eq_ignore_ascii_case(&entry.original.to_string_lossy());
+            if entry.original.exists() && !same_file {
+                return Err(format!("{} already exists", entry.original.file_name().unwrap_or_default().to_string_lossy()));
+            }
+            fs::rename(renamed, &entry.original).map_err(|e| e.to_string())?;
+        }
+        None => {
+            fs::copy(backup_dir.join(&entry.id), &entry.original).map_err(|e| e.to_string())?;
+        }
+    }
+    let _ = fs::remove_file(backup_dir.join(&entry.id));
+    save_manifest(backup_dir, &entries)?;
+    Ok(entry)
+}
@@ -0,0 +1,164 @@
+//! Small labels in the corners of a card. Each kind has a default look, and `badges` in
+//! theme.json can move one to another corner, hide it or draw an image instead of the text, e.g.
+//! `{"new": {"corner": "BottomRight", "icon": "new.png"}, "region": {"icon": "flags/{label}.png"}}`
+//! where `{label}` is replaced by the badge's text. Kinds: favorite, new, region, checksum, save, locked, age.
+
+use sdl2::image::LoadTexture;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::atlas::Atlas;
+use crate::font::Font;
+use crate::theme::{self, Theme};
+use crate::ui;
+use crate::widgets::Corner;
+
+const MARGIN: i32 = 4;
+/// The bottom of a card is its title, bottom corner badges sit on the cover above it.
+const LABEL_HEIGHT: i32 = 60;
+
+/// Kind, default corner, text color and background color.
+const KINDS: [(&str, Corner, (u8, u8, u8), (u8, u8, u8)); 7] = [
+    ("new", Corner::TopLeft, (255, 255, 255), (230, 40, 40)),
+    ("favorite", Corner::TopRight, (0, 0, 0), (255, 220, 0)),
+    ("checksum", Corner::TopRight, (0, 0, 0), (255, 140, 0)),
+    ("save", Corner::TopRight, (255, 255, 255), (200, 30, 120)),
+    ("locked", Corner::TopRight, (255, 255, 255), (90, 90, 90)),
+    ("region", Corner::BottomLeft, (255, 255, 255), (40, 40, 40)),
+    ("age", Corner::BottomRight, (255, 255, 255), (30, 90, 170)),
+];
+
+/// A theme's changes to one kind of badge.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct BadgeStyle {
+    pub corner: Option<Corner>,
+    pub icon: String, // image in the theme folder or the launcher folder, empty for the text label
+    pub hidden: bool,
+}
+
+pub struct Badge {
+    pub kind: &'static str,
+    pub label: String,
+}
+
+impl Badge {
+    pub fn new(kind: &'static str, label: &str) -> Self {
+        Badge { kind, label: label.to_string() }
+    }
+}
+
+/// Two-letter label for a region from `rom::header_region`, None for ones without a code.
+pub fn region_code(region: &str) -> Option<&'static str> {
+    Some(match region {
+        "Japan" => "JP",
+        "USA" => "US",
+        "Europe" => "EU",
+        "Sweden" => "SE",
+        "France" => "FR",
+        "Netherlands" => "NL",
+        "Spain" => "ES",
+        "Germany" => "DE",
+        "Italy" => "IT",
+        "Korea" => "KR",
+        _ => return None,
+    })
+}
+
+/// Draws badges, keeping the icon images a theme uses loaded when they aren't in the UI atlas.
+pub struct BadgeRenderer<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    icons: HashMap<PathBuf, Option<Texture<'a>>>,
+}
+
+impl<'a> BadgeRenderer<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        BadgeRenderer { texture_creator, icons: HashMap::new() }
+    }
+
+    fn icon(&mut self, path: PathBuf) -> Option<&Texture<'a>> {
+        let texture_creator = self.texture_creator;
+        self.icons
+            .entry(path)
+            .or_insert_with_key(|path| match texture_creator.load_texture(path) {
+                Ok(texture) => Some(texture),
+                Err(e) => {
+                    eprintln!("Failed to load badge icon {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    /// Draws `badges` on the card at `rect`, lining up the ones that share a corner.
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        font: &Font,
+        atlas: &Atlas,
+        rect: Rect,
+        theme: &Theme,
+        launcher_dir: &Path,
+        badges: &[Badge],
+    ) -> Result<(), String> {
+        let texture_creator = self.texture_creator;
+        let mut offsets: HashMap<&str, i32> = HashMap::new();
+        for badge in badges {
+            let Some(&(_, default_corner, text_color, background)) = KINDS.iter().find(|(kind, ..)| *kind == badge.kind) else {
+                continue;
+            };
+            let style = theme.badges.get(badge.kind).cloned().unwrap_or_default();
+            if style.hidden {
+                continue;
+            }
+            let corner = style.corner.unwrap_or(default_corner);
+
+            let (text_width, text_height) = font.size_of(&badge.label)?;
+            let height = text_height + 4;
+            let icon = Some(style.icon.replace("{label}", &badge.label)).filter(|icon| !icon.is_empty());
+            let sprite = icon.as_ref().map(|icon| format!("badge/{}", icon)).filter(|name| atlas.sprite(name).is_some());
+            let texture = match (&sprite, icon) {
+                (None, Some(icon)) => self.icon(theme::themed_file(launcher_dir, theme, &icon)),
+                _ => None,
+            };
+            let icon_size = match (&sprite, texture) {
+                (Some(name), _) => atlas.sprite(name).map(|r| (r.width(), r.height())),
+                (None, Some(texture)) => Some((texture.query().width, texture.query().height)),
+                (None, None) => None,
+            };
+            let width = match icon_size {
+                Some((icon_width, icon_height)) => (icon_width * height / icon_height.max(1)).max(1),
+                None => text_width + 12,
+            };
+
+            let offset = offsets.entry(corner.label()).or_insert(MARGIN);
+            let x = match corner {
+                Corner::TopLeft | Corner::BottomLeft => rect.x() + *offset,
+                Corner::TopRight | Corner::BottomRight => rect.right() - *offset - width as i32,
+            };
+            let y = match corner {
+                Corner::TopLeft | Corner::TopRight => rect.y() + MARGIN,
+                Corner::BottomLeft | Corner::BottomRight => rect.bottom() - LABEL_HEIGHT - MARGIN - height as i32,
+            };
+            *offset += width as i32 + MARGIN;
+
+            let area = Rect::new(x, y, width, height);
+            match (&sprite, texture) {
+                (Some(name), _) => {
+                    atlas.draw(canvas, name, area)?;
+                }
+                (None, Some(texture)) => canvas.copy(texture, None, area)?,
+                (None, None) => {
+                    canvas.set_draw_color(theme::rgb(background));
+                    canvas.fill_rect(area)?;
+                    ui::draw_text(canvas, texture_creator, font, &badge.label, x + 6, y + 2, theme::rgb(text_color))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
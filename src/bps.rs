@@ -0,0 +1,127 @@
+use crate::rom::crc32;
+
+const MAX_TARGET: usize = 16 * 1024 * 1024; // well past the largest SNES ROM; sizes come from the patch file
+
+/// Applies a BPS patch to `source` and returns the patched ROM.
+/// Checksums in the patch footer are verified on both ends.
+pub fn apply(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    if patch.len() < 4 + 12 || &patch[..4] != b"BPS1" {
+        return Err("Not a BPS patch".to_string());
+    }
+
+    let footer = patch.len() - 12;
+    let expected_source = read_u32(patch, footer);
+    let expected_target = read_u32(patch, footer + 4);
+    let expected_patch = read_u32(patch, footer + 8);
+    if crc32(&patch[..footer + 8]) != expected_patch {
+        return Err("Patch file is corrupt".to_string());
+    }
+    if crc32(source) != expected_source {
+        return Err(format!(
+            "Wrong base ROM (expected CRC32 {:08X}, got {:08X})",
+            expected_source,
+            crc32(source)
+        ));
+    }
+
+    let mut pos = 4;
+    let source_size = read_number(patch, &mut pos)? as usize;
+    let target_size = read_number(patch, &mut pos)? as usize;
+    let metadata_size = read_number(patch, &mut pos)? as usize;
+    pos = pos.checked_add(metadata_size).ok_or(OVERFLOW)?;
+    if source_size != source.len() {
+        return Err("Base ROM size does not match the patch".to_string());
+    }
+    if target_size > MAX_TARGET {
+        return Err(format!("Patched ROM would be {} bytes, too large for a SNES ROM", target_size));
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_rel: i64 = 0;
+    let mut target_rel: i64 = 0;
+
+    while pos < footer {
+        let command = read_number(patch, &mut pos)?;
+        let length = usize::try_from((command >> 2) + 1).map_err(|_| OVERFLOW)?;
+        // Every command adds `length` bytes, so this also bounds the loops below
+        if length > target_size - target.len() {
+            return Err("Patch writes past the end of the patched ROM".to_string());
+        }
+        let signed_length = i64::try_from(length).map_err(|_| OVERFLOW)?;
+
+        match command & 3 {
+            // SourceRead
+            0 => {
+                let start = target.len();
+                let bytes = source.get(start..start + length).ok_or("SourceRead out of range")?;
+                target.extend_from_slice(bytes);
+            }
+            // TargetRead
+            1 => {
+                let end = pos.checked_add(length).ok_or(OVERFLOW)?;
+                let bytes = patch.get(pos..end).ok_or("TargetRead out of range")?;
+                target.extend_from_slice(bytes);
+                pos = end;
+            }
+            // SourceCopy
+            2 => {
+                source_rel = source_rel.checked_add(read_offset(patch, &mut pos)?).ok_or(OVERFLOW)?;
+                let start = usize::try_from(source_rel).map_err(|_| "SourceCopy out of range")?;
+                let end = start.checked_add(length).ok_or(OVERFLOW)?;
+                let bytes = source.get(start..end).ok_or("SourceCopy out of range")?;
+                target.extend_from_slice(bytes);
+                source_rel = source_rel.checked_add(signed_length).ok_or(OVERFLOW)?;
+            }
+            // TargetCopy, byte by byte since the ranges may overlap
+            _ => {
+                target_rel = target_rel.checked_add(read_offset(patch, &mut pos)?).ok_or(OVERFLOW)?;
+                for _ in 0..length {
+                    let idx = usize::try_from(target_rel).map_err(|_| "TargetCopy out of range")?;
+                    let byte = *target.get(idx).ok_or("TargetCopy out of range")?;
+                    target.push(byte);
+                    target_rel += 1;
+                }
+            }
+        }
+    }
+
+    if target.len() != target_size || crc32(&target) != expected_target {
+        return Err("Patched ROM failed verification".to_string());
+    }
+    Ok(target)
+}
+
+/// CRC32 of the ROM a patch expects, read from its footer.
+pub fn source_crc32(patch: &[u8]) -> Option<u32> {
+    if patch.len() < 16 || &patch[..4] != b"BPS1" {
+        return None;
+    }
+    Some(read_u32(patch, patch.len() - 12))
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+const OVERFLOW: &str = "Patch file is corrupt (number out of range)";
+
+fn read_number(data: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut value: u64 = 0;
+    let mut shift: u64 = 1;
+    loop {
+        let byte = *data.get(*pos).ok_or("Unexpected end of patch")? as u64;
+        *pos += 1;
+        value = (byte & 0x7f).checked_mul(shift).and_then(|part| value.checked_add(part)).ok_or(OVERFLOW)?;
+        if byte & 0x80 != 0 {
+            return Ok(value);
+        }
+        shift = shift.checked_mul(0x80).ok_or(OVERFLOW)?;
+        value = value.checked_add(shift).ok_or(OVERFLOW)?;
+    }
+}
+
+fn read_offset(data: &[u8], pos: &mut usize) -> Result<i64, String> {
+    let raw = read_number(data, pos)?;
+    let magnitude = (raw >> 1) as i64; // at most 2^63 - 1, so the negation can't overflow
+    Ok(if raw & 1 != 0 { -magnitude } else { magnitude })
+}
@@ -0,0 +1,218 @@
+//! Builds the smw port from source for platforms without a prebuilt binary.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::{streamer, GamepadAction};
+
+const VISIBLE_LINES: usize = 18;
+
+#[derive(Default)]
+struct BuildState {
+    lines: VecDeque<String>,
+    status: String,
+    outcome: Option<Result<PathBuf, String>>,
+    reported: bool, // the outcome was handed to `take_outcome`
+}
+
+/// Clones or updates the source and runs its build on a thread of its own. The launcher keeps
+/// the handle, so leaving the view doesn't lose the build and reopening it shows the same one.
+#[derive(Clone)]
+pub struct SourceBuild {
+    state: Arc<Mutex<BuildState>>,
+}
+
+impl SourceBuild {
+    /// `target` is where the finished binary is installed, normally the launcher's game executable.
+    pub fn start(repo: String, source_dir: PathBuf, target: PathBuf) -> Self {
+        let state = Arc::new(Mutex::new(BuildState { status: "Building...".to_string(), ..BuildState::default() }));
+        let shared = Arc::clone(&state);
+        thread::spawn(move || {
+            let result = build(&repo, &source_dir, &target, &shared);
+            match &result {
+                Ok(path) => println!("Installed smw build to {}", streamer::path(path)),
+                Err(e) => eprintln!("smw build failed: {}", e),
+            }
+            if let Ok(mut state) = shared.lock() {
+                state.status = match &result {
+                    Ok(path) => format!("Done, installed {}", streamer::path(path)),
+                    Err(e) => format!("Build failed: {}", streamer::scrub(e)),
+                };
+                state.outcome = Some(result);
+            }
+        });
+        SourceBuild { state }
+    }
+
+    pub fn running(&self) -> bool {
+        self.state.lock().map(|state| state.outcome.is_none()).unwrap_or(false)
+    }
+
+    /// How the build went, once, after it has ended.
+    pub fn take_outcome(&self) -> Option<String> {
+        let mut state = self.state.lock().ok()?;
+        if state.reported {
+            return None;
+        }
+        let message = match state.outcome.as_ref()? {
+            Ok(_) => "smw build finished".to_string(),
+            Err(e) => format!("smw build failed: {}", e),
+        };
+        state.reported = true;
+        Some(message)
+    }
+
+    pub fn view(&self) -> BuildView {
+        BuildView { build: self.clone() }
+    }
+}
+
+/// Shows a build's compiler output as it arrives.
+pub struct BuildView {
+    build: SourceBuild,
+}
+
+impl View for BuildView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => ViewAction::Close,
+            _ => ViewAction::None,
+        }
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Back => ViewAction::Close,
+            _ => ViewAction::None,
+        }
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Build smw from source")?;
+        let Some(f) = font else {
+            return Ok(());
+        };
+        let (lines, status) = match self.build.state.lock() {
+            Ok(state) => (state.lines.iter().cloned().collect::<Vec<_>>(), state.status.clone()),
+            Err(_) => return Ok(()),
+        };
+        let gray = Color::RGB(180, 180, 180);
+        for (idx, line) in lines.iter().enumerate() {
+            ui::draw_text(canvas, texture_creator, f, line, 30, 75 + idx as i32 * 26, gray)?;
+        }
+        ui::draw_text(canvas, texture_creator, f, &status, 30, 580, ui::accent_color())?;
+        let help = if self.build.running() { "Esc: back (the build keeps running)" } else { "Esc: back" };
+        ui::draw_text(canvas, texture_creator, f, help, 30, 620, ui::text_color())?;
+        Ok(())
+    }
+}
+
+/// Adds a line of build output, keeping the last `VISIBLE_LINES`.
+fn log(state: &Mutex<BuildState>, line: String) {
+    if let Ok(mut state) = state.lock() {
+        state.lines.push_back(streamer::scrub(&line));
+        if state.lines.len() > VISIBLE_LINES {
+            state.lines.pop_front();
+        }
+    }
+}
+
+fn build(repo: &str, source_dir: &Path, target: &Path, state: &Arc<Mutex<BuildState>>) -> Result<PathBuf, String> {
+    if source_dir.join(".git").is_dir() {
+        run(Command::new("git").arg("-C").arg(source_dir).args(["pull", "--ff-only"]), state)?;
+    } else {
+        run(Command::new("git").args(["clone", "--depth", "1", repo]).arg(source_dir), state)?;
+    }
+
+    let jobs = thread::available_parallelism().map(|n| n.get()).unwrap_or(2).to_string();
+    if source_dir.join("Makefile").is_file() {
+        run(Command::new("make").arg("-j").arg(&jobs).current_dir(source_dir), state)?;
+    } else if source_dir.join("CMakeLists.txt").is_file() {
+        let build_dir = source_dir.join("build");
+        run(
+            Command::new("cmake")
+                .arg("-S")
+                .arg(source_dir)
+                .arg("-B")
+                .arg(&build_dir)
+                .arg("-DCMAKE_BUILD_TYPE=Release"),
+            state,
+        )?;
+        run(
+            Command::new("cmake")
+                .arg("--build")
+                .arg(&build_dir)
+                .args(["--config", "Release", "--parallel", &jobs]),
+            state,
+        )?;
+    } else {
+        return Err("No Makefile or CMakeLists.txt in the source".to_string());
+    }
+
+    let exe_name = if cfg!(target_os = "windows") { "smw.exe" } else { "smw" };
+    let built = [
+        source_dir.join(exe_name),
+        source_dir.join("build").join(exe_name),
+        source_dir.join("build").join("Release").join(exe_name),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .ok_or("The build finished but no smw binary was produced")?;
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::copy(&built, target).map_err(|e| e.to_string())?;
+    Ok(target.to_path_buf())
+}
+
+/// Runs one build step, forwarding stdout and stderr line by line.
+fn run(command: &mut Command, state: &Arc<Mutex<BuildState>>) -> Result<(), String> {
+    log(state, format!("$ {:?}", command));
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run {:?}: {}", command.get_program(), e))?;
+
+    let stderr = child.stderr.take().map(|stderr| {
+        let state = Arc::clone(state);
+        thread::spawn(move || forward(stderr, &state))
+    });
+    if let Some(stdout) = child.stdout.take() {
+        forward(stdout, state);
+    }
+    if let Some(handle) = stderr {
+        let _ = handle.join();
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{:?} exited with {}", command.get_program(), status))
+    }
+}
+
+fn forward(output: impl Read, state: &Arc<Mutex<BuildState>>) {
+    for line in BufReader::new(output).lines().map_while(Result::ok) {
+        log(state, line);
+    }
+}
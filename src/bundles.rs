@@ -0,0 +1,62 @@
+//! Bundles: a `.json` file in sfcs/ that shows several ROMs as one card with a game menu, the
+//! way the SMAS + SMW cart offered all its games from one title screen. For example
+//! `allstars+world.json`:
+//!
+//! ```json
+//! { "title": "Super Mario All-Stars + World",
+//!   "games": [{ "name": "Super Mario Bros.", "rom": "smb1.sfc" },
+//!             { "name": "Super Mario World", "rom": "smw.sfc" }] }
+//! ```
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::streamer;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BundleGame {
+    pub name: String,
+    pub rom: String, // file name in sfcs/
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Bundle {
+    pub title: String,
+    pub games: Vec<BundleGame>,
+}
+
+impl Bundle {
+    pub fn contains(&self, rom: &str) -> bool {
+        self.games.iter().any(|g| g.rom == rom)
+    }
+}
+
+/// Every bundle in `sfc_dir`, by file name. Broken files are skipped with a message.
+pub fn load_all(sfc_dir: &Path) -> Vec<Bundle> {
+    let mut files: Vec<_> = match fs::read_dir(sfc_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("json")).unwrap_or(false))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    files.sort();
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let parsed = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<Bundle>(&content).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(bundle) if !bundle.games.is_empty() => Some(bundle),
+                Ok(_) => None,
+                Err(e) => {
+                    eprintln!("Skipping bundle {}: {}", streamer::path(&path), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,93 @@
+//! Shared on-disk cache for online lookups (SMW Central listings and hack downloads, news) in
+//! `launcher/cache/`. A copy younger than `cache_ttl_hours` is used without asking the network,
+//! and an older one still stands in when the network is unreachable.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::net;
+use crate::rom::crc32;
+
+/// TTLs the options menu cycles through, in hours.
+pub const TTL_HOURS: [u32; 5] = [1, 6, 24, 72, 168];
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+pub fn cache_dir(launcher_dir: &Path) -> PathBuf {
+    launcher_dir.join("cache")
+}
+
+impl Cache {
+    pub fn new(launcher_dir: &Path, ttl_hours: u32) -> Self {
+        Cache { dir: cache_dir(launcher_dir), ttl: Duration::from_secs(ttl_hours as u64 * 60 * 60) }
+    }
+
+    fn entry(&self, url: &str) -> PathBuf {
+        // The length tells apart the rare URLs with the same checksum
+        self.dir.join(format!("{:08x}-{:x}", crc32(url.as_bytes()), url.len()))
+    }
+
+    fn age(path: &Path) -> Option<Duration> {
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        Some(SystemTime::now().duration_since(modified).unwrap_or_default())
+    }
+
+    /// The cached copy of `url` while it's within the TTL, else a fresh download that replaces it.
+    pub fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        let path = self.entry(url);
+        if Self::age(&path).map(|age| age < self.ttl).unwrap_or(false) {
+            if let Ok(data) = fs::read(&path) {
+                return Ok(data);
+            }
+        }
+        let data = net::download(url)?;
+        if let Err(e) = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, &data)) {
+            eprintln!("Failed to cache {}: {}", url, e);
+        }
+        Ok(data)
+    }
+
+    /// Whatever copy of `url` is cached, however old.
+    pub fn stale(&self, url: &str) -> Option<Vec<u8>> {
+        fs::read(self.entry(url)).ok()
+    }
+
+    /// `fetch`, falling back to an expired copy when the download fails.
+    pub fn download(&self, url: &str) -> Result<Vec<u8>, String> {
+        self.fetch(url).or_else(|e| match self.stale(url) {
+            Some(data) => {
+                eprintln!("Using the cached copy of {}: {}", url, e);
+                Ok(data)
+            }
+            None => Err(e),
+        })
+    }
+
+    pub fn get_json(&self, url: &str) -> Result<serde_json::Value, String> {
+        serde_json::from_slice(&self.download(url)?).map_err(|e| e.to_string())
+    }
+}
+
+/// Bytes used by the cache.
+pub fn size(launcher_dir: &Path) -> u64 {
+    fs::read_dir(cache_dir(launcher_dir))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+pub fn clear(launcher_dir: &Path) -> Result<(), String> {
+    let dir = cache_dir(launcher_dir);
+    if !dir.exists() {
+        return Ok(());
+    }
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())
+}
@@ -0,0 +1,227 @@
+//! HSV color picker for the theme editor, laid out for a controller on the couch: the stick or
+//! D-pad moves across a hue/saturation grid and the triggers turn the value (brightness) down or
+//! up. Keyboard (arrows, Page Up/Down) and mouse (click the grid or the value bar, wheel) work too.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::font::Font;
+use crate::{ui, GamepadAction};
+
+const HUE_STEPS: i32 = 24; // grid columns, 15 degrees apart
+const SATURATION_STEPS: i32 = 10; // grid rows, full color at the top down to grey
+const VALUE_STEP: i32 = 5; // percent per trigger press
+const CELL: i32 = 16;
+const PANEL: (i32, i32, u32, u32) = (10, 50, 400, 380); // over the theme editor's list
+
+pub enum PickerInput {
+    None,
+    Changed((u8, u8, u8)),
+    Done,
+    Cancel((u8, u8, u8)), // the color the picker was opened with
+}
+
+pub struct ColorPicker {
+    title: String,
+    hue: i32,        // grid column
+    saturation: i32, // grid row, 0 = fully saturated
+    value: i32,      // percent
+    original: (u8, u8, u8),
+}
+
+impl ColorPicker {
+    pub fn open(title: &str, color: (u8, u8, u8)) -> Self {
+        let (h, s, v) = rgb_to_hsv(color);
+        ColorPicker {
+            title: title.to_string(),
+            hue: (h / (360.0 / HUE_STEPS as f32)).round() as i32 % HUE_STEPS,
+            saturation: ((1.0 - s) * (SATURATION_STEPS - 1) as f32).round() as i32,
+            value: (v * 100.0).round() as i32,
+            original: color,
+        }
+    }
+
+    fn hsv(&self, hue: i32, saturation: i32, value: i32) -> (u8, u8, u8) {
+        let h = hue as f32 * 360.0 / HUE_STEPS as f32;
+        let s = 1.0 - saturation as f32 / (SATURATION_STEPS - 1) as f32;
+        hsv_to_rgb(h, s, value as f32 / 100.0)
+    }
+
+    pub fn color(&self) -> (u8, u8, u8) {
+        self.hsv(self.hue, self.saturation, self.value)
+    }
+
+    fn moved(&mut self, hue: i32, saturation: i32, value: i32) -> PickerInput {
+        let before = (self.hue, self.saturation, self.value);
+        self.hue = hue.rem_euclid(HUE_STEPS);
+        self.saturation = saturation.clamp(0, SATURATION_STEPS - 1);
+        self.value = value.clamp(0, 100);
+        if (self.hue, self.saturation, self.value) == before {
+            PickerInput::None
+        } else {
+            PickerInput::Changed(self.color())
+        }
+    }
+
+    fn step(&mut self, hue: i32, saturation: i32, value: i32) -> PickerInput {
+        self.moved(self.hue + hue, self.saturation + saturation, self.value + value * VALUE_STEP)
+    }
+
+    fn panel_rect() -> Rect {
+        Rect::new(PANEL.0, PANEL.1, PANEL.2, PANEL.3)
+    }
+
+    fn grid_rect() -> Rect {
+        let panel = Self::panel_rect();
+        Rect::new(panel.x() + 8, panel.y() + 40, (HUE_STEPS * CELL) as u32, (SATURATION_STEPS * CELL) as u32)
+    }
+
+    fn value_rect() -> Rect {
+        let grid = Self::grid_rect();
+        Rect::new(grid.x(), grid.bottom() + 12, grid.width(), CELL as u32)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> PickerInput {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Left), .. } => self.step(-1, 0, 0),
+            Event::KeyDown { keycode: Some(Keycode::Right), .. } => self.step(1, 0, 0),
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.step(0, -1, 0),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => self.step(0, 1, 0),
+            Event::KeyDown { keycode: Some(Keycode::PageDown), .. } => self.step(0, 0, -1),
+            Event::KeyDown { keycode: Some(Keycode::PageUp), .. } => self.step(0, 0, 1),
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => PickerInput::Done,
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => PickerInput::Cancel(self.original),
+            Event::MouseWheel { y, .. } => self.step(0, 0, y.signum()),
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let (grid, bar) = (Self::grid_rect(), Self::value_rect());
+                if grid.contains_point((*x, *y)) {
+                    self.moved((x - grid.x()) / CELL, (y - grid.y()) / CELL, self.value)
+                } else if bar.contains_point((*x, *y)) {
+                    let value = (x - bar.x()) * 100 / (bar.width() as i32 - 1);
+                    self.moved(self.hue, self.saturation, value)
+                } else {
+                    PickerInput::None
+                }
+            }
+            _ => PickerInput::None,
+        }
+    }
+
+    pub fn handle_gamepad(&mut self, action: &GamepadAction) -> PickerInput {
+        match action {
+            GamepadAction::Left => self.step(-1, 0, 0),
+            GamepadAction::Right => self.step(1, 0, 0),
+            GamepadAction::Up => self.step(0, -1, 0),
+            GamepadAction::Down => self.step(0, 1, 0),
+            // The triggers are tabs or pages depending on the key setup, either pair works here
+            GamepadAction::PrevTab | GamepadAction::PrevPage => self.step(0, 0, -1),
+            GamepadAction::NextTab | GamepadAction::NextPage => self.step(0, 0, 1),
+            GamepadAction::Confirm => PickerInput::Done,
+            GamepadAction::Back => PickerInput::Cancel(self.original),
+            _ => PickerInput::None,
+        }
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        let panel = Self::panel_rect();
+        canvas.set_draw_color(ui::panel_color());
+        canvas.fill_rect(panel)?;
+        canvas.set_draw_color(ui::accent_color());
+        canvas.draw_rect(panel)?;
+
+        // The grid shows every hue and saturation at the current value
+        let grid = Self::grid_rect();
+        for row in 0..SATURATION_STEPS {
+            for col in 0..HUE_STEPS {
+                let (r, g, b) = self.hsv(col, row, self.value);
+                canvas.set_draw_color(Color::RGB(r, g, b));
+                canvas.fill_rect(Rect::new(grid.x() + col * CELL, grid.y() + row * CELL, CELL as u32, CELL as u32))?;
+            }
+        }
+        let cell = Rect::new(grid.x() + self.hue * CELL, grid.y() + self.saturation * CELL, CELL as u32, CELL as u32);
+        draw_marker(canvas, cell)?;
+
+        let bar = Self::value_rect();
+        for dx in 0..bar.width() as i32 {
+            let (r, g, b) = self.hsv(self.hue, self.saturation, dx * 100 / (bar.width() as i32 - 1));
+            canvas.set_draw_color(Color::RGB(r, g, b));
+            canvas.draw_line((bar.x() + dx, bar.y()), (bar.x() + dx, bar.bottom() - 1))?;
+        }
+        let x = bar.x() + self.value * (bar.width() as i32 - 4) / 100;
+        draw_marker(canvas, Rect::new(x, bar.y(), 4, bar.height()))?;
+
+        // Before and after, side by side
+        let swatch_y = bar.bottom() + 12;
+        let (r, g, b) = self.original;
+        canvas.set_draw_color(Color::RGB(r, g, b));
+        canvas.fill_rect(Rect::new(grid.x(), swatch_y, 60, 30))?;
+        let (r, g, b) = self.color();
+        canvas.set_draw_color(Color::RGB(r, g, b));
+        canvas.fill_rect(Rect::new(grid.x() + 60, swatch_y, 60, 30))?;
+        canvas.set_draw_color(ui::outline_color());
+        canvas.draw_rect(Rect::new(grid.x(), swatch_y, 120, 30))?;
+
+        if let Some(f) = font {
+            ui::draw_text(canvas, texture_creator, f, &self.title, panel.x() + 8, panel.y() + 8, ui::accent_color())?;
+            let saturation = 100 - self.saturation * 100 / (SATURATION_STEPS - 1);
+            let hsv = format!("H {}  S {}%  V {}%", self.hue * 360 / HUE_STEPS, saturation, self.value);
+            ui::draw_text(canvas, texture_creator, f, &hsv, grid.x() + 135, swatch_y + 2, ui::text_color())?;
+            let hint = "Stick: hue/saturation   Triggers: value";
+            ui::draw_text(canvas, texture_creator, f, hint, grid.x(), swatch_y + 40, ui::text_color())?;
+            ui::draw_text(canvas, texture_creator, f, "A/Enter: keep   B/Esc: undo", grid.x(), swatch_y + 68, ui::text_color())?;
+        }
+        Ok(())
+    }
+}
+
+/// Black and white outline so it shows on any color.
+fn draw_marker(canvas: &mut Canvas<Window>, rect: Rect) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.draw_rect(Rect::new(rect.x() - 2, rect.y() - 2, rect.width() + 4, rect.height() + 4))?;
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.draw_rect(Rect::new(rect.x() - 1, rect.y() - 1, rect.width() + 2, rect.height() + 2))
+}
+
+/// `h` in degrees, `s` and `v` from 0 to 1.
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match (h / 60.0) as i32 % 6 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let channel = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+fn rgb_to_hsv((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    (h, s, max)
+}
@@ -0,0 +1,221 @@
+//! Detects the installed smw build's version and checks it against what the launcher writes to smw.ini.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+pub type Version = (u32, u32, u32);
+
+/// Oldest supported smw version and the first one with an smw.ini layout the launcher doesn't know yet.
+const SUPPORTED: (Version, Version) = ((0, 1, 0), (1, 0, 0));
+
+/// smw.ini settings added after the oldest supported build, with the version that introduced them.
+const SETTINGS: &[(&str, Version)] = &[
+    ("NewRenderer", (0, 2, 0)),
+    ("Shader", (0, 2, 0)),
+    ("NoSpriteLimits", (0, 3, 0)),
+    ("GamepadControls", (0, 3, 0)),
+];
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(2);
+/// Command line flag of smw builds that can start from a savestate, followed by the state's path.
+pub const LOAD_STATE_FLAG: &str = "--load-state";
+/// Command line flag of smw builds that keep saves in another folder, followed by its path.
+pub const SAVE_DIR_FLAG: &str = "--save-dir";
+
+// Detection reads the whole binary or runs it, so the result is kept until the file changes
+static CACHE: Mutex<Option<(PathBuf, SystemTime, Option<Version>)>> = Mutex::new(None);
+static FLAG_CACHE: Mutex<Vec<(PathBuf, &str, SystemTime, bool)>> = Mutex::new(Vec::new());
+
+/// Version of the smw executable as far as it is known without running it: an embedded version
+/// string, or the sidecar file an earlier `smw --version` left next to it.
+pub fn known_version(exe: &Path) -> Option<Version> {
+    let modified = fs::metadata(exe).and_then(|m| m.modified()).ok()?;
+    if let Some(version) = cached(exe, modified) {
+        return version;
+    }
+    let version = embedded_version(exe).or_else(|| sidecar_version(exe, modified));
+    if version.is_some() {
+        remember(exe, modified, version);
+    }
+    version
+}
+
+/// `known_version`, otherwise None while `smw --version` runs in the background. Its answer is
+/// cached and written to the sidecar, so the next check has it.
+pub fn game_version(exe: &Path) -> Option<Version> {
+    let modified = fs::metadata(exe).and_then(|m| m.modified()).ok()?;
+    if let Some(version) = cached(exe, modified) {
+        return version;
+    }
+    if let Some(version) = known_version(exe) {
+        return Some(version);
+    }
+
+    // Cached as unknown right away so the probe runs once per build
+    remember(exe, modified, None);
+    let exe = exe.to_path_buf();
+    thread::spawn(move || {
+        let version = version_flag(&exe);
+        if let Some(version) = version {
+            if let Err(e) = fs::write(sidecar(&exe), format_version(version)) {
+                eprintln!("Failed to save the smw version: {}", e);
+            }
+        }
+        remember(&exe, modified, version);
+    });
+    None
+}
+
+fn cached(exe: &Path, modified: SystemTime) -> Option<Option<Version>> {
+    let cache = CACHE.lock().ok()?;
+    let (path, time, version) = cache.as_ref()?;
+    (path == exe && *time == modified).then_some(*version)
+}
+
+fn remember(exe: &Path, modified: SystemTime, version: Option<Version>) {
+    if let Ok(mut cache) = CACHE.lock() {
+        *cache = Some((exe.to_path_buf(), modified, version));
+    }
+}
+
+/// smw.version next to smw(.exe), holding what `smw --version` said.
+fn sidecar(exe: &Path) -> PathBuf {
+    exe.with_extension("version")
+}
+
+/// The sidecar's version, unless the executable was replaced after it was written.
+fn sidecar_version(exe: &Path, modified: SystemTime) -> Option<Version> {
+    let path = sidecar(exe);
+    let written = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    if written < modified {
+        return None;
+    }
+    parse_version(fs::read_to_string(&path).ok()?.trim())
+}
+
+/// True when the smw build knows `LOAD_STATE_FLAG`.
+pub fn can_load_state(exe: &Path) -> bool {
+    knows_flag(exe, LOAD_STATE_FLAG)
+}
+
+/// True when the smw build knows `SAVE_DIR_FLAG`.
+pub fn can_relocate_saves(exe: &Path) -> bool {
+    knows_flag(exe, SAVE_DIR_FLAG)
+}
+
+/// Looks for a command line flag among the binary's strings, the way its usage text would carry it.
+fn knows_flag(exe: &Path, flag: &'static str) -> bool {
+    let Some(modified) = fs::metadata(exe).and_then(|m| m.modified()).ok() else {
+        return false;
+    };
+    if let Ok(cache) = FLAG_CACHE.lock() {
+        let cached = cache.iter().find(|(path, f, time, _)| path == exe && *f == flag && *time == modified);
+        if let Some((_, _, _, supported)) = cached {
+            return *supported;
+        }
+    }
+
+    let bytes = flag.as_bytes();
+    let supported = fs::read(exe).map(|data| data.windows(bytes.len()).any(|w| w == bytes)).unwrap_or(false);
+    if let Ok(mut cache) = FLAG_CACHE.lock() {
+        cache.retain(|(path, f, _, _)| !(path == exe && *f == flag));
+        cache.push((exe.to_path_buf(), flag, modified, supported));
+    }
+    supported
+}
+
+/// Looks for "smw v1.2.3" / "smw version 1.2.3" among the binary's strings.
+fn embedded_version(exe: &Path) -> Option<Version> {
+    let data = fs::read(exe).ok()?;
+    let markers: [&[u8]; 2] = [b"smw v", b"smw version "];
+    markers.iter().find_map(|marker| {
+        data.windows(marker.len())
+            .enumerate()
+            .filter(|(_, window)| window.eq_ignore_ascii_case(marker))
+            .find_map(|(pos, _)| {
+                let start = pos + marker.len();
+                let text = String::from_utf8_lossy(&data[start..(start + 16).min(data.len())]);
+                parse_version(&text)
+            })
+    })
+}
+
+/// Runs `smw --version`; builds that don't know the flag start the game instead, so it is killed after a moment.
+fn version_flag(exe: &Path) -> Option<Version> {
+    let mut child = Command::new(exe)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < VERSION_TIMEOUT => thread::sleep(Duration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    output.split_whitespace().find_map(|word| parse_version(word.trim_start_matches('v')))
+}
+
+/// Parses the leading "1.2" or "1.2.3" of `text`.
+pub fn parse_version(text: &str) -> Option<Version> {
+    let end = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let mut parts = text[..end].trim_end_matches('.').split('.').map(|p| p.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = match parts.next() {
+        Some(patch) => patch.ok()?,
+        None => 0,
+    };
+    Some((major, minor, patch))
+}
+
+pub fn format_version((major, minor, patch): Version) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// Warnings for a game build outside the supported range or missing settings the launcher writes.
+pub fn check(version: Version) -> Vec<String> {
+    let (oldest, too_new) = SUPPORTED;
+    if version < oldest {
+        return vec![format!(
+            "smw {} is too old for this launcher, update it to {} or newer",
+            format_version(version),
+            format_version(oldest)
+        )];
+    }
+    if version >= too_new {
+        return vec![format!(
+            "smw {} is newer than this launcher supports, some settings may be ignored",
+            format_version(version)
+        )];
+    }
+    let missing: Vec<&str> = SETTINGS
+        .iter()
+        .filter(|(_, since)| version < *since)
+        .map(|(setting, _)| *setting)
+        .collect();
+    if missing.is_empty() {
+        Vec::new()
+    } else {
+        vec![format!(
+            "smw {} ignores these settings: {}",
+            format_version(version),
+            missing.join(", ")
+        )]
+    }
+}
@@ -0,0 +1,109 @@
+//! Reading and writing the launcher options as `launcher.toml` or `launcher.json`.
+
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+/// The options file in use; TOML wins when both exist, JSON is the default for new installs.
+pub fn locate(launcher_dir: &Path) -> (PathBuf, Format) {
+    let toml_path = launcher_dir.join("launcher.toml");
+    if toml_path.exists() {
+        (toml_path, Format::Toml)
+    } else {
+        (launcher_dir.join("launcher.json"), Format::Json)
+    }
+}
+
+/// Parses the options, naming the offending field along with the parser's line and column.
+pub fn parse<T: DeserializeOwned>(content: &str, format: Format) -> Result<T, String> {
+    match format {
+        Format::Json => {
+            let mut deserializer = serde_json::Deserializer::from_str(content);
+            serde_path_to_error::deserialize(&mut deserializer).map_err(|e| describe(e.path(), e.inner()))
+        }
+        Format::Toml => serde_path_to_error::deserialize(toml::Deserializer::new(content))
+            .map_err(|e| describe(e.path(), e.inner())),
+    }
+}
+
+fn describe(path: &serde_path_to_error::Path, error: &dyn std::fmt::Display) -> String {
+    if path.iter().next().is_none() {
+        error.to_string()
+    } else {
+        format!("{}: {}", path, error)
+    }
+}
+
+/// Moves a broken options file aside as `<name>.<timestamp>.bak` and returns the new path.
+pub fn backup(path: &Path) -> Result<PathBuf, String> {
+    let backup = backup_path(path);
+    fs::rename(path, &backup).map_err(|e| e.to_string())?;
+    Ok(backup)
+}
+
+/// Like `backup` but leaves the file in place, for before it gets overwritten wholesale.
+pub fn copy_backup(path: &Path) -> Result<PathBuf, String> {
+    let backup = backup_path(path);
+    fs::copy(path, &backup).map_err(|e| e.to_string())?;
+    Ok(backup)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("launcher.json");
+    path.with_file_name(format!("{}.{}.bak", name, Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+/// Writes `value` to `path`. An existing TOML file is updated in place so comments and key order survive.
+pub fn save<T: Serialize>(path: &Path, format: Format, value: &T) -> Result<(), String> {
+    let content = match format {
+        Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string())?,
+        Format::Toml => {
+            let updated = toml_edit::ser::to_document(value).map_err(|e| e.to_string())?;
+            match fs::read_to_string(path).ok().and_then(|old| old.parse::<DocumentMut>().ok()) {
+                Some(mut document) => {
+                    merge(document.as_table_mut(), updated.as_table());
+                    document.to_string()
+                }
+                None => updated.to_string(),
+            }
+        }
+    };
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Copies values from `updated` into `existing`, keeping the comments and formatting around each key.
+fn merge(existing: &mut Table, updated: &Table) {
+    let stale: Vec<String> = existing
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !updated.contains_key(key))
+        .collect();
+    for key in stale {
+        existing.remove(&key);
+    }
+
+    for (key, item) in updated.iter() {
+        match (existing.get_mut(key), item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge(old, new),
+            // The serializer emits nested structs inline, keep the user's [section] layout instead
+            (Some(Item::Table(old)), Item::Value(Value::InlineTable(new))) => merge(old, &new.clone().into_table()),
+            (Some(Item::Value(old)), Item::Value(new)) => {
+                let decor = old.decor().clone();
+                *old = new.clone();
+                *old.decor_mut() = decor;
+            }
+            _ => {
+                existing.insert(key, item.clone());
+            }
+        }
+    }
+}
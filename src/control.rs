@@ -0,0 +1,134 @@
+//! Local control API for stream decks, scripts and home automation.
+//!
+//! Clients connect to `launcher/control.sock` (on Windows, a localhost TCP port written to
+//! `launcher/control.port`) and send one JSON request per line, getting one JSON reply per line:
+//!
+//! - `{"cmd": "list"}` -> `{"ok": true, "games": [{"rom": "smw.sfc", "title": "smw", "hidden": false}]}`
+//! - `{"cmd": "launch", "rom": "smw.sfc"}` -> `{"ok": true}`
+//! - `{"cmd": "focus"}` -> `{"ok": true}`
+//! - `{"cmd": "status"}` -> `{"ok": true, "running": "smw.sfc", "title": "smw", "playing_secs": 42, "selected": "smw.sfc"}`
+//!
+//! Failures reply `{"ok": false, "error": "..."}`.
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// A request waiting for the main loop, which owns the launcher state.
+pub struct ControlRequest {
+    pub command: Value,
+    reply: Sender<Value>,
+}
+
+impl ControlRequest {
+    pub fn new(command: Value, reply: Sender<Value>) -> Self {
+        ControlRequest { command, reply }
+    }
+
+    pub fn respond(self, reply: Value) {
+        let _ = self.reply.send(reply);
+    }
+}
+
+pub fn error(message: impl Into<String>) -> Value {
+    json!({ "ok": false, "error": message.into() })
+}
+
+pub struct ControlServer {
+    tx: Sender<ControlRequest>,
+    rx: Receiver<ControlRequest>,
+}
+
+impl ControlServer {
+    pub fn start(launcher_dir: &Path) -> Result<ControlServer, String> {
+        let (tx, rx) = mpsc::channel();
+        listen(launcher_dir, tx.clone())?;
+        Ok(ControlServer { tx, rx })
+    }
+
+    /// Sender other front ends (e.g. the remote page) use to queue requests.
+    pub fn sender(&self) -> Sender<ControlRequest> {
+        self.tx.clone()
+    }
+
+    pub fn pending(&self) -> Vec<ControlRequest> {
+        self.rx.try_iter().collect()
+    }
+}
+
+#[cfg(unix)]
+fn listen(launcher_dir: &Path, tx: Sender<ControlRequest>) -> Result<(), String> {
+    use crate::streamer;
+    use std::os::unix::net::UnixListener;
+
+    let path = launcher_dir.join("control.sock");
+    // A socket left behind by a previous run would make bind fail
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).map_err(|e| format!("{}: {}", streamer::path(&path), e))?;
+    println!("Control API listening on {}", streamer::path(&path));
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                if let Ok(writer) = stream.try_clone() {
+                    serve(stream, writer, tx);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn listen(launcher_dir: &Path, tx: Sender<ControlRequest>) -> Result<(), String> {
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    std::fs::write(launcher_dir.join("control.port"), port.to_string()).map_err(|e| e.to_string())?;
+    println!("Control API listening on 127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                if let Ok(writer) = stream.try_clone() {
+                    serve(stream, writer, tx);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn serve(reader: impl Read, mut writer: impl Write, tx: Sender<ControlRequest>) {
+    for line in BufReader::new(reader).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str(&line) {
+            Ok(command) => request(&tx, command),
+            Err(e) => error(format!("Bad request: {}", e)),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// Queues a command for the main loop and waits for its reply.
+pub fn request(tx: &Sender<ControlRequest>, command: Value) -> Value {
+    let (reply_tx, reply_rx) = mpsc::channel();
+    if tx.send(ControlRequest::new(command, reply_tx)).is_err() {
+        return error("Launcher is shutting down");
+    }
+    reply_rx.recv().unwrap_or_else(|_| error("No reply from launcher"))
+}
@@ -0,0 +1,97 @@
+//! Cover packs: a zip of cover images installed into `launcher/pngs/` without going online. An
+//! image is matched to a ROM by its file name (`smw.png` covers `smw.sfc`) or by the ROM's
+//! library ID (`a31bead4.jpg`), so packs made for other file names still find their games.
+//! Folders inside the zip are ignored, and ROMs that already have a cover keep it.
+
+use image::ImageFormat;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::textures::COVER_EXTENSIONS;
+
+/// What an import did, for the summary menu.
+#[derive(Default)]
+pub struct ImportReport {
+    pub installed: Vec<String>, // ROM file names that got a cover
+    pub kept: usize, // already had a cover
+    pub invalid: usize, // not a readable image, or not a format covers can use
+    pub unmatched: usize, // hash-keyed, but no ROM in the library has that ID
+}
+
+/// The extension a cover in `format` is saved with, None for formats covers can't use.
+fn extension(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Png => Some("png"),
+        ImageFormat::WebP => Some("webp"),
+        ImageFormat::Jpeg => Some("jpg"),
+        ImageFormat::Gif => Some("gif"),
+        _ => None,
+    }
+}
+
+/// A library ID is the ROM's CRC32 as 8 hex digits.
+fn is_id(stem: &str) -> bool {
+    stem.len() == 8 && stem.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Installs the covers in `archive` into `pngs_dir`. `files_by_id` maps library IDs to ROM file names.
+pub fn install(pngs_dir: &Path, archive: &[u8], files_by_id: &HashMap<String, String>) -> Result<ImportReport, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| format!("Not a zip file: {}", e))?;
+    fs::create_dir_all(pngs_dir).map_err(|e| e.to_string())?;
+
+    let mut report = ImportReport::default();
+    for idx in 0..zip.len() {
+        let mut file = zip.by_index(idx).map_err(|e| e.to_string())?;
+        let Some(name) = file.enclosed_name().and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned())) else {
+            continue;
+        };
+        if file.is_dir() || name.starts_with('.') {
+            continue;
+        }
+        let stem = match name.rsplit_once('.') {
+            Some((stem, ext)) if COVER_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()) => stem.to_string(),
+            _ => continue, // readmes and the like
+        };
+
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        // The content decides the format, a renamed BMP is still a BMP
+        let ext = match image::guess_format(&data).ok().and_then(|f| extension(f).map(|ext| (f, ext))) {
+            Some((format, ext)) if image::load_from_memory_with_format(&data, format).is_ok() => ext,
+            _ => {
+                eprintln!("Skipping {} from the cover pack: not a PNG, WebP, JPEG or GIF image", name);
+                report.invalid += 1;
+                continue;
+            }
+        };
+
+        let rom_stem = if is_id(&stem) {
+            match files_by_id.get(&stem.to_ascii_lowercase()) {
+                Some(file) => file.trim_end_matches(".sfc").to_string(),
+                None => {
+                    report.unmatched += 1;
+                    continue;
+                }
+            }
+        } else {
+            stem
+        };
+        if COVER_EXTENSIONS.iter().any(|ext| pngs_dir.join(format!("{}.{}", rom_stem, ext)).exists()) {
+            report.kept += 1;
+            continue;
+        }
+        fs::write(pngs_dir.join(format!("{}.{}", rom_stem, ext)), &data).map_err(|e| e.to_string())?;
+        report.installed.push(format!("{}.sfc", rom_stem));
+    }
+
+    println!(
+        "Cover pack: {} installed, {} kept, {} invalid, {} unmatched",
+        report.installed.len(),
+        report.kept,
+        report.invalid,
+        report.unmatched
+    );
+    Ok(report)
+}
@@ -0,0 +1,147 @@
+//! Optional CRT look for the launcher (`crt` in theme.json). A frame is drawn into a texture and
+//! copied back to the window in horizontal strips, narrower towards the top and bottom like a
+//! curved tube, with dark scanlines on top. Renderers without render targets, and machines that
+//! keep missing frames with the filter on, fall back to the scanlines alone drawn over the frame.
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::time::{Duration, Instant};
+
+pub const CRT_OFF: u8 = 0;
+pub const CRT_SCANLINES: u8 = 1;
+pub const CRT_CURVED: u8 = 2;
+
+const SCANLINE_SPACING: i32 = 3; // one dark row every this many
+const SCANLINE_ALPHA: u8 = 70;
+const STRIPS: u32 = 64; // slices of the frame drawn back, more is smoother and slower
+const CURVE: f32 = 0.035; // inset of the top and bottom rows, as a share of the width
+const SLOW_FRAMES: u32 = 120; // missed frames in a row before dropping the curvature
+
+pub fn label(mode: u8) -> &'static str {
+    match mode {
+        CRT_SCANLINES => "scanlines",
+        CRT_CURVED => "scanlines and curvature",
+        _ => "off",
+    }
+}
+
+pub struct Crt<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    target: Option<Texture<'a>>,
+    redirected: Option<Instant>, // when the current frame started going into `target`
+    slow_frames: u32,
+    fallback: bool, // curvature given up for this session
+}
+
+impl<'a> Crt<'a> {
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        Crt { texture_creator, target: None, redirected: None, slow_frames: 0, fallback: false }
+    }
+
+    /// Size of the frame the launcher draws: the logical size when SDL scales it, else the window.
+    fn frame_size(canvas: &Canvas<Window>) -> Result<(u32, u32), String> {
+        match canvas.logical_size() {
+            (0, 0) => canvas.output_size(),
+            size => Ok(size),
+        }
+    }
+
+    /// Sends the drawing that follows into the filter's texture when the mode needs one.
+    /// Call before anything of the frame is drawn.
+    pub fn begin(&mut self, canvas: &mut Canvas<Window>, mode: u8) -> Result<(), String> {
+        if mode != CRT_CURVED || self.fallback {
+            self.target = None;
+            return Ok(());
+        }
+        if !canvas.render_target_supported() {
+            eprintln!("CRT filter: the renderer can't draw to textures, using scanlines only");
+            self.fallback = true;
+            return Ok(());
+        }
+
+        let (width, height) = Self::frame_size(canvas)?;
+        let stale = self.target.as_ref().map(|t| {
+            let query = t.query();
+            (query.width, query.height) != (width, height)
+        });
+        if stale.unwrap_or(true) {
+            let texture = self
+                .texture_creator
+                .create_texture_target(PixelFormatEnum::ARGB8888, width, height)
+                .map_err(|e| e.to_string())?;
+            self.target = Some(texture);
+        }
+        if let Some(target) = &self.target {
+            // The sdl2 crate only offers render targets through a closure, which the main
+            // loop's drawing doesn't fit into
+            let result = unsafe { sdl2::sys::SDL_SetRenderTarget(canvas.raw(), target.raw()) };
+            if result != 0 {
+                eprintln!("CRT filter: failed to set the render target: {}", sdl2::get_error());
+                self.fallback = true;
+                self.target = None;
+                return Ok(());
+            }
+            self.redirected = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Puts the frame back on the window with the effect applied. Call right before `present`.
+    pub fn finish(&mut self, canvas: &mut Canvas<Window>, mode: u8) -> Result<(), String> {
+        // A theme preview can turn the filter off mid-frame, the window still needs the frame back
+        let redirected = self.target.is_some() && self.redirected.is_some();
+        if mode == CRT_OFF && !redirected {
+            return Ok(());
+        }
+        let (width, height) = Self::frame_size(canvas)?;
+        if let (Some(target), true) = (&self.target, redirected) {
+            unsafe { sdl2::sys::SDL_SetRenderTarget(canvas.raw(), std::ptr::null_mut()) };
+            canvas.set_draw_color(Color::RGB(0, 0, 0));
+            canvas.clear();
+            let strip = height.div_ceil(STRIPS);
+            let middle = height as f32 / 2.0;
+            for idx in 0..STRIPS {
+                let y = (idx * strip) as i32;
+                if y >= height as i32 {
+                    break;
+                }
+                let rows = strip.min(height - y as u32);
+                let offset = (y as f32 + rows as f32 / 2.0 - middle) / middle;
+                let inset = (CURVE * width as f32 * offset * offset) as i32;
+                let src = Rect::new(0, y, width, rows);
+                let dst = Rect::new(inset, y, (width as i32 - inset * 2).max(1) as u32, rows);
+                canvas.copy(target, src, dst)?;
+            }
+        }
+
+        let lines: Vec<Rect> = (0..height as i32)
+            .step_by(SCANLINE_SPACING as usize)
+            .map(|y| Rect::new(0, y, width, 1))
+            .collect();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, SCANLINE_ALPHA));
+        canvas.fill_rects(&lines)?;
+        canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+
+    /// Called after `present` so the time includes the GPU work; gives up the curvature when
+    /// frames keep taking half again as long as the display allows.
+    pub fn presented(&mut self, target_frame_time: Duration) {
+        let Some(started) = self.redirected.take() else {
+            return;
+        };
+        if started.elapsed() > target_frame_time * 3 / 2 {
+            self.slow_frames += 1;
+        } else {
+            self.slow_frames = 0;
+        }
+        if self.slow_frames >= SLOW_FRAMES {
+            println!("CRT filter: frames are too slow with curvature, using scanlines only");
+            self.fallback = true;
+            self.target = None;
+        }
+    }
+}
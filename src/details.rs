@@ -0,0 +1,152 @@
+//! The back of a card. The details key flips the selected card over, squashing it horizontally to
+//! nothing and opening it back up with the other side showing, to list what the launcher knows
+//! about the game next to a few quick actions.
+
+use chrono::{Local, TimeZone};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
+use crate::anim::Motion;
+use crate::font::Font;
+use crate::theme::{self, Theme};
+use crate::ui::{self, MenuAction};
+
+const FLIP_TIME: Duration = Duration::from_millis(300);
+const LINE_HEIGHT: i32 = 28;
+
+/// What the back of a card shows.
+pub struct Details {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub actions: Vec<(String, MenuAction)>,
+}
+
+/// Unix seconds as a local date for the details lines.
+pub fn date(secs: i64) -> String {
+    Local
+        .timestamp_opt(secs, 0)
+        .single()
+        .map(|t| t.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// A card turning over or showing its back.
+pub struct CardFlip {
+    pub card: usize,
+    pub selected: usize, // quick action under the selector
+    from: f32, // how far turned the card was when the flip started, 0 = front, 1 = back
+    open: bool,
+    since: Instant,
+    motion: Motion,
+}
+
+impl CardFlip {
+    pub fn open(card: usize, motion: Motion) -> Self {
+        CardFlip { card, selected: 0, from: 0.0, open: true, since: Instant::now(), motion }
+    }
+
+    /// Turns back to the front from wherever the card is now.
+    pub fn close(&mut self) {
+        if self.open {
+            self.from = self.turned();
+            self.open = false;
+            self.since = Instant::now();
+        }
+    }
+
+    /// 0 with the front showing, 1 with the back showing.
+    fn turned(&self) -> f32 {
+        let target = if self.open { 1.0 } else { 0.0 };
+        let distance = (target - self.from).abs();
+        let t = if distance == 0.0 {
+            1.0
+        } else {
+            (self.since.elapsed().as_secs_f32() / (self.motion.duration(FLIP_TIME).as_secs_f32() * distance)).min(1.0)
+        };
+        self.from + (target - self.from) * t
+    }
+
+    /// True while the back is up or on its way, so input belongs to the quick actions.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn is_turning(&self) -> bool {
+        let turned = self.turned();
+        turned > 0.0 && turned < 1.0
+    }
+
+    /// Back at the front, the flip can be dropped.
+    pub fn is_done(&self) -> bool {
+        !self.open && self.turned() <= 0.0
+    }
+
+    pub fn showing_back(&self) -> bool {
+        self.motion.ease(self.turned()) > 0.5
+    }
+
+    /// The card's rect squashed to how wide it looks at this point of the turn.
+    pub fn face(&self, rect: Rect) -> Rect {
+        let scale = (self.motion.ease(self.turned()) * PI).cos().abs();
+        let width = ((rect.width() as f32 * scale) as u32).max(1);
+        Rect::new(rect.x() + (rect.width() - width) as i32 / 2, rect.y(), width, rect.height())
+    }
+
+    pub fn move_selection(&mut self, delta: i32, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected as i32 + delta).rem_euclid(count as i32) as usize;
+        }
+    }
+}
+
+/// Draws the back of the card at `rect`; the text waits until the card has turned far enough to read it.
+pub fn draw_back(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    rect: Rect,
+    theme: &Theme,
+    details: &Details,
+    flip: &CardFlip,
+) -> Result<(), String> {
+    let face = flip.face(rect);
+    canvas.set_draw_color(theme::rgb(theme.card_color));
+    canvas.fill_rect(face)?;
+    canvas.set_draw_color(theme::rgb(theme.selector_color));
+    for i in 0..3 {
+        canvas.draw_rect(Rect::new(face.x() - i, face.y() - i, face.width() + i as u32 * 2, face.height() + i as u32 * 2))?;
+    }
+    if face.width() * 10 < rect.width() * 9 {
+        return Ok(());
+    }
+
+    let text_color = theme::rgb(theme.title_color);
+    let max_width = face.width().saturating_sub(20);
+    let (x, mut y) = (face.x() + 10, face.y() + 12);
+    let title = ui::ellipsize(font, &details.title, max_width)?;
+    ui::draw_text(canvas, texture_creator, font, &title, x, y, text_color)?;
+    y += LINE_HEIGHT + 8;
+    for line in &details.lines {
+        let line = ui::ellipsize(font, line, max_width)?;
+        ui::draw_text(canvas, texture_creator, font, &line, x, y, text_color)?;
+        y += LINE_HEIGHT;
+    }
+
+    // Quick actions stacked at the bottom, the selected one highlighted
+    let mut y = face.bottom() - 10 - details.actions.len() as i32 * LINE_HEIGHT;
+    for (idx, (label, _)) in details.actions.iter().enumerate() {
+        let selected = idx == flip.selected;
+        if selected {
+            canvas.set_draw_color(theme::rgb(theme.selector_color));
+            canvas.fill_rect(Rect::new(face.x() + 6, y - 2, face.width() - 12, LINE_HEIGHT as u32))?;
+        }
+        let color = if selected { Color::RGB(0, 0, 0) } else { text_color };
+        ui::draw_text(canvas, texture_creator, font, label, x, y, color)?;
+        y += LINE_HEIGHT;
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! `smas_launcher doctor`: checks the installation without changing anything and prints a report
+//! to paste into bug reports. The same report is written to `launcher/logs/doctor.log`.
+
+use gilrs::Gilrs;
+use sdl2::mixer::{self, AUDIO_S16LSB, DEFAULT_CHANNELS};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{compat, install, platform, streamer};
+
+/// Files the launcher uses from `launcher/`; each has a built-in fallback, so missing ones only warn.
+const ASSETS: [&str; 4] = ["MBG.png", "smw.ttf", "pointer.png", "UI/Cursor.png"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Level {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// What the checks look at, resolved the way the launcher itself would.
+pub struct Setup {
+    pub install_dir: PathBuf,
+    pub config: Result<PathBuf, String>, // the options file that loaded, or why it didn't
+    pub game_executable: PathBuf,
+}
+
+#[derive(Default)]
+pub struct Report {
+    entries: Vec<(Level, &'static str, String)>,
+}
+
+impl Report {
+    fn add(&mut self, level: Level, check: &'static str, detail: String) {
+        self.entries.push((level, check, detail));
+    }
+
+    pub fn failed(&self) -> bool {
+        self.entries.iter().any(|(level, _, _)| *level == Level::Fail)
+    }
+
+    pub fn text(&self, setup: &Setup) -> String {
+        let mut text = format!(
+            "SMAS Launcher doctor\nlauncher: {}\nplatform: {}-{} (sandbox: {:?})\ninstall dir: {}\n\n",
+            env!("CARGO_PKG_VERSION"),
+            env::consts::OS,
+            env::consts::ARCH,
+            platform::detect_sandbox(),
+            streamer::path(&setup.install_dir)
+        );
+        for (level, check, detail) in &self.entries {
+            let tag = match level {
+                Level::Ok => "[ OK ]",
+                Level::Warn => "[WARN]",
+                Level::Fail => "[FAIL]",
+            };
+            text.push_str(&format!("{} {}: {}\n", tag, check, detail));
+        }
+        let count = |wanted| self.entries.iter().filter(|(level, _, _)| *level == wanted).count();
+        text.push_str(&format!(
+            "\n{} ok, {} warning(s), {} failure(s)\n",
+            count(Level::Ok),
+            count(Level::Warn),
+            count(Level::Fail)
+        ));
+        text
+    }
+}
+
+pub fn run(setup: &Setup) -> Report {
+    let mut report = Report::default();
+    check_layout(&mut report, &setup.install_dir);
+    check_assets(&mut report, &setup.install_dir.join("launcher"));
+    check_game(&mut report, &setup.game_executable);
+    match &setup.config {
+        Ok(path) if path.exists() => report.add(Level::Ok, "Config", format!("{} is valid", streamer::path(path))),
+        Ok(path) => report.add(Level::Ok, "Config", format!("no {}, using the defaults", streamer::path(path))),
+        Err(e) => report.add(Level::Fail, "Config", e.clone()),
+    }
+    check_roms(&mut report, &setup.install_dir.join("sfcs"));
+    check_audio(&mut report);
+    check_gamepads(&mut report);
+    report
+}
+
+/// Writes the report next to the game logs.
+pub fn save(launcher_dir: &Path, text: &str) -> Result<PathBuf, String> {
+    let dir = launcher_dir.join("logs");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join("doctor.log");
+    fs::write(&path, text).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+fn check_layout(report: &mut Report, install_dir: &Path) {
+    let missing: Vec<&str> = install::DIRS.iter().copied().filter(|dir| !install_dir.join(dir).is_dir()).collect();
+    if missing.is_empty() {
+        report.add(Level::Ok, "Directories", install::DIRS.join(", "));
+    } else {
+        report.add(Level::Fail, "Directories", format!("missing {} (run `smas_launcher install`)", missing.join(", ")));
+    }
+}
+
+fn check_assets(report: &mut Report, launcher_dir: &Path) {
+    let missing: Vec<&str> = ASSETS.iter().copied().filter(|asset| !launcher_dir.join(asset).is_file()).collect();
+    if missing.is_empty() {
+        report.add(Level::Ok, "Assets", format!("{} present", ASSETS.len()));
+    } else {
+        report.add(Level::Warn, "Assets", format!("missing {}, the built-in copies are used", missing.join(", ")));
+    }
+}
+
+fn check_game(report: &mut Report, exe: &Path) {
+    let meta = match fs::metadata(exe) {
+        Ok(meta) if meta.is_file() => meta,
+        Ok(_) => return report.add(Level::Fail, "Game executable", format!("{} is not a file", streamer::path(exe))),
+        Err(e) => return report.add(Level::Fail, "Game executable", format!("{}: {}", streamer::path(exe), e)),
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if meta.permissions().mode() & 0o111 == 0 {
+            return report.add(Level::Fail, "Game executable", format!("{} is not executable (chmod +x)", streamer::path(exe)));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = meta;
+
+    // Without an embedded version or a sidecar from an earlier run it stays unknown, the probe runs smw
+    let version = compat::known_version(exe);
+    let warnings = version.map(compat::check).unwrap_or_default();
+    let detail = format!(
+        "{}, version {}",
+        streamer::path(exe),
+        version.map(compat::format_version).unwrap_or_else(|| "unknown".to_string())
+    );
+    if warnings.is_empty() {
+        report.add(Level::Ok, "Game executable", detail);
+    } else {
+        report.add(Level::Warn, "Game executable", format!("{}; {}", detail, warnings.join("; ")));
+    }
+}
+
+fn check_roms(report: &mut Report, sfc_dir: &Path) {
+    let count = fs::read_dir(sfc_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().map(|e| e.eq_ignore_ascii_case("sfc")).unwrap_or(false))
+        .count();
+    match count {
+        0 => report.add(Level::Warn, "ROMs", format!("none in {}", streamer::path(sfc_dir))),
+        count => report.add(Level::Ok, "ROMs", format!("{} in {}", count, streamer::path(sfc_dir))),
+    }
+}
+
+fn check_audio(report: &mut Report) {
+    let result = sdl2::init().and_then(|sdl| {
+        let audio = sdl.audio()?;
+        mixer::open_audio(44_100, AUDIO_S16LSB, DEFAULT_CHANNELS, 1024)?;
+        mixer::close_audio();
+        Ok(audio.current_audio_driver().to_string())
+    });
+    match result {
+        Ok(driver) => report.add(Level::Ok, "Audio", format!("opened with the {} driver", driver)),
+        Err(e) => report.add(Level::Fail, "Audio", e),
+    }
+}
+
+fn check_gamepads(report: &mut Report) {
+    match Gilrs::new() {
+        Ok(gilrs) => {
+            let names: Vec<String> = gilrs.gamepads().map(|(_, pad)| pad.name().to_string()).collect();
+            let detail = if names.is_empty() { "no controller connected".to_string() } else { names.join(", ") };
+            report.add(Level::Ok, "Gamepads", detail);
+        }
+        Err(e) => report.add(Level::Fail, "Gamepads", e.to_string()),
+    }
+}
@@ -0,0 +1,107 @@
+//! Library export for other front-ends: EmulationStation's `gamelist.xml` and Pegasus'
+//! `metadata.pegasus.txt`, with ROM paths, covers and play statistics filled in, so adding the
+//! port to a bigger setup doesn't mean typing the library in again. Paths are absolute, both
+//! front-ends accept that wherever the files end up.
+
+use chrono::{Local, TimeZone};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct Game {
+    pub rom: PathBuf,
+    pub title: String,
+    pub description: String,
+    pub cover: Option<PathBuf>,
+    pub favorite: bool,
+    pub hidden: bool,
+    pub play_count: u32,
+    pub play_secs: u64,
+    pub last_played: Option<i64>, // unix seconds
+}
+
+/// Writes both files to `dir`. `executable` runs a ROM given as its argument from `game_dir`,
+/// which Pegasus needs to launch the games itself; EmulationStation takes that from its own config.
+pub fn export(games: &[Game], executable: &Path, game_dir: &Path, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let gamelist_path = dir.join("gamelist.xml");
+    fs::write(&gamelist_path, gamelist(games)).map_err(|e| e.to_string())?;
+
+    let pegasus_path = dir.join("metadata.pegasus.txt");
+    fs::write(&pegasus_path, pegasus(games, executable, game_dir)).map_err(|e| e.to_string())?;
+
+    Ok(vec![gamelist_path, pegasus_path])
+}
+
+fn gamelist(games: &[Game]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\"?>\n<gameList>\n");
+    for game in games {
+        let mut fields = vec![
+            ("path", game.rom.to_string_lossy().into_owned()),
+            ("name", game.title.clone()),
+            ("desc", game.description.clone()),
+        ];
+        if let Some(cover) = &game.cover {
+            fields.push(("image", cover.to_string_lossy().into_owned()));
+        }
+        if game.favorite {
+            fields.push(("favorite", "true".to_string()));
+        }
+        if game.hidden {
+            fields.push(("hidden", "true".to_string()));
+        }
+        if game.play_count > 0 {
+            fields.push(("playcount", game.play_count.to_string()));
+            fields.push(("gametime", game.play_secs.to_string()));
+        }
+        if let Some(last) = game.last_played.and_then(|secs| Local.timestamp_opt(secs, 0).single()) {
+            fields.push(("lastplayed", last.format("%Y%m%dT%H%M%S").to_string()));
+        }
+        xml.push_str("  <game>\n");
+        for (tag, value) in fields {
+            xml.push_str(&format!("    <{0}>{1}</{0}>\n", tag, xml_escape(&value)));
+        }
+        xml.push_str("  </game>\n");
+    }
+    xml.push_str("</gameList>\n");
+    xml
+}
+
+fn pegasus(games: &[Game], executable: &Path, game_dir: &Path) -> String {
+    let mut text = String::from("collection: Super Mario World\nshortname: smw\n");
+    text.push_str(&format!("launch: \"{}\" \"{{file.path}}\"\n", executable.display()));
+    text.push_str(&format!("workdir: {}\n", game_dir.display()));
+    for game in games.iter().filter(|game| !game.hidden) {
+        text.push_str(&format!("\ngame: {}\n", one_line(&game.title)));
+        text.push_str(&format!("file: {}\n", game.rom.display()));
+        text.push_str(&format!("description: {}\n", one_line(&game.description)));
+        if let Some(cover) = &game.cover {
+            text.push_str(&format!("assets.boxFront: {}\n", cover.display()));
+        }
+        // Pegasus keeps its own statistics, these are along for reference
+        if game.favorite {
+            text.push_str("x-favorite: true\n");
+        }
+        if game.play_count > 0 {
+            text.push_str(&format!("x-play-count: {}\nx-play-seconds: {}\n", game.play_count, game.play_secs));
+        }
+        if let Some(last) = game.last_played.and_then(|secs| Local.timestamp_opt(secs, 0).single()) {
+            text.push_str(&format!("x-last-played: {}\n", last.format("%Y-%m-%d")));
+        }
+    }
+    text
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Pegasus values run to the end of the line, a line break would start a new field.
+fn one_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
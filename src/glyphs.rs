@@ -0,0 +1,235 @@
+//! Button glyphs matching the controller in use, so hints say "Cross" on a DualShock
+//! and "B" on a Switch pad instead of one generic layout.
+
+use gilrs::Gamepad;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::font::Font;
+use crate::ui;
+
+const GLYPH_SIZE: i32 = 26;
+const SPACING: i32 = 8;
+pub const LOW_BATTERY: u8 = 10; // percent below which the controller battery shows red
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadStyle {
+    Xbox,
+    PlayStation,
+    Nintendo,
+    Generic,
+}
+
+/// Physical buttons by position, as gilrs reports them.
+#[derive(Clone, Copy, Debug)]
+pub enum PadButton {
+    South,
+    East,
+    West,
+    North,
+    Start,
+    Select,
+}
+
+const VENDOR_MICROSOFT: u16 = 0x045e;
+const VENDOR_SONY: u16 = 0x054c;
+const VENDOR_NINTENDO: u16 = 0x057e;
+
+pub fn detect(gamepad: &Gamepad) -> PadStyle {
+    match gamepad.vendor_id() {
+        Some(VENDOR_MICROSOFT) => return PadStyle::Xbox,
+        Some(VENDOR_SONY) => return PadStyle::PlayStation,
+        Some(VENDOR_NINTENDO) => return PadStyle::Nintendo,
+        _ => {}
+    }
+    // Third-party pads usually copy one of the big three and say so in their name
+    let name = gamepad.name().to_lowercase();
+    if ["xbox", "xinput", "x-box"].iter().any(|n| name.contains(n)) {
+        PadStyle::Xbox
+    } else if ["playstation", "dualshock", "dualsense", "ps3", "ps4", "ps5"].iter().any(|n| name.contains(n)) {
+        PadStyle::PlayStation
+    } else if ["nintendo", "switch", "joy-con", "pro controller"].iter().any(|n| name.contains(n)) {
+        PadStyle::Nintendo
+    } else {
+        PadStyle::Generic
+    }
+}
+
+/// What the button is called on this controller.
+pub fn name(style: PadStyle, button: PadButton) -> &'static str {
+    match (style, button) {
+        (PadStyle::PlayStation, PadButton::South) => "Cross",
+        (PadStyle::PlayStation, PadButton::East) => "Circle",
+        (PadStyle::PlayStation, PadButton::West) => "Square",
+        (PadStyle::PlayStation, PadButton::North) => "Triangle",
+        (PadStyle::PlayStation, PadButton::Start) => "Options",
+        (PadStyle::PlayStation, PadButton::Select) => "Share",
+        // Nintendo swaps the letters around the face buttons
+        (PadStyle::Nintendo, PadButton::South) => "B",
+        (PadStyle::Nintendo, PadButton::East) => "A",
+        (PadStyle::Nintendo, PadButton::West) => "Y",
+        (PadStyle::Nintendo, PadButton::North) => "X",
+        (PadStyle::Nintendo, PadButton::Start) => "+",
+        (PadStyle::Nintendo, PadButton::Select) => "-",
+        (PadStyle::Xbox, PadButton::Start) => "Menu",
+        (PadStyle::Xbox, PadButton::Select) => "View",
+        (_, PadButton::South) => "A",
+        (_, PadButton::East) => "B",
+        (_, PadButton::West) => "X",
+        (_, PadButton::North) => "Y",
+        (_, PadButton::Start) => "Start",
+        (_, PadButton::Select) => "Select",
+    }
+}
+
+fn fill_circle(canvas: &mut Canvas<Window>, cx: i32, cy: i32, radius: i32) -> Result<(), String> {
+    for dy in -radius..=radius {
+        let dx = ((radius * radius - dy * dy) as f32).sqrt() as i32;
+        canvas.draw_line((cx - dx, cy + dy), (cx + dx, cy + dy))?;
+    }
+    Ok(())
+}
+
+/// The PlayStation face symbols, drawn with lines inside a dark button.
+fn draw_ps_symbol(canvas: &mut Canvas<Window>, button: PadButton, cx: i32, cy: i32) -> Result<(), String> {
+    let r = GLYPH_SIZE / 4;
+    for offset in 0..2 {
+        match button {
+            PadButton::South => {
+                canvas.set_draw_color(Color::RGB(120, 150, 230));
+                canvas.draw_line((cx - r + offset, cy - r), (cx + r + offset, cy + r))?;
+                canvas.draw_line((cx + r + offset, cy - r), (cx - r + offset, cy + r))?;
+            }
+            PadButton::East => {
+                canvas.set_draw_color(Color::RGB(230, 90, 90));
+                let radius = r - offset;
+                for step in 0..32 {
+                    let a = step as f32 / 32.0 * std::f32::consts::TAU;
+                    let b = (step + 1) as f32 / 32.0 * std::f32::consts::TAU;
+                    canvas.draw_line(
+                        (cx + (a.cos() * radius as f32) as i32, cy + (a.sin() * radius as f32) as i32),
+                        (cx + (b.cos() * radius as f32) as i32, cy + (b.sin() * radius as f32) as i32),
+                    )?;
+                }
+            }
+            PadButton::West => {
+                canvas.set_draw_color(Color::RGB(220, 130, 200));
+                let side = (r - offset) * 2;
+                canvas.draw_rect(Rect::new(cx - r + offset, cy - r + offset, side as u32, side as u32))?;
+            }
+            PadButton::North => {
+                canvas.set_draw_color(Color::RGB(90, 200, 160));
+                let (top, left, right) = ((cx, cy - r + offset), (cx - r + offset, cy + r - offset), (cx + r - offset, cy + r - offset));
+                canvas.draw_line(top, left)?;
+                canvas.draw_line(left, right)?;
+                canvas.draw_line(right, top)?;
+            }
+            PadButton::Start | PadButton::Select => {}
+        }
+    }
+    Ok(())
+}
+
+/// Draws one button glyph with its top-left corner at (x, y) and returns its width.
+pub fn draw(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    style: PadStyle,
+    button: PadButton,
+    x: i32,
+    y: i32,
+) -> Result<i32, String> {
+    let label = name(style, button);
+    if let PadButton::Start | PadButton::Select = button {
+        // Small pill with the button's name
+        let (text_width, text_height) = font.size_of(label)?;
+        let width = text_width as i32 + 16;
+        canvas.set_draw_color(Color::RGB(70, 70, 80));
+        canvas.fill_rect(Rect::new(x, y, width as u32, GLYPH_SIZE as u32))?;
+        let text_y = y + (GLYPH_SIZE - text_height as i32) / 2;
+        ui::draw_text(canvas, texture_creator, font, label, x + 8, text_y, Color::RGB(255, 255, 255))?;
+        return Ok(width);
+    }
+
+    let (cx, cy) = (x + GLYPH_SIZE / 2, y + GLYPH_SIZE / 2);
+    let fill = match (style, button) {
+        (PadStyle::Xbox, PadButton::South) => Color::RGB(16, 124, 16),
+        (PadStyle::Xbox, PadButton::East) => Color::RGB(200, 30, 30),
+        (PadStyle::Xbox, PadButton::West) => Color::RGB(20, 90, 200),
+        (PadStyle::Xbox, PadButton::North) => Color::RGB(220, 170, 0),
+        (PadStyle::PlayStation, _) => Color::RGB(30, 30, 35),
+        _ => Color::RGB(70, 70, 80),
+    };
+    canvas.set_draw_color(fill);
+    fill_circle(canvas, cx, cy, GLYPH_SIZE / 2)?;
+    if style == PadStyle::PlayStation {
+        draw_ps_symbol(canvas, button, cx, cy)?;
+    } else {
+        let (text_width, text_height) = font.size_of(label)?;
+        let (text_x, text_y) = (cx - text_width as i32 / 2, cy - text_height as i32 / 2);
+        ui::draw_text(canvas, texture_creator, font, label, text_x, text_y, Color::RGB(255, 255, 255))?;
+    }
+    Ok(GLYPH_SIZE)
+}
+
+/// A row of "glyph action" hints ending at `right`, e.g. [A] Launch [Y] Quick settings.
+pub fn draw_hints(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    style: PadStyle,
+    hints: &[(PadButton, &str)],
+    right: i32,
+    y: i32,
+) -> Result<i32, String> {
+    let mut widths = Vec::new();
+    for (button, text) in hints {
+        let glyph_width = match button {
+            PadButton::Start | PadButton::Select => font.size_of(name(style, *button))?.0 as i32 + 16,
+            _ => GLYPH_SIZE,
+        };
+        widths.push(glyph_width + 6 + font.size_of(text)?.0 as i32);
+    }
+    let total: i32 = widths.iter().sum::<i32>() + SPACING * 2 * (hints.len() as i32 - 1).max(0);
+    let mut x = right - total;
+    for ((button, text), width) in hints.iter().zip(widths) {
+        let glyph_width = draw(canvas, texture_creator, font, style, *button, x, y)?;
+        let text_y = y + (GLYPH_SIZE - font.size_of(text)?.1 as i32) / 2;
+        ui::draw_text(canvas, texture_creator, font, text, x + glyph_width + 6, text_y, Color::RGB(255, 255, 255))?;
+        x += width + SPACING * 2;
+    }
+    Ok(right - total)
+}
+
+/// Controller battery gauge ending at `right`: a small battery outline filled to `percent`,
+/// red when low, and the percentage. `charging` adds a plus to the label.
+pub fn draw_battery(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    percent: u8,
+    charging: bool,
+    right: i32,
+    y: i32,
+) -> Result<(), String> {
+    let label = format!("{}%{}", percent, if charging { "+" } else { "" });
+    let (label_width, label_height) = font.size_of(&label)?;
+    let (width, height) = (24, 12);
+    let x = right - label_width as i32 - 6 - width - 3;
+    let top = y + (GLYPH_SIZE - height) / 2;
+    let color = if percent < LOW_BATTERY && !charging { Color::RGB(230, 60, 60) } else { Color::RGB(255, 255, 255) };
+
+    canvas.set_draw_color(color);
+    canvas.draw_rect(Rect::new(x, top, width as u32, height as u32))?;
+    canvas.fill_rect(Rect::new(x + width, top + 3, 3, height as u32 - 6))?; // terminal
+    let fill = (width - 4) * percent.min(100) as i32 / 100;
+    if fill > 0 {
+        canvas.fill_rect(Rect::new(x + 2, top + 2, fill as u32, height as u32 - 4))?;
+    }
+    let text_y = y + (GLYPH_SIZE - label_height as i32) / 2;
+    ui::draw_text(canvas, texture_creator, font, &label, right - label_width as i32, text_y, color)?;
+    Ok(())
+}
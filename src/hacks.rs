@@ -0,0 +1,302 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::cache::Cache;
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::{backup, bps, net, rom, streamer, GamepadAction};
+
+const SECTION_URL: &str = "https://www.smwcentral.net/ajax.php?a=getsectionlist&s=smwhacks";
+const VISIBLE_ROWS: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct HackInfo {
+    pub name: String,
+    pub authors: String,
+    pub difficulty: String,
+    pub length: String,
+    pub download_url: String,
+}
+
+impl HackInfo {
+    /// Exit count from the listing's length, e.g. "96 exit(s)".
+    pub fn exits(&self) -> Option<u32> {
+        self.length.split_whitespace().next()?.parse().ok()
+    }
+}
+
+enum BrowserMsg {
+    Results(Result<Vec<HackInfo>, String>),
+    Installed(Result<(String, String, Option<u32>), String>),
+}
+
+/// Online browser for the SMW Central hack section: search, then download a hack and
+/// patch it onto the clean base ROM so it shows up in the library.
+pub struct HackBrowser {
+    query: String,
+    query_dirty: bool,
+    results: Vec<HackInfo>,
+    selected: usize,
+    status: String,
+    busy: bool,
+    rx: Option<Receiver<BrowserMsg>>,
+    base_rom: PathBuf,
+    sfc_dir: PathBuf,
+    backup_dir: PathBuf,
+    cache: Cache,
+}
+
+impl HackBrowser {
+    pub fn new(base_rom: PathBuf, sfc_dir: PathBuf, backup_dir: PathBuf, cache: Cache) -> Self {
+        let mut browser = HackBrowser {
+            query: String::new(),
+            query_dirty: false,
+            results: Vec::new(),
+            selected: 0,
+            status: String::new(),
+            busy: false,
+            rx: None,
+            base_rom,
+            sfc_dir,
+            backup_dir,
+            cache,
+        };
+        browser.search();
+        browser
+    }
+
+    fn search(&mut self) {
+        if self.busy {
+            return;
+        }
+        let mut url = SECTION_URL.to_string();
+        if !self.query.is_empty() {
+            url.push_str(&format!("&f[name]={}", net::encode(&self.query)));
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let cache = self.cache.clone();
+        thread::spawn(move || {
+            let _ = tx.send(BrowserMsg::Results(fetch_hacks(&cache, &url)));
+        });
+        self.rx = Some(rx);
+        self.busy = true;
+        self.query_dirty = false;
+        self.status = "Searching...".to_string();
+    }
+
+    fn install_selected(&mut self) {
+        if self.busy {
+            return;
+        }
+        let hack = match self.results.get(self.selected) {
+            Some(hack) => hack.clone(),
+            None => return,
+        };
+        let base_rom = self.base_rom.clone();
+        let sfc_dir = self.sfc_dir.clone();
+        let backup_dir = self.backup_dir.clone();
+        let cache = self.cache.clone();
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = install_hack(&cache, &hack, &base_rom, &sfc_dir, &backup_dir).map(|file| (file, hack.name.clone(), hack.exits()));
+            let _ = tx.send(BrowserMsg::Installed(result));
+        });
+        self.rx = Some(rx);
+        self.busy = true;
+        self.status = format!("Downloading {}...", self.results[self.selected].name);
+    }
+}
+
+impl View for HackBrowser {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
+                self.selected = (self.selected + 1).min(self.results.len().saturating_sub(1));
+            }
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                self.query.pop();
+                self.query_dirty = true;
+            }
+            // Enter searches after the query was edited, otherwise installs the selected hack
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
+                if self.query_dirty {
+                    self.search();
+                } else {
+                    self.install_selected();
+                }
+            }
+            Event::TextInput { text, .. } => {
+                self.query.push_str(text);
+                self.query_dirty = true;
+            }
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.selected = self.selected.saturating_sub(1),
+            GamepadAction::Down => {
+                self.selected = (self.selected + 1).min(self.results.len().saturating_sub(1));
+            }
+            GamepadAction::Confirm => self.install_selected(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn update(&mut self) -> ViewAction {
+        let msg = match self.rx.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(msg)) => msg,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                self.rx = None;
+                self.busy = false;
+                return ViewAction::None;
+            }
+            _ => return ViewAction::None,
+        };
+        self.rx = None;
+        self.busy = false;
+
+        match msg {
+            BrowserMsg::Results(Ok(results)) => {
+                self.status = format!("{} hacks found", results.len());
+                self.results = results;
+                self.selected = 0;
+                ViewAction::None
+            }
+            BrowserMsg::Installed(Ok((file_name, hack_name, exits))) => {
+                println!("Installed hack: {}", file_name);
+                self.status = format!("Added {} to the library", file_name);
+                ViewAction::HackInstalled(file_name, hack_name, exits)
+            }
+            BrowserMsg::Results(Err(e)) | BrowserMsg::Installed(Err(e)) => {
+                eprintln!("SMW Central: {}", e);
+                self.status = e;
+                ViewAction::None
+            }
+        }
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "SMW Central hacks")?;
+
+        let rows: Vec<String> = self.results.iter().map(|h| h.name.clone()).collect();
+        ui::draw_list(canvas, texture_creator, font, &rows, self.selected, 120, VISIBLE_ROWS)?;
+
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            ui::draw_text(canvas, texture_creator, f, &format!("Search: {}_", self.query), 30, 75, text_color)?;
+            if let Some(hack) = self.results.get(self.selected) {
+                let info = format!("by {} - {} - {}", hack.authors, hack.difficulty, hack.length);
+                ui::draw_text(canvas, texture_creator, f, &info, 30, 500, ui::accent_color())?;
+            }
+            ui::draw_text(canvas, texture_creator, f, &self.status, 30, 540, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, "Enter: search / install   Esc: back", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
+
+fn fetch_hacks(cache: &Cache, url: &str) -> Result<Vec<HackInfo>, String> {
+    let json = cache.get_json(url)?;
+    let entries = json["data"].as_array().ok_or("Unexpected response from SMW Central")?;
+
+    Ok(entries
+        .iter()
+        .map(|entry| HackInfo {
+            name: entry["name"].as_str().unwrap_or("?").to_string(),
+            authors: entry["authors"]
+                .as_array()
+                .map(|authors| {
+                    authors
+                        .iter()
+                        .filter_map(|a| a["name"].as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default(),
+            difficulty: entry["fields"]["difficulty"].as_str().unwrap_or("").to_string(),
+            length: entry["fields"]["length"].as_str().unwrap_or("").to_string(),
+            download_url: entry["download_url"].as_str().unwrap_or("").to_string(),
+        })
+        .filter(|hack| !hack.download_url.is_empty())
+        .collect())
+}
+
+/// Downloads a hack archive, applies the BPS inside to the base ROM and writes the result into sfcs/.
+fn install_hack(cache: &Cache, hack: &HackInfo, base_rom: &Path, sfc_dir: &Path, backup_dir: &Path) -> Result<String, String> {
+    let archive = cache.download(&hack.download_url)?;
+    let patch = extract_bps(&archive)?;
+
+    let base = fs::read(base_rom).map_err(|e| format!("Cannot read base ROM {}: {}", streamer::path(&base_rom), e))?;
+    let patched = bps::apply(rom::strip_header(&base), &patch)?;
+
+    let file_name = format!("{}.sfc", sanitize_file_name(&hack.name));
+    let output = sfc_dir.join(&file_name);
+    backup::backup_file(backup_dir, &output, "hack install")?;
+    fs::write(output, patched).map_err(|e| e.to_string())?;
+    // The readme becomes the hack's manual, see manual.rs
+    if let Some(readme) = extract_readme(&archive) {
+        let path = sfc_dir.join(format!("{}.txt", sanitize_file_name(&hack.name)));
+        if let Err(e) = fs::write(&path, readme) {
+            eprintln!("Failed to save the readme of {}: {}", hack.name, e);
+        }
+    }
+    Ok(file_name)
+}
+
+/// The first text file in a hack archive, usually its readme.
+fn extract_readme(archive: &[u8]) -> Option<Vec<u8>> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).ok()?;
+    for idx in 0..zip.len() {
+        let mut file = zip.by_index(idx).ok()?;
+        if file.name().to_lowercase().ends_with(".txt") {
+            let mut readme = Vec::new();
+            file.read_to_end(&mut readme).ok()?;
+            return Some(readme);
+        }
+    }
+    None
+}
+
+fn extract_bps(archive: &[u8]) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| e.to_string())?;
+    for idx in 0..zip.len() {
+        let mut file = zip.by_index(idx).map_err(|e| e.to_string())?;
+        if file.name().to_lowercase().ends_with(".bps") {
+            let mut patch = Vec::new();
+            file.read_to_end(&mut patch).map_err(|e| e.to_string())?;
+            return Ok(patch);
+        }
+    }
+    Err("No BPS patch in this download".to_string())
+}
+
+pub fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || " -_.()".contains(c) { c } else { '_' })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,76 @@
+//! Minimal smw.ini editing that keeps comments, key order and settings the launcher doesn't know about.
+
+fn section_name(line: &str) -> Option<&str> {
+    line.strip_prefix('[')?.strip_suffix(']').map(str::trim)
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#') || line.starts_with(';')
+}
+
+pub fn get(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut current = "";
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(name) = section_name(line) {
+            current = name;
+        } else if current.eq_ignore_ascii_case(section) && !is_comment(line) {
+            if let Some((k, v)) = line.split_once('=') {
+                if k.trim().eq_ignore_ascii_case(key) {
+                    return Some(v.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns `content` with `key` set to `value`, replacing the existing line in place
+/// or appending it to the end of its section (created if missing).
+pub fn set(content: &str, section: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut in_section = false;
+    let mut insert_at = None;
+
+    for idx in 0..lines.len() {
+        let line = lines[idx].trim().to_string();
+        if let Some(name) = section_name(&line) {
+            in_section = name.eq_ignore_ascii_case(section);
+            if in_section {
+                insert_at = Some(idx + 1);
+            }
+            continue;
+        }
+        if !in_section || line.is_empty() {
+            continue;
+        }
+        if !is_comment(&line) {
+            if let Some((k, _)) = lines[idx].split_once('=') {
+                if k.trim().eq_ignore_ascii_case(key) {
+                    lines[idx] = format!("{}= {}", k, value);
+                    return join(lines);
+                }
+            }
+        }
+        insert_at = Some(idx + 1);
+    }
+
+    let entry = format!("{} = {}", key, value);
+    match insert_at {
+        Some(idx) => lines.insert(idx, entry),
+        None => {
+            if lines.last().map(|l| !l.trim().is_empty()).unwrap_or(false) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", section));
+            lines.push(entry);
+        }
+    }
+    join(lines)
+}
+
+fn join(lines: Vec<String>) -> String {
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
@@ -0,0 +1,178 @@
+//! `smas_launcher install` and the first-run bootstrap: data folders, the smw build, assets and a shortcut.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{net, platform, streamer};
+
+/// Folders every installation has, relative to the install directory.
+pub const DIRS: [&str; 4] = ["sfcs", "launcher/UI", "launcher/pngs", "launcher/plugins"];
+
+#[derive(Debug, Default)]
+pub struct InstallOptions {
+    pub dir: Option<PathBuf>, // --dir, defaults to the usual install directory
+    pub server: Option<String>, // --server, where prebuilt smw binaries are downloaded from
+    pub shortcut: bool, // false with --no-shortcut
+}
+
+pub fn parse_args(args: &[String]) -> Result<InstallOptions, String> {
+    let mut options = InstallOptions {
+        shortcut: true,
+        ..Default::default()
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => options.dir = Some(PathBuf::from(args.next().ok_or("--dir needs a path")?)),
+            "--server" => options.server = Some(args.next().ok_or("--server needs a URL")?.clone()),
+            "--no-shortcut" => options.shortcut = false,
+            other => return Err(format!("Unknown install option: {}", other)),
+        }
+    }
+    Ok(options)
+}
+
+/// Full install; every step is safe to repeat on an existing installation.
+pub fn run(install_dir: &Path, server: Option<&str>, shortcut: bool) -> Result<(), String> {
+    println!("Installing into {}", streamer::path(install_dir));
+    for dir in DIRS {
+        fs::create_dir_all(install_dir.join(dir)).map_err(|e| e.to_string())?;
+    }
+    place_assets(install_dir)?;
+
+    match ensure_game(install_dir, server) {
+        Ok(path) => println!("Game executable: {}", streamer::path(&path)),
+        Err(e) => eprintln!("{}", e),
+    }
+    if shortcut {
+        match register_shortcut(install_dir) {
+            Ok(Some(location)) => println!("Registered shortcut: {}", location),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to register a shortcut: {}", e),
+        }
+    }
+    println!("Copy your ROMs into {}", streamer::path(&install_dir.join("sfcs")));
+    Ok(())
+}
+
+/// Copies the launcher assets shipped next to the executable, leaving files the user already has alone.
+fn place_assets(install_dir: &Path) -> Result<(), String> {
+    let exe_dir = match env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        Some(dir) => dir,
+        None => return Ok(()),
+    };
+    let shipped = exe_dir.join("launcher");
+    let target = install_dir.join("launcher");
+    if !shipped.is_dir() || same_dir(&shipped, &target) {
+        return Ok(());
+    }
+    copy_missing(&shipped, &target).map_err(|e| format!("Failed to copy launcher assets: {}", e))
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn copy_missing(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_missing(&entry.path(), &target)?;
+        } else if !target.exists() {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn exe_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "smw.exe"
+    } else {
+        "smw"
+    }
+}
+
+/// Finds the smw build in the install folder, next to the launcher or on PATH,
+/// and otherwise downloads `<server>/builds/<os>-<arch>/smw` for this machine.
+fn ensure_game(install_dir: &Path, server: Option<&str>) -> Result<PathBuf, String> {
+    let target = install_dir.join(exe_name());
+    if target.is_file() {
+        return Ok(target);
+    }
+
+    let beside_launcher = env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join(exe_name())));
+    let on_path = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).map(|dir| dir.join(exe_name())).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if let Some(found) = beside_launcher.into_iter().chain(on_path).find(|p| p.is_file()) {
+        fs::copy(&found, &target).map_err(|e| e.to_string())?;
+        return Ok(target);
+    }
+
+    let server = server.ok_or_else(|| {
+        format!(
+            "No smw build found. Build it for {}-{} (Launcher > Build smw from source) and place it at {}, or pass --server",
+            env::consts::OS,
+            env::consts::ARCH,
+            streamer::path(&target)
+        )
+    })?;
+    let url = format!("{}/builds/{}-{}/{}", server.trim_end_matches('/'), env::consts::OS, env::consts::ARCH, exe_name());
+    println!("Downloading {}", url);
+    let data = net::download(&url).map_err(|e| format!("Download of the smw build failed: {}", e))?;
+    fs::write(&target, data).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+    Ok(target)
+}
+
+/// Start menu entry: a .desktop file on Linux, the registry key `windows_install_dir` reads on Windows.
+/// Returns where it was registered, or None on platforms that need nothing (macOS app bundles).
+fn register_shortcut(install_dir: &Path) -> Result<Option<String>, String> {
+    // An AppImage runs from a temporary mount, the shortcut has to point at the image itself
+    let exe = match env::var_os("APPIMAGE") {
+        Some(appimage) => PathBuf::from(appimage),
+        None => env::current_exe().map_err(|e| e.to_string())?,
+    };
+
+    if cfg!(target_os = "windows") {
+        let status = std::process::Command::new("reg")
+            .args(["add", r"HKCU\Software\SMAS Launcher", "/v", "InstallDir", "/t", "REG_SZ", "/d"])
+            .arg(install_dir)
+            .arg("/f")
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("reg add exited with {}", status));
+        }
+        return Ok(Some(r"HKCU\Software\SMAS Launcher".to_string()));
+    }
+    if !cfg!(target_os = "linux") || platform::detect_sandbox() == platform::Sandbox::Flatpak {
+        return Ok(None);
+    }
+
+    let applications = platform::xdg_data_home().ok_or("Could not resolve the XDG data directory")?.join("applications");
+    fs::create_dir_all(&applications).map_err(|e| e.to_string())?;
+    let icon = install_dir.join("launcher").join("snes.png");
+    let mut entry = format!(
+        "[Desktop Entry]\nType=Application\nName=SMAS Launcher\nComment=Super Mario All-Stars launcher\nExec=\"{}\"\nPath={}\nTerminal=false\nCategories=Game;\n",
+        exe.display(),
+        install_dir.display()
+    );
+    if icon.is_file() {
+        entry.push_str(&format!("Icon={}\n", icon.display()));
+    }
+    let path = applications.join("smas_launcher.desktop");
+    fs::write(&path, entry).map_err(|e| e.to_string())?;
+    Ok(Some(streamer::path(&path)))
+}
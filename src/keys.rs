@@ -0,0 +1,197 @@
+//! Launcher navigation keys. The defaults always work; `launcher_keys` in launcher.json adds one
+//! more key per action, e.g. `{"up": "W"}`, which is turned into the default key before any
+//! screen sees it, so WASD or non-QWERTY layouts work everywhere without touching each handler.
+//! The map also holds which gamepad buttons flip pages, under `PAD_PAGES`.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::collections::HashMap;
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::GamepadAction;
+
+/// ID used in launcher.json, label, and the key the action is hardcoded to.
+pub const ACTIONS: [(&str, &str, Keycode); 13] = [
+    ("up", "Up", Keycode::Up),
+    ("down", "Down", Keycode::Down),
+    ("left", "Left", Keycode::Left),
+    ("right", "Right", Keycode::Right),
+    ("confirm", "Confirm", Keycode::Return),
+    ("back", "Back", Keycode::Escape),
+    ("menu", "Game menu", Keycode::Application),
+    ("quick_settings", "Quick settings", Keycode::Tab),
+    ("prev_tab", "Previous tab", Keycode::Q),
+    ("next_tab", "Next tab", Keycode::E),
+    ("prev_page", "Previous page", Keycode::PageUp),
+    ("next_page", "Next page", Keycode::PageDown),
+    ("details", "Card details", Keycode::I),
+];
+
+/// `launcher_keys` entry picking the gamepad's page buttons: "triggers" (L2/R2, the default, tabs
+/// stay on the shoulder buttons) or "shoulders" (L1/R1, tabs move to the triggers).
+pub const PAD_PAGES: &str = "pad_pages";
+
+pub fn pages_on_shoulders(bindings: &HashMap<String, String>) -> bool {
+    bindings.get(PAD_PAGES).map(|v| v == "shoulders").unwrap_or(false)
+}
+
+/// Rewrites a key bound in `bindings` into the default key of its action.
+pub fn translate(bindings: &HashMap<String, String>, event: &mut Event) {
+    if bindings.is_empty() {
+        return;
+    }
+    if let Event::KeyDown { keycode, .. } | Event::KeyUp { keycode, .. } = event {
+        let Some(key) = *keycode else {
+            return;
+        };
+        let bound = ACTIONS
+            .iter()
+            .find(|(id, _, _)| bindings.get(*id).and_then(|name| Keycode::from_name(name)) == Some(key));
+        if let Some((_, _, default)) = bound {
+            *keycode = Some(*default);
+        }
+    }
+}
+
+/// Remap screen: pick an action, press the extra key for it. Changes are saved when it closes.
+pub struct KeyBindingsView {
+    bindings: HashMap<String, String>,
+    selected: usize,
+    capturing: bool,
+    status: String,
+}
+
+impl KeyBindingsView {
+    pub fn new(bindings: &HashMap<String, String>) -> Self {
+        KeyBindingsView {
+            bindings: bindings.clone(),
+            selected: 0,
+            capturing: false,
+            status: String::new(),
+        }
+    }
+
+    fn bind(&mut self, key: Keycode) {
+        self.capturing = false;
+        let (id, label, _) = ACTIONS[self.selected];
+        if let Some((_, other, _)) = ACTIONS.iter().find(|(_, _, default)| *default == key) {
+            self.status = format!("{} already means {}", key.name(), other);
+            return;
+        }
+        // A key can only stand for one action
+        self.bindings.retain(|_, name| Keycode::from_name(name) != Some(key));
+        self.bindings.insert(id.to_string(), key.name());
+        self.status = format!("{} now also means {}", key.name(), label);
+    }
+
+    fn close(&self) -> ViewAction {
+        ViewAction::LauncherKeys(self.bindings.clone())
+    }
+
+    /// The last row, after the keys, is the gamepad page button choice.
+    fn on_pad_row(&self) -> bool {
+        self.selected == ACTIONS.len()
+    }
+
+    fn toggle_pad_pages(&mut self) {
+        let value = if pages_on_shoulders(&self.bindings) { "triggers" } else { "shoulders" };
+        self.bindings.insert(PAD_PAGES.to_string(), value.to_string());
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        self.selected = (self.selected as i32 + delta).rem_euclid(ACTIONS.len() as i32 + 1) as usize;
+    }
+}
+
+impl View for KeyBindingsView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        let Event::KeyDown { keycode: Some(key), repeat: false, .. } = event else {
+            return ViewAction::None;
+        };
+        if self.capturing {
+            if *key == Keycode::Escape {
+                self.capturing = false;
+                self.status.clear();
+            } else {
+                self.bind(*key);
+            }
+            return ViewAction::None;
+        }
+        match *key {
+            Keycode::Up => self.move_selection(-1),
+            Keycode::Down => self.move_selection(1),
+            Keycode::Return | Keycode::Left | Keycode::Right if self.on_pad_row() => self.toggle_pad_pages(),
+            Keycode::Backspace | Keycode::Delete if self.on_pad_row() => {
+                self.bindings.remove(PAD_PAGES);
+            }
+            Keycode::Return => {
+                self.capturing = true;
+                self.status = format!("Press a key for {}, Esc to cancel", ACTIONS[self.selected].1);
+            }
+            Keycode::Backspace | Keycode::Delete => {
+                self.bindings.remove(ACTIONS[self.selected].0);
+                self.status.clear();
+            }
+            Keycode::Escape => return self.close(),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.move_selection(-1),
+            GamepadAction::Down => self.move_selection(1),
+            GamepadAction::Confirm | GamepadAction::Left | GamepadAction::Right if self.on_pad_row() => {
+                self.toggle_pad_pages()
+            }
+            GamepadAction::Confirm => {
+                self.capturing = true;
+                self.status = format!("Press a key on the keyboard for {}", ACTIONS[self.selected].1);
+            }
+            GamepadAction::Back if self.capturing => {
+                self.capturing = false;
+                self.status.clear();
+            }
+            GamepadAction::Back => return self.close(),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    /// The keys pressed here are the ones being bound, so they must arrive untranslated.
+    fn wants_raw_keys(&self) -> bool {
+        true
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Launcher keys")?;
+
+        let mut rows: Vec<String> = ACTIONS
+            .iter()
+            .map(|(id, label, default)| match self.bindings.get(*id) {
+                Some(extra) => format!("{}: {} or {}", label, default.name(), extra),
+                None => format!("{}: {}", label, default.name()),
+            })
+            .collect();
+        let pad_pages = if pages_on_shoulders(&self.bindings) { "Shoulder buttons" } else { "Triggers" };
+        rows.push(format!("Gamepad page flip: {}", pad_pages));
+        ui::draw_list(canvas, texture_creator, font, &rows, self.selected, 80, rows.len())?;
+
+        if let Some(f) = font {
+            let accent = ui::accent_color();
+            let text_color = ui::text_color();
+            ui::draw_text(canvas, texture_creator, f, &self.status, 30, 540, accent)?;
+            ui::draw_text(canvas, texture_creator, f, "Enter: set extra key   Backspace: clear   Esc: save and close", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
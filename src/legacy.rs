@@ -0,0 +1,144 @@
+//! Settings import from an older install: the Python SMAS Launcher or an earlier build of this one.
+//! Both keep `selector`, `bgtype`, `background_color` and `onload` in an options file (JSON, INI or
+//! TOML) in the install or its `launcher` folder, and the game's settings in smw.ini. Values are
+//! read leniently (Python writes `True`, `(66, 113, 183)` and the like) and handed over by name;
+//! the launcher keeps the ones it has a field for.
+
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::streamer;
+
+const OPTION_FILES: [&str; 5] = ["launcher.json", "launcher.toml", "options.json", "launcher.ini", "options.ini"];
+/// Older spellings -> the field they are now.
+const RENAMED: [(&str, &str); 4] = [
+    ("bg_color", "background_color"),
+    ("bgcolor", "background_color"),
+    ("background_colour", "background_color"),
+    ("bg_type", "bgtype"),
+];
+/// Left as they are: paths into the old install, which wouldn't point anywhere useful from the new
+/// one; the parental lock, which only the PIN may change; and everything that runs commands or
+/// fetches code, since the folder imported from may be anyone's.
+pub const NOT_CARRIED: [&str; 14] = [
+    "game_executable",
+    "saves_dir",
+    "saves_dir_used",
+    "playtime_pin",
+    "playtime_limit_mins",
+    "max_age",
+    "pre_launch_hook",
+    "post_exit_hook",
+    "launch_wrapper",
+    "launch_env",
+    "update_server",
+    "smw_source_repo",
+    "stats_upload_url",
+    "remote_port",
+];
+/// The same for each game's own settings under `games`.
+const NOT_CARRIED_PER_GAME: [&str; 4] = ["env", "wrapper", "pre_launch_hook", "post_exit_hook"];
+
+pub struct Import {
+    pub options: Map<String, Value>, // launcher options by field name
+    pub game_ini: Option<String>,    // the old smw.ini
+    pub sources: Vec<PathBuf>,
+}
+
+/// Reads whatever settings `old_dir` has; an error when there are none at all.
+pub fn read(old_dir: &Path) -> Result<Import, String> {
+    // Either the old install or its launcher folder may be given
+    let dirs = [old_dir.to_path_buf(), old_dir.join("launcher")];
+    let mut import = Import { options: Map::new(), game_ini: None, sources: Vec::new() };
+
+    for dir in &dirs {
+        for name in OPTION_FILES {
+            let path = dir.join(name);
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let values = match name.rsplit('.').next() {
+                Some("json") => json_values(&content),
+                Some("toml") => toml::from_str(&content).map_err(|e| e.to_string()),
+                _ => Ok(ini_values(&content)),
+            };
+            match values {
+                Ok(values) => {
+                    for (key, value) in values {
+                        let key = RENAMED.iter().find(|(old, _)| key.eq_ignore_ascii_case(old)).map_or(key, |(_, new)| new.to_string());
+                        import.options.insert(key, value);
+                    }
+                    import.sources.push(path);
+                }
+                Err(e) => eprintln!("Skipping {}: {}", streamer::path(&path), e),
+            }
+        }
+        if import.game_ini.is_none() {
+            let path = dir.join("smw.ini");
+            if let Ok(content) = fs::read_to_string(&path) {
+                import.game_ini = Some(content);
+                import.sources.push(path);
+            }
+        }
+    }
+
+    if let Some(Value::Object(games)) = import.options.get_mut("games") {
+        for game in games.values_mut().filter_map(Value::as_object_mut) {
+            game.retain(|key, _| !NOT_CARRIED_PER_GAME.contains(&key.as_str()));
+        }
+    }
+
+    if import.sources.is_empty() {
+        return Err(format!("No launcher settings or smw.ini found in {}", streamer::path(old_dir)));
+    }
+    Ok(import)
+}
+
+fn json_values(content: &str) -> Result<Map<String, Value>, String> {
+    match serde_json::from_str(content).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map.into_iter().map(|(key, value)| (key.to_lowercase(), loosen(value))).collect()),
+        _ => Err("not a JSON object".to_string()),
+    }
+}
+
+/// Every `key = value` line regardless of section; the old files kept everything in one.
+fn ini_values(content: &str) -> Map<String, Value> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with(';') && !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_lowercase(), loosen(Value::String(value.trim().to_string()))))
+        .collect()
+}
+
+/// Turns strings that spell a number, flag or color into the value they mean.
+fn loosen(value: Value) -> Value {
+    let Value::String(text) = &value else {
+        return value;
+    };
+    let text = text.trim().trim_matches('"');
+    if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false") {
+        return Value::Bool(text.eq_ignore_ascii_case("true"));
+    }
+    if let Ok(number) = text.parse::<u64>() {
+        return Value::from(number);
+    }
+    if let Ok(number) = text.parse::<f64>() {
+        return Value::from(number);
+    }
+    if let Some(hex) = text.strip_prefix('#').filter(|hex| hex.len() == 6) {
+        if let Ok(rgb) = u32::from_str_radix(hex, 16) {
+            return Value::from(vec![rgb >> 16, (rgb >> 8) & 0xFF, rgb & 0xFF]);
+        }
+    }
+    // "(66, 113, 183)" or "66,113,183"
+    let parts: Vec<&str> = text.trim_matches(|c| c == '(' || c == ')' || c == '[' || c == ']').split(',').map(str::trim).collect();
+    if parts.len() > 1 {
+        if let Ok(numbers) = parts.iter().map(|part| part.parse::<u64>()).collect::<Result<Vec<_>, _>>() {
+            return Value::from(numbers);
+        }
+    }
+    value
+}
@@ -0,0 +1,356 @@
+//! What the launcher knows about each ROM, stored as `launcher/library.json` and keyed by the ROM's
+//! content so a file renamed or moved outside the launcher keeps its name, collections and play time.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+use crate::rom;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Entry {
+    pub file: String, // file name in sfcs/ it was last seen as
+    pub first_seen: i64, // unix seconds
+    pub display_name: Option<String>, // shown instead of the file name, set with Rename
+    pub favorite: bool,
+    pub hidden: bool,
+    pub collections: Vec<String>,
+    pub play_secs: u64,
+    pub play_count: u32,
+    pub last_played: Option<i64>, // unix seconds
+    pub cover: Option<PathBuf>, // used instead of pngs/<rom>.png
+    pub age_rating: u8, // minimum age from rating::AGES, 0 = unrated
+    pub descriptors: Vec<String>, // from rating::DESCRIPTORS
+    pub titles: HashMap<String, String>, // language code ("en", "ja", "pt-BR") -> the game's title in it
+    pub exits: Option<u32>, // exits the game has in all, from SMW Central's listing; shows a progress bar on the card
+}
+
+impl Entry {
+    /// Title in `language`, else in its base language ("pt" for "pt-BR").
+    pub fn title(&self, language: &str) -> Option<&str> {
+        let find = |code: &str| self.titles.iter().find(|(k, _)| k.eq_ignore_ascii_case(code)).map(|(_, title)| title.as_str());
+        find(language).or_else(|| language.split_once('-').and_then(|(base, _)| find(base)))
+    }
+}
+
+/// What the internal SNES header says about a file, read while hashing it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct HeaderInfo {
+    pub region: Option<String>,
+    pub checksum_ok: bool, // also true when there was no header to check against
+}
+
+impl HeaderInfo {
+    fn of(data: &[u8]) -> Self {
+        HeaderInfo {
+            region: rom::header_region(data).map(str::to_string),
+            checksum_ok: rom::checksum_matches(data).unwrap_or(true),
+        }
+    }
+}
+
+/// A file's content hash, valid while its size and modification time stay the same.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct CachedHash {
+    size: u64,
+    modified: i64, // unix seconds
+    id: String,
+    #[serde(default)]
+    header: Option<HeaderInfo>, // None for hashes cached before headers were read
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Library {
+    pub entries: HashMap<String, Entry>, // CRC32 of the headerless ROM, as 8 hex digits
+    hashes: HashMap<String, CachedHash>, // file name -> its entry ID when last hashed
+    #[serde(skip_serializing)]
+    first_seen: HashMap<String, i64>, // by file name, from before entries existed
+    #[serde(skip)]
+    by_file: HashMap<String, String>, // file name -> entry ID
+    #[serde(skip)]
+    existed: bool, // library.json was there at load, so unseen ROMs really are new
+}
+
+/// One ROM hashed by the pool.
+pub struct Hashed {
+    pub file: String,
+    size: u64,
+    modified: i64,
+    crc: Result<u32, String>,
+    header: Option<HeaderInfo>,
+}
+
+/// What a scan found besides ROMs it already knew.
+#[derive(Default)]
+pub struct ScanChanges {
+    pub added: Vec<String>, // files that got a new entry
+    pub moved: Vec<(String, String)>, // old and new file name of a known ROM
+    pub changed: bool, // library.json needs saving
+}
+
+/// Built-in titles for an entry ID, see `rom::known_titles`.
+fn known_titles(id: &str) -> HashMap<String, String> {
+    u32::from_str_radix(id, 16).map(rom::known_titles).unwrap_or_default()
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, i64)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    Some((meta.len(), modified))
+}
+
+fn hash_file(sfc_dir: &Path, file: String) -> Hashed {
+    let path = sfc_dir.join(&file);
+    let (size, modified) = file_stamp(&path).unwrap_or((0, 0));
+    let (crc, header) = match fs::read(&path) {
+        Ok(data) => (Ok(rom::crc32(rom::strip_header(&data))), Some(HeaderInfo::of(&data))),
+        Err(e) => (Err(e.to_string()), None),
+    };
+    Hashed { file, size, modified, crc, header }
+}
+
+/// Hashes ROMs on one thread per core so hundreds of hacks don't hold up the UI.
+/// Results are picked up with `poll` as they finish.
+pub struct HashPool {
+    tx: Sender<Hashed>,
+    rx: Receiver<Hashed>,
+    in_flight: HashSet<String>,
+}
+
+impl HashPool {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        HashPool { tx, rx, in_flight: HashSet::new() }
+    }
+
+    /// Starts hashing `files`, skipping any that are already being hashed.
+    pub fn queue(&mut self, sfc_dir: &Path, files: Vec<String>) {
+        let files: Vec<String> = files.into_iter().filter(|f| self.in_flight.insert(f.clone())).collect();
+        if files.is_empty() {
+            return;
+        }
+        let workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(2).min(files.len());
+        for chunk in files.chunks((files.len() + workers - 1) / workers) {
+            let (tx, sfc_dir, chunk) = (self.tx.clone(), sfc_dir.to_path_buf(), chunk.to_vec());
+            thread::spawn(move || {
+                for file in chunk {
+                    if tx.send(hash_file(&sfc_dir, file)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    }
+
+    pub fn poll(&mut self) -> Vec<Hashed> {
+        let done: Vec<Hashed> = self.rx.try_iter().collect();
+        for hashed in &done {
+            self.in_flight.remove(&hashed.file);
+        }
+        done
+    }
+
+    /// ROMs still being hashed.
+    pub fn pending(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Hashes one ROM on the calling thread, for when its entry is needed right away.
+    pub fn hash_now(sfc_dir: &Path, file: &str) -> Hashed {
+        hash_file(sfc_dir, file.to_string())
+    }
+}
+
+impl Library {
+    pub fn load(launcher_dir: &Path) -> Self {
+        let path = launcher_dir.join("library.json");
+        let mut library = match fs::read_to_string(&path) {
+            Ok(content) => {
+                let mut library: Library = serde_json::from_str(&content).unwrap_or_else(|e| {
+                    eprintln!("Failed to parse library.json: {}", e);
+                    Library::default()
+                });
+                library.existed = true;
+                library
+            }
+            Err(_) => Library::default(),
+        };
+        library.by_file = library.entries.iter().map(|(id, entry)| (entry.file.clone(), id.clone())).collect();
+        // Entries made before the built-in titles existed get them too
+        for (id, entry) in library.entries.iter_mut().filter(|(_, entry)| entry.titles.is_empty()) {
+            entry.titles = known_titles(id);
+        }
+        library
+    }
+
+    pub fn save(&self, launcher_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(launcher_dir.join("library.json"), content).map_err(|e| e.to_string())
+    }
+
+    /// Matches `roms` to their entries using the cached hashes and returns the files that are
+    /// new or changed since they were last hashed, for the `HashPool`.
+    pub fn scan(&mut self, sfc_dir: &Path, roms: &[String]) -> (ScanChanges, Vec<String>) {
+        let mut changes = ScanChanges::default();
+        let mut stale = Vec::new();
+        for file in roms {
+            let Some((size, modified)) = file_stamp(&sfc_dir.join(file)) else {
+                continue;
+            };
+            match self.hashes.get(file) {
+                Some(cached) if cached.size == size && cached.modified == modified && cached.header.is_some() => {
+                    let id = cached.id.clone();
+                    self.link(sfc_dir, file, id, modified, &mut changes);
+                }
+                _ => stale.push(file.clone()),
+            }
+        }
+        (changes, stale)
+    }
+
+    /// Files a hash from the pool and matches the ROM to its entry.
+    pub fn apply(&mut self, sfc_dir: &Path, hashed: Hashed, changes: &mut ScanChanges) {
+        let crc = match hashed.crc {
+            Ok(crc) => crc,
+            Err(e) => {
+                eprintln!("Failed to hash {}: {}", hashed.file, e);
+                return;
+            }
+        };
+        let id = format!("{:08x}", crc);
+        let cached = CachedHash { size: hashed.size, modified: hashed.modified, id: id.clone(), header: hashed.header };
+        if self.hashes.get(&hashed.file) == Some(&cached) {
+            // Verified again with the same result, the scan already linked it
+            return;
+        }
+        self.hashes.insert(hashed.file.clone(), cached);
+        changes.changed = true;
+        self.link(sfc_dir, &hashed.file, id, hashed.modified, changes);
+    }
+
+    /// Points `file` at the entry for its content. A known ROM under another name is followed
+    /// when its old file is gone, a second copy shares the entry, and a file that changed in place
+    /// (e.g. re-patched) keeps its entry's metadata under the new hash.
+    fn link(&mut self, sfc_dir: &Path, file: &str, id: String, modified: i64, changes: &mut ScanChanges) {
+        if self.by_file.get(file) == Some(&id) {
+            return;
+        }
+        match self.entries.get_mut(&id) {
+            Some(entry) if entry.file != file => {
+                // A second copy of the same ROM shares the entry, the original stays its file
+                if !sfc_dir.join(&entry.file).exists() {
+                    println!("{} was renamed to {}", entry.file, file);
+                    changes.moved.push((entry.file.clone(), file.to_string()));
+                    entry.file = file.to_string();
+                }
+            }
+            Some(_) => {}
+            None => {
+                // Only the entry's own file takes it along; a copy that changed leaves it to the others
+                let previous = self
+                    .by_file
+                    .get(file)
+                    .filter(|old| self.entries.get(*old).map_or(false, |entry| entry.file == file))
+                    .and_then(|old| self.entries.remove(old));
+                let mut entry = previous.unwrap_or_else(|| {
+                    changes.added.push(file.to_string());
+                    // In the session that creates library.json, file times stand in for "first seen"
+                    // so an existing library isn't all new
+                    let now = Local::now().timestamp();
+                    Entry {
+                        first_seen: self.first_seen.get(file).copied().unwrap_or(if self.existed { now } else { modified }),
+                        titles: known_titles(&id),
+                        ..Entry::default()
+                    }
+                });
+                entry.file = file.to_string();
+                self.entries.insert(id.clone(), entry);
+            }
+        }
+        self.by_file.insert(file.to_string(), id);
+        changes.changed = true;
+    }
+
+    /// Entry ID of a file, once it has been hashed.
+    pub fn id(&self, file: &str) -> Option<&str> {
+        self.by_file.get(file).map(String::as_str)
+    }
+
+    /// Header details of a file, once it has been hashed.
+    pub fn header(&self, file: &str) -> Option<&HeaderInfo> {
+        self.hashes.get(file)?.header.as_ref()
+    }
+
+    /// File name an entry was last seen as.
+    pub fn file(&self, id: &str) -> Option<&str> {
+        self.entries.get(id).map(|e| e.file.as_str())
+    }
+
+    pub fn entry(&self, file: &str) -> Option<&Entry> {
+        self.entries.get(self.by_file.get(file)?)
+    }
+
+    pub fn entry_mut(&mut self, file: &str) -> Option<&mut Entry> {
+        self.entries.get_mut(self.by_file.get(file)?)
+    }
+
+    /// Points the entry at its new file name after a rename done by the launcher.
+    pub fn rename_file(&mut self, old: &str, new: &str) {
+        if let Some(id) = self.by_file.remove(old) {
+            if let Some(entry) = self.entries.get_mut(&id).filter(|entry| entry.file == old) {
+                entry.file = new.to_string();
+            }
+            self.by_file.insert(new.to_string(), id);
+        }
+        // A rename keeps the size and modification time, so the hash stays good
+        if let Some(cached) = self.hashes.remove(old) {
+            self.hashes.insert(new.to_string(), cached);
+        }
+    }
+
+    pub fn record_play(&mut self, file: &str, secs: u64) {
+        if let Some(entry) = self.entry_mut(file) {
+            entry.play_secs += secs;
+            entry.play_count += 1;
+            entry.last_played = Some(Local::now().timestamp());
+        }
+    }
+
+    /// Cover overrides by file name, for the texture store. Copies of a ROM share its cover.
+    pub fn cover_overrides(&self) -> HashMap<String, PathBuf> {
+        self.by_file
+            .iter()
+            .filter_map(|(file, id)| Some((file.clone(), self.entries.get(id)?.cover.clone()?)))
+            .collect()
+    }
+
+    /// Entry ID -> the file name it was last seen as.
+    pub fn files_by_id(&self) -> HashMap<String, String> {
+        self.entries.iter().map(|(id, entry)| (id.clone(), entry.file.clone())).collect()
+    }
+
+    /// File name each entry was last seen as, by its headerless CRC32.
+    pub fn files_by_crc(&self) -> HashMap<u32, String> {
+        self.entries
+            .iter()
+            .filter_map(|(id, entry)| Some((u32::from_str_radix(id, 16).ok()?, entry.file.clone())))
+            .collect()
+    }
+
+    /// When the ROM was first seen; ROMs not scanned yet count as just added.
+    pub fn added(&self, file: &str) -> i64 {
+        self.entry(file).map(|e| e.first_seen).unwrap_or_else(|| Local::now().timestamp())
+    }
+
+    pub fn is_new(&self, file: &str, days: u32) -> bool {
+        days > 0 && Local::now().timestamp() - self.added(file) < days as i64 * 24 * 3600
+    }
+}
@@ -1,34 +1,242 @@
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+mod achievements;
+mod anim;
+mod archive;
+mod assets;
+mod atlas;
+mod audio;
+mod autocover;
+mod backup;
+mod badges;
+mod bps;
+mod build;
+mod bundles;
+mod cache;
+mod color_picker;
+mod compat;
+mod config;
+mod control;
+mod coverpack;
+mod crt;
+mod details;
+mod doctor;
+mod font;
+mod frontends;
+mod glyphs;
+mod hacks;
+mod ini;
+mod install;
+mod keys;
+mod legacy;
+mod library;
+mod manual;
+mod net;
+mod news;
+mod option_widgets;
+mod patches;
+mod platform;
+mod playtime;
+mod plugins;
+mod quick_settings;
+mod rating;
+mod remote;
+mod rename;
+mod replay;
+mod rom;
+mod session;
+mod sram;
+mod startup;
+mod stats;
+mod status;
+mod streamer;
+mod textures;
+mod theme;
+mod ui;
+mod updates;
+mod verify;
+mod visualizer;
+mod widgets;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Canvas, TextureCreator};
-use sdl2::video::{Window, WindowContext};
-use sdl2::image::{LoadTexture, InitFlag};
-use sdl2::mixer::{InitFlag as MixerFlag, AUDIO_S16LSB, DEFAULT_CHANNELS};
+use sdl2::render::{BlendMode, Canvas, TextureCreator};
+use sdl2::video::{FullscreenType, Window, WindowContext};
+use sdl2::image::{InitFlag, LoadTexture, SaveSurface};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
 use sdl2::render::Texture;
 use sdl2::ttf;
 use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
 use std::fs;
+use std::iter;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::time::Duration;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
 use std::collections::HashMap;
-use gilrs::{Gilrs, Button, Event as GilrsEvent, EventType};
+use gilrs::{Axis, Gilrs, GilrsBuilder, Button, Event as GilrsEvent, EventType, PowerInfo};
+use font::Font;
+use option_widgets::{OptionRow, OptionsScreen};
+use platform::Sandbox;
+use quick_settings::{QuickAction, QuickSetting, QuickSettings};
+use status::Subsystem;
+use ui::{Menu, MenuAction, View, ViewAction};
 
+const SDL_TOUCH_MOUSEID: u32 = u32::MAX;
 const SCREEN_WIDTH: u32 = 981;
 const SCREEN_HEIGHT: u32 = 673;
 const BOX_SIZE: (u32, u32) = (267, 400);
 const SHAD_SIZE: (u32, u32) = (294, 440);
-const HOVER_BOX_SIZE: (u32, u32) = (294, 440);
-const TRANSITION_SPEED: f32 = 0.15; // Higher = faster transition
+const HOVER_BOX_SIZE: (u32, u32) = (294, 440); // the selected card grows to this from BOX_SIZE
+const TRANSITION_SPEED: f32 = 0.15; // share of a card's color fade covered per frame at normal speed
+const GRID_COLUMNS: usize = 3; // cards per row, the theme sets the number of rows
+/// File name suffixes (`smb1_pal.sfc`) treated as region variants of the same game.
+const REGION_TAGS: &[&str] = &[
+    "pal", "ntsc", "jp", "jpn", "japan", "us", "usa", "eu", "eur", "europe", "de", "fr", "es", "it", "kr",
+];
+const LONG_PRESS_TIME: Duration = Duration::from_millis(600);
+const SWIPE_DISTANCE: i32 = 80;
+const DRAG_THRESHOLD: i32 = 10;
+const TAB_BAR_Y: i32 = 20;
+const TAB_HEIGHT: u32 = 40;
+const BOOT_DELAY: Duration = Duration::from_secs(3);
+const CONTINUE_RECT: (i32, i32, u32, u32) = (30, 68, 560, 66);
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_INTERVAL: Duration = Duration::from_millis(120);
+const BUMP_TIME: Duration = Duration::from_millis(250);
+/// Name, launcher mixer chunk size and smw's AudioSamples. Smaller buffers mean less delay
+/// but crackle on slow or busy machines.
+const AUDIO_LATENCY_PRESETS: [(&str, i32, u32); 3] = [("Low", 512, 512), ("Default", 1024, 2048), ("Safe", 4096, 4096)];
+/// smw.ini display keys a game can override, with the values they cycle through. `Shader` can be
+/// overridden too and cycles through the shaders the game has.
+const DISPLAY_OVERRIDES: [(&str, &[&str]); 4] = [
+    ("Fullscreen", &["0", "1", "2"]),
+    ("WindowScale", &["1", "2", "3", "4", "5", "6"]),
+    ("LinearFiltering", &["0", "1"]),
+    ("NewRenderer", &["0", "1"]),
+];
+const STICK_PRESS: f32 = 0.6; // left stick deflection that counts as a D-pad press
+const STICK_RELEASE: f32 = 0.3;
+const BUMP_DISTANCE: f32 = 6.0; // pixels the selector shakes at the end of the list
+const LAUNCH_FADE: Duration = Duration::from_millis(400); // music and screen fade out before a game starts
+const LAUNCH_CHIME: Duration = Duration::from_millis(100); // head start the launch sound gets over the game
+const PRE_LAUNCH_HOOK_TIMEOUT: Duration = Duration::from_secs(60); // a hook still running then is killed and the launch cancelled
+const RETURN_FADE: Duration = Duration::from_millis(600); // fade in from black after a game exits
+const BACKDROP_FADE: Duration = Duration::from_millis(300); // blurred cover background crossfade between selections
+const BACK_HOLD: Duration = Duration::from_millis(800); // how long B is held on the grid to quit
+/// smw.ini overrides the crash watchdog tries one after another, each step keeping the ones before it.
+const SAFE_STEPS: [(&str, &[(&str, &str)]); 3] = [
+    ("no shader", &[("Shader", "None")]),
+    ("software renderer", &[("OutputMethod", "SDL-Software"), ("LinearFiltering", "0")]),
+    ("default audio", &[("AudioFreq", "44100"), ("AudioChannels", "2"), ("AudioSamples", "2048")]),
+];
+const WATCHDOG_CHOICES: [u32; 4] = [0, 5, 10, 30]; // seconds a crash counts as "right at startup", 0 = off
+/// Languages the title language option offers; rom.rs's `KNOWN_TITLES` covers each of them.
+const TITLE_LANGUAGES: [(&str, &str); 7] = [
+    ("", "System"),
+    ("en", "English"),
+    ("ja", "Japanese"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+];
+const BATTERY_SAVER_CHOICES: [u8; 4] = [100, 50, 20, 0]; // battery_saver thresholds the options menu cycles through
+const BATTERY_FPS_CHOICES: [u8; 3] = [30, 20, 60];
+const SMW_EXITS: u32 = 96; // exits in the unmodified game, for the base ROM's progress bar
+const PRIORITY_CHOICES: [(i8, &str); 4] = [(0, "Normal"), (1, "Above normal"), (2, "High"), (-1, "Below normal")];
+const ACCENT_TINT: f32 = 70.0; // alpha of the selected cover's accent color over the background
+const IDLE_WAKE: Duration = Duration::from_millis(500); // background work still gets polled this often when idle
+const PAD_POLL_INTERVAL: Duration = Duration::from_millis(16); // gilrs input doesn't wake SDL, so idle waits are sliced
+const PAD_BATTERY_POLL: Duration = Duration::from_secs(30); // some drivers read the charge over the wire
+const REFRESH_CHECK: Duration = Duration::from_secs(5); // mode changes like VRR toggling come without a window event
+const INI_CHECK: Duration = Duration::from_secs(2); // how often smw.ini is looked at for edits made outside the launcher
+const RENDER_RETRIES: u32 = 50; // frames in a row that may fail to draw before the launcher gives up
+const HANDHELD_SIZE: (u32, u32) = (1280, 800);
+const HANDHELD_TOUCH_PADDING: i32 = 20;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 struct LauncherOptions {
     selector: u8,
     bgtype: u8,
     background_color: (u8, u8, u8),
     onload: u8,
+    startup: u8, // 0 = show the grid, 1 = boot straight into the last-played game
+    unfocused_fps: u8, // 0 = stop rendering while unfocused
+    battery_saver: u8, // on battery with this percent or less left: battery_fps, no visualizer, battery widget; 0 = off
+    battery_fps: u8, // frame rate cap while saving battery
+    pause_music_unfocused: bool,
+    launch_env: HashMap<String, String>,
+    launch_wrapper: Vec<String>, // e.g. ["gamemoderun"], empty = run smw directly
+    games: HashMap<String, GameConfig>, // keyed by ROM file name
+    handheld: Option<bool>, // None = auto-detect Steam Deck
+    game_executable: Option<PathBuf>, // overrides the smw next to the launcher
+    game_order: Vec<String>, // custom card order, unlisted games follow alphabetically
+    show_hidden: bool,
+    collections: Vec<String>,
+    base_rom: String, // clean ROM in sfcs/ that downloaded hacks are patched onto
+    pre_launch_hook: Vec<String>, // command run before the game starts, a failure cancels the launch
+    post_exit_hook: Vec<String>, // command run after the game exits
+    remote_port: Option<u16>, // serves the phone remote page when set
+    streamer_mode: bool, // hides paths and personal info from the UI and logs
+    now_playing_file: bool, // writes launcher/now_playing.txt for OBS while streaming
+    theme: Option<String>, // folder under launcher/themes/, None = bgtype and background_color above
+    palette: String, // name in theme::PALETTES recoloring the theme, empty = the theme's own colors
+    language: String, // code game titles are shown in when the library has them, empty = the system's language
+    widgets: widgets::WidgetOptions,
+    update_server: Option<String>, // base URL serving news.json and the update manifest.json
+    dismissed_news: Vec<String>,
+    stats_upload_url: Option<String>, // exported stats are also POSTed here as JSON
+    smw_source_repo: String, // git repository "Build smw from source" clones
+    game_options: Option<GameOptions>, // written to smw.ini before each launch, seeded from the existing file
+    volume: u8, // launcher music and sounds, in percent
+    audio_latency: u8, // index into AUDIO_LATENCY_PRESETS
+    sort_mode: u8, // 0 = custom order, 1 = recently added first
+    new_badge_days: u32, // games added within this many days get a NEW badge, 0 = never
+    launcher_keys: HashMap<String, String>, // extra key per navigation action, e.g. "up" -> "W"
+    auto_covers: bool, // capture a title-screen cover for ROMs without one, see autocover.rs
+    selected_rom: Option<String>, // library ID of the card selected at exit, so the next run starts there
+    pad_back_quit: u8, // B on the grid: 0 = hold to quit, 1 = ask first, 2 = quit right away
+    transition_speed: f32, // multiplies every animation's speed, 1.0 = normal
+    easing: u8, // 0 = linear, 1 = ease-out, 2 = spring, see anim.rs
+    cache_ttl_hours: u32, // how long downloaded listings and news are reused before fetching again
+    playtime_limit_mins: u32, // daily playtime after which games need playtime_pin to start, 0 = no limit
+    playtime_pin: String, // digits; also guards changing the limit once set
+    max_age: u8, // games rated above this are left off the grid, 0 = no limit; loosening it or editing ratings asks for playtime_pin when needs_pin() does
+    task_notifications: bool, // desktop notification when background work finishes while the window is in the background
+    fullscreen_handoff: bool, // a fullscreen launcher starts games fullscreen on its own display
+    saves_dir: Option<PathBuf>, // where smw builds with compat::SAVE_DIR_FLAG keep saves, None = launcher/saves
+    saves_dir_used: Option<PathBuf>, // where the saves are now, None = the game folder's saves/; only set_saves_dir moves them
+    slow_startup_warned: bool, // the slow startup notice was shown; a fast start clears it for the next slow one
+    low_latency: bool, // present without waiting for vsync and pace frames with the clock instead; may tear, read at startup
+}
+
+/// Per-game launch settings, layered on top of the global ones.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct GameConfig {
+    env: HashMap<String, String>,
+    wrapper: Option<Vec<String>>,
+    // hidden, collections and display_name moved to library.json; they're only read so the
+    // ROM's next scan can migrate them
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    collections: Vec<String>,
+    hack_name: Option<String>, // set for ROMs produced by patching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    variant: Option<String>, // region variant launched from this card
+    pre_launch_hook: Option<Vec<String>>,
+    post_exit_hook: Option<Vec<String>>,
+    game_options: HashMap<String, String>, // smw.ini overrides by key, e.g. "Shader"
+    monitor: Option<u32>, // display the game goes fullscreen on, 0-based
+    watchdog_secs: u32, // failing within this long offers a relaunch with SAFE_STEPS, 0 = off
+    priority: i8, // process priority: -1 below normal, 0 normal, 1 above normal, 2 high
+    performance_cores: bool, // keep the game off a hybrid CPU's efficiency cores
 }
 
 impl Default for LauncherOptions {
@@ -38,11 +246,68 @@ impl Default for LauncherOptions {
             bgtype: 1,
             background_color: (66, 113, 183),
             onload: 1,
+            startup: 0,
+            unfocused_fps: 10,
+            battery_saver: 100,
+            battery_fps: 30,
+            pause_music_unfocused: true,
+            launch_env: HashMap::new(),
+            launch_wrapper: Vec::new(),
+            games: HashMap::new(),
+            handheld: None,
+            game_executable: None,
+            game_order: vec![
+                "smb1.sfc".to_string(),
+                "smbll.sfc".to_string(),
+                "smw.sfc".to_string(),
+            ],
+            show_hidden: false,
+            collections: vec![
+                "Originals".to_string(),
+                "Hacks".to_string(),
+                "Kaizo".to_string(),
+            ],
+            base_rom: "smw.sfc".to_string(),
+            pre_launch_hook: Vec::new(),
+            post_exit_hook: Vec::new(),
+            remote_port: None,
+            streamer_mode: false,
+            now_playing_file: true,
+            theme: None,
+            palette: String::new(),
+            language: String::new(),
+            widgets: widgets::WidgetOptions::default(),
+            update_server: None,
+            dismissed_news: Vec::new(),
+            stats_upload_url: None,
+            smw_source_repo: "https://github.com/snesrev/smw.git".to_string(),
+            game_options: None,
+            volume: 100,
+            audio_latency: 1,
+            sort_mode: 0,
+            new_badge_days: 7,
+            launcher_keys: HashMap::new(),
+            auto_covers: false,
+            selected_rom: None,
+            pad_back_quit: 0,
+            transition_speed: 1.0,
+            easing: anim::EASE_OUT,
+            cache_ttl_hours: 24,
+            playtime_limit_mins: 0,
+            playtime_pin: String::new(),
+            max_age: 0,
+            task_notifications: true,
+            fullscreen_handoff: false,
+            saves_dir: None,
+            saves_dir_used: None,
+            slow_startup_warned: false,
+            low_latency: false,
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 struct GameOptions {
     autosave: bool,
     disable_frame_delay: bool,
@@ -89,683 +354,6180 @@ impl Default for GameOptions {
     }
 }
 
+/// A setting changed both in the launcher and by an outside edit of smw.ini.
+struct IniConflict {
+    section: &'static str,
+    key: &'static str,
+    ours: String,
+    theirs: String,
+}
+
+impl GameOptions {
+    /// Reads smw.ini, keeping the defaults for anything missing or unparsable.
+    fn from_ini(content: &str) -> Self {
+        let mut options = GameOptions::default();
+        for (section, key, _) in GameOptions::default().ini_values() {
+            if let Some(value) = ini::get(content, section, key) {
+                if !options.set_ini_value(Self::value_name(section, key), &value) {
+                    eprintln!("Ignoring smw.ini value {}.{} = {}, keeping the default", section, key, value);
+                }
+            }
+        }
+        options
+    }
+    
+    /// (section, key, value) as smw.ini spells them.
+    fn ini_values(&self) -> Vec<(&'static str, &'static str, String)> {
+        let flag = |b: bool| if b { "1" } else { "0" }.to_string();
+        vec![
+            ("General", "Autosave", flag(self.autosave)),
+            ("General", "DisableFrameDelay", flag(self.disable_frame_delay)),
+            ("General", "SavePlaythrough", flag(self.save_playthrough)),
+            ("Graphics", "WindowSize", self.window_size.clone()),
+            ("Graphics", "Fullscreen", self.fullscreen.to_string()),
+            ("Graphics", "WindowScale", self.window_scale.to_string()),
+            ("Graphics", "NewRenderer", flag(self.new_renderer)),
+            ("Graphics", "IgnoreAspectRatio", flag(self.ignore_aspect_ratio)),
+            ("Graphics", "NoSpriteLimits", flag(self.no_sprite_limits)),
+            ("Graphics", "OutputMethod", self.output_method.clone()),
+            ("Graphics", "LinearFiltering", flag(self.linear_filtering)),
+            ("Graphics", "Shader", self.shader.clone()),
+            ("Sound", "EnableAudio", flag(self.enable_audio)),
+            ("Sound", "AudioFreq", self.audio_freq.to_string()),
+            ("Sound", "AudioChannels", self.audio_channels.to_string()),
+            ("Sound", "AudioSamples", self.audio_samples.to_string()),
+            ("KeyMap", "Controls", self.controls.clone()),
+            ("GamepadMap", "Controls", self.gamepad_controls.clone()),
+        ]
+    }
+    
+    /// Name `set_ini_value` knows an smw.ini key by.
+    fn value_name(section: &str, key: &'static str) -> &'static str {
+        if section == "GamepadMap" { "GamepadControls" } else { key }
+    }
+    
+    /// Applies one smw.ini value by key; false for unknown keys or values that don't parse.
+    /// `Controls` is ambiguous here and sets the keyboard map, use `GamepadControls` for the pad.
+    fn set_ini_value(&mut self, key: &str, value: &str) -> bool {
+        let flag = |v: &str| match v {
+            "1" | "true" => Some(true),
+            "0" | "false" => Some(false),
+            _ => None,
+        };
+        fn set<T>(field: &mut T, value: Option<T>) -> bool {
+            value.map(|v| *field = v).is_some()
+        }
+        match key {
+            "Autosave" => set(&mut self.autosave, flag(value)),
+            "DisableFrameDelay" => set(&mut self.disable_frame_delay, flag(value)),
+            "SavePlaythrough" => set(&mut self.save_playthrough, flag(value)),
+            "WindowSize" => set(&mut self.window_size, Some(value.to_string())),
+            "Fullscreen" => set(&mut self.fullscreen, value.parse().ok()),
+            "WindowScale" => set(&mut self.window_scale, value.parse().ok()),
+            "NewRenderer" => set(&mut self.new_renderer, flag(value)),
+            "IgnoreAspectRatio" => set(&mut self.ignore_aspect_ratio, flag(value)),
+            "NoSpriteLimits" => set(&mut self.no_sprite_limits, flag(value)),
+            "OutputMethod" => set(&mut self.output_method, Some(value.to_string())),
+            "LinearFiltering" => set(&mut self.linear_filtering, flag(value)),
+            "Shader" => set(&mut self.shader, Some(value.to_string())),
+            "EnableAudio" => set(&mut self.enable_audio, flag(value)),
+            "AudioFreq" => set(&mut self.audio_freq, value.parse().ok()),
+            "AudioChannels" => set(&mut self.audio_channels, value.parse().ok()),
+            "AudioSamples" => set(&mut self.audio_samples, value.parse().ok()),
+            "Controls" => set(&mut self.controls, Some(value.to_string())),
+            "GamepadControls" => set(&mut self.gamepad_controls, Some(value.to_string())),
+            _ => false,
+        }
+    }
+}
+
+/// A problem found by the pre-launch checklist. Fatal issues block the launch,
+/// the rest can be skipped with "Launch anyway".
+struct LaunchIssue {
+    fatal: bool,
+    message: String,
+    fix: Option<(String, MenuAction)>, // offered next to "Launch anyway"
+}
+
+/// A game process started by the launcher, polled every frame until it exits.
+struct RunningGame {
+    child: Child,
+    rom: String,
+    log_path: PathBuf,
+    started: Instant,
+}
+
+/// A launch in progress. It runs over several frames so the window keeps drawing:
+/// the music and screen fade out, the chime plays, the pre-launch hook runs, then the game is spawned.
+struct Launching {
+    rom: String,
+    phase: LaunchPhase,
+    since: Instant,
+    hook: Option<Receiver<Result<(), String>>>, // outcome of the pre-launch hook, see prepare_launch
+}
+
+enum LaunchPhase {
+    Fade,
+    Chime,
+    Hook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum NavDirection {
+    Left,
+    Right,
+}
+
+impl NavDirection {
+    fn delta(self) -> i32 {
+        match self {
+            NavDirection::Left => -1,
+            NavDirection::Right => 1,
+        }
+    }
+}
+
+/// Launcher-side auto-repeat for a held direction, shared by keyboard and D-pad
+/// so both behave the same regardless of OS key repeat settings.
+struct NavRepeat {
+    held: Option<(NavDirection, Instant)>,
+    last_fire: Instant,
+}
+
+impl NavRepeat {
+    fn new() -> Self {
+        NavRepeat {
+            held: None,
+            last_fire: Instant::now(),
+        }
+    }
+    
+    fn press(&mut self, direction: NavDirection) {
+        let now = Instant::now();
+        self.held = Some((direction, now));
+        self.last_fire = now;
+    }
+    
+    fn release(&mut self, direction: NavDirection) {
+        if matches!(self.held, Some((held, _)) if held == direction) {
+            self.held = None;
+        }
+    }
+    
+    fn clear(&mut self) {
+        self.held = None;
+    }
+    
+    fn is_held(&self) -> bool {
+        self.held.is_some()
+    }
+    
+    /// Returns the held direction whenever a repeat step is due.
+    fn poll(&mut self) -> Option<NavDirection> {
+        let (direction, since) = self.held?;
+        if since.elapsed() >= REPEAT_DELAY && self.last_fire.elapsed() >= REPEAT_INTERVAL {
+            self.last_fire = Instant::now();
+            return Some(direction);
+        }
+        None
+    }
+}
+
 struct Launcher {
     install_dir: PathBuf,
     sfc_dir: PathBuf,
     launcher_dir: PathBuf,
     launcher_options: LauncherOptions,
     gamepad_system: Option<Gilrs>,
+    pad_style: Option<glyphs::PadStyle>, // controller used last, None once the keyboard or mouse is used
+    pad_battery: Option<(u8, bool)>, // emptiest wireless controller's charge in percent, and whether it's charging
+    pad_battery_polled: Option<Instant>,
+    pad_battery_warned: bool, // the low battery toast was shown for this discharge
+    pad_lost: Option<String>, // the only controller disconnected; shown until it's back or a key is pressed
+    stick: (i8, i8), // left stick direction held on the X and Y axes, -1/0/1
     selected_game: usize,
     mouse_x: i32,
     mouse_y: i32,
+    pointer_inside: bool, // the pointer is over the focused window; hover and the drawn cursor need it
     color_transitions: HashMap<usize, f32>, // Track color blend for each game (0.0 = grayscale, 1.0 = full color)
+    game_process: Option<RunningGame>,
+    handheld: bool,
+    fullscreen_display: Option<i32>, // display the launcher window was fullscreen on at the last launch
+    saves_dir: PathBuf, // where the game's saves are, see locate_saves
+    sandbox: Sandbox,
+    nav_repeat: NavRepeat,
+    bump: Option<(Instant, i32)>, // selection ran into the end of the list: when, and the direction pressed
+    moved: Option<Instant>, // selection last moved by keys or a gamepad, for the navigation sound
+    active_tab: usize, // 0 = all games, then one tab per collection
+    variants: HashMap<String, Vec<String>>, // card ROM -> all region variants, itself included
+    bundles: HashMap<String, bundles::Bundle>, // card ROM -> its bundle, listing only installed games
+    plugins: plugins::PluginHost,
+    remote: Option<remote::RemoteServer>,
+    theme: theme::Theme,
+    stats: stats::Stats,
+    library: library::Library,
+    hashing: library::HashPool,
+    cover_capture: autocover::CoverCapture, // hidden game runs saving title screens, see autocover.rs
+    source_build: Option<build::SourceBuild>, // last smw build from source, kept while its view is closed
+    library_unsaved: bool, // scan results waiting for the hash pool to finish before library.json is written
+    news: news::News,
+    achievements: achievements::Unlocked,
+    continue_focused: bool, // the Continue tile has the selector instead of a card
+    last_session: Option<usize>, // index into stats.sessions for the Continue tile, see refresh_continue
+    last_save: Option<std::time::SystemTime>, // newest file in saves_dir, see refresh_continue
+    focused_button: Option<usize>, // button bar entry that has the selector instead of a card
+    config_error: Option<String>, // the options file failed to load, so it must not be overwritten
+    status: status::SystemStatus,
+    safe_trial: Option<(String, usize)>, // ROM running with SAFE_STEPS up to this index, to see if they help
+    damaged_saves: HashMap<String, Vec<usize>>, // ROM file -> save slots failing their checksum, see sram.rs
+    exits_cleared: HashMap<String, u32>, // ROM file -> most exits cleared in one of its save slots
+    session: Option<(String, session::Overrides)>, // "Launch with options" settings for the next run of this ROM
+    playtime_unlocked: Option<chrono::NaiveDate>, // day the PIN lifted the playtime limit
+    playtime_warned: bool, // the running game got its ten minute notice
+    ini_seen: Option<(std::time::SystemTime, String)>, // smw.ini as the launcher last read or wrote it
+    ini_conflicts: Vec<IniConflict>, // waiting for the player to pick a side
+    read_only: bool, // set up by `load` for a CLI command: nothing is written, started or probed
 }
 
 impl Launcher {
-    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    fn new(flags: status::StartupFlags) -> Result<Self, Box<dyn std::error::Error>> {
         let install_dir = Self::get_install_dir()?;
-        let sfc_dir = install_dir.join("sfcs");
         let launcher_dir = install_dir.join("launcher");
         
-        fs::create_dir_all(&sfc_dir)?;
+        let first_run = !launcher_dir.exists();
+        fs::create_dir_all(install_dir.join("sfcs"))?;
         fs::create_dir_all(&launcher_dir)?;
-        fs::create_dir_all(&launcher_dir.join("UI"))?;
-        fs::create_dir_all(&launcher_dir.join("pngs"))?;
-        let launcher_options = Self::load_launcher_options(&launcher_dir)?;
+        fs::create_dir_all(launcher_dir.join("UI"))?;
+        fs::create_dir_all(launcher_dir.join("pngs"))?;
+        fs::create_dir_all(launcher_dir.join("plugins"))?;
+        if first_run {
+            // There are no settings yet, so the default server it is
+            println!("First run, setting up {}", streamer::path(&install_dir));
+            if let Err(e) = install::run(&install_dir, LauncherOptions::default().update_server.as_deref(), true) {
+                eprintln!("First-run setup failed: {}", e);
+            }
+        }
+        
+        let mut launcher = Self::load(install_dir, flags)?;
+        launcher.read_only = false;
+        launcher.plugins = plugins::PluginHost::load(&launcher.launcher_dir.join("plugins"));
+        if flags.no_gamepad {
+            launcher.status.set(Subsystem::Gamepad, Some("Off (started with --no-gamepad)".to_string()));
+        } else {
+            launcher.gamepad_system = Self::init_gamepads(&mut launcher.status, &launcher.launcher_dir);
+        }
+        let update_server = if flags.safe_mode {
+            launcher.status.set(Subsystem::Network, Some("Off in safe mode".to_string()));
+            None
+        } else {
+            launcher.launcher_options.update_server.clone()
+        };
+        let download_cache = cache::Cache::new(&launcher.launcher_dir, launcher.launcher_options.cache_ttl_hours);
+        launcher.news = news::News::start(download_cache, update_server.as_deref());
         
-        let gamepad_system = Gilrs::new().ok();
-        if gamepad_system.is_none() {
-            eprintln!("Warning: Could not initialize gamepad support");
+        launcher.import_game_ini();
+        launcher.note_game_ini();
+        launcher.locate_saves();
+        sram::recover_slots(&launcher.saves_dir);
+        launcher.check_saves();
+        Ok(launcher)
+    }
+    
+    /// The launcher as `new` sets it up, minus everything that writes, starts or moves something:
+    /// no folders or first-run setup, plugins, gamepads, news fetch or save recovery. For the
+    /// command line modes that only report on or export the install.
+    fn load(install_dir: PathBuf, flags: status::StartupFlags) -> Result<Self, Box<dyn std::error::Error>> {
+        let sfc_dir = install_dir.join("sfcs");
+        let launcher_dir = install_dir.join("launcher");
+        // A broken options file falls back to the defaults; the error is shown once the window is up
+        let (launcher_options, config_error) = match Self::load_launcher_options(&launcher_dir) {
+            Ok(options) => (options, None),
+            Err(e) => {
+                eprintln!("Failed to load launcher options: {}", e);
+                (LauncherOptions::default(), Some(e.to_string()))
+            }
+        };
+        streamer::set_enabled(launcher_options.streamer_mode);
+        let theme = if flags.safe_mode {
+            println!("Safe mode: default theme, no network");
+            theme::Theme { bgtype: theme::BG_COLOR, ..theme::Theme::default() }
         } else {
-            println!("Gamepad system initialized successfully");
+            Self::saved_theme(&launcher_dir, &launcher_options)
+        };
+        let handheld = launcher_options.handheld.unwrap_or_else(platform::is_steam_deck);
+        if handheld {
+            println!("Handheld preset enabled");
         }
+        let download_cache = cache::Cache::new(&launcher_dir, launcher_options.cache_ttl_hours);
         
-        Ok(Launcher {
+        let mut launcher = Launcher {
             install_dir,
             sfc_dir,
             launcher_dir,
             launcher_options,
-            gamepad_system,
+            gamepad_system: None,
+            pad_style: None,
+            pad_battery: None,
+            pad_battery_polled: None,
+            pad_battery_warned: false,
+            pad_lost: None,
+            stick: (0, 0),
             selected_game: 0,
             mouse_x: 0,
             mouse_y: 0,
+            pointer_inside: false,
             color_transitions: HashMap::new(),
-        })
+            game_process: None,
+            handheld,
+            fullscreen_display: None,
+            saves_dir: PathBuf::new(),
+            sandbox: platform::detect_sandbox(),
+            nav_repeat: NavRepeat::new(),
+            bump: None,
+            moved: None,
+            active_tab: 0,
+            variants: HashMap::new(),
+            bundles: HashMap::new(),
+            plugins: plugins::PluginHost::default(),
+            remote: None,
+            theme,
+            stats: stats::Stats::load(&launcher_dir),
+            library: library::Library::load(&launcher_dir),
+            hashing: library::HashPool::new(),
+            cover_capture: autocover::CoverCapture::new(&launcher_dir.join("pngs")),
+            source_build: None,
+            library_unsaved: false,
+            achievements: achievements::Unlocked::load(&launcher_dir),
+            news: news::News::start(download_cache, None),
+            continue_focused: false,
+            last_session: None,
+            last_save: None,
+            focused_button: None,
+            config_error,
+            status: status::SystemStatus::default(),
+            safe_trial: None,
+            damaged_saves: HashMap::new(),
+            exits_cleared: HashMap::new(),
+            session: None,
+            playtime_unlocked: None,
+            playtime_warned: false,
+            ini_seen: None,
+            ini_conflicts: Vec::new(),
+            read_only: true,
+        };
+        
+        launcher.locate_saves();
+        launcher.refresh_continue();
+        Ok(launcher)
+    }
+    
+    fn init_gamepads(status: &mut status::SystemStatus, launcher_dir: &Path) -> Option<Gilrs> {
+        // The controller database from the updater adds mappings for pads gilrs doesn't know
+        let mut builder = GilrsBuilder::new();
+        if let Ok(mappings) = fs::read_to_string(launcher_dir.join(updates::CONTROLLER_DB)) {
+            builder = builder.add_mappings(&mappings);
+        }
+        match builder.build() {
+            Ok(gilrs) => {
+                println!("Gamepad system initialized successfully");
+                status.set(Subsystem::Gamepad, None);
+                Some(gilrs)
+            }
+            Err(e) => {
+                eprintln!("Warning: Could not initialize gamepad support: {}", e);
+                status.set(Subsystem::Gamepad, Some(e.to_string()));
+                None
+            }
+        }
+    }
+    
+    /// What's wrong with a subsystem, None when it works.
+    fn subsystem_problem(&self, subsystem: Subsystem) -> Option<String> {
+        if let Some(problem) = self.status.problem(subsystem) {
+            return Some(problem.to_string());
+        }
+        if subsystem != Subsystem::Network || self.launcher_options.update_server.is_none() {
+            return None;
+        }
+        self.news.error().map(streamer::scrub)
     }
     
     fn get_install_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let current_dir = std::env::current_dir()?;
-        Ok(current_dir)
+        match platform::detect_sandbox() {
+            Sandbox::None => {
+                #[cfg(target_os = "windows")]
+                if let Some(dir) = platform::windows_install_dir() {
+                    return Ok(dir);
+                }
+                #[cfg(target_os = "macos")]
+                if let Some(dir) = platform::macos_install_dir() {
+                    return Ok(dir);
+                }
+                
+                let current_dir = std::env::current_dir()?;
+                Ok(current_dir)
+            }
+            // The working directory is meaningless inside a Flatpak/AppImage, use XDG data instead
+            sandbox => {
+                let data_dir = platform::xdg_data_dir().ok_or("Could not resolve the XDG data directory")?;
+                println!("Running as {:?}, using data directory: {}", sandbox, streamer::path(&data_dir));
+                Ok(data_dir)
+            }
+        }
     }
     
     fn load_launcher_options(launcher_dir: &Path) -> Result<LauncherOptions, Box<dyn std::error::Error>> {
-        let options_path = launcher_dir.join("launcher.json");
+        let (options_path, format) = config::locate(launcher_dir);
         
         if options_path.exists() {
-            let content = fs::read_to_string(options_path)?;
-            Ok(serde_json::from_str(&content)?)
+            let content = fs::read_to_string(&options_path)?;
+            let name = options_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            Ok(config::parse(&content, format).map_err(|e| format!("{}: {}", name, e))?)
         } else {
             Ok(LauncherOptions::default())
         }
     }
     
-    fn save_launcher_options(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let options_path = self.launcher_dir.join("launcher.json");
-        let content = serde_json::to_string_pretty(&self.launcher_options)?;
-        fs::write(options_path, content)?;
-        Ok(())
+    /// Unlocks achievements the play history now qualifies for and returns their names.
+    fn check_achievements(&mut self) -> Vec<&'static str> {
+        let library = self.scan_sfc_files();
+        let new: Vec<&'static str> = self.achievements.check(&self.stats, &library).iter().map(|a| a.name).collect();
+        if !new.is_empty() {
+            if let Err(e) = self.achievements.save(&self.launcher_dir) {
+                eprintln!("Failed to save achievements: {}", e);
+            }
+        }
+        new
     }
     
-    fn load_game_options(install_dir: &Path) -> Result<GameOptions, Box<dyn std::error::Error>> {
-        let ini_path = install_dir.join("smw.ini");
-        
-        if ini_path.exists() {
-            Ok(GameOptions::default())
-        } else {
-            Ok(GameOptions::default())
-        }
+    fn current_news(&self) -> Option<&news::NewsItem> {
+        self.news.current(&self.launcher_options.dismissed_news)
     }
     
-    fn scan_sfc_files(&self) -> Vec<String> {
-        let mut sfcs = Vec::new();
-        
-        if let Ok(entries) = fs::read_dir(&self.sfc_dir) {
-            for entry in entries.flatten() {
-                if let Some(file_name) = entry.file_name().to_str() {
-                    if file_name.to_lowercase().ends_with(".sfc") {
-                        sfcs.push(file_name.to_string());
-                    }
-                }
-            }
+    fn dismiss_news(&mut self, id: &str) {
+        self.launcher_options.dismissed_news.push(id.to_string());
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
         }
-        
-        let priority = ["smb1.sfc", "smbll.sfc", "smw.sfc"];
-        sfcs.sort_by(|a, b| {
-            let a_idx = priority.iter().position(|&x| x == a).unwrap_or(priority.len());
-            let b_idx = priority.iter().position(|&x| x == b).unwrap_or(priority.len());
-            a_idx.cmp(&b_idx)
-        });
-        
-        sfcs
     }
     
-    fn launch_game(&self, sfc_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let sfc_path = self.sfc_dir.join(sfc_name);
-        let exe_name = if cfg!(target_os = "windows") { "smw.exe" } else { "smw" };
-        let smw_path = self.install_dir.join(exe_name);
-        
-        if !smw_path.exists() {
-            eprintln!("SMW executable not found at: {}", smw_path.display());
-            return Err("SMW executable not found".into());
+    /// The theme selected in the options, as opposed to one being previewed in the editor.
+    fn saved_theme(launcher_dir: &Path, options: &LauncherOptions) -> theme::Theme {
+        let mut theme = match &options.theme {
+            Some(name) => theme::load(launcher_dir, name),
+            None => theme::Theme {
+                bgtype: options.bgtype,
+                background_color: options.background_color,
+                ..theme::Theme::default()
+            },
+        };
+        if let Some(palette) = theme::palette(&options.palette) {
+            theme.apply_palette(palette);
         }
-        
-        println!("Launching: {} with ROM: {}", exe_name, sfc_name);
-        
-        Command::new(smw_path)
-            .arg(sfc_path)
-            .current_dir(&self.install_dir)
-            .spawn()?;
-        
-        Ok(())
+        theme
     }
     
-    fn handle_gamepad_input(&mut self) -> Option<GamepadAction> {
-        if let Some(ref mut gilrs) = self.gamepad_system {
-            while let Some(GilrsEvent { event, .. }) = gilrs.next_event() {
-                match event {
-                    EventType::ButtonPressed(button, _) => {
-                        return Some(match button {
-                            Button::South => GamepadAction::Confirm,
-                            Button::East => GamepadAction::Back,
-                            Button::DPadUp | Button::North => GamepadAction::Up,
-                            Button::DPadDown => GamepadAction::Down,
-                            Button::DPadLeft | Button::West => GamepadAction::Left,
-                            Button::DPadRight => GamepadAction::Right,
-                            Button::Start => GamepadAction::Start,
-                            _ => GamepadAction::None,
-                        });
-                    }
-                    _ => {}
-                }
-            }
+    fn save_launcher_options(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.read_only {
+            return Err("the launcher was loaded read-only".into());
         }
-        None
-    }
-
-    fn get_game_box_rect(&self, idx: usize) -> Option<Rect> {
-        if idx >= 3 {
-            return None;
+        if self.config_error.is_some() {
+            return Err("the options file has errors, fix or reset it first".into());
         }
-        
-        let col = (idx % 3) + 1;
-        let box_x = match col {
-            1 => 30,
-            2 => 357,
-            _ => 684,
-        } as i32;
-        let box_y = 143;
-        
-        Some(Rect::new(box_x, box_y, BOX_SIZE.0, BOX_SIZE.1))
+        let (options_path, format) = config::locate(&self.launcher_dir);
+        config::save(&options_path, format, &self.launcher_options)?;
+        Ok(())
     }
-
-    fn update_selection_from_mouse(&mut self, sfcs: &[String]) {
-        for (idx, _) in sfcs.iter().enumerate().take(3) {
-            if let Some(rect) = self.get_game_box_rect(idx) {
-                if rect.contains_point((self.mouse_x, self.mouse_y)) {
-                    self.selected_game = idx;
-                    break;
-                }
-            }
+    
+    /// Takes over the settings of an older install, see `legacy`. Returns what happened, line by line.
+    fn import_old_settings(&mut self, old_dir: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        if self.config_error.is_some() {
+            return Err("the options file has errors, fix or reset it first".into());
         }
+        let (options, report) = Self::import_settings_into(&self.launcher_dir, &self.launcher_options, old_dir)?;
+        self.launcher_options = options;
+        Ok(report)
     }
     
-    fn update_color_transitions(&mut self, num_games: usize) {
-        for idx in 0..num_games {
-            let target = if idx == self.selected_game { 1.0 } else { 0.0 };
-            let current = self.color_transitions.entry(idx).or_insert(0.0);
-            
-            // Smooth lerp towards target
-            if (*current - target).abs() > 0.01 {
-                *current += (target - *current) * TRANSITION_SPEED;
+    /// `import_old_settings` without a Launcher, for the command line: merges the old settings
+    /// into `current` and writes the result to the options file.
+    fn import_settings_into(
+        launcher_dir: &Path,
+        current: &LauncherOptions,
+        old_dir: &Path,
+    ) -> Result<(LauncherOptions, Vec<String>), Box<dyn std::error::Error>> {
+        let import = legacy::read(old_dir)?;
+        let mut report: Vec<String> = import.sources.iter().map(|path| format!("Read {}", streamer::path(path))).collect();
+        
+        // One field at a time, so a value of the wrong type only loses that setting
+        let mut merged = serde_json::to_value(current)?;
+        let mut carried = 0;
+        let mut skipped = Vec::new();
+        for (key, value) in import.options {
+            let known = merged.get(&key).is_some() && !legacy::NOT_CARRIED.contains(&key.as_str());
+            let mut candidate = merged.clone();
+            candidate[key.as_str()] = value;
+            if known && serde_json::from_value::<LauncherOptions>(candidate.clone()).is_ok() {
+                merged = candidate;
+                carried += 1;
             } else {
-                *current = target;
+                skipped.push(key);
             }
         }
+        let mut options: LauncherOptions = serde_json::from_value(merged)?;
+        report.push(format!("Carried over {} launcher setting(s)", carried));
+        if !skipped.is_empty() {
+            skipped.sort();
+            report.push(format!("Left behind: {}", skipped.join(", ")));
+        }
+        if let Some(content) = &import.game_ini {
+            options.game_options = Some(GameOptions::from_ini(content));
+            report.push("Game settings taken from the old smw.ini".to_string());
+        }
+        
+        let (options_path, format) = config::locate(launcher_dir);
+        if options_path.exists() {
+            let backup = config::copy_backup(&options_path)?;
+            report.push(format!("Previous settings kept in {}", streamer::path(&backup)));
+        }
+        config::save(&options_path, format, &options)?;
+        Ok((options, report))
     }
     
-    fn get_color_blend(&self, idx: usize) -> f32 {
-        *self.color_transitions.get(&idx).unwrap_or(&0.0)
+    /// Replaces a broken options file with the defaults, keeping the old one as a backup.
+    fn reset_launcher_options(&mut self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let (options_path, _) = config::locate(&self.launcher_dir);
+        let backup = config::backup(&options_path)?;
+        self.launcher_options = LauncherOptions::default();
+        self.config_error = None;
+        self.save_launcher_options()?;
+        Ok(backup)
     }
-}
-
-#[derive(Debug)]
-enum GamepadAction {
-    Confirm,
-    Back,
-    Up,
-    Down,
-    Left,
-    Right,
-    Start,
-    None,
-}
-
-struct UIButton {
-    rect: Rect,
-    label: String,
-    normal_color: Color,
-    hover_color: Color,
-    pressed_color: Color,
-}
-
-impl UIButton {
-    fn new(x: i32, y: i32, width: u32, height: u32, label: &str) -> Self {
-        UIButton {
-            rect: Rect::new(x, y, width, height),
-            label: label.to_string(),
-            normal_color: Color::RGB(100, 100, 150),
-            hover_color: Color::RGB(150, 150, 200),
-            pressed_color: Color::RGB(200, 200, 250),
+    
+    fn load_game_options(game_dir: &Path) -> GameOptions {
+        match fs::read_to_string(game_dir.join("smw.ini")) {
+            Ok(content) => GameOptions::from_ini(&content),
+            Err(_) => GameOptions::default(),
         }
     }
     
-    fn is_hovered(&self, mouse_x: i32, mouse_y: i32) -> bool {
-        self.rect.contains_point((mouse_x, mouse_y))
+    /// The first time smw.ini is managed, starts from the user's current file instead of the defaults.
+    /// Until there is a file (the game may not be installed yet) nothing is saved, so a configured
+    /// smw.ini that shows up later is still picked up rather than overwritten.
+    fn import_game_ini(&mut self) {
+        if self.launcher_options.game_options.is_some() {
+            return;
+        }
+        let path = self.game_dir().join("smw.ini");
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+        let imported = GameOptions::default()
+            .ini_values()
+            .into_iter()
+            .filter(|(section, key, _)| ini::get(&content, section, key).is_some())
+            .count();
+        println!("Imported {} setting(s) from {}", imported, streamer::path(&path));
+        self.launcher_options.game_options = Some(GameOptions::from_ini(&content));
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
     }
     
-    fn draw(&self, canvas: &mut Canvas<Window>, mouse_x: i32, mouse_y: i32, pressed: bool) {
-        let color = if pressed && self.is_hovered(mouse_x, mouse_y) {
-            self.pressed_color
-        } else if self.is_hovered(mouse_x, mouse_y) {
-            self.hover_color
-        } else {
-            self.normal_color
+    /// Remembers smw.ini as it is now, so only later edits count as made outside the launcher.
+    fn note_game_ini(&mut self) {
+        let path = self.game_dir().join("smw.ini");
+        let modified = fs::metadata(&path).and_then(|m| m.modified());
+        self.ini_seen = modified.ok().zip(fs::read_to_string(&path).ok());
+    }
+    
+    /// Picks up edits made to smw.ini by hand or by the game since the launcher last read or wrote it.
+    /// Settings only the file changed are taken over; ones the launcher has its own value for are
+    /// left in `ini_conflicts`. True when there are conflicts to ask about.
+    fn reconcile_game_ini(&mut self) -> bool {
+        let path = self.game_dir().join("smw.ini");
+        let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) else {
+            return false;
+        };
+        if self.ini_seen.as_ref().map(|(seen, _)| *seen == modified).unwrap_or(false) {
+            return false;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            return false;
+        };
+        // A file that just appeared has nothing to compare with, it is written over on the next launch
+        let Some((_, before)) = self.ini_seen.replace((modified, content.clone())) else {
+            return false;
+        };
+        // Without saved options the launcher reads smw.ini as it goes, so there is nothing to update
+        let Some(ours) = self.launcher_options.game_options.clone() else {
+            return false;
         };
         
-        canvas.set_draw_color(color);
-        canvas.fill_rect(self.rect).unwrap();
-        
-        canvas.set_draw_color(Color::RGB(50, 50, 50));
-        canvas.draw_rect(self.rect).unwrap();
+        let mut updated = ours.clone();
+        let mut taken = 0;
+        self.ini_conflicts.clear();
+        for (section, key, value) in ours.ini_values() {
+            let old = ini::get(&before, section, key);
+            let Some(theirs) = ini::get(&content, section, key).filter(|theirs| Some(theirs) != old.as_ref() && *theirs != value) else {
+                continue;
+            };
+            if old.as_ref() != Some(&value) {
+                self.ini_conflicts.push(IniConflict { section, key, ours: value, theirs });
+            } else if updated.set_ini_value(GameOptions::value_name(section, key), &theirs) {
+                taken += 1;
+            } else {
+                eprintln!("Ignoring smw.ini value {}.{} = {}, keeping the launcher's", section, key, theirs);
+            }
+        }
+        if taken > 0 {
+            println!("Took {} setting(s) from {} changed outside the launcher", taken, streamer::path(&path));
+            self.launcher_options.game_options = Some(updated);
+            if let Err(e) = self.save_launcher_options() {
+                eprintln!("Failed to save launcher options: {}", e);
+            }
+        }
+        !self.ini_conflicts.is_empty()
     }
-
-    fn draw_with_text<'a>(
-        &self,
-        canvas: &mut Canvas<Window>,
-        font: &ttf::Font,
-        mouse_x: i32,
-        mouse_y: i32,
-        pressed: bool,
-        texture_creator: &'a TextureCreator<WindowContext>,
-    ) -> Result<(), String> {
-        self.draw(canvas, mouse_x, mouse_y, pressed);
-        
-        let surface = font
-            .render(&self.label)
-            .blended(Color::RGB(255, 255, 255))
-            .map_err(|e| e.to_string())?;
-        
-        let texture = texture_creator
-            .create_texture_from_surface(&surface)
-            .map_err(|e| e.to_string())?;
-        
-        let text_query = texture.query();
-        let text_rect = Rect::new(
-            self.rect.x() + (self.rect.width() as i32 - text_query.width as i32) / 2,
-            self.rect.y() + (self.rect.height() as i32 - text_query.height as i32) / 2,
-            text_query.width,
-            text_query.height,
-        );
-        
-        canvas.copy(&texture, None, Some(text_rect))?;
-        Ok(())
+    
+    /// Settles `ini_conflicts`: smw.ini's values become the launcher's, or the launcher's are
+    /// written over them on the next launch.
+    fn resolve_ini_conflicts(&mut self, use_file: bool) {
+        let conflicts = std::mem::take(&mut self.ini_conflicts);
+        if !use_file || conflicts.is_empty() {
+            return;
+        }
+        let mut game = self.game_options();
+        for conflict in conflicts {
+            if !game.set_ini_value(GameOptions::value_name(conflict.section, conflict.key), &conflict.theirs) {
+                eprintln!("Ignoring smw.ini value {}.{} = {}, keeping the launcher's", conflict.section, conflict.key, conflict.theirs);
+            }
+        }
+        self.launcher_options.game_options = Some(game);
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
     }
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("=== SMAS Launcher (Rust) - Grayscale Selection ===");
-    println!("Initializing...");
     
-    let mut launcher = Launcher::new()?;
+    /// Global game options: the saved ones, or what smw.ini says while none were saved yet.
+    fn game_options(&self) -> GameOptions {
+        self.launcher_options
+            .game_options
+            .clone()
+            .unwrap_or_else(|| Self::load_game_options(&self.game_dir()))
+    }
     
-    println!("Install directory: {}", launcher.install_dir.display());
-    println!("SFC directory: {}", launcher.sfc_dir.display());
-    println!("Launcher directory: {}", launcher.launcher_dir.display());
+    /// Display the game should go fullscreen on to take over from the launcher, if it should.
+    fn handoff_display(&self) -> Option<i32> {
+        self.fullscreen_display.filter(|_| self.launcher_options.fullscreen_handoff)
+    }
     
-    let sdl_context = sdl2::init()?;
-    let video_subsystem = sdl_context.video()?;
-    let _image_context = sdl2::image::init(InitFlag::PNG)?;
-    let ttf_context = ttf::init().map_err(|e| e.to_string())?;
-    
-    let frequency = 44_100;
-    let format = AUDIO_S16LSB;
-    let channels = DEFAULT_CHANNELS;
-    let chunk_size = 1_024;
-    
-    sdl2::mixer::open_audio(frequency, format, channels, chunk_size)?;
-    let _mixer_context = sdl2::mixer::init(MixerFlag::MP3 | MixerFlag::OGG)?;
-    sdl2::mixer::allocate_channels(4);
-    
-    // Load background music
-    let music_path = launcher.launcher_dir.join("smas.wav");
-    let music = if music_path.exists() {
-        match sdl2::mixer::Music::from_file(&music_path) {
-            Ok(m) => {
-                println!("Loaded background music: {}", music_path.display());
-                Some(m)
+    /// Global game options with the game's own overrides on top.
+    fn effective_game_options(&self, sfc_name: &str) -> GameOptions {
+        let mut options = self.game_options();
+        // A windowed global setting gives way to the handoff; exclusive fullscreen is kept
+        if self.handoff_display().is_some() && options.fullscreen == 0 {
+            options.fullscreen = 1;
+        }
+        if let Some(game) = self.launcher_options.games.get(sfc_name) {
+            for (key, value) in &game.game_options {
+                if !options.set_ini_value(key, value) {
+                    eprintln!("Ignoring smw.ini override {} = {} for {}", key, value, sfc_name);
+                }
             }
-            Err(e) => {
-                eprintln!("Failed to load background music: {}", e);
-                None
+        }
+        if let Some((_, step)) = self.safe_trial.as_ref().filter(|(rom, _)| rom == sfc_name) {
+            for (key, value) in SAFE_STEPS[..=*step].iter().flat_map(|(_, overrides)| overrides.iter()) {
+                options.set_ini_value(key, value);
             }
         }
-    } else {
-        eprintln!("Background music not found at: {}", music_path.display());
-        None
-    };
-    
-    // Load launch sound effect
-    let launch_sound_path = launcher.launcher_dir.join("pg.wav");
-    let launch_sound = if launch_sound_path.exists() {
-        match sdl2::mixer::Chunk::from_file(&launch_sound_path) {
-            Ok(s) => {
-                println!("Loaded launch sound: {}", launch_sound_path.display());
-                Some(s)
+        if let Some((_, overrides)) = self.session.as_ref().filter(|(rom, _)| rom == sfc_name) {
+            for (key, value) in overrides.ini_values() {
+                options.set_ini_value(key, &value);
             }
-            Err(e) => {
-                eprintln!("Failed to load launch sound: {}", e);
-                None
+        }
+        options
+    }
+    
+    /// Current smw.ini and the content it gets before launching this game.
+    fn game_ini(&self, sfc_name: &str) -> (String, String) {
+        let old = fs::read_to_string(self.game_dir().join("smw.ini")).unwrap_or_default();
+        let mut new = old.clone();
+        for (section, key, value) in self.effective_game_options(sfc_name).ini_values() {
+            // Empty key maps mean "use the game's built-in bindings"
+            if !value.is_empty() {
+                new = ini::set(&new, section, key, &value);
             }
         }
-    } else {
-        eprintln!("Launch sound not found at: {}", launch_sound_path.display());
-        None
-    };
+        (old, new)
+    }
     
-    // Play music if loaded
-    if let Some(ref m) = music {
-        m.play(-1)?; // -1 for infinite loop
+    /// Human-readable "Section.Key: old -> new" lines for the dry-run view.
+    fn game_ini_changes(&self, sfc_name: &str) -> Vec<String> {
+        let (old, new) = self.game_ini(sfc_name);
+        GameOptions::default()
+            .ini_values()
+            .into_iter()
+            .filter_map(|(section, key, _)| {
+                let before = ini::get(&old, section, key);
+                let after = ini::get(&new, section, key);
+                (before != after).then(|| {
+                    format!("{}.{}: {} -> {}", section, key, before.as_deref().unwrap_or("(unset)"), after.unwrap_or_default())
+                })
+            })
+            .collect()
     }
     
-    let display_mode = video_subsystem.current_display_mode(0)?;
-    let refresh_rate = display_mode.refresh_rate;
-    println!("Display refresh rate: {}Hz", refresh_rate);
+    fn write_game_ini(&self, sfc_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (old, new) = self.game_ini(sfc_name);
+        if old == new {
+            return Ok(());
+        }
+        let ini_path = self.game_dir().join("smw.ini");
+        backup::backup_file(&self.backup_dir(), &ini_path, "smw.ini update")?;
+        fs::write(&ini_path, new)?;
+        Ok(())
+    }
     
-    let target_frame_time = Duration::from_secs_f64(1.0 / refresh_rate as f64);
+    /// "None" followed by the shaders in the game's shaders/ folder, as smw.ini refers to them.
+    fn shader_choices(&self) -> Vec<String> {
+        let mut shaders: Vec<String> = fs::read_dir(self.game_dir().join("shaders"))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|name| name.ends_with(".glsl") || name.ends_with(".glslp"))
+                    .map(|name| format!("shaders/{}", name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        shaders.sort();
+        shaders.insert(0, "None".to_string());
+        shaders
+    }
     
-    let window = video_subsystem
-        .window("SMAS Launcher", SCREEN_WIDTH, SCREEN_HEIGHT)
-        .position_centered()
-        .build()?;
+    /// Saves a latency preset and carries its buffer size over to smw.ini for the next launch.
+    fn set_audio_latency(&mut self, preset: u8) {
+        self.launcher_options.audio_latency = preset;
+        let (_, _, samples) = AUDIO_LATENCY_PRESETS[preset as usize];
+        let mut game = self.game_options();
+        game.audio_samples = samples;
+        self.launcher_options.game_options = Some(game);
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
     
-    let mut canvas = window.into_canvas()
-        .accelerated()
-        .present_vsync()
-        .build()?;
+    /// Screenshot showing what a shader looks like: `<shader>.png` next to the shader file,
+    /// or `launcher/shader_previews/<name>.png` (where "None.png" can show the unfiltered look).
+    fn shader_preview_path(&self, shader: &str) -> Option<PathBuf> {
+        let name = Path::new(shader).file_stem()?.to_string_lossy().into_owned();
+        [
+            self.game_dir().join(shader).with_extension("png"),
+            self.launcher_dir.join("shader_previews").join(format!("{}.png", name)),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+    }
     
-    let texture_creator = canvas.texture_creator();
+    /// One row per entry of `quick_settings::SETTINGS`.
+    fn quick_setting_rows(&self) -> Vec<OptionRow> {
+        let game = self.game_options();
+        let shaders = self.shader_choices();
+        quick_settings::SETTINGS
+            .iter()
+            .map(|setting| match setting {
+                QuickSetting::Fullscreen => {
+                    let modes = ["Off", "Desktop", "Exclusive"].map(String::from).to_vec();
+                    OptionRow::choice("Fullscreen", modes, game.fullscreen as usize)
+                }
+                QuickSetting::Shader => {
+                    let names = shaders
+                        .iter()
+                        .map(|s| Path::new(s).file_stem().map_or(s.clone(), |n| n.to_string_lossy().into_owned()))
+                        .collect();
+                    let current = shaders.iter().position(|s| *s == game.shader).unwrap_or(0);
+                    OptionRow::choice("Shader", names, current)
+                }
+                QuickSetting::Volume => OptionRow::slider("Volume", self.launcher_options.volume as i32, 0, 100, 10).unit("%"),
+                QuickSetting::Autosave => OptionRow::toggle("Autosave", game.autosave),
+            })
+            .collect()
+    }
     
-    sdl_context.mouse().show_cursor(false);
+    /// Sets one quick setting to its row's new `value` and saves right away; game settings reach smw.ini on the next launch.
+    fn change_quick_setting(&mut self, setting: QuickSetting, value: i32) {
+        let shaders = self.shader_choices();
+        let current = self.game_options();
+        let volume = &mut self.launcher_options.volume;
+        let game = self.launcher_options.game_options.get_or_insert(current);
+        match setting {
+            QuickSetting::Fullscreen => game.fullscreen = value.clamp(0, 2) as u8,
+            QuickSetting::Shader => {
+                if let Some(shader) = shaders.get(value.max(0) as usize) {
+                    game.shader = shader.clone();
+                }
+            }
+            QuickSetting::Volume => *volume = value.clamp(0, 100) as u8,
+            QuickSetting::Autosave => game.autosave = value != 0,
+        }
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    fn scan_sfc_files(&self) -> Vec<String> {
+        let mut sfcs = Vec::new();
+        
+        if let Ok(entries) = fs::read_dir(&self.sfc_dir) {
+            for entry in entries.flatten() {
+                if let Some(file_name) = entry.file_name().to_str() {
+                    if file_name.to_lowercase().ends_with(".sfc") {
+                        sfcs.push(file_name.to_string());
+                    }
+                }
+            }
+        }
+        
+        if self.launcher_options.sort_mode == 1 {
+            sfcs.sort_by_key(|name| (std::cmp::Reverse(self.library.added(name)), name.to_lowercase()));
+        } else {
+            let order = &self.launcher_options.game_order;
+            sfcs.sort_by_key(|name| {
+                let idx = order.iter().position(|x| x == name).unwrap_or(order.len());
+                (idx, name.to_lowercase())
+            });
+        }
+        
+        sfcs
+    }
+    
+    /// Matches scanned ROMs to their library entries. New entries take over the metadata older
+    /// versions kept in launcher.json, and files renamed outside the launcher bring their settings along.
+    fn note_scan(&mut self, all_games: &[String]) {
+        let (changes, stale) = self.library.scan(&self.sfc_dir, all_games);
+        self.hashing.queue(&self.sfc_dir, stale);
+        self.apply_scan(changes);
+    }
+    
+    /// Picks up ROMs the hash pool finished; returns true when the grid needs refreshing.
+    /// Files that failed to hash don't count, or the refresh would queue them again right away.
+    fn poll_hashing(&mut self) -> bool {
+        let done = self.hashing.poll();
+        self.apply_hashes(done)
+    }
+    
+    /// Files finished hashes in the library; true when that changed anything.
+    fn apply_hashes(&mut self, done: Vec<library::Hashed>) -> bool {
+        if done.is_empty() {
+            return false;
+        }
+        let mut changes = library::ScanChanges::default();
+        for hashed in done {
+            self.library.apply(&self.sfc_dir, hashed, &mut changes);
+        }
+        let changed = changes.changed;
+        self.apply_scan(changes);
+        changed
+    }
+    
+    fn apply_scan(&mut self, changes: library::ScanChanges) {
+        let mut migrated = false;
+        for file in &changes.added {
+            let (Some(config), Some(entry)) = (self.launcher_options.games.get_mut(file), self.library.entry_mut(file)) else {
+                continue;
+            };
+            if config.hidden || !config.collections.is_empty() || config.display_name.is_some() {
+                entry.hidden = std::mem::take(&mut config.hidden);
+                entry.collections = std::mem::take(&mut config.collections);
+                entry.display_name = config.display_name.take();
+                migrated = true;
+            }
+        }
+        if migrated {
+            if let Err(e) = self.save_launcher_options() {
+                eprintln!("Failed to save launcher options: {}", e);
+            }
+        }
+        for (old, new) in &changes.moved {
+            if let Err(e) = self.follow_rename(old, new) {
+                eprintln!("Failed to carry {} settings over to {}: {}", old, new, e);
+            }
+        }
+        // While the pool is busy, library.json is written once it finishes rather than per result
+        self.library_unsaved |= changes.changed;
+        if self.library_unsaved && self.hashing.pending() == 0 {
+            self.save_library();
+            self.library_unsaved = false;
+        }
+    }
+    
+    /// The ROM's library entry, scanning it first when it only just appeared, e.g. a freshly patched hack.
+    fn library_entry(&mut self, sfc_name: &str) -> Option<&mut library::Entry> {
+        if self.library.entry(sfc_name).is_none() {
+            let mut changes = library::ScanChanges::default();
+            let hashed = library::HashPool::hash_now(&self.sfc_dir, sfc_name);
+            self.library.apply(&self.sfc_dir, hashed, &mut changes);
+            self.apply_scan(changes);
+        }
+        self.library.entry_mut(sfc_name)
+    }
+    
+    fn save_library(&self) {
+        if let Err(e) = self.library.save(&self.launcher_dir) {
+            eprintln!("Failed to save library: {}", e);
+        }
+    }
+    
+    /// Whether a card gets the NEW badge; a freshly added region version counts for its card.
+    fn is_new(&self, card: &str) -> bool {
+        let days = self.launcher_options.new_badge_days;
+        match self.variants.get(card) {
+            Some(members) => members.iter().any(|m| self.library.is_new(m, days)),
+            None => self.library.is_new(card, days),
+        }
+    }
+    
+    /// Corner badges of a card, drawn by `badges::BadgeRenderer`.
+    fn card_badges(&self, card: &str) -> Vec<badges::Badge> {
+        let rom = self.resolve_variant(card);
+        let header = self.library.header(&rom);
+        let mut list = Vec::new();
+        if self.is_new(card) {
+            list.push(badges::Badge::new("new", "NEW"));
+        }
+        if self.is_favorite(card) {
+            list.push(badges::Badge::new("favorite", "*"));
+        }
+        if header.map(|h| !h.checksum_ok).unwrap_or(false) {
+            list.push(badges::Badge::new("checksum", "!"));
+        }
+        if self.damaged_saves.contains_key(&rom) {
+            list.push(badges::Badge::new("save", "SAVE"));
+        }
+        if let Some(code) = header.and_then(|h| h.region.as_deref()).and_then(badges::region_code) {
+            list.push(badges::Badge::new("region", code));
+        }
+        match self.age_rating(card) {
+            0 => {}
+            age => list.push(badges::Badge::new("age", &rating::label(age))),
+        }
+        list
+    }
+    
+    fn is_hidden(&self, sfc_name: &str) -> bool {
+        self.library.entry(sfc_name).map(|e| e.hidden).unwrap_or(false)
+    }
+    
+    fn age_rating(&self, sfc_name: &str) -> u8 {
+        self.library.entry(sfc_name).map(|e| e.age_rating).unwrap_or(0)
+    }
+    
+    fn cycle_age_rating(&mut self, sfc_name: &str) {
+        if let Some(entry) = self.library_entry(sfc_name) {
+            entry.age_rating = rating::next(&rating::AGES, entry.age_rating);
+            self.save_library();
+        }
+    }
+    
+    fn toggle_descriptor(&mut self, sfc_name: &str, descriptor: &str) {
+        if let Some(entry) = self.library_entry(sfc_name) {
+            match entry.descriptors.iter().position(|d| d == descriptor) {
+                Some(idx) => {
+                    entry.descriptors.remove(idx);
+                }
+                None => entry.descriptors.push(descriptor.to_string()),
+            }
+            self.save_library();
+        }
+    }
+    
+    fn is_favorite(&self, sfc_name: &str) -> bool {
+        self.library.entry(sfc_name).map(|e| e.favorite).unwrap_or(false)
+    }
+    
+    fn set_favorite(&mut self, sfc_name: &str, favorite: bool) {
+        if let Some(entry) = self.library_entry(sfc_name) {
+            entry.favorite = favorite;
+            self.save_library();
+        }
+    }
+    
+    /// Shows `path` as the game's cover instead of the one in pngs/, or goes back to that with None.
+    fn set_cover_override(&mut self, sfc_name: &str, path: Option<PathBuf>) -> Result<(), String> {
+        if let Some(path) = &path {
+            let supported = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| textures::COVER_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                .unwrap_or(false);
+            if !path.is_file() || !supported {
+                return Err(format!("{} is not an image file", streamer::path(path)));
+            }
+        }
+        let entry = self.library_entry(sfc_name).ok_or_else(|| format!("{} is not in the library", sfc_name))?;
+        entry.cover = path;
+        self.library.save(&self.launcher_dir)
+    }
+    
+    /// Games shown in the grid: everything scanned minus hidden entries, unless those are shown.
+    /// Bundles and region variants are each folded into a single card.
+    fn visible_games(&mut self) -> Vec<String> {
+        let games: Vec<String> = self.scan_sfc_files()
+            .into_iter()
+            .filter(|sfc| self.launcher_options.show_hidden || !self.is_hidden(sfc))
+            .filter(|sfc| rating::allowed(self.age_rating(sfc), self.launcher_options.max_age))
+            .filter(|sfc| match self.active_collection() {
+                Some(collection) => self.in_collection(sfc, collection),
+                None => true,
+            })
+            .collect();
+        let games = self.group_bundles(games);
+        self.group_variants(games)
+    }
+    
+    /// Replaces the games of each bundle with one card, shown where its first game would be.
+    fn group_bundles(&mut self, mut games: Vec<String>) -> Vec<String> {
+        self.bundles.clear();
+        for mut bundle in bundles::load_all(&self.sfc_dir) {
+            bundle.games.retain(|g| games.contains(&g.rom));
+            let Some(pos) = games.iter().position(|g| bundle.contains(g)) else {
+                continue;
+            };
+            let card = games[pos].clone();
+            if self.bundles.contains_key(&card) {
+                // Its ROM is already on another bundle's card
+                continue;
+            }
+            games.retain(|g| *g == card || !bundle.contains(g));
+            self.bundles.insert(card, bundle);
+        }
+        games
+    }
+    
+    fn group_variants(&mut self, games: Vec<String>) -> Vec<String> {
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for game in games {
+            // A bundle card already stands for several ROMs, its file name keeps it out of any group
+            let base = if self.bundles.contains_key(&game) { game.clone() } else { split_region_tag(&game).0 };
+            match groups.iter_mut().find(|(b, _)| *b == base) {
+                Some((_, members)) => members.push(game),
+                None => groups.push((base, vec![game])),
+            }
+        }
+        
+        self.variants.clear();
+        let mut cards = Vec::new();
+        for (_, mut members) in groups {
+            // The untagged file represents the group when there is one
+            if let Some(pos) = members.iter().position(|m| split_region_tag(m).1.is_none()) {
+                let primary = members.remove(pos);
+                members.insert(0, primary);
+            }
+            let primary = members[0].clone();
+            if members.len() > 1 {
+                self.variants.insert(primary.clone(), members);
+            }
+            cards.push(primary);
+        }
+        cards
+    }
+    
+    /// ROM actually launched from a card, honoring the chosen region variant or bundle game.
+    fn resolve_variant(&self, card: &str) -> String {
+        self.launcher_options
+            .games
+            .get(card)
+            .and_then(|g| g.variant.clone())
+            .filter(|v| {
+                self.variants.get(card).map(|members| members.contains(v)).unwrap_or(false)
+                    || self.bundles.get(card).map(|b| b.contains(v)).unwrap_or(false)
+            })
+            .unwrap_or_else(|| card.to_string())
+    }
+    
+    /// Card a ROM is shown on, which may be another variant's or a bundle's card.
+    fn card_index(&self, sfcs: &[String], rom: &str) -> Option<usize> {
+        sfcs.iter().position(|s| s == rom).or_else(|| {
+            sfcs.iter().position(|s| {
+                self.variants.get(s).map(|m| m.iter().any(|v| v == rom)).unwrap_or(false)
+                    || self.bundles.get(s).map(|b| b.contains(rom)).unwrap_or(false)
+            })
+        })
+    }
+    
+    /// Puts the selector back on the card selected when the launcher last closed.
+    fn restore_selection(&mut self, sfcs: &[String]) {
+        let rom = self.launcher_options.selected_rom.as_deref().and_then(|id| self.library.file(id));
+        if let Some(idx) = rom.and_then(|rom| self.card_index(sfcs, rom)) {
+            self.selected_game = idx;
+        }
+    }
+    
+    /// Remembers the selected card by content, so renaming the ROM in between doesn't lose it.
+    fn save_selection(&mut self, sfcs: &[String]) {
+        let Some(id) = sfcs.get(self.selected_game).and_then(|card| self.library.id(card)) else {
+            return;
+        };
+        if self.launcher_options.selected_rom.as_deref() == Some(id) {
+            return;
+        }
+        self.launcher_options.selected_rom = Some(id.to_string());
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save the selected game: {}", e);
+        }
+    }
+    
+    /// Card index for a ROM, switching to the "All" tab when the current one filters it out.
+    fn reveal_game(&mut self, sfcs: &mut Vec<String>, rom: &str) -> Result<usize, String> {
+        if let Some(idx) = self.card_index(sfcs, rom) {
+            return Ok(idx);
+        }
+        self.active_tab = 0;
+        self.refresh_visible(sfcs);
+        self.card_index(sfcs, rom).ok_or_else(|| format!("{} is hidden", rom))
+    }
+    
+    /// Most recent session whose ROM is still installed, shown on the Continue tile.
+    fn last_played(&self) -> Option<&stats::Session> {
+        self.stats.sessions.get(self.last_session?)
+    }
+    
+    /// Newest file in the game's saves folder.
+    fn last_save_time(&self) -> Option<std::time::SystemTime> {
+        self.last_save
+    }
+    
+    /// Looks up what the Continue tile shows again. Both read the disk, so this runs after scans,
+    /// games and save folder changes instead of every frame.
+    fn refresh_continue(&mut self) {
+        self.last_session = self.stats.sessions.iter().rposition(|s| self.sfc_dir.join(&s.rom).is_file());
+        self.last_save = fs::read_dir(&self.saves_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+            .max();
+    }
+    
+    fn choose_variant(&mut self, card: &str, variant: &str) {
+        self.launcher_options.games.entry(card.to_string()).or_default().variant = Some(variant.to_string());
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save variant choice: {}", e);
+        }
+    }
+    
+    fn tab_names(&self) -> Vec<String> {
+        let mut tabs = vec!["All".to_string()];
+        tabs.extend(self.launcher_options.collections.iter().cloned());
+        tabs
+    }
+    
+    fn active_collection(&self) -> Option<&str> {
+        if self.active_tab == 0 {
+            return None;
+        }
+        self.launcher_options.collections.get(self.active_tab - 1).map(String::as_str)
+    }
+    
+    fn in_collection(&self, sfc_name: &str, collection: &str) -> bool {
+        self.library
+            .entry(sfc_name)
+            .map(|e| e.collections.iter().any(|c| c == collection))
+            .unwrap_or(false)
+    }
+    
+    fn toggle_collection(&mut self, sfc_name: &str, collection: &str) {
+        let Some(entry) = self.library_entry(sfc_name) else {
+            return;
+        };
+        if let Some(pos) = entry.collections.iter().position(|c| c == collection) {
+            entry.collections.remove(pos);
+        } else {
+            entry.collections.push(collection.to_string());
+        }
+        if let Err(e) = self.library.save(&self.launcher_dir) {
+            eprintln!("Failed to save collections: {}", e);
+        }
+    }
+    
+    /// Switches collection tab, wrapping around at both ends.
+    fn switch_tab(&mut self, delta: i32, sfcs: &mut Vec<String>) {
+        let count = self.tab_names().len() as i32;
+        self.active_tab = (self.active_tab as i32 + delta).rem_euclid(count) as usize;
+        self.selected_game = 0;
+        self.refresh_visible(sfcs);
+    }
+    
+    fn tab_rect(&self, idx: usize) -> Rect {
+        let count = self.tab_names().len() as u32;
+        let width = (SCREEN_WIDTH - 60) / count.max(1);
+        Rect::new(30 + (idx as u32 * width) as i32, TAB_BAR_Y, width - 6, TAB_HEIGHT)
+    }
+    
+    /// Re-applies the visibility filter after a setting changed, keeping the selection in range.
+    fn refresh_visible(&mut self, sfcs: &mut Vec<String>) {
+        let selected = sfcs.get(self.selected_game).cloned();
+        let all_games = self.scan_sfc_files();
+        self.note_scan(&all_games);
+        self.plugins.on_scan(&all_games);
+        *sfcs = self.visible_games();
+        self.selected_game = selected
+            .and_then(|name| sfcs.iter().position(|s| *s == name))
+            .unwrap_or(self.selected_game)
+            .min(sfcs.len().saturating_sub(1));
+        self.color_transitions.clear();
+        self.refresh_continue();
+    }
+    
+    /// Language game titles are shown in: the `language` option, else the system's.
+    fn language(&self) -> String {
+        Some(self.launcher_options.language.clone())
+            .filter(|language| !language.is_empty())
+            .unwrap_or_else(platform::system_language)
+    }
+    
+    /// Title shown on a game's card: the player's own name for it, else its title in the launcher's language.
+    fn display_title(&self, sfc_name: &str) -> String {
+        let entry = self.library.entry(sfc_name);
+        entry
+            .and_then(|e| e.display_name.clone())
+            .or_else(|| entry.and_then(|e| e.title(&self.language())).map(str::to_string))
+            .or_else(|| self.launcher_options.games.get(sfc_name).and_then(|g| g.hack_name.clone()))
+            .or_else(|| self.plugins.title(sfc_name).map(str::to_string))
+            .unwrap_or_else(|| sfc_name.trim_end_matches(".sfc").to_string())
+    }
+    
+    /// One-line description of a ROM: its hack name, else the release its header matches.
+    fn rom_description(&self, sfc_name: &str) -> String {
+        match self.launcher_options.games.get(sfc_name).and_then(|g| g.hack_name.as_deref()) {
+            Some(hack) => format!("Hack: {}", hack),
+            None => self
+                .library
+                .id(sfc_name)
+                .and_then(|id| u32::from_str_radix(id, 16).ok())
+                .and_then(rom::known_rom_name)
+                .unwrap_or("Unrecognized ROM")
+                .to_string(),
+        }
+    }
+    
+    /// Writes the library out for EmulationStation and Pegasus, see `frontends`.
+    fn export_frontends(&self, dir: &Path) -> Result<Vec<PathBuf>, String> {
+        let pngs_dir = self.launcher_dir.join("pngs");
+        let games: Vec<frontends::Game> = self
+            .scan_sfc_files()
+            .iter()
+            .map(|rom| {
+                let entry = self.library.entry(rom);
+                frontends::Game {
+                    rom: self.sfc_dir.join(rom),
+                    title: self.display_title(rom),
+                    description: self.rom_description(rom),
+                    cover: textures::cover_path(&pngs_dir, entry.and_then(|e| e.cover.as_ref()), rom),
+                    favorite: entry.map_or(false, |e| e.favorite),
+                    hidden: entry.map_or(false, |e| e.hidden),
+                    play_count: entry.map_or(0, |e| e.play_count),
+                    play_secs: entry.map_or(0, |e| e.play_secs),
+                    last_played: entry.and_then(|e| e.last_played),
+                }
+            })
+            .collect();
+        frontends::export(&games, &self.game_executable(), &self.game_dir(), dir)
+    }
+    
+    /// Labels a freshly patched ROM with its hack name and files it under "Hacks" when that collection exists.
+    fn register_hack(&mut self, sfc_name: &str, hack_name: &str, exits: Option<u32>) {
+        self.launcher_options.games.entry(sfc_name.to_string()).or_default().hack_name = Some(hack_name.to_string());
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save hack label: {}", e);
+        }
+        if let Some(entry) = exits.and_then(|_| self.library_entry(sfc_name)) {
+            entry.exits = exits;
+            self.save_library();
+        }
+        if self.launcher_options.collections.iter().any(|c| c == "Hacks") && !self.in_collection(sfc_name, "Hacks") {
+            self.toggle_collection(sfc_name, "Hacks");
+        }
+    }
+    
+    fn backup_dir(&self) -> PathBuf {
+        self.launcher_dir.join("backups")
+    }
     
-    let cursor_path = launcher.launcher_dir.join("UI").join("Cursor.png");
-    let cursor_texture = if cursor_path.exists() {
-        match texture_creator.load_texture(&cursor_path) {
-            Ok(t) => {
-                println!("Loaded cursor texture: {}", cursor_path.display());
-                Some(t)
+    /// Full data archives live outside launcher/ so they are not zipped into each other.
+    fn archive_dir(&self) -> PathBuf {
+        self.install_dir.join("data_backups")
+    }
+    
+    fn data_dirs(&self) -> archive::DataDirs {
+        archive::DataDirs {
+            launcher_dir: self.launcher_dir.clone(),
+            game_dir: self.game_dir(),
+            saves_dir: self.saves_dir.clone(),
+        }
+    }
+    
+    /// Re-reads everything a restored archive may have replaced.
+    fn reload_data(&mut self) {
+        match Self::load_launcher_options(&self.launcher_dir) {
+            Ok(options) => {
+                self.launcher_options = options;
+                self.config_error = None;
+                // The restored saves went to the current folder, whatever the archive's options say
+                self.launcher_options.saves_dir_used = Some(self.saves_dir.clone());
             }
             Err(e) => {
-                eprintln!("Failed to load cursor texture: {}", e);
-                None
+                eprintln!("Failed to load launcher options: {}", e);
+                self.config_error = Some(e.to_string());
             }
         }
-    } else {
-        eprintln!("Cursor texture not found at: {}", cursor_path.display());
-        None
-    };
+        streamer::set_enabled(self.launcher_options.streamer_mode);
+        self.theme = Self::saved_theme(&self.launcher_dir, &self.launcher_options);
+        self.stats = stats::Stats::load(&self.launcher_dir);
+        self.library = library::Library::load(&self.launcher_dir);
+        self.achievements = achievements::Unlocked::load(&self.launcher_dir);
+        self.locate_saves();
+        self.check_saves();
+        self.refresh_continue();
+    }
+    
+    /// The folder the `saves_dir` setting names, launcher/saves by default.
+    fn wanted_saves_dir(&self) -> PathBuf {
+        self.launcher_options.saves_dir.clone().unwrap_or_else(|| self.launcher_dir.join("saves"))
+    }
+    
+    /// Finds the saves where they were last put. Nothing moves here: an smw build without
+    /// `compat::SAVE_DIR_FLAG` (a downgrade, say) just uses its own saves/ until one with it is back.
+    fn locate_saves(&mut self) {
+        let game_saves = self.game_dir().join("saves");
+        let can_relocate = compat::can_relocate_saves(&self.game_executable());
+        let used = match self.launcher_options.saves_dir_used.clone() {
+            Some(dir) => dir,
+            // A fresh install starts in the setting's folder, there is nothing to carry over
+            None if can_relocate && sram::is_empty(&game_saves) => {
+                let wanted = self.wanted_saves_dir();
+                if !self.read_only {
+                    self.launcher_options.saves_dir_used = Some(wanted.clone());
+                    if let Err(e) = self.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                }
+                wanted
+            }
+            None => game_saves.clone(),
+        };
+        if !can_relocate && used != game_saves {
+            eprintln!("This smw build keeps saves in its own folder, the ones in {} wait for a build that can use them", streamer::path(&used));
+            self.status.set(Subsystem::Saves, Some(format!("this smw build can't use the saves in {}", streamer::path(&used))));
+            self.saves_dir = game_saves;
+        } else {
+            self.saves_dir = used;
+        }
+    }
+    
+    /// Changes the `saves_dir` setting and moves the saves there. If they can't all move, none do
+    /// and the old setting stays.
+    fn set_saves_dir(&mut self, dir: Option<PathBuf>) -> Result<usize, String> {
+        if !compat::can_relocate_saves(&self.game_executable()) {
+            return Err("this smw build can't keep saves elsewhere".to_string());
+        }
+        let previous = std::mem::replace(&mut self.launcher_options.saves_dir, dir);
+        let (current, wanted) = (self.saves_dir.clone(), self.wanted_saves_dir());
+        let moved = if wanted == current {
+            0
+        } else {
+            sram::migrate(&current, &wanted).map_err(|e| {
+                self.launcher_options.saves_dir = previous;
+                e
+            })?
+        };
+        println!("Moved {} save file(s) from {} to {}", moved, streamer::path(&current), streamer::path(&wanted));
+        self.saves_dir = wanted.clone();
+        self.launcher_options.saves_dir_used = Some(wanted);
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+        self.check_saves();
+        self.refresh_continue();
+        Ok(moved)
+    }
+    
+    /// Picks up whatever changed on disk while a game ran, e.g. stats touched by post-exit hooks or plugins.
+    fn reload_after_game(&mut self) {
+        self.stats = stats::Stats::load(&self.launcher_dir);
+        self.achievements = achievements::Unlocked::load(&self.launcher_dir);
+        self.check_saves();
+        self.refresh_continue();
+    }
+    
+    /// Looks for battery saves that fail their checksums, so the card can warn before a launch.
+    /// Also reads how far each save got, for the cards' progress bars.
+    fn check_saves(&mut self) {
+        self.damaged_saves = sram::scan(&self.saves_dir).into_iter().collect();
+        for (rom, slots) in &self.damaged_saves {
+            eprintln!("Save for {} looks damaged, slot(s) {:?}", rom, slots);
+        }
+        self.exits_cleared = sram::progress(&self.saves_dir).into_iter().collect();
+    }
+    
+    /// Exits cleared and exits in all for a card's game, when its exit count is known.
+    fn exit_progress(&self, card: &str) -> Option<(u32, u32)> {
+        let rom = self.resolve_variant(card);
+        let total = self
+            .library
+            .entry(&rom)
+            .and_then(|e| e.exits)
+            .or_else(|| (rom == self.launcher_options.base_rom).then_some(SMW_EXITS))
+            .filter(|&total| total > 0)?;
+        let cleared = self.exits_cleared.get(&rom).copied().unwrap_or(0);
+        Some((cleared.min(total), total))
+    }
+    
+    /// Puts back the newest copy of a game's save from the weekly backups that passes its checks.
+    /// The damaged file goes to launcher/backups/ first. Returns the archive it came from.
+    fn restore_save(&mut self, sfc_name: &str) -> Result<PathBuf, String> {
+        let path = sram::save_path(&self.saves_dir, sfc_name);
+        let name = format!("game/saves/{}", path.file_name().unwrap_or_default().to_string_lossy());
+        for archive_path in archive::auto_archives(&self.archive_dir()) {
+            let data = match archive::read_entry(&archive_path, &name) {
+                Ok(Some(data)) => data,
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", streamer::path(&archive_path), e);
+                    continue;
+                }
+            };
+            // An older week may still have it intact
+            if sram::damaged_slots(&data).map(|slots| !slots.is_empty()).unwrap_or(false) {
+                continue;
+            }
+            backup::backup_file(&self.backup_dir(), &path, "save restore")?;
+            fs::write(&path, &data).map_err(|e| e.to_string())?;
+            self.check_saves();
+            return Ok(archive_path);
+        }
+        Err("No weekly backup has a good copy of this save".to_string())
+    }
+    
+    fn has_copier_header(&self, sfc_name: &str) -> bool {
+        fs::metadata(self.sfc_dir.join(sfc_name))
+            .map(|m| m.len() % 1024 == 512)
+            .unwrap_or(false)
+    }
+    
+    /// Removes the 512-byte copier header in place, keeping a backup of the original.
+    fn strip_header(&self, sfc_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.sfc_dir.join(sfc_name);
+        let data = fs::read(&path)?;
+        backup::backup_file(&self.backup_dir(), &path, "header stripping")?;
+        fs::write(&path, rom::strip_header(&data))?;
+        println!("Removed copier header from {}", sfc_name);
+        Ok(())
+    }
+    
+    /// Sets the card's display name, or with `on_disk` renames the ROM file itself.
+    /// Returns the ROM's file name afterwards.
+    fn rename_game(&mut self, sfc_name: &str, name: &str, on_disk: bool) -> Result<String, Box<dyn std::error::Error>> {
+        if !on_disk {
+            // An empty name goes back to the file name (or hack name)
+            let name = Some(name.to_string()).filter(|n| !n.is_empty());
+            let entry = self.library_entry(sfc_name).ok_or_else(|| format!("{} is not in the library", sfc_name))?;
+            entry.display_name = name;
+            self.library.save(&self.launcher_dir)?;
+            return Ok(sfc_name.to_string());
+        }
+        
+        let stem = name.rsplit_once('.').filter(|(_, ext)| ext.eq_ignore_ascii_case("sfc")).map_or(name, |(stem, _)| stem);
+        if stem.is_empty() || stem.chars().any(|c| "/\\:*?\"<>|".contains(c) || c.is_control()) {
+            return Err(format!("\"{}\" can't be used as a file name", name).into());
+        }
+        let new_name = format!("{}.sfc", stem);
+        if new_name == sfc_name {
+            return Ok(new_name);
+        }
+        if self.game_process.as_ref().map(|g| g.rom == sfc_name).unwrap_or(false) {
+            return Err("the game is running".into());
+        }
+        let to = self.sfc_dir.join(&new_name);
+        // A case-only rename finds the ROM itself on case-insensitive file systems; only a listing
+        // tells that apart from a second file whose name differs in case
+        let same_file = new_name.eq_ignore_ascii_case(sfc_name)
+            && !fs::read_dir(&self.sfc_dir)?.flatten().any(|entry| entry.file_name() == new_name.as_str());
+        if to.exists() && !same_file {
+            return Err(format!("{} already exists", new_name).into());
+        }
+        let from = self.sfc_dir.join(sfc_name);
+        backup::backup_rename(&self.backup_dir(), &from, &to)?;
+        fs::rename(&from, &to)?;
+        println!("Renamed {} to {}", sfc_name, new_name);
+        
+        self.library.rename_file(sfc_name, &new_name);
+        if let Some(entry) = self.library.entry_mut(&new_name) {
+            entry.display_name = None;
+        }
+        self.library.save(&self.launcher_dir)?;
+        self.follow_rename(sfc_name, &new_name)?;
+        Ok(new_name)
+    }
+    
+    /// Points the library and everything keyed by file name back at a ROM whose rename was undone
+    /// from the Backups menu.
+    fn undo_rename(&mut self, renamed: &str, original: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.library.rename_file(renamed, original);
+        self.library.save(&self.launcher_dir)?;
+        self.follow_rename(renamed, original)
+    }
+    
+    /// Moves covers, settings and play history keyed by file name over to a ROM's new name.
+    fn follow_rename(&mut self, sfc_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Covers are looked up by file name, so they move along
+        let pngs_dir = self.launcher_dir.join("pngs");
+        let old_stem = sfc_name.trim_end_matches(".sfc");
+        let stem = new_name.trim_end_matches(".sfc");
+        for ext in textures::COVER_EXTENSIONS {
+            let from = pngs_dir.join(format!("{}.{}", old_stem, ext));
+            let to = pngs_dir.join(format!("{}.{}", stem, ext));
+            if !from.exists() {
+                continue;
+            }
+            if to.exists() {
+                eprintln!("Keeping the old cover, {} already exists", streamer::path(&to));
+            } else if let Err(e) = fs::rename(&from, &to) {
+                eprintln!("Failed to rename cover {}: {}", streamer::path(&from), e);
+            }
+        }
+        
+        // Same for everything else keyed by file name
+        let options = &mut self.launcher_options;
+        if let Some(config) = options.games.remove(sfc_name) {
+            options.games.insert(new_name.to_string(), config);
+        }
+        for config in options.games.values_mut() {
+            if config.variant.as_deref() == Some(sfc_name) {
+                config.variant = Some(new_name.to_string());
+            }
+        }
+        for entry in options.game_order.iter_mut().filter(|e| *e == sfc_name) {
+            *entry = new_name.to_string();
+        }
+        self.save_launcher_options()?;
+        if self.stats.sessions.iter().any(|s| s.rom == sfc_name) {
+            for session in self.stats.sessions.iter_mut().filter(|s| s.rom == sfc_name) {
+                session.rom = new_name.to_string();
+            }
+            self.stats.save(&self.launcher_dir)?;
+        }
+        Ok(())
+    }
+    
+    /// Moves a ROM to the system trash. Its settings stay in launcher.json in case it comes back.
+    fn trash_rom(&mut self, sfc_name: &str) -> Result<(), String> {
+        if self.game_process.as_ref().map(|g| g.rom == sfc_name).unwrap_or(false) {
+            return Err("the game is running".to_string());
+        }
+        platform::move_to_trash(&self.sfc_dir.join(sfc_name))?;
+        println!("Moved {} to the trash", sfc_name);
+        Ok(())
+    }
+    
+    fn set_hidden(&mut self, sfc_name: &str, hidden: bool) {
+        let Some(entry) = self.library_entry(sfc_name) else {
+            return;
+        };
+        entry.hidden = hidden;
+        if let Err(e) = self.library.save(&self.launcher_dir) {
+            eprintln!("Failed to save hidden games: {}", e);
+        }
+    }
+    
+    /// Moves a game to a new position in the grid and persists the resulting order.
+    fn reorder_game(&mut self, sfcs: &mut Vec<String>, from: usize, to: usize) {
+        if from == to || from >= sfcs.len() || to >= sfcs.len() {
+            return;
+        }
+        
+        let game = sfcs.remove(from);
+        sfcs.insert(to, game);
+        self.selected_game = to;
+        self.color_transitions.clear();
+        
+        // Hidden games aren't in the grid but keep their place at the end of the list
+        let mut order = sfcs.clone();
+        order.extend(self.launcher_options.game_order.iter().filter(|g| !sfcs.contains(g)).cloned());
+        self.launcher_options.game_order = order;
+        // Dragging a card means the user wants their own order again
+        self.launcher_options.sort_mode = 0;
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save game order: {}", e);
+        }
+    }
+    
+    fn game_executable(&self) -> PathBuf {
+        Self::locate_game_executable(&self.launcher_options, &self.install_dir, self.sandbox)
+    }
+    
+    /// `game_executable` before there is a Launcher, for `doctor`.
+    fn locate_game_executable(options: &LauncherOptions, install_dir: &Path, sandbox: Sandbox) -> PathBuf {
+        if let Some(path) = &options.game_executable {
+            return path.clone();
+        }
+        
+        let exe_name = if cfg!(target_os = "windows") { "smw.exe" } else { "smw" };
+        if let Some(contents) = platform::macos_bundle_contents() {
+            let bundled = contents.join("MacOS").join(exe_name);
+            if bundled.exists() {
+                return bundled;
+            }
+        }
+        if sandbox == Sandbox::AppImage {
+            if let Some(dir) = platform::appimage_dir() {
+                let bundled = dir.join(exe_name);
+                if bundled.exists() {
+                    return bundled;
+                }
+            }
+        }
+        install_dir.join(exe_name)
+    }
+    
+    /// Folder the game runs in, which is also where it reads smw.ini from.
+    fn game_dir(&self) -> PathBuf {
+        self.game_executable()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| self.install_dir.clone())
+    }
     
-    let bg_path = launcher.launcher_dir.join("MBG.png");
-    let bg_texture = if bg_path.exists() && launcher.launcher_options.bgtype == 2 {
-        match texture_creator.load_texture(&bg_path) {
-            Ok(t) => {
-                println!("Loaded background texture: {}", bg_path.display());
-                Some(t)
+    /// Runs the pre-launch checklist so problems are reported before anything is spawned.
+    fn validate_launch(&self, sfc_name: &str) -> Vec<LaunchIssue> {
+        let mut issues = Vec::new();
+        let mut fatal = |message: String| issues.push(LaunchIssue { fatal: true, message, fix: None });
+        
+        let smw_path = self.game_executable();
+        match fs::metadata(&smw_path) {
+            Ok(meta) if meta.is_file() => {
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    if meta.permissions().mode() & 0o111 == 0 {
+                        fatal(format!("{} is not executable (chmod +x)", exe_file_name(&smw_path)));
+                    }
+                }
+            }
+            _ => fatal(format!("Put {} in the launcher folder", exe_file_name(&smw_path))),
+        }
+        // Read-only runs don't start the version probe, it runs smw
+        let version = if self.read_only { compat::known_version(&smw_path) } else { compat::game_version(&smw_path) };
+        let version_warnings = version.map(compat::check).unwrap_or_default();
+        
+        let sfc_path = self.sfc_dir.join(sfc_name);
+        let crc = match rom::rom_crc32(&sfc_path) {
+            Ok(crc) => Some(crc),
+            Err(e) => {
+                fatal(format!("Cannot read {}: {}", sfc_name, e));
+                None
+            }
+        };
+        
+        let game_dir = self.game_dir();
+        let ini_path = game_dir.join("smw.ini");
+        let ini_readonly = match fs::metadata(&ini_path) {
+            Ok(meta) => meta.permissions().readonly(),
+            Err(_) => fs::metadata(&game_dir).map(|m| m.permissions().readonly()).unwrap_or(true),
+        };
+        if ini_readonly {
+            fatal("smw.ini is read-only, make it writable".to_string());
+        }
+        
+        if let Some(crc) = crc {
+            if rom::known_rom_name(crc).is_none() && !rom::trusted_roms(&self.launcher_dir).contains_key(&crc) {
+                issues.push(LaunchIssue {
+                    fatal: false,
+                    message: format!("Unknown ROM checksum {:08X}", crc),
+                    fix: Some(("This ROM is fine, stop asking".to_string(), MenuAction::TrustRom(sfc_name.to_string(), crc))),
+                });
+            }
+        }
+        for message in self.missing_assets(sfc_name) {
+            issues.push(LaunchIssue {
+                fatal: false,
+                message,
+                fix: Some(("Updates".to_string(), MenuAction::OpenUpdates)).filter(|_| self.launcher_options.update_server.is_some()),
+            });
+        }
+        for message in version_warnings {
+            issues.push(LaunchIssue { fatal: false, message, fix: None });
+        }
+        if let Some(slots) = self.damaged_saves.get(sfc_name) {
+            let slots: Vec<String> = slots.iter().map(|s| s.to_string()).collect();
+            // Launching now would let the game overwrite what is left of it
+            issues.push(LaunchIssue {
+                fatal: false,
+                message: format!("Save file {} looks damaged", slots.join(", ")),
+                fix: Some(("Restore save from weekly backup".to_string(), MenuAction::RestoreSave(sfc_name.to_string())))
+                    .filter(|_| !archive::auto_archives(&self.archive_dir()).is_empty()),
+            });
+        }
+        
+        if self.playtime_left() == Some(0) {
+            let limit = stats::format_duration(self.launcher_options.playtime_limit_mins as u64 * 60);
+            issues.push(LaunchIssue {
+                fatal: true,
+                message: format!("Today's playtime of {} is used up", limit),
+                fix: Some(("Enter PIN".to_string(), MenuAction::EnterPin(Box::new(MenuAction::Launch(sfc_name.to_string())))))
+                    .filter(|_| !self.launcher_options.playtime_pin.is_empty()),
+            });
+        }
+        
+        issues
+    }
+    
+    /// Files the game is set up to load that aren't there: the shader smw.ini names and the
+    /// controller database handed to its SDL.
+    fn missing_assets(&self, sfc_name: &str) -> Vec<String> {
+        let mut missing = Vec::new();
+        let shader = self.effective_game_options(sfc_name).shader;
+        if !shader.is_empty() && shader != "None" && !self.game_dir().join(&shader).is_file() {
+            missing.push(format!("Shader {} is missing, reinstall the shader pack or pick another", shader));
+        }
+        if let Some(db) = self.launch_env(sfc_name).get("SDL_GAMECONTROLLERCONFIG_FILE") {
+            if !Path::new(db).is_file() {
+                missing.push(format!("Controller database {} is missing", streamer::path(Path::new(db))));
+            }
+        }
+        missing
+    }
+    
+    /// Effective environment for a game: global variables overridden by per-game ones.
+    fn launch_env(&self, sfc_name: &str) -> HashMap<String, String> {
+        let mut env = self.launcher_options.launch_env.clone();
+        // smw's SDL reads the same controller database the launcher uses
+        let controller_db = self.launcher_dir.join(updates::CONTROLLER_DB);
+        if controller_db.is_file() {
+            env.insert("SDL_GAMECONTROLLERCONFIG_FILE".to_string(), controller_db.to_string_lossy().into_owned());
+        }
+        if let Some(game) = self.launcher_options.games.get(sfc_name) {
+            // smw.ini has no monitor setting, but SDL picks its fullscreen display from this
+            if let Some(monitor) = game.monitor {
+                env.insert("SDL_VIDEO_FULLSCREEN_DISPLAY".to_string(), monitor.to_string());
+            }
+        }
+        if let Some(display) = self.handoff_display() {
+            env.entry("SDL_VIDEO_FULLSCREEN_DISPLAY".to_string()).or_insert_with(|| display.to_string());
+        }
+        if let Some(game) = self.launcher_options.games.get(sfc_name) {
+            env.extend(game.env.clone());
+        }
+        env
+    }
+    
+    /// Steps a per-game smw.ini override through `DISPLAY_OVERRIDES`, "use the global value" first.
+    /// The values a game can override smw.ini `key` with, empty for keys that can't be.
+    fn ini_override_choices(&self, key: &str) -> Vec<String> {
+        match DISPLAY_OVERRIDES.iter().find(|(k, _)| *k == key) {
+            Some((_, choices)) => choices.iter().map(|c| c.to_string()).collect(),
+            None if key == "Shader" => self.shader_choices(),
+            None => Vec::new(),
+        }
+    }
+    
+    /// Steps `key` through the global setting (no override) and then each of its choices.
+    fn cycle_ini_override(&mut self, sfc_name: &str, key: &str, step: i32) {
+        let choices = self.ini_override_choices(key);
+        if choices.is_empty() {
+            return;
+        }
+        let overrides = &mut self.launcher_options.games.entry(sfc_name.to_string()).or_default().game_options;
+        let current = overrides.get(key).and_then(|v| choices.iter().position(|c| c == v)).map_or(0, |i| i + 1);
+        match (current as i32 + step).rem_euclid(choices.len() as i32 + 1) as usize {
+            0 => overrides.remove(key),
+            next => overrides.insert(key.to_string(), choices[next - 1].clone()),
+        };
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    fn cycle_watchdog(&mut self, sfc_name: &str) {
+        let game = self.launcher_options.games.entry(sfc_name.to_string()).or_default();
+        let current = WATCHDOG_CHOICES.iter().position(|&s| s == game.watchdog_secs).unwrap_or(0);
+        game.watchdog_secs = WATCHDOG_CHOICES[(current + 1) % WATCHDOG_CHOICES.len()];
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    fn cycle_priority(&mut self, sfc_name: &str) {
+        let game = self.launcher_options.games.entry(sfc_name.to_string()).or_default();
+        let current = PRIORITY_CHOICES.iter().position(|(p, _)| *p == game.priority).unwrap_or(0);
+        game.priority = PRIORITY_CHOICES[(current + 1) % PRIORITY_CHOICES.len()].0;
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    fn toggle_performance_cores(&mut self, sfc_name: &str) {
+        let game = self.launcher_options.games.entry(sfc_name.to_string()).or_default();
+        game.performance_cores = !game.performance_cores;
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    /// The watchdog step to offer after `game` failed, None when it ran long enough not to count
+    /// as a startup crash, the watchdog is off, or every step was tried.
+    fn watchdog_step(&self, game: &RunningGame, status: ExitStatus, trial: Option<usize>) -> Option<usize> {
+        let secs = self.launcher_options.games.get(&game.rom).map(|g| g.watchdog_secs).unwrap_or(0);
+        if status.success() || secs == 0 || game.started.elapsed() >= Duration::from_secs(secs as u64) {
+            return None;
+        }
+        Some(trial.map(|step| step + 1).unwrap_or(0)).filter(|&step| step < SAFE_STEPS.len())
+    }
+    
+    /// Makes the safe settings that got a game running its own smw.ini overrides, and names them.
+    fn keep_safe_settings(&mut self, sfc_name: &str, step: usize) -> String {
+        let game = self.launcher_options.games.entry(sfc_name.to_string()).or_default();
+        for (key, value) in SAFE_STEPS[..=step].iter().flat_map(|(_, overrides)| overrides.iter()) {
+            game.game_options.insert(key.to_string(), value.to_string());
+        }
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+        SAFE_STEPS[..=step].iter().map(|(label, _)| *label).collect::<Vec<_>>().join(", ")
+    }
+    
+    fn cycle_monitor(&mut self, sfc_name: &str, displays: u32, step: i32) {
+        let game = self.launcher_options.games.entry(sfc_name.to_string()).or_default();
+        let current = game.monitor.map_or(0, |m| m as i32 + 1);
+        game.monitor = match (current + step).rem_euclid(displays as i32 + 1) {
+            0 => None,
+            next => Some(next as u32 - 1),
+        };
+        if let Err(e) = self.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    fn launch_wrapper(&self, sfc_name: &str) -> Vec<String> {
+        let wrapper = self.launcher_options
+            .games
+            .get(sfc_name)
+            .and_then(|g| g.wrapper.clone())
+            .unwrap_or_else(|| self.launcher_options.launch_wrapper.clone());
+        
+        // In Deck desktop mode, run the game through gamescope so it gets a proper fullscreen surface
+        if wrapper.is_empty() && self.handheld && platform::is_steam_deck() && !platform::in_gamescope() {
+            return vec![
+                "gamescope".to_string(),
+                "-W".to_string(), HANDHELD_SIZE.0.to_string(),
+                "-H".to_string(), HANDHELD_SIZE.1.to_string(),
+                "-f".to_string(),
+                "--".to_string(),
+            ];
+        }
+        wrapper
+    }
+    
+    /// `nice` and `taskset` in front of the game for its priority and core options. Windows
+    /// gets its priority class when the game is created instead, see `build_command`.
+    fn tuning_prefix(&self, sfc_name: &str) -> Vec<String> {
+        let mut prefix = Vec::new();
+        let Some(game) = self.launcher_options.games.get(sfc_name).filter(|_| cfg!(unix)) else {
+            return prefix;
+        };
+        if game.priority != 0 {
+            // Raising it usually needs privileges; nice then warns and runs the game at normal priority
+            prefix.extend(["nice".to_string(), "-n".to_string(), platform::nice_value(game.priority).to_string()]);
+        }
+        if game.performance_cores {
+            match platform::performance_cores() {
+                Some(cpus) => prefix.extend(["taskset".to_string(), "-c".to_string(), cpus]),
+                None => eprintln!("No performance cores found, {} runs on all cores", sfc_name),
+            }
+        }
+        prefix
+    }
+    
+    /// Builds the command for a game with its wrapper prefix and environment applied.
+    fn build_command(&self, smw_path: &Path, sfc_name: &str) -> Command {
+        let mut argv: Vec<OsString> = self
+            .tuning_prefix(sfc_name)
+            .into_iter()
+            .chain(self.launch_wrapper(sfc_name))
+            .map(OsString::from)
+            .collect();
+        argv.push(smw_path.as_os_str().to_owned());
+        if compat::can_relocate_saves(smw_path) {
+            argv.push(OsString::from(compat::SAVE_DIR_FLAG));
+            argv.push(self.saves_dir.as_os_str().to_owned());
+        }
+        let mut command = self.host_command(argv, self.launch_env(sfc_name));
+        if let Some(priority) = self.launcher_options.games.get(sfc_name).map(|g| g.priority).filter(|&p| p != 0) {
+            platform::set_priority(&mut command, priority);
+        }
+        command
+    }
+    
+    /// A hidden run of the game that saves its title screen to `output`, for `autocover`.
+    fn capture_command(&self, sfc_name: &str, output: &Path) -> Command {
+        let argv = vec![self.game_executable().into_os_string(), self.sfc_dir.join(sfc_name).into_os_string()];
+        let mut env = self.launch_env(sfc_name);
+        env.extend(autocover::CoverCapture::env(output));
+        let mut command = self.host_command(argv, env);
+        command.current_dir(self.game_dir());
+        command
+    }
+    
+    fn host_command(&self, argv: Vec<OsString>, env: HashMap<String, String>) -> Command {
+        // Inside a Flatpak the game runs on the host, which does not inherit our env or working dir
+        if self.sandbox == Sandbox::Flatpak {
+            let mut command = Command::new("flatpak-spawn");
+            command.arg("--host").arg(format!("--directory={}", self.game_dir().display()));
+            for (key, value) in &env {
+                command.arg(format!("--env={}={}", key, value));
+            }
+            command.args(argv);
+            return command;
+        }
+        
+        let mut command = Command::new(&argv[0]);
+        command.args(&argv[1..]).envs(env);
+        command
+    }
+    
+    /// Hook command for a game, per-game settings taking precedence over the global ones.
+    fn hook(&self, sfc_name: &str, pre_launch: bool) -> Vec<String> {
+        let game = self.launcher_options.games.get(sfc_name);
+        if pre_launch {
+            game.and_then(|g| g.pre_launch_hook.clone())
+                .unwrap_or_else(|| self.launcher_options.pre_launch_hook.clone())
+        } else {
+            game.and_then(|g| g.post_exit_hook.clone())
+                .unwrap_or_else(|| self.launcher_options.post_exit_hook.clone())
+        }
+    }
+    
+    /// Variables a hook sees on top of the game's launch environment.
+    fn hook_env(&self, sfc_name: &str, exit_status: Option<ExitStatus>) -> HashMap<String, String> {
+        let mut env = self.launch_env(sfc_name);
+        env.insert("SMAS_ROM".to_string(), sfc_name.to_string());
+        env.insert("SMAS_GAME".to_string(), self.display_title(sfc_name));
+        env.insert("SMAS_PROFILE".to_string(), if self.handheld { "handheld" } else { "desktop" }.to_string());
+        env.insert("SMAS_ROM_PATH".to_string(), self.sfc_dir.join(sfc_name).display().to_string());
+        env.insert("SMAS_GAME_DIR".to_string(), self.game_dir().display().to_string());
+        env.insert("SMAS_EXECUTABLE".to_string(), self.game_executable().display().to_string());
+        env.insert("SMAS_HANDHELD".to_string(), (self.handheld as u8).to_string());
+        if let Some(code) = exit_status.and_then(|s| s.code()) {
+            env.insert("SMAS_EXIT_CODE".to_string(), code.to_string());
+        }
+        env
+    }
+    
+    /// Starts the pre-launch hook and waits for it on a thread, so the launcher keeps drawing.
+    /// The receiver gets its outcome: a non-zero exit or running past `PRE_LAUNCH_HOOK_TIMEOUT`
+    /// cancels the launch. None when there is no hook.
+    fn start_pre_launch_hook(&self, sfc_name: &str) -> Result<Option<Receiver<Result<(), String>>>, Box<dyn std::error::Error>> {
+        let hook = self.hook(sfc_name, true);
+        if hook.is_empty() {
+            return Ok(None);
+        }
+        
+        println!("Running pre-launch hook: {}", hook.join(" "));
+        let argv = hook.into_iter().map(OsString::from).collect();
+        let mut child = self.host_command(argv, self.hook_env(sfc_name, None))
+            .current_dir(self.game_dir())
+            .spawn()?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let started = Instant::now();
+            let result = loop {
+                match child.try_wait() {
+                    Ok(Some(status)) if status.success() => break Ok(()),
+                    Ok(Some(status)) => break Err(format!("Pre-launch hook failed ({})", status)),
+                    Ok(None) if started.elapsed() < PRE_LAUNCH_HOOK_TIMEOUT => std::thread::sleep(Duration::from_millis(50)),
+                    Ok(None) => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        break Err(format!("Pre-launch hook took over {}s and was stopped", PRE_LAUNCH_HOOK_TIMEOUT.as_secs()));
+                    }
+                    Err(e) => break Err(e.to_string()),
+                }
+            };
+            let _ = tx.send(result);
+        });
+        Ok(Some(rx))
+    }
+    
+    /// Starts the post-exit hook without waiting for it so slow jobs like save syncing
+    /// don't freeze the launcher.
+    fn run_post_exit_hook(&self, sfc_name: &str, status: ExitStatus) {
+        let hook = self.hook(sfc_name, false);
+        if hook.is_empty() {
+            return;
+        }
+        
+        println!("Running post-exit hook: {}", hook.join(" "));
+        let argv = hook.into_iter().map(OsString::from).collect();
+        match self.host_command(argv, self.hook_env(sfc_name, Some(status)))
+            .current_dir(self.game_dir())
+            .spawn()
+        {
+            Ok(mut child) => {
+                std::thread::spawn(move || {
+                    if let Ok(status) = child.wait() {
+                        if !status.success() {
+                            eprintln!("Post-exit hook failed ({})", status);
+                        }
+                    }
+                });
+            }
+            Err(e) => eprintln!("Failed to run post-exit hook: {}", e),
+        }
+    }
+    
+    /// Everything before the game is spawned: checks, then the pre-launch hook, whose outcome comes
+    /// on the returned receiver. `launch_game` follows once it's done and only then writes smw.ini,
+    /// so a hook that fails or times out cancels the launch without touching it.
+    fn prepare_launch(&mut self, sfc_name: &str) -> Result<Option<Receiver<Result<(), String>>>, Box<dyn std::error::Error>> {
+        if self.game_process.is_some() {
+            return Err("A game is already running".into());
+        }
+        // Launches that skip the checklist (remote, control socket) still respect the limit
+        if self.playtime_left() == Some(0) {
+            return Err("Today's playtime is used up".into());
+        }
+        // Never two games at once, the hidden one would hold the audio device and saves too
+        self.cover_capture.stop();
+        
+        let smw_path = self.game_executable();
+        if !smw_path.exists() {
+            eprintln!("SMW executable not found at: {}", streamer::path(&smw_path));
+            return Err("SMW executable not found".into());
+        }
+        
+        self.import_game_ini();
+        self.start_pre_launch_hook(sfc_name)
+    }
+    
+    fn launch_game(&mut self, sfc_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.game_process.is_some() {
+            return Err("A game is already running".into());
+        }
+        self.write_game_ini(sfc_name)?;
+        self.note_game_ini();
+        self.playtime_warned = false;
+        let sfc_path = self.sfc_dir.join(sfc_name);
+        let smw_path = self.game_executable();
+        
+        println!("Launching: {} with ROM: {}", streamer::path(&smw_path), sfc_name);
+        
+        // Capture the game's output so it can be shown if it crashes
+        let log_dir = self.launcher_dir.join("logs");
+        fs::create_dir_all(&log_dir)?;
+        let log_path = log_dir.join("smw.log");
+        let log_file = fs::File::create(&log_path)?;
+        
+        let overrides = self.session.as_ref().filter(|(rom, _)| rom == sfc_name).map(|(_, o)| o.clone()).unwrap_or_default();
+        let saves_dir = self.saves_dir.clone();
+        if overrides.save_slot > 0 {
+            sram::use_slot(&saves_dir, sfc_name, overrides.save_slot)?;
+        }
+        
+        let mut command = self.build_command(&smw_path, sfc_name);
+        let spawned = command
+            .arg(sfc_path)
+            .args(overrides.args())
+            .current_dir(self.game_dir())
+            .stdout(Stdio::from(log_file.try_clone()?))
+            .stderr(Stdio::from(log_file))
+            .spawn();
+        let child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                if overrides.save_slot > 0 {
+                    if let Err(e) = sram::end_slot(&saves_dir, sfc_name, overrides.save_slot) {
+                        eprintln!("Failed to put back the usual save: {}", e);
+                    }
+                }
+                self.session = None;
+                return Err(e.into());
+            }
+        };
+        
+        self.plugins.on_launch(sfc_name);
+        self.write_now_playing(Some(sfc_name));
+        self.game_process = Some(RunningGame {
+            child,
+            rom: sfc_name.to_string(),
+            log_path,
+            started: Instant::now(),
+        });
+        
+        Ok(())
+    }
+    
+    /// Prints what `launch_game` would do for a ROM without writing or spawning anything,
+    /// for `--dry-run-launch`.
+    fn dry_run_launch(&self, sfc_name: &str) {
+        let smw_path = self.game_executable();
+        let mut command = self.build_command(&smw_path, sfc_name);
+        command.arg(self.sfc_dir.join(sfc_name)).current_dir(self.game_dir());
+        
+        println!("Dry run: launching {}", sfc_name);
+        println!("Sandbox: {:?}", self.sandbox);
+        println!("Executable: {}", streamer::path(&smw_path));
+        println!("Program: {}", streamer::path(Path::new(command.get_program())));
+        let args: Vec<String> = command.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        println!("Arguments: {:?}", args);
+        if let Some(dir) = command.get_current_dir() {
+            println!("Working directory: {}", streamer::path(dir));
+        }
+        let mut env: Vec<String> = command
+            .get_envs()
+            .map(|(key, value)| format!("{}={}", key.to_string_lossy(), value.map(|v| v.to_string_lossy().into_owned()).unwrap_or_default()))
+            .collect();
+        env.sort();
+        println!("Environment (on top of the launcher's own): {}", if env.is_empty() { "nothing" } else { "" });
+        for var in env {
+            println!("  {}", var);
+        }
+        
+        let hook = self.hook(sfc_name, true);
+        if !hook.is_empty() {
+            println!("Pre-launch hook: {}", hook.join(" "));
+        }
+        let hook = self.hook(sfc_name, false);
+        if !hook.is_empty() {
+            println!("Post-exit hook: {}", hook.join(" "));
+        }
+        
+        let changes = self.game_ini_changes(sfc_name);
+        println!("smw.ini changes: {}", if changes.is_empty() { "none" } else { "" });
+        for change in changes {
+            println!("  {}", change);
+        }
+        
+        let issues = self.validate_launch(sfc_name);
+        if issues.is_empty() {
+            println!("Launch checks: all passed");
+        }
+        for issue in issues {
+            println!("{}: {}", if issue.fatal { "Would not launch" } else { "Warning" }, issue.message);
+        }
+    }
+    
+    /// Returns the finished game and its exit status once the tracked process has exited.
+    fn poll_game_exit(&mut self) -> Option<(RunningGame, ExitStatus)> {
+        let status = match self.game_process.as_mut()?.child.try_wait() {
+            Ok(Some(status)) => status,
+            Ok(None) => return None,
+            Err(e) => {
+                eprintln!("Failed to query game process: {}", e);
+                return None;
+            }
+        };
+        
+        let game = self.game_process.take()?;
+        if let Some((rom, overrides)) = self.session.take().filter(|(rom, _)| *rom == game.rom) {
+            if overrides.save_slot > 0 {
+                if let Err(e) = sram::end_slot(&self.saves_dir, &rom, overrides.save_slot) {
+                    eprintln!("Failed to put back the usual save for {}: {}", rom, e);
+                }
+            }
+        }
+        self.write_now_playing(None);
+        self.stats.record(&game.rom, game.started.elapsed().as_secs());
+        if let Err(e) = self.stats.save(&self.launcher_dir) {
+            eprintln!("Failed to save stats: {}", e);
+        }
+        self.library.record_play(&game.rom, game.started.elapsed().as_secs());
+        self.save_library();
+        self.refresh_continue();
+        println!("{} exited after {}s ({})", game.rom, game.started.elapsed().as_secs(), status);
+        Some((game, status))
+    }
+    
+    /// Keeps `launcher/now_playing.txt` in sync for OBS text sources; empty while no game runs.
+    fn write_now_playing(&self, sfc_name: Option<&str>) {
+        let options = &self.launcher_options;
+        if !(options.streamer_mode && options.now_playing_file) {
+            return;
+        }
+        let text = sfc_name.map(|rom| self.display_title(rom)).unwrap_or_default();
+        if let Err(e) = fs::write(self.launcher_dir.join("now_playing.txt"), text) {
+            eprintln!("Failed to write now playing file: {}", e);
+        }
+    }
+    
+    /// Drains every pending gilrs event (from all controllers) and returns the resulting
+    /// actions in the order they happened.
+    fn handle_gamepad_input(&mut self) -> Vec<GamepadAction> {
+        let mut actions = Vec::new();
+        let (tab_buttons, page_buttons) = if keys::pages_on_shoulders(&self.launcher_options.launcher_keys) {
+            ((Button::LeftTrigger2, Button::RightTrigger2), (Button::LeftTrigger, Button::RightTrigger))
+        } else {
+            ((Button::LeftTrigger, Button::RightTrigger), (Button::LeftTrigger2, Button::RightTrigger2))
+        };
+        if let Some(ref mut gilrs) = self.gamepad_system {
+            while let Some(GilrsEvent { id, event, .. }) = gilrs.next_event() {
+                if let EventType::ButtonPressed(..) = event {
+                    self.pad_style = Some(glyphs::detect(&gilrs.gamepad(id)));
+                }
+                match event {
+                    // Whatever the pad was holding is let go, or a held direction or B would never end
+                    EventType::Disconnected => {
+                        self.stick = (0, 0);
+                        actions.extend([
+                            GamepadAction::Released(NavDirection::Left),
+                            GamepadAction::Released(NavDirection::Right),
+                            GamepadAction::BackReleased,
+                        ]);
+                        if gilrs.gamepads().next().is_none() {
+                            let name = gilrs.gamepad(id).name().to_string();
+                            println!("Controller disconnected: {}", name);
+                            self.pad_lost = Some(name);
+                        }
+                        continue;
+                    }
+                    EventType::Connected => {
+                        self.pad_style = Some(glyphs::detect(&gilrs.gamepad(id)));
+                        if self.pad_lost.take().is_some() {
+                            actions.push(GamepadAction::Reconnected);
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+                let action = match event {
+                    EventType::ButtonPressed(button, _) => match button {
+                        Button::South => GamepadAction::Confirm,
+                        Button::East => GamepadAction::Back,
+                        Button::DPadUp => GamepadAction::Up,
+                        Button::North => GamepadAction::QuickSettings,
+                        Button::DPadDown => GamepadAction::Down,
+                        Button::DPadLeft | Button::West => GamepadAction::Left,
+                        Button::DPadRight => GamepadAction::Right,
+                        Button::Start | Button::Mode => GamepadAction::Start,
+                        Button::Select => GamepadAction::ContextMenu,
+                        b if b == tab_buttons.0 => GamepadAction::PrevTab,
+                        b if b == tab_buttons.1 => GamepadAction::NextTab,
+                        b if b == page_buttons.0 => GamepadAction::PrevPage,
+                        b if b == page_buttons.1 => GamepadAction::NextPage,
+                        Button::LeftThumb | Button::RightThumb => GamepadAction::Details,
+                        _ => GamepadAction::None,
+                    },
+                    EventType::ButtonReleased(Button::DPadLeft | Button::West, _) => {
+                        GamepadAction::Released(NavDirection::Left)
+                    }
+                    EventType::ButtonReleased(Button::DPadRight, _) => {
+                        GamepadAction::Released(NavDirection::Right)
+                    }
+                    EventType::ButtonReleased(Button::East, _) => GamepadAction::BackReleased,
+                    EventType::AxisChanged(axis @ (Axis::LeftStickX | Axis::LeftStickY), value, _) => {
+                        Self::stick_action(&mut self.stick, axis, value)
+                    }
+                    _ => GamepadAction::None,
+                };
+                if !matches!(action, GamepadAction::None) {
+                    actions.push(action);
+                }
+            }
+        }
+        actions
+    }
+
+    /// Turns left stick movement into D-pad presses. A direction is pressed past `STICK_PRESS`
+    /// and only let go below `STICK_RELEASE`, so a stick resting near the edge doesn't flicker.
+    fn stick_action(stick: &mut (i8, i8), axis: Axis, value: f32) -> GamepadAction {
+        let held = if axis == Axis::LeftStickX { &mut stick.0 } else { &mut stick.1 };
+        let direction = if value > STICK_PRESS {
+            1
+        } else if value < -STICK_PRESS {
+            -1
+        } else if value.abs() < STICK_RELEASE {
+            0
+        } else {
+            *held
+        };
+        if direction == *held {
+            return GamepadAction::None;
+        }
+        let previous = std::mem::replace(held, direction);
+        match (axis, direction) {
+            (Axis::LeftStickX, 1) => GamepadAction::Right,
+            (Axis::LeftStickX, -1) => GamepadAction::Left,
+            (Axis::LeftStickX, _) if previous > 0 => GamepadAction::Released(NavDirection::Right),
+            (Axis::LeftStickX, _) => GamepadAction::Released(NavDirection::Left),
+            // gilrs reports pushing up as positive
+            (_, 1) => GamepadAction::Up,
+            (_, -1) => GamepadAction::Down,
+            _ => GamepadAction::None,
+        }
+    }
+    
+    fn games_per_page(&self) -> usize {
+        GRID_COLUMNS * self.theme.card_rows.max(1) as usize
+    }
+    
+    fn current_page(&self) -> usize {
+        self.selected_game / self.games_per_page()
+    }
+    
+    /// Switches to the previous/next page, keeping the selection in the same column.
+    fn change_page(&mut self, delta: i32, num_games: usize) -> bool {
+        if num_games == 0 {
+            return false;
+        }
+        let per_page = self.games_per_page();
+        let page_count = (num_games + per_page - 1) / per_page;
+        let target = self.current_page() as i32 + delta;
+        if target < 0 || target >= page_count as i32 {
+            return false;
+        }
+        let slot = self.selected_game % per_page;
+        self.selected_game = (target as usize * per_page + slot).min(num_games - 1);
+        true
+    }
+    
+    /// `change_page`, or the first or last card when there is no page that way, so single-page
+    /// libraries still get a quick jump to either end.
+    fn flip_page(&mut self, delta: i32, num_games: usize) {
+        if num_games == 0 || self.change_page(delta, num_games) {
+            return;
+        }
+        self.selected_game = if delta < 0 { 0 } else { num_games - 1 };
+        self.moved = Some(Instant::now());
+    }
+    
+    /// Moves the selection one row up or down on the current page, keeping the column.
+    /// Returns false from the top or bottom row (or the only row), where Up and Down do something else.
+    fn move_row(&mut self, delta: i32, num_games: usize) -> bool {
+        let per_page = self.games_per_page();
+        if num_games == 0 || per_page <= GRID_COLUMNS {
+            return false;
+        }
+        let page_start = self.current_page() * per_page;
+        let slot = self.selected_game - page_start;
+        let target_row = (slot / GRID_COLUMNS) as i32 + delta;
+        if target_row < 0 || target_row >= self.theme.card_rows as i32 {
+            return false;
+        }
+        let row_start = page_start + target_row as usize * GRID_COLUMNS;
+        if row_start >= num_games {
+            // The last page ends before that row
+            return false;
+        }
+        // A short last row: land on its last card
+        self.selected_game = (row_start + slot % GRID_COLUMNS).min(num_games - 1);
+        self.moved = Some(Instant::now());
+        true
+    }
+    
+    /// Card rectangle for a game, or None when it is not on the current page.
+    fn get_game_box_rect(&self, idx: usize) -> Option<Rect> {
+        let per_page = self.games_per_page();
+        if idx / per_page != self.current_page() {
+            return None;
+        }
+        
+        Some(self.theme.card_rect(idx % per_page, GRID_COLUMNS))
+    }
+
+    /// Area that reacts to clicks and taps, enlarged in the handheld preset.
+    fn get_game_hit_rect(&self, idx: usize) -> Option<Rect> {
+        let rect = self.get_game_box_rect(idx)?;
+        if !self.handheld {
+            return Some(rect);
+        }
+        Some(Rect::new(
+            rect.x() - HANDHELD_TOUCH_PADDING,
+            rect.y() - HANDHELD_TOUCH_PADDING,
+            rect.width() + HANDHELD_TOUCH_PADDING as u32 * 2,
+            rect.height() + HANDHELD_TOUCH_PADDING as u32 * 2,
+        ))
+    }
+
+    fn game_at(&self, x: i32, y: i32, num_games: usize) -> Option<usize> {
+        (0..num_games).find(|&idx| {
+            self.get_game_hit_rect(idx)
+                .map(|rect| rect.contains_point((x, y)))
+                .unwrap_or(false)
+        })
+    }
+
+    fn update_selection_from_mouse(&mut self, sfcs: &[String]) {
+        for (idx, _) in sfcs.iter().enumerate() {
+            if let Some(rect) = self.get_game_box_rect(idx) {
+                if rect.contains_point((self.mouse_x, self.mouse_y)) {
+                    self.selected_game = idx;
+                    break;
+                }
+            }
+        }
+    }
+    
+    /// Tracks the pointer from motion events, clamped to the layout so positions reported over
+    /// letterbox bars or past the window edge don't put it off screen.
+    fn move_pointer(&mut self, x: i32, y: i32, window_focused: bool) {
+        self.mouse_x = x.clamp(0, SCREEN_WIDTH as i32 - 1);
+        self.mouse_y = y.clamp(0, SCREEN_HEIGHT as i32 - 1);
+        // Motion over an unfocused window is the pointer passing by, not someone using the launcher
+        self.pointer_inside = window_focused;
+    }
+    
+    /// The pointer left the window or the window lost focus: drops whatever it was hovering and
+    /// settles the card fades, so nothing stays half-highlighted until it comes back.
+    fn release_pointer(&mut self, buttons: &[(UIButton, MenuAction)], num_games: usize) {
+        if self.pointer_inside {
+            let (x, y) = (self.mouse_x, self.mouse_y);
+            let hovered = self.focused_button.and_then(|idx| buttons.get(idx)).map_or(false, |(b, _)| b.is_hovered(x, y));
+            if hovered {
+                self.focused_button = None;
+            }
+            if continue_rect().contains_point((x, y)) {
+                self.continue_focused = false;
+            }
+        }
+        self.pointer_inside = false;
+        for idx in 0..num_games {
+            let target = if idx == self.selected_game { 1.0 } else { 0.0 };
+            self.color_transitions.insert(idx, target);
+        }
+    }
+    
+    /// Where hover effects look, None while the pointer is outside the focused window.
+    fn hover_point(&self) -> Option<(i32, i32)> {
+        self.pointer_inside.then_some((self.mouse_x, self.mouse_y))
+    }
+    
+    /// Moves the selection by `delta` games, returning false when already at the edge.
+    fn move_selection(&mut self, delta: i32, sfcs: &[String]) -> bool {
+        let target = self.selected_game as i32 + delta;
+        if sfcs.is_empty() || target < 0 || target >= sfcs.len() as i32 {
+            // Held keys keep hitting the end; let the running shake finish instead of restarting it
+            if !sfcs.is_empty() && !self.is_bumping() {
+                self.bump = Some((Instant::now(), delta.signum()));
+            }
+            return false;
+        }
+        self.selected_game = target as usize;
+        self.moved = Some(Instant::now());
+        println!("Selected: {}", sfcs[self.selected_game]);
+        true
+    }
+    
+    fn is_bumping(&self) -> bool {
+        matches!(self.bump, Some((at, _)) if at.elapsed() < BUMP_TIME)
+    }
+    
+    /// Horizontal selector offset for the shake after running into the end of the list.
+    fn bump_offset(&self) -> i32 {
+        match self.bump {
+            Some((at, direction)) if at.elapsed() < BUMP_TIME => {
+                let t = at.elapsed().as_secs_f32() / BUMP_TIME.as_secs_f32();
+                // Two wiggles that start towards the pressed direction and die out
+                let offset = (t * std::f32::consts::TAU * 2.0).sin() * (1.0 - t) * BUMP_DISTANCE;
+                offset as i32 * direction
+            }
+            _ => 0,
+        }
+    }
+    
+    /// Speed and easing for every animation, from the launcher options.
+    fn motion(&self) -> anim::Motion {
+        anim::Motion::new(self.launcher_options.transition_speed, self.launcher_options.easing)
+    }
+    
+    /// Seconds of today's playtime limit left, counting the running game; None without a limit
+    /// or after the PIN lifted it for today.
+    fn playtime_left(&self) -> Option<u64> {
+        if self.playtime_unlocked == Some(chrono::Local::now().date_naive()) {
+            return None;
+        }
+        let running = self.game_process.as_ref().map(|g| g.started.elapsed().as_secs()).unwrap_or(0);
+        playtime::remaining(self.launcher_options.playtime_limit_mins, self.stats.playtime_today() + running)
+    }
+    
+    /// True when changes to the playtime limit need the PIN: one is set and today isn't unlocked yet.
+    fn needs_pin(&self) -> bool {
+        !self.launcher_options.playtime_pin.is_empty() && self.playtime_unlocked != Some(chrono::Local::now().date_naive())
+    }
+    
+    /// The savestate "Resume from latest state" would load, when the smw build can load one at startup.
+    fn resumable_state(&self, sfc_name: &str) -> Option<(PathBuf, std::time::SystemTime)> {
+        if !compat::can_load_state(&self.game_executable()) {
+            return None;
+        }
+        sram::latest_state(&self.saves_dir, &self.resolve_variant(sfc_name))
+    }
+    
+    fn cache(&self) -> cache::Cache {
+        cache::Cache::new(&self.launcher_dir, self.launcher_options.cache_ttl_hours)
+    }
+    
+    fn update_color_transitions(&mut self, num_games: usize) {
+        let step = self.motion().step(TRANSITION_SPEED);
+        for idx in 0..num_games {
+            let target = if idx == self.selected_game { 1.0 } else { 0.0 };
+            let current = self.color_transitions.entry(idx).or_insert(0.0);
+            
+            // Linear progress towards target, get_color_blend applies the easing
+            if *current < target {
+                *current = (*current + step).min(target);
+            } else {
+                *current = (*current - step).max(target);
+            }
+        }
+    }
+    
+    /// True while any card is still fading towards its selected or unselected look.
+    fn transitions_running(&self, num_games: usize) -> bool {
+        (0..num_games).any(|idx| {
+            let target = if idx == self.selected_game { 1.0 } else { 0.0 };
+            *self.color_transitions.get(&idx).unwrap_or(&0.0) != target
+        })
+    }
+    
+    /// Refreshes `pad_battery` every `PAD_BATTERY_POLL`; returns a warning the first time the
+    /// charge drops below `glyphs::LOW_BATTERY`. Wired pads and ones gilrs can't read are left out.
+    fn poll_pad_battery(&mut self) -> Option<String> {
+        if self.pad_battery_polled.map(|t| t.elapsed() < PAD_BATTERY_POLL).unwrap_or(false) {
+            return None;
+        }
+        self.pad_battery_polled = Some(Instant::now());
+        let gilrs = self.gamepad_system.as_ref()?;
+        self.pad_battery = gilrs
+            .gamepads()
+            .filter_map(|(_, pad)| match pad.power_info() {
+                PowerInfo::Discharging(percent) => Some((percent, false)),
+                PowerInfo::Charging(percent) => Some((percent, true)),
+                _ => None,
+            })
+            .min_by_key(|&(percent, _)| percent);
+        
+        match self.pad_battery {
+            Some((percent, false)) if percent < glyphs::LOW_BATTERY => {
+                if self.pad_battery_warned {
+                    return None;
+                }
+                self.pad_battery_warned = true;
+                Some(format!("Controller battery low ({}%)", percent))
+            }
+            _ => {
+                self.pad_battery_warned = false;
+                None
+            }
+        }
+    }
+    
+    fn has_gamepad(&self) -> bool {
+        self.gamepad_system.as_ref().map(|g| g.gamepads().next().is_some()).unwrap_or(false)
+    }
+    
+    /// How far a card has faded into its selected look, eased; spring easing overshoots 1.
+    fn get_color_blend(&self, idx: usize) -> f32 {
+        self.motion().ease(*self.color_transitions.get(&idx).unwrap_or(&0.0))
+    }
+}
+
+fn open_in_file_manager(path: &Path) {
+    let opener = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    
+    if let Err(e) = Command::new(opener).arg(path).spawn() {
+        eprintln!("Failed to open {}: {}", streamer::path(&path), e);
+    }
+}
+
+/// Opens the folder containing `path` with the file itself selected where the file manager supports it.
+fn reveal_in_file_manager(path: &Path) {
+    let result = if cfg!(target_os = "windows") {
+        let mut select = OsString::from("/select,");
+        select.push(path);
+        Command::new("explorer").arg(select).spawn()
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg("-R").arg(path).spawn()
+    } else {
+        // xdg-open can't select a file, the folder has to do
+        match path.parent() {
+            Some(dir) => return open_in_file_manager(dir),
+            None => return,
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to show {}: {}", streamer::path(path), e);
+    }
+}
+
+/// Cmd+Q on macOS. SDL usually turns it into a Quit event already, but not with every setup.
+fn is_quit_shortcut(event: &Event) -> bool {
+    if !cfg!(target_os = "macos") {
+        return false;
+    }
+    matches!(
+        event,
+        Event::KeyDown { keycode: Some(Keycode::Q), keymod, .. }
+            if keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD)
+    )
+}
+
+fn exe_file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn launch_issues_menu(sfc_name: &str, issues: &[LaunchIssue]) -> Menu {
+    let blocked = issues.iter().any(|i| i.fatal);
+    let title = if blocked { "Cannot launch this game" } else { "Launch warnings" };
+    
+    let mut menu = Menu::new(title).line(sfc_name.to_string());
+    for issue in issues {
+        let marker = if issue.fatal { "x" } else { "!" };
+        menu = menu.line(format!("{} {}", marker, issue.message));
+    }
+    
+    for (label, action) in issues.iter().filter_map(|i| i.fix.clone()) {
+        menu = menu.item(&label, action);
+    }
+    if !blocked {
+        menu = menu.item("Launch anyway", MenuAction::LaunchUnchecked(sfc_name.to_string()));
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+fn read_log_tail(path: &Path, count: usize) -> Vec<String> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(count)..]
+        .iter()
+        .map(|l| l.chars().take(40).collect())
+        .collect()
+}
+
+fn config_error_menu(error: &str) -> Menu {
+    let mut menu = Menu::new("Launcher options could not be read");
+    for line in error.lines().take(8) {
+        menu = menu.line(streamer::scrub(line));
+    }
+    menu.line("Defaults are in use and changes will not be saved.")
+        .item("Reset to defaults (keep backup)", MenuAction::ResetConfig)
+        .item("Close", MenuAction::Dismiss)
+}
+
+fn ini_conflict_menu(launcher: &Launcher) -> Menu {
+    // Key maps are long lists, the start is enough to tell them apart
+    let short = |value: &str| match value.char_indices().nth(16) {
+        Some((end, _)) => format!("{}...", &value[..end]),
+        None => value.to_string(),
+    };
+    let mut menu = Menu::new("smw.ini was changed").line("smw.ini was edited outside the launcher. These differ:");
+    for conflict in launcher.ini_conflicts.iter().take(8) {
+        menu = menu.line(format!(
+            "{}.{}: launcher {}, smw.ini {}",
+            conflict.section,
+            conflict.key,
+            short(&conflict.ours),
+            short(&conflict.theirs),
+        ));
+    }
+    menu.item("Keep the launcher's settings", MenuAction::ResolveIni(false))
+        .item("Use the settings from smw.ini", MenuAction::ResolveIni(true))
+}
+
+fn crash_report_menu(game: &RunningGame, status: ExitStatus) -> Menu {
+    let reason = match status.code() {
+        Some(code) => format!("Exit code: {}", code),
+        None => "Terminated abnormally (no exit code)".to_string(),
+    };
+    
+    let mut menu = Menu::new("The game stopped unexpectedly")
+        .line(game.rom.clone())
+        .line(reason);
+    for line in read_log_tail(&game.log_path, 6) {
+        menu = menu.line(streamer::scrub(&line));
+    }
+    
+    menu.item("Relaunch", MenuAction::Launch(game.rom.clone()))
+        .item("Open log", MenuAction::OpenPath(game.log_path.clone()))
+        .item("Close", MenuAction::Dismiss)
+}
+
+/// Shown instead of the crash report when the watchdog has safe settings left to try.
+fn watchdog_menu(game: &RunningGame, status: ExitStatus, step: usize) -> Menu {
+    let reason = match status.code() {
+        Some(code) => format!("exit code {}", code),
+        None => "no exit code".to_string(),
+    };
+    let settings = SAFE_STEPS[..=step].iter().map(|(label, _)| *label).collect::<Vec<_>>().join(", ");
+    Menu::new("The game crashed at startup")
+        .line(game.rom.clone())
+        .line(format!("Stopped after {}s ({})", game.started.elapsed().as_secs(), reason))
+        .line(format!("Try again with: {}", settings))
+        .item("Relaunch with safe settings", MenuAction::SafeRelaunch(game.rom.clone(), step))
+        .item("Open log", MenuAction::OpenPath(game.log_path.clone()))
+        .item("Close", MenuAction::Dismiss)
+}
+
+/// A card grabbed with the mouse; it only becomes a drag once the pointer moves far enough,
+/// otherwise releasing it counts as a click.
+struct CardDrag {
+    from: usize,
+    start: (i32, i32),
+    dragging: bool,
+}
+
+/// A finger currently on the screen, tracked to tell taps, swipes and long presses apart.
+struct TouchState {
+    finger_id: i64,
+    start: (i32, i32),
+    last: (i32, i32),
+    started: Instant,
+    long_press_fired: bool,
+}
+
+/// Converts normalized touch coordinates into layout coordinates, accounting for the
+/// letterboxing applied when the canvas uses a logical size.
+fn touch_to_layout(canvas: &Canvas<Window>, x: f32, y: f32) -> (i32, i32) {
+    let (w, h) = canvas.window().size();
+    let scale = (w as f32 / SCREEN_WIDTH as f32).min(h as f32 / SCREEN_HEIGHT as f32);
+    let offset_x = (w as f32 - SCREEN_WIDTH as f32 * scale) / 2.0;
+    let offset_y = (h as f32 - SCREEN_HEIGHT as f32 * scale) / 2.0;
+    (
+        ((x * w as f32 - offset_x) / scale) as i32,
+        ((y * h as f32 - offset_y) / scale) as i32,
+    )
+}
+
+fn game_context_menu(launcher: &Launcher, sfc_name: &str) -> Menu {
+    let hidden = launcher.is_hidden(sfc_name);
+    let favorite = launcher.is_favorite(sfc_name);
+    let mut menu = Menu::new(sfc_name.trim_end_matches(".sfc"))
+        .item("Launch", MenuAction::Launch(sfc_name.to_string()))
+        .item("Launch with options...", MenuAction::LaunchWithOptions(sfc_name.to_string()));
+    if let Some((_, written)) = launcher.resumable_state(sfc_name) {
+        let age = written.elapsed().map(|age| age.as_secs()).unwrap_or(0);
+        menu = menu.item(
+            &format!("Resume from latest state ({})", stats::format_age(age)),
+            MenuAction::ResumeState(sfc_name.to_string()),
+        );
+    }
+    menu = menu
+        .item("Move earlier", MenuAction::MoveGame(sfc_name.to_string(), -1))
+        .item("Move later", MenuAction::MoveGame(sfc_name.to_string(), 1))
+        .item(
+            if favorite { "Remove from favorites" } else { "Add to favorites" },
+            MenuAction::SetFavorite(sfc_name.to_string(), !favorite),
+        )
+        .item(if hidden { "Unhide" } else { "Hide" }, MenuAction::SetHidden(sfc_name.to_string(), !hidden))
+        .item("Collections", MenuAction::OpenCollections(sfc_name.to_string()))
+        .item("Content", MenuAction::OpenContent(sfc_name.to_string()))
+        .item("Rename", MenuAction::Rename(sfc_name.to_string()))
+        .item("Files", MenuAction::OpenFiles(sfc_name.to_string()))
+        .item("Use image path in clipboard as cover", MenuAction::SetCoverFromClipboard(sfc_name.to_string()))
+        .item("Display", MenuAction::OpenDisplayOverrides(sfc_name.to_string()))
+        .item(
+            &match launcher.launcher_options.games.get(sfc_name).map(|g| g.watchdog_secs).unwrap_or(0) {
+                0 => "Crash watchdog: Off".to_string(),
+                secs => format!("Crash watchdog: {}s", secs),
+            },
+            MenuAction::CycleWatchdog(sfc_name.to_string()),
+        );
+    let game = launcher.launcher_options.games.get(sfc_name);
+    let priority = game.map(|g| g.priority).unwrap_or(0);
+    let priority = PRIORITY_CHOICES.iter().find(|(p, _)| *p == priority).map(|(_, name)| *name).unwrap_or("Normal");
+    menu = menu.item(&format!("Process priority: {}", priority), MenuAction::CyclePriority(sfc_name.to_string()));
+    if platform::performance_cores().is_some() {
+        menu = menu.item(
+            &format!("Performance cores only: {}", on_off(game.map(|g| g.performance_cores).unwrap_or(false))),
+            MenuAction::TogglePerformanceCores(sfc_name.to_string()),
+        );
+    }
+    menu = menu.item("Preview smw.ini changes", MenuAction::PreviewGameIni(sfc_name.to_string()));
+    if !manual::pages(&launcher.launcher_dir, &launcher.sfc_dir, &launcher.resolve_variant(sfc_name)).is_empty() {
+        menu = menu.item("Manual", MenuAction::OpenManual(sfc_name.to_string()));
+    }
+    if launcher.variants.contains_key(sfc_name) {
+        menu = menu.item("Region versions", MenuAction::OpenVariants(sfc_name.to_string()));
+    }
+    if launcher.library.entry(sfc_name).map(|e| e.cover.is_some()).unwrap_or(false) {
+        menu = menu.item("Use the default cover", MenuAction::ResetCover(sfc_name.to_string()));
+    }
+    if launcher.has_copier_header(sfc_name) {
+        menu = menu.item("Remove copier header", MenuAction::StripHeader(sfc_name.to_string()));
+    }
+    menu
+        .item("Launcher options", MenuAction::OpenLauncherOptions)
+        .item("Close", MenuAction::Dismiss)
+}
+
+/// How a per-game graphics override reads in menus and on the back of the card.
+fn graphics_value_label(key: &str, value: &str) -> String {
+    match key {
+        "Shader" => Path::new(value).file_stem().map_or(value.to_string(), |s| s.to_string_lossy().into_owned()),
+        "LinearFiltering" => if value == "1" { "Smooth" } else { "Sharp pixels" }.to_string(),
+        "NewRenderer" => if value == "1" { "New" } else { "Original" }.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Per-game fullscreen, window scale, monitor and graphics, each falling back to the global setting.
+fn display_page(launcher: &Launcher, sfc_name: &str, displays: u32) -> OptionsScreen {
+    let global = launcher.game_options();
+    let flag = |on: bool| if on { "1" } else { "0" };
+    let fullscreen_name = |value: &str| match value {
+        "0" => "Off".to_string(),
+        "1" => "Desktop".to_string(),
+        _ => "Exclusive".to_string(),
+    };
+    let monitor = launcher.launcher_options.games.get(sfc_name).and_then(|g| g.monitor).map_or(0, |m| m as usize + 1);
+    let monitors = iter::once("Default".to_string()).chain((1..=displays).map(|m| m.to_string())).collect();
+    let rom = sfc_name.to_string();
+    let page = OptionsScreen::new("Display")
+        .line(sfc_name.to_string())
+        .line("Written to smw.ini when this game launches.".to_string());
+    let page = override_row(page, launcher, sfc_name, "Fullscreen", &global.fullscreen.to_string(), fullscreen_name);
+    let page = override_row(page, launcher, sfc_name, "WindowScale", &global.window_scale.to_string(), |v| format!("{}x", v))
+        .row(OptionRow::choice("Monitor", monitors, monitor), move |step| MenuAction::CycleMonitor(rom.clone(), step));
+    let page = override_row(page, launcher, sfc_name, "Shader", &global.shader, |v| graphics_value_label("Shader", v));
+    let filtering = flag(global.linear_filtering);
+    let page = override_row(page, launcher, sfc_name, "LinearFiltering", filtering, |v| graphics_value_label("LinearFiltering", v));
+    override_row(page, launcher, sfc_name, "NewRenderer", flag(global.new_renderer), |v| graphics_value_label("NewRenderer", v))
+}
+
+/// A display page row for smw.ini `key`: the global value first, then each value the game can
+/// override it with, all named by `name`.
+fn override_row(
+    page: OptionsScreen,
+    launcher: &Launcher,
+    sfc_name: &str,
+    key: &str,
+    global: &str,
+    name: impl Fn(&str) -> String,
+) -> OptionsScreen {
+    let label = match key {
+        "WindowScale" => "Window scale",
+        "LinearFiltering" => "Filtering",
+        "NewRenderer" => "Renderer",
+        key => key,
+    };
+    let values = launcher.ini_override_choices(key);
+    let overridden = launcher.launcher_options.games.get(sfc_name).and_then(|g| g.game_options.get(key));
+    let current = overridden.and_then(|v| values.iter().position(|c| c == v)).map_or(0, |i| i + 1);
+    let names = iter::once(format!("Default ({})", name(global))).chain(values.iter().map(|v| name(v))).collect();
+    let (rom, key) = (sfc_name.to_string(), key.to_string());
+    page.row(OptionRow::choice(label, names, current), move |step| MenuAction::CycleIniOverride(rom.clone(), key.clone(), step))
+}
+
+fn files_menu(launcher: &Launcher, sfc_name: &str) -> Menu {
+    Menu::new("Files")
+        .line(sfc_name.to_string())
+        .item("Show ROM in folder", MenuAction::RevealPath(launcher.sfc_dir.join(sfc_name)))
+        .item("Open saves folder", MenuAction::OpenPath(launcher.saves_dir.clone()))
+        .item("Open screenshots folder", MenuAction::OpenPath(launcher.launcher_dir.join("screenshots")))
+        .item("Open logs folder", MenuAction::OpenPath(launcher.launcher_dir.join("logs")))
+        .item("Delete ROM", MenuAction::ConfirmDeleteRom(sfc_name.to_string()))
+        .item("Close", MenuAction::Dismiss)
+}
+
+fn delete_rom_menu(sfc_name: &str) -> Menu {
+    Menu::new("Delete ROM")
+        .line(format!("Move {} to the trash?", sfc_name))
+        .line("It can be restored from the trash later.")
+        .item("Cancel", MenuAction::Dismiss)
+        .item("Move to trash", MenuAction::DeleteRom(sfc_name.to_string()))
+}
+
+fn game_ini_menu(launcher: &Launcher, sfc_name: &str) -> Menu {
+    let changes = launcher.game_ini_changes(sfc_name);
+    let mut menu = Menu::new("smw.ini changes").line(format!("Launching {} will write:", sfc_name));
+    if changes.is_empty() {
+        menu = menu.line("No changes");
+    }
+    for change in changes.iter().take(10) {
+        menu = menu.line(change.clone());
+    }
+    if changes.len() > 10 {
+        menu = menu.line(format!("...and {} more", changes.len() - 10));
+    }
+    menu.item("Launch", MenuAction::Launch(sfc_name.to_string()))
+        .item("Close", MenuAction::Dismiss)
+}
+
+fn collections_menu(launcher: &Launcher, sfc_name: &str) -> Menu {
+    let mut menu = Menu::new("Collections").line(sfc_name.to_string());
+    for collection in &launcher.launcher_options.collections {
+        let mark = if launcher.in_collection(sfc_name, collection) { "[x]" } else { "[ ]" };
+        menu = menu.item(
+            &format!("{} {}", mark, collection),
+            MenuAction::ToggleCollection(sfc_name.to_string(), collection.clone()),
+        );
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+/// Age rating and content descriptors of one game.
+fn content_menu(launcher: &Launcher, sfc_name: &str) -> Menu {
+    let entry = launcher.library.entry(sfc_name);
+    // Ratings decide what the age limit hides, so they're locked along with it
+    let locked = |action: MenuAction| if launcher.needs_pin() { MenuAction::EnterPin(Box::new(action)) } else { action };
+    let mut menu = Menu::new("Content").line(sfc_name.to_string()).item(
+        &format!("Age rating: {}", rating::label(launcher.age_rating(sfc_name))),
+        locked(MenuAction::CycleAgeRating(sfc_name.to_string())),
+    );
+    for descriptor in rating::DESCRIPTORS {
+        let tagged = entry.map(|e| e.descriptors.iter().any(|d| d == descriptor)).unwrap_or(false);
+        menu = menu.item(
+            &format!("{} {}", if tagged { "[x]" } else { "[ ]" }, descriptor),
+            locked(MenuAction::ToggleDescriptor(sfc_name.to_string(), descriptor.to_string())),
+        );
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+fn maintenance_menu(launcher: &Launcher) -> Menu {
+    let dir = launcher.archive_dir();
+    let last = match archive::list(&dir).first() {
+        Some(path) => format!("Last backup: {}", path.file_name().unwrap_or_default().to_string_lossy()),
+        None => "No backups yet".to_string(),
+    };
+    Menu::new("Maintenance")
+        .line("Backs up launcher data, smw.ini and saves.")
+        .line("A backup is also made automatically every week.")
+        .line(last)
+        .item("Backup everything", MenuAction::BackupEverything)
+        .item("Restore from backup", MenuAction::OpenArchives)
+        .item("Open backup folder", MenuAction::OpenPath(dir))
+        .item("Close", MenuAction::Dismiss)
+}
+
+fn archives_menu(dir: &Path) -> Menu {
+    let archives = archive::list(dir);
+    let mut menu = Menu::new("Restore from backup").line("The current data is backed up before restoring.");
+    if archives.is_empty() {
+        menu = menu.line("No backups yet");
+    }
+    for path in archives.into_iter().take(10) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        menu = menu.item(&name, MenuAction::RestoreArchive(path));
+    }
+    menu.item("Back", MenuAction::OpenMaintenance)
+}
+
+fn backups_menu(backup_dir: &Path) -> Menu {
+    let entries = backup::load_manifest(backup_dir);
+    let mut menu = Menu::new("Restore backups");
+    if entries.is_empty() {
+        menu = menu.line("No backups yet");
+    }
+    // Newest first, the top entry undoes the last change
+    for entry in entries.iter().rev().take(10) {
+        let name = entry.original.file_name().unwrap_or_default().to_string_lossy();
+        menu = menu.item(
+            &format!("{} (before {})", name, entry.operation),
+            MenuAction::RestoreBackup(entry.id.clone()),
+        );
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+/// Splits `smb1_pal.sfc` into ("smb1", Some("pal")); names without a region tag return None.
+fn split_region_tag(sfc_name: &str) -> (String, Option<String>) {
+    let stem = sfc_name.trim_end_matches(".sfc").to_lowercase();
+    if let Some((base, tag)) = stem.rsplit_once('_') {
+        if REGION_TAGS.contains(&tag) {
+            return (base.to_string(), Some(tag.to_string()));
+        }
+    }
+    (stem, None)
+}
+
+/// Up/Down on the grid: moves between rows, past the top row to the Continue tile and past
+/// the bottom row to the button bar under it (`button_count` buttons, left to right).
+/// With no Continue tile, Up on a card with region variants offers them instead.
+fn vertical_nav(launcher: &mut Launcher, sfcs: &[String], delta: i32, button_count: usize) -> Option<Menu> {
+    if launcher.continue_focused {
+        return None;
+    }
+    if launcher.move_row(delta, sfcs.len()) {
+        return None;
+    }
+    if delta > 0 {
+        // The button under the card's column
+        let col = launcher.selected_game % GRID_COLUMNS;
+        launcher.focused_button = Some((col * button_count / GRID_COLUMNS).min(button_count - 1));
+        return None;
+    }
+    if launcher.last_played().is_some() {
+        launcher.continue_focused = true;
+        return None;
+    }
+    let card = sfcs.get(launcher.selected_game)?;
+    if launcher.variants.contains_key(card) {
+        Some(variants_menu(launcher, card))
+    } else {
+        None
+    }
+}
+
+/// What the back of a card lists, see `details`.
+fn card_details(launcher: &Launcher, card: &str) -> details::Details {
+    let rom = launcher.resolve_variant(card);
+    let entry = launcher.library.entry(&rom);
+    let mut lines = vec![launcher.rom_description(&rom)];
+    match entry.filter(|e| e.play_count > 0) {
+        Some(e) => {
+            lines.push(format!("Played {} in {} session(s)", stats::format_duration(e.play_secs), e.play_count));
+            if let Some(last) = e.last_played {
+                lines.push(format!("Last played {}", details::date(last)));
+            }
+        }
+        None => lines.push("Not played yet".to_string()),
+    }
+    if let Some(e) = entry {
+        lines.push(format!("Added {}", details::date(e.first_seen)));
+    }
+    if let Some(game) = launcher.launcher_options.games.get(&rom) {
+        let graphics: Vec<String> = ["Shader", "LinearFiltering", "NewRenderer"]
+            .iter()
+            .filter_map(|key| game.game_options.get(*key).map(|value| graphics_value_label(key, value)))
+            .collect();
+        if !graphics.is_empty() {
+            lines.push(format!("Own graphics: {}", graphics.join(", ")));
+        }
+    }
+    let favorite = launcher.is_favorite(card);
+    details::Details {
+        title: launcher.display_title(&rom),
+        lines,
+        actions: vec![
+            ("Launch".to_string(), MenuAction::Launch(rom.clone())),
+            (
+                if favorite { "Unfavorite" } else { "Favorite" }.to_string(),
+                MenuAction::SetFavorite(card.to_string(), !favorite),
+            ),
+            ("Collections".to_string(), MenuAction::OpenCollections(card.to_string())),
+            ("Files".to_string(), MenuAction::OpenFiles(card.to_string())),
+        ],
+    }
+}
+
+/// Game menu of a bundle card; the game picked last is marked and launched by the context menu.
+fn bundle_menu(launcher: &Launcher, card: &str) -> Menu {
+    let current = launcher.resolve_variant(card);
+    let Some(bundle) = launcher.bundles.get(card) else {
+        return Menu::new("Choose game").item("Close", MenuAction::Dismiss);
+    };
+    let mut menu = Menu::new(&bundle.title);
+    for game in &bundle.games {
+        let mark = if game.rom == current { ">" } else { " " };
+        menu = menu.item(&format!("{} {}", mark, game.name), MenuAction::Launch(game.rom.clone()));
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+fn variants_menu(launcher: &Launcher, card: &str) -> Menu {
+    let current = launcher.resolve_variant(card);
+    let mut menu = Menu::new("Choose version").line(launcher.display_title(card));
+    for variant in launcher.variants.get(card).into_iter().flatten() {
+        let region = fs::read(launcher.sfc_dir.join(variant))
+            .ok()
+            .and_then(|data| rom::header_region(&data))
+            .unwrap_or("Unknown region");
+        let mark = if *variant == current { ">" } else { " " };
+        menu = menu.item(
+            &format!("{} {} ({})", mark, variant, region),
+            MenuAction::SelectVariant(card.to_string(), variant.clone()),
+        );
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+/// Answers a control API request; launches go through the same path as the menus.
+fn control_command(
+    launcher: &mut Launcher,
+    sfcs: &mut Vec<String>,
+    window: &mut Window,
+    command: &serde_json::Value,
+) -> (serde_json::Value, Option<MenuAction>) {
+    match command["cmd"].as_str() {
+        Some("list") => {
+            let games: Vec<serde_json::Value> = launcher
+                .scan_sfc_files()
+                .iter()
+                .map(|rom| {
+                    serde_json::json!({
+                        "rom": rom,
+                        "title": launcher.display_title(rom),
+                        "hidden": launcher.is_hidden(rom),
+                    })
+                })
+                .collect();
+            (serde_json::json!({ "ok": true, "games": games }), None)
+        }
+        Some("launch") => {
+            let rom = match command["rom"].as_str() {
+                Some(rom) if launcher.sfc_dir.join(rom).is_file() => rom.to_string(),
+                Some(rom) => return (control::error(format!("No such game: {}", rom)), None),
+                None => return (control::error("Missing \"rom\""), None),
+            };
+            if launcher.game_process.is_some() {
+                return (control::error("A game is already running"), None);
+            }
+            if let Err(e) = launcher.reveal_game(sfcs, &rom) {
+                return (control::error(e), None);
+            }
+            (serde_json::json!({ "ok": true }), Some(MenuAction::Launch(rom)))
+        }
+        Some("focus") => {
+            window.restore();
+            window.raise();
+            (serde_json::json!({ "ok": true }), None)
+        }
+        Some("status") => {
+            let running = launcher.game_process.as_ref();
+            (
+                serde_json::json!({
+                    "ok": true,
+                    "running": running.map(|g| g.rom.clone()),
+                    "title": running.map(|g| launcher.display_title(&g.rom)),
+                    "playing_secs": running.map(|g| g.started.elapsed().as_secs()),
+                    "selected": sfcs.get(launcher.selected_game),
+                }),
+                None,
+            )
+        }
+        Some(other) => (control::error(format!("Unknown command: {}", other)), None),
+        None => (control::error("Missing \"cmd\""), None),
+    }
+}
+
+/// Writes the current render target to `dir` as a timestamped PNG.
+fn save_screenshot(canvas: &Canvas<Window>, dir: &Path) -> Result<PathBuf, String> {
+    let format = PixelFormatEnum::ABGR8888;
+    let (width, height) = canvas.output_size()?;
+    let mut pixels = canvas.read_pixels(None, format)?;
+    let surface = Surface::from_data(&mut pixels, width, height, width * 4, format)?;
+    
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("launcher_{}.png", timestamp));
+    surface.save(&path)?;
+    Ok(path)
+}
+
+fn theme_installed_menu(result: &Result<String, String>) -> Menu {
+    match result {
+        Ok(name) => Menu::new("Theme installed")
+            .line(name.clone())
+            .item("Use it now", MenuAction::ActivateTheme(name.clone()))
+            .item("Later", MenuAction::Dismiss),
+        Err(e) => Menu::new("Theme install failed")
+            .line(streamer::scrub(e))
+            .item("Close", MenuAction::Dismiss),
+    }
+}
+
+fn cover_pack_menu(result: &Result<coverpack::ImportReport, String>) -> Menu {
+    match result {
+        Ok(report) => {
+            let mut menu = Menu::new("Cover pack imported").line(format!("{} cover(s) installed", report.installed.len()));
+            if report.kept > 0 {
+                menu = menu.line(format!("{} skipped, those games already have a cover", report.kept));
+            }
+            if report.invalid > 0 {
+                menu = menu.line(format!("{} file(s) were not usable images", report.invalid));
+            }
+            if report.unmatched > 0 {
+                menu = menu.line(format!("{} matched no ROM in the library", report.unmatched));
+            }
+            menu.item("Close", MenuAction::Dismiss)
+        }
+        Err(e) => Menu::new("Cover pack import failed")
+            .line(streamer::scrub(e))
+            .item("Close", MenuAction::Dismiss),
+    }
+}
+
+/// Installs a cover pack zip and makes the new covers show up right away.
+fn import_cover_pack(launcher: &Launcher, covers: &mut textures::TextureStore, data: &[u8]) -> Menu {
+    let result = coverpack::install(&launcher.launcher_dir.join("pngs"), data, &launcher.library.files_by_id());
+    if let Ok(report) = &result {
+        for rom in &report.installed {
+            covers.forget(rom);
+        }
+    }
+    cover_pack_menu(&result)
+}
+
+/// Refresh rate of the display the window is on, 0 when the mode doesn't say.
+fn query_refresh_rate(video: &sdl2::VideoSubsystem, window: &Window) -> Result<i32, String> {
+    window
+        .display_index()
+        .and_then(|display| video.current_display_mode(display))
+        .or_else(|_| video.desktop_display_mode(0))
+        .map(|mode| mode.refresh_rate)
+}
+
+/// Refresh rate of the display the window landed on. Some Wayland and VM setups can't report
+/// a display mode at all, so anything unusable falls back to 60 Hz instead of failing.
+fn display_refresh_rate(video: &sdl2::VideoSubsystem, window: &Window) -> i32 {
+    match query_refresh_rate(video, window) {
+        Ok(rate) if rate > 0 => rate,
+        Ok(_) => 60,
+        Err(e) => {
+            eprintln!("Could not query the display mode ({}), assuming 60Hz", e);
+            60
+        }
+    }
+}
+
+/// Reports finished background work with a desktop notification, but only when the launcher
+/// window isn't focused; a focused window already shows the result.
+fn notify_if_away(launcher: &Launcher, window: &Window, message: &str) {
+    let focused = window.window_flags() & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32 != 0;
+    if focused || !launcher.launcher_options.task_notifications {
+        return;
+    }
+    if let Err(e) = platform::notify("SMAS Launcher", &streamer::scrub(message)) {
+        eprintln!("Failed to show a notification: {}", e);
+    }
+}
+
+fn continue_rect() -> Rect {
+    let (x, y, width, height) = CONTINUE_RECT;
+    Rect::new(x, y, width, height)
+}
+
+fn load_background<'a>(texture_creator: &'a TextureCreator<WindowContext>, launcher: &mut Launcher) -> Option<Texture<'a>> {
+    visualizer::set_enabled(launcher.theme.bgtype == theme::BG_VISUALIZER);
+    if launcher.theme.bgtype != theme::BG_IMAGE {
+        launcher.status.set(Subsystem::Background, None);
+        return None;
+    }
+    let bg_path = theme::background_path(&launcher.launcher_dir, &launcher.theme);
+    let (texture, problem) = assets::load_texture_reporting(texture_creator, &bg_path, assets::BACKGROUND, "background texture");
+    launcher.status.set(Subsystem::Background, problem);
+    texture
+}
+
+/// Cursor, pointer, button and badge images of the current theme, packed into one texture.
+fn load_atlas<'a>(texture_creator: &'a TextureCreator<WindowContext>, launcher: &Launcher) -> atlas::Atlas<'a> {
+    atlas::load(texture_creator, &launcher.launcher_dir, &launcher.theme, launcher.launcher_options.selector == 1)
+}
+
+/// Loads the theme's font, falling back to the embedded smw.ttf and then to the bitmap font.
+/// Without SDL_ttf it's always the bitmap font, and the status keeps the reason set at startup.
+fn load_font<'ttf>(ttf_context: Option<&'ttf ttf::Sdl2TtfContext>, launcher: &mut Launcher) -> Font<'ttf> {
+    let fallback = Font::bitmap(launcher.theme.font_size);
+    let Some(ttf_context) = ttf_context else {
+        return fallback;
+    };
+    let font_path = theme::font_path(&launcher.launcher_dir, &launcher.theme);
+    let problem = if font_path.exists() {
+        match ttf_context.load_font(&font_path, launcher.theme.font_size) {
+            Ok(f) => {
+                println!("Loaded font: {}", streamer::path(&font_path));
+                launcher.status.set(Subsystem::Font, None);
+                return Font::Ttf(f);
+            }
+            Err(e) => {
+                eprintln!("Failed to load font: {}", e);
+                format!("{} is unreadable ({})", streamer::path(&font_path), e)
+            }
+        }
+    } else {
+        eprintln!("Font not found at: {}", streamer::path(&font_path));
+        format!("{} is missing", streamer::path(&font_path))
+    };
+    match assets::load_font(ttf_context, launcher.theme.font_size) {
+        Ok(f) => {
+            launcher.status.set(Subsystem::Font, Some(format!("{}, using the built-in one", problem)));
+            Font::Ttf(f)
+        }
+        Err(e) => {
+            eprintln!("Failed to load the embedded font: {}, using the bitmap font", e);
+            launcher.status.set(Subsystem::Font, Some(format!("{}, and the built-in one failed ({}), using the bitmap font", problem, e)));
+            fallback
+        }
+    }
+}
+
+/// Opens the mixer with a latency preset and starts the music, from the top or fading in at `music_from`.
+fn open_audio(launcher: &mut Launcher, preset: u8, music_from: Option<Duration>) -> Option<audio::AudioSystem> {
+    let (_, chunk_size, _) = AUDIO_LATENCY_PRESETS[(preset as usize).min(AUDIO_LATENCY_PRESETS.len() - 1)];
+    match audio::AudioSystem::init(&theme::sound_files(&launcher.launcher_dir, &launcher.theme), chunk_size) {
+        Ok(mut audio) => {
+            audio.set_volume(launcher.launcher_options.volume);
+            match music_from {
+                Some(position) => audio.resume_music_from(position),
+                None => audio.play_music(),
+            }
+            launcher.status.set(Subsystem::Audio, None);
+            Some(audio)
+        }
+        Err(e) => {
+            eprintln!("Audio unavailable, running muted: {}", e);
+            launcher.status.set(Subsystem::Audio, Some(format!("{}, running muted", e)));
+            None
+        }
+    }
+}
+
+/// Audio device settings only apply when it's opened, so trying a preset reopens it.
+fn reopen_audio(audio: &mut Option<audio::AudioSystem>, launcher: &mut Launcher, preset: u8) {
+    // The old device has to be closed before the new one opens
+    *audio = None;
+    *audio = open_audio(launcher, preset, None);
+    visualizer::set_enabled(launcher.theme.bgtype == theme::BG_VISUALIZER);
+}
+
+/// After a theme change: reopens the mixer when the new theme plays different files.
+fn apply_theme_sounds(audio: &mut Option<audio::AudioSystem>, launcher: &mut Launcher, preset: u8) {
+    let files = theme::sound_files(&launcher.launcher_dir, &launcher.theme);
+    if audio.as_ref().map(|a| *a.files() != files).unwrap_or(false) {
+        reopen_audio(audio, launcher, preset);
+    }
+}
+
+fn audio_latency_menu(launcher: &Launcher, trying: u8) -> Menu {
+    let saved = launcher.launcher_options.audio_latency;
+    let mut menu = Menu::new("Audio latency")
+        .line("Pick a preset and listen to the test sound.")
+        .line("Crackling means the buffer is too small for this machine.");
+    for (idx, (name, _, samples)) in AUDIO_LATENCY_PRESETS.iter().enumerate() {
+        let mark = if idx as u8 == trying { ">" } else { " " };
+        menu = menu.item(&format!("{} {} ({} samples)", mark, name, samples), MenuAction::TryAudioLatency(idx as u8));
+    }
+    menu = menu.item("Play test sound", MenuAction::TryAudioLatency(trying));
+    if trying != saved {
+        menu = menu.item(&format!("Save {}", AUDIO_LATENCY_PRESETS[trying as usize].0), MenuAction::SaveAudioLatency(trying));
+    }
+    menu.item("Close", MenuAction::RevertAudioLatency)
+}
+
+fn apply_quick_setting(launcher: &mut Launcher, audio: Option<&audio::AudioSystem>, setting: QuickSetting, value: i32) {
+    launcher.change_quick_setting(setting, value);
+    if let (QuickSetting::Volume, Some(audio)) = (setting, audio) {
+        audio.set_volume(launcher.launcher_options.volume);
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "On" } else { "Off" }
+}
+
+fn system_status_menu(launcher: &Launcher) -> Menu {
+    let mut menu = Menu::new("System status");
+    for subsystem in status::SUBSYSTEMS {
+        let state = match launcher.subsystem_problem(subsystem) {
+            Some(problem) => problem,
+            None if subsystem == Subsystem::Network && launcher.launcher_options.update_server.is_none() => {
+                "No update server configured".to_string()
+            }
+            None if subsystem == Subsystem::Network && launcher.news.is_fetching() => "Checking...".to_string(),
+            None => "OK".to_string(),
+        };
+        menu = menu.line(format!("{}: {}", subsystem.name(), state));
+    }
+    for subsystem in status::SUBSYSTEMS {
+        if launcher.subsystem_problem(subsystem).is_some() {
+            menu = menu.item(&format!("Retry {}", subsystem.name().to_lowercase()), MenuAction::Retry(subsystem));
+        }
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+fn launcher_options_page(launcher: &Launcher) -> OptionsScreen {
+    let options = &launcher.launcher_options;
+    let mut page = OptionsScreen::new("Launcher options");
+    if let Some(remote) = &launcher.remote {
+        // The LAN address stays off stream, the PIN is useless without it
+        if options.streamer_mode {
+            page = page.line(format!("Remote: port {}  PIN {}", remote.port, remote.pin));
+        } else {
+            page = page.line(format!("Remote: {}  PIN {}", remote.url, remote.pin));
+        }
+    }
+    let choice = |label: &str, names: Vec<String>, current: Option<usize>| OptionRow::choice(label, names, current.unwrap_or(0));
+    let languages = TITLE_LANGUAGES
+        .iter()
+        .map(|(code, name)| match *code {
+            "" => format!("System ({})", platform::system_language()),
+            _ => name.to_string(),
+        })
+        .collect();
+    let battery_saver = BATTERY_SAVER_CHOICES
+        .iter()
+        .map(|&percent| match percent {
+            0 => "Off".to_string(),
+            100 => "Whenever on battery".to_string(),
+            percent => format!("Below {}%", percent),
+        })
+        .collect();
+    let playtime_limits = playtime::LIMITS
+        .iter()
+        .map(|&mins| match mins {
+            0 => "Off".to_string(),
+            mins => stats::format_duration(mins as u64 * 60),
+        })
+        .collect();
+    let age_limits = rating::LIMITS
+        .iter()
+        .map(|&age| match age {
+            0 => "Off".to_string(),
+            age => rating::label(age),
+        })
+        .collect();
+    let palettes = iter::once("Theme's own").chain(theme::PALETTES.iter().map(|p| p.name)).map(str::to_string).collect();
+    let needs_pin = launcher.needs_pin();
+    let max_age = options.max_age;
+    page = page
+        .item(OptionRow::toggle("Show hidden games", options.show_hidden), MenuAction::ToggleShowHidden)
+        .item(OptionRow::toggle("Streamer mode", options.streamer_mode), MenuAction::ToggleStreamerMode)
+        .row(
+            OptionRow::choice("Sort", vec!["Custom order".to_string(), "Recently added".to_string()], options.sort_mode as usize),
+            MenuAction::CycleSortMode,
+        )
+        .row(
+            choice("Title language", languages, TITLE_LANGUAGES.iter().position(|(code, _)| *code == options.language)),
+            MenuAction::CycleLanguage,
+        )
+        .item(OptionRow::toggle("Generate missing covers", options.auto_covers), MenuAction::ToggleAutoCovers)
+        .item(
+            OptionRow::toggle("Notify when background tasks finish", options.task_notifications),
+            MenuAction::ToggleTaskNotifications,
+        )
+        .item(
+            OptionRow::toggle("Fullscreen launcher starts games fullscreen", options.fullscreen_handoff),
+            MenuAction::ToggleFullscreenHandoff,
+        )
+        .item(OptionRow::toggle("Low-latency menus, may tear (after restart)", options.low_latency), MenuAction::ToggleLowLatency)
+        .row(
+            choice("Battery saver", battery_saver, BATTERY_SAVER_CHOICES.iter().position(|&p| p == options.battery_saver)),
+            MenuAction::CycleBatterySaver,
+        )
+        .row(
+            choice(
+                "Frame rate on battery saver",
+                BATTERY_FPS_CHOICES.iter().map(|fps| format!("{} fps", fps)).collect(),
+                BATTERY_FPS_CHOICES.iter().position(|&f| f == options.battery_fps),
+            ),
+            MenuAction::CycleBatteryFps,
+        )
+        .row(
+            OptionRow::choice(
+                "Gamepad B quits",
+                ["When held", "Ask first", "Right away"].map(str::to_string).to_vec(),
+                options.pad_back_quit as usize,
+            ),
+            MenuAction::CyclePadBackQuit,
+        )
+        .row(
+            choice(
+                "Animation speed",
+                anim::SPEEDS.iter().map(|speed| format!("{}x", speed)).collect(),
+                anim::SPEEDS.iter().position(|&s| s == options.transition_speed),
+            ),
+            MenuAction::CycleTransitionSpeed,
+        )
+        .row(
+            OptionRow::choice("Animation easing", anim::EASINGS.map(str::to_string).to_vec(), options.easing as usize),
+            MenuAction::CycleEasing,
+        )
+        .item(
+            OptionRow::button(&format!(
+                "Audio latency: {}",
+                AUDIO_LATENCY_PRESETS[(options.audio_latency as usize).min(AUDIO_LATENCY_PRESETS.len() - 1)].0
+            )),
+            MenuAction::OpenAudioLatency,
+        )
+        .item(OptionRow::button("Browse SMW Central hacks"), MenuAction::OpenHackBrowser)
+        .item(OptionRow::button("Patch manager"), MenuAction::OpenPatchManager)
+        .item(OptionRow::button("Restore backups"), MenuAction::OpenBackups)
+        .item(OptionRow::button("Maintenance"), MenuAction::OpenMaintenance)
+        .item(OptionRow::button("Launcher keys"), MenuAction::OpenLauncherKeys)
+        .item(
+            OptionRow::button(&match status::SUBSYSTEMS.iter().filter(|s| launcher.subsystem_problem(**s).is_some()).count() {
+                0 => "System status".to_string(),
+                problems => format!("System status ({} problem(s))", problems),
+            }),
+            MenuAction::OpenSystemStatus,
+        )
+        .row(
+            choice("Daily playtime limit", playtime_limits, playtime::LIMITS.iter().position(|&m| m == options.playtime_limit_mins)),
+            move |step| {
+                if needs_pin {
+                    MenuAction::EnterPin(Box::new(MenuAction::CyclePlaytimeLimit(step)))
+                } else {
+                    MenuAction::CyclePlaytimeLimit(step)
+                }
+            },
+        )
+        .row(choice("Age limit", age_limits, rating::LIMITS.iter().position(|&a| a == max_age)), move |step| {
+            if rating::loosens(max_age, step_choice(&rating::LIMITS, &max_age, step)) && needs_pin {
+                MenuAction::EnterPin(Box::new(MenuAction::CycleAgeLimit(step)))
+            } else {
+                MenuAction::CycleAgeLimit(step)
+            }
+        })
+        .row(
+            choice(
+                "Reuse downloads for",
+                cache::TTL_HOURS.iter().map(|hours| format!("{}h", hours)).collect(),
+                cache::TTL_HOURS.iter().position(|&h| h == options.cache_ttl_hours),
+            ),
+            MenuAction::CycleCacheTtl,
+        )
+        .item(
+            OptionRow::button(&format!(
+                "Clear download cache ({:.1} MB)",
+                cache::size(&launcher.launcher_dir) as f64 / 1_000_000.0
+            )),
+            MenuAction::ClearCache,
+        );
+    if compat::can_relocate_saves(&launcher.game_executable()) {
+        // Labelled by where the saves are, which older installs may still have in the game folder
+        let game_saves = launcher.game_dir().join("saves");
+        let (label, next) = if launcher.saves_dir == game_saves {
+            ("Game folder".to_string(), None)
+        } else if launcher.saves_dir == launcher.launcher_dir.join("saves") {
+            ("Launcher data".to_string(), Some(game_saves))
+        } else {
+            (streamer::path(&launcher.saves_dir), None)
+        };
+        page = page
+            .item(OptionRow::button(&format!("Saves folder: {}", label)), MenuAction::SetSavesDir(next))
+            .item(OptionRow::button("Keep saves in folder path in clipboard"), MenuAction::SavesDirFromClipboard);
+    }
+    page = page
+        .item(OptionRow::button("Updates"), MenuAction::OpenUpdates)
+        .item(OptionRow::button("Build smw from source"), MenuAction::BuildFromSource)
+        .row(
+            choice("Colors", palettes, theme::PALETTES.iter().position(|p| p.name == options.palette).map(|i| i + 1)),
+            MenuAction::CyclePalette,
+        )
+        .item(OptionRow::button("Theme editor"), MenuAction::OpenThemeEditor)
+        .item(OptionRow::button("Corner widgets"), MenuAction::OpenWidgets)
+        .item(OptionRow::button("Statistics"), MenuAction::OpenStats)
+        .item(OptionRow::button("Export statistics"), MenuAction::ExportStats)
+        .item(OptionRow::button("Export library for EmulationStation/Pegasus"), MenuAction::ExportFrontends)
+        .item(OptionRow::button("Trophies"), MenuAction::OpenTrophies)
+        .item(OptionRow::button("Install theme from URL in clipboard"), MenuAction::InstallThemeFromClipboard)
+        .item(OptionRow::button("Import cover pack from path in clipboard"), MenuAction::ImportCoverPack)
+        .item(OptionRow::button("Import old launcher settings from path in clipboard"), MenuAction::ImportOldSettings);
+    if !launcher.plugins.is_empty() {
+        page = page.item(OptionRow::button("Plugins"), MenuAction::OpenPluginSettings);
+    }
+    if let Some(item) = launcher.current_news() {
+        page = page.item(OptionRow::button("Dismiss news banner"), MenuAction::DismissNews(item.id.clone()));
+    }
+    page
+}
+
+/// Shows `page` on the options screen already open under the same title, keeping its selection,
+/// or opens it.
+fn show_options(screen: &mut Option<Box<dyn View + '_>>, page: OptionsScreen) {
+    if let Some(open) = screen.as_mut().and_then(|view| view.options()).filter(|open| open.title() == page.title()) {
+        open.refresh(page);
+        return;
+    }
+    *screen = Some(Box::new(page));
+}
+
+/// The choice `step` places away from `current`, wrapping around; one that isn't among `choices`
+/// goes to the first.
+fn step_choice<T: PartialEq + Clone>(choices: &[T], current: &T, step: i32) -> T {
+    match choices.iter().position(|c| c == current) {
+        Some(idx) => choices[(idx as i32 + step).rem_euclid(choices.len() as i32) as usize].clone(),
+        None => choices[0].clone(),
+    }
+}
+
+/// Opened with Start or Guide; everything in it is reachable with the D-pad and A/B.
+fn quick_menu() -> Menu {
+    Menu::new("Menu")
+        .item("Resume", MenuAction::Dismiss)
+        .item("Options", MenuAction::OpenLauncherOptions)
+        .item("Controller setup", MenuAction::OpenControllerSetup)
+        .item("Quit", MenuAction::Quit)
+}
+
+fn quit_confirm_menu() -> Menu {
+    Menu::new("Quit the launcher?")
+        .item("Cancel", MenuAction::Dismiss)
+        .item("Quit", MenuAction::Quit)
+}
+
+fn controller_menu(launcher: &Launcher) -> Menu {
+    let mut menu = Menu::new("Controller setup");
+    let mut style = launcher.pad_style;
+    match &launcher.gamepad_system {
+        Some(gilrs) => {
+            let mut connected = 0;
+            for (_, gamepad) in gilrs.gamepads() {
+                let pad_style = glyphs::detect(&gamepad);
+                menu = menu.line(format!("Connected: {} ({:?} layout)", gamepad.name(), pad_style));
+                style.get_or_insert(pad_style);
+                connected += 1;
+            }
+            if connected == 0 {
+                menu = menu.line("No controller connected");
+            }
+        }
+        None => menu = menu.line("Controller support is unavailable"),
+    }
+    let button = |b| glyphs::name(style.unwrap_or(glyphs::PadStyle::Generic), b);
+    menu.line(format!(
+        "D-pad: move    {}: select    {}: back",
+        button(glyphs::PadButton::South),
+        button(glyphs::PadButton::East)
+    ))
+    .line(format!("{}: game menu    LT/RT: switch tab", button(glyphs::PadButton::Select)))
+    .line(format!(
+        "{}: quick settings    {}/Guide: this menu",
+        button(glyphs::PadButton::North),
+        button(glyphs::PadButton::Start)
+    ))
+    .item("Back", MenuAction::Dismiss)
+}
+
+fn widgets_menu(options: &widgets::WidgetOptions) -> Menu {
+    let mut menu = Menu::new("Corner widgets");
+    for (widget, name) in [
+        (widgets::Widget::Clock, "Clock"),
+        (widgets::Widget::PlaytimeToday, "Playtime today"),
+        (widgets::Widget::Battery, "Battery"),
+    ] {
+        menu = menu.item(&format!("{}: {}", name, on_off(options.enabled(widget))), MenuAction::ToggleWidget(widget));
+    }
+    menu.item(&format!("Corner: {}", options.corner.label()), MenuAction::CycleWidgetCorner)
+        .item("Close", MenuAction::Dismiss)
+}
+
+fn plugin_settings_menu(launcher: &Launcher) -> Menu {
+    let settings = launcher.plugins.settings();
+    let mut menu = Menu::new("Plugins");
+    if settings.is_empty() {
+        menu = menu.line("No plugin settings");
+    }
+    for (idx, plugin, entry) in settings {
+        menu = menu.item(
+            &format!("{}: {}", plugin, entry.label),
+            MenuAction::PluginSetting(idx, entry.id.clone()),
+        );
+    }
+    menu.item("Close", MenuAction::Dismiss)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum GamepadAction {
+    Confirm,
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+    Start,
+    QuickSettings,
+    ContextMenu,
+    PrevTab,
+    NextTab,
+    PrevPage,
+    NextPage,
+    Details,
+    Released(NavDirection),
+    BackReleased,
+    Reconnected,
+    None,
+}
+
+struct UIButton {
+    rect: Rect,
+    label: String,
+}
+
+impl UIButton {
+    fn new(x: i32, y: i32, width: u32, height: u32, label: &str) -> Self {
+        UIButton {
+            rect: Rect::new(x, y, width, height),
+            label: label.to_string(),
+        }
+    }
+    
+    fn is_hovered(&self, mouse_x: i32, mouse_y: i32) -> bool {
+        self.rect.contains_point((mouse_x, mouse_y))
+    }
+    
+    /// `hover` is the pointer position, None while it's outside the window.
+    fn draw(&self, canvas: &mut Canvas<Window>, ui_atlas: &atlas::Atlas, hover: Option<(i32, i32)>, pressed: bool, focused: bool) -> Result<(), String> {
+        let hovered = hover.map_or(false, |(x, y)| self.is_hovered(x, y));
+        let state = if pressed && hovered {
+            atlas::BUTTON_PRESSED
+        } else if focused {
+            atlas::BUTTON_FOCUSED
+        } else if hovered {
+            atlas::BUTTON_HOVER
+        } else {
+            atlas::BUTTON
+        };
+        if ui_atlas.draw(canvas, state, self.rect)? {
+            return Ok(());
+        }
+        
+        // Without an image for this state the plain button image stands in, marked like the drawn look
+        let image = ui_atlas.draw(canvas, atlas::BUTTON, self.rect)?;
+        if !image {
+            let color = if pressed && hovered {
+                ui::accent_color()
+            } else if focused || hovered {
+                ui::outline_color()
+            } else {
+                ui::highlight_color()
+            };
+            canvas.set_draw_color(color);
+            canvas.fill_rect(self.rect)?;
+        }
+        
+        if focused {
+            // Keyboard/gamepad focus gets the selector look so it reads the same as a selected card
+            canvas.set_draw_color(ui::accent_color());
+            for i in 0..3 {
+                canvas.draw_rect(Rect::new(
+                    self.rect.x() - i,
+                    self.rect.y() - i,
+                    self.rect.width() + (i * 2) as u32,
+                    self.rect.height() + (i * 2) as u32,
+                ))?;
+            }
+        } else if !image {
+            canvas.set_draw_color(Color::RGB(50, 50, 50));
+            canvas.draw_rect(self.rect)?;
+        }
+        Ok(())
+    }
+
+    fn draw_with_text<'a>(
+        &self,
+        canvas: &mut Canvas<Window>,
+        font: &Font,
+        ui_atlas: &atlas::Atlas,
+        hover: Option<(i32, i32)>,
+        pressed: bool,
+        focused: bool,
+        texture_creator: &'a TextureCreator<WindowContext>,
+    ) -> Result<(), String> {
+        self.draw(canvas, ui_atlas, hover, pressed, focused)?;
+        
+        let (width, height) = font.size_of(&self.label)?;
+        let x = self.rect.x() + (self.rect.width() as i32 - width as i32) / 2;
+        let y = self.rect.y() + (self.rect.height() as i32 - height as i32) / 2;
+        ui::draw_text(canvas, texture_creator, font, &self.label, x, y, ui::text_color())?;
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== SMAS Launcher (Rust) - Grayscale Selection ===");
+    println!("Initializing...");
+    let mut timer = startup::StartupTimer::start();
+    
+    // `smas_launcher install [--dir <path>] [--server <url>] [--no-shortcut]` sets up an installation and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("install") {
+        let options = install::parse_args(&args[2..])?;
+        let install_dir = match options.dir.clone() {
+            Some(dir) => dir,
+            None => Launcher::get_install_dir()?,
+        };
+        install::run(&install_dir, options.server.as_deref(), options.shortcut)?;
+        return Ok(());
+    }
+    
+    // `smas_launcher doctor` checks the installation, prints a report for bug reports and exits
+    if args.get(1).map(String::as_str) == Some("doctor") {
+        let install_dir = Launcher::get_install_dir()?;
+        let launcher_dir = install_dir.join("launcher");
+        let options = Launcher::load_launcher_options(&launcher_dir).map_err(|e| e.to_string());
+        let setup = doctor::Setup {
+            game_executable: Launcher::locate_game_executable(
+                options.as_ref().unwrap_or(&LauncherOptions::default()),
+                &install_dir,
+                platform::detect_sandbox(),
+            ),
+            config: options.map(|_| config::locate(&launcher_dir).0),
+            install_dir,
+        };
+        let report = doctor::run(&setup);
+        let text = report.text(&setup);
+        println!("{}", text);
+        match doctor::save(&launcher_dir, &text) {
+            Ok(path) => println!("Report saved to {}", streamer::path(&path)),
+            Err(e) => eprintln!("Failed to save the report: {}", e),
+        }
+        std::process::exit(if report.failed() { 1 } else { 0 });
+    }
+    
+    // `--no-audio`, `--no-gamepad` and `--safe-mode` skip what may be crashing the launcher
+    let flags = status::StartupFlags::parse(&args);
+    
+    // `--dry-run-launch <rom>` prints what launching a ROM from sfcs/ would do and exits
+    if let Some(i) = args.iter().position(|a| a == "--dry-run-launch") {
+        let rom = args.get(i + 1).ok_or("--dry-run-launch needs a ROM file name")?;
+        // A path works too, only its file name is looked up in sfcs/
+        let rom = Path::new(rom).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| rom.clone());
+        let launcher = Launcher::load(Launcher::get_install_dir()?, status::StartupFlags { safe_mode: true, ..flags })?;
+        launcher.dry_run_launch(&rom);
+        return Ok(());
+    }
+    
+    // `smas_launcher import-settings <old install>` takes over an older launcher's settings and exits
+    if args.get(1).map(String::as_str) == Some("import-settings") {
+        let old_dir = args.get(2).ok_or("import-settings needs the old launcher's folder")?;
+        // Only the options file is touched, nothing a full Launcher would start or move
+        let launcher_dir = Launcher::get_install_dir()?.join("launcher");
+        let current = Launcher::load_launcher_options(&launcher_dir)
+            .map_err(|e| format!("the options file has errors, fix or reset it first: {}", e))?;
+        let (_, report) = Launcher::import_settings_into(&launcher_dir, &current, Path::new(old_dir))?;
+        for line in report {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    
+    // `smas_launcher export [<dir>]` writes gamelist.xml and metadata.pegasus.txt for other front-ends and exits
+    if args.get(1).map(String::as_str) == Some("export") {
+        let launcher = Launcher::load(Launcher::get_install_dir()?, status::StartupFlags { safe_mode: true, ..flags })?;
+        let dir = args.get(2).map(PathBuf::from).unwrap_or_else(|| launcher.launcher_dir.join("exports"));
+        for path in launcher.export_frontends(&dir)? {
+            println!("Wrote {}", streamer::path(&path));
+        }
+        return Ok(());
+    }
+    let mut launcher = Launcher::new(flags)?;
+    timer.stage("loading settings");
+    
+    println!("Install directory: {}", streamer::path(&launcher.install_dir));
+    println!("SFC directory: {}", streamer::path(&launcher.sfc_dir));
+    println!("Launcher directory: {}", streamer::path(&launcher.launcher_dir));
+    
+    // The weekly backup runs in the background so a large saves folder doesn't delay startup
+    let (archive_dir, data_dirs) = (launcher.archive_dir(), launcher.data_dirs());
+    std::thread::spawn(move || match archive::auto_backup(&archive_dir, &data_dirs) {
+        Ok(Some(path)) => println!("Weekly backup saved to {}", streamer::path(&path)),
+        Ok(None) => {}
+        Err(e) => eprintln!("Weekly backup failed: {}", e),
+    });
+    
+    let sdl_context = sdl2::init()?;
+    let video_subsystem = sdl_context.video()?;
+    // JPEG and WebP are only needed for covers, so a build without them still starts
+    let _image_context = match sdl2::image::init(InitFlag::PNG | InitFlag::JPG | InitFlag::WEBP) {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("JPEG/WebP cover support unavailable: {}", e);
+            sdl2::image::init(InitFlag::PNG)?
+        }
+    };
+    let ttf_context = match ttf::init() {
+        Ok(context) => Some(context),
+        Err(e) => {
+            eprintln!("Failed to initialize SDL_ttf: {}, using the built-in font", e);
+            launcher.status.set(Subsystem::Font, Some(format!("SDL_ttf failed to start ({}), using the bitmap font", e)));
+            None
+        }
+    };
+    timer.stage("starting SDL");
+    
+    let audio_latency = launcher.launcher_options.audio_latency;
+    let mut audio = if flags.no_audio {
+        launcher.status.set(Subsystem::Audio, Some("Off (started with --no-audio)".to_string()));
+        None
+    } else {
+        open_audio(&mut launcher, audio_latency, None)
+    };
+    timer.stage("opening audio");
+    
+    let (window_width, window_height) = if launcher.handheld {
+        HANDHELD_SIZE
+    } else {
+        (SCREEN_WIDTH, SCREEN_HEIGHT)
+    };
+    let mut window_builder = video_subsystem.window("SMAS Launcher", window_width, window_height);
+    window_builder.position_centered();
+    if launcher.handheld {
+        window_builder.fullscreen_desktop();
+    }
+    let window = window_builder.build()?;
+    
+    let mut refresh_rate = display_refresh_rate(&video_subsystem, &window);
+    println!("Display refresh rate: {}Hz", refresh_rate);
+    
+    let mut target_frame_time = Duration::from_secs_f64(1.0 / refresh_rate as f64);
+    let mut refresh_checked = Instant::now();
+    let mut refresh_due = false;
+    let mut ini_checked = Instant::now();
+    
+    // A vsync'd present blocks until the next refresh, so input read before it waits on screen that long
+    let mut canvas_builder = window.into_canvas().accelerated();
+    if !launcher.launcher_options.low_latency {
+        canvas_builder = canvas_builder.present_vsync();
+    }
+    let mut canvas = canvas_builder.build()?;
+    
+    // The layout is authored for the desktop window size; let SDL scale it on other screens
+    if launcher.handheld {
+        canvas.set_logical_size(SCREEN_WIDTH, SCREEN_HEIGHT)?;
+    }
+    timer.stage("creating the window");
+    
+    let texture_creator = canvas.texture_creator();
+    
+    sdl_context.mouse().show_cursor(false);
+    
+    let mut ui_atlas = load_atlas(&texture_creator, &launcher);
+    let mut bg_texture = load_background(&texture_creator, &mut launcher);
+    let mut visualizer = visualizer::Visualizer::new();
+    let mut battery = widgets::BatteryMonitor::new();
+    let mut battery_saving = false; // on battery below the battery_saver threshold
+    let mut toasts = ui::Toasts::new();
+    
+    let mut font = load_font(ttf_context.as_ref(), &mut launcher);
+    if status::SUBSYSTEMS.iter().any(|s| launcher.subsystem_problem(*s).is_some()) {
+        toasts.push("Some features are unavailable, see Options > System status".to_string());
+    }
+    timer.stage("loading textures");
+    
+    let mut event_pump = sdl_context.event_pump()?;
+    let mouse_pressed = false;
+    
+    let all_games = launcher.scan_sfc_files();
+    launcher.note_scan(&all_games);
+    launcher.plugins.on_scan(&all_games);
+    let mut sfcs = launcher.visible_games();
+    launcher.restore_selection(&sfcs);
+    timer.stage("scanning ROMs");
+    
+    if sfcs.is_empty() {
+        println!("\nWARNING: No SFC files found!");
+        println!("Please add .sfc ROM files to: {}", streamer::path(&launcher.sfc_dir));
+    } else {
+        println!("\nFound {} game(s):", sfcs.len());
+        for (idx, sfc) in sfcs.iter().enumerate() {
+            println!("  {}. {}", idx + 1, sfc);
+        }
+        let grouped: usize = launcher.variants.values().map(|v| v.len() - 1).sum::<usize>()
+            + launcher.bundles.values().map(|b| b.games.len() - 1).sum::<usize>();
+        let hidden_count = all_games.len() - sfcs.len() - grouped;
+        if hidden_count > 0 {
+            println!("  ({} hidden)", hidden_count);
+        }
+    }
+    
+    let mut covers = textures::TextureStore::new(&texture_creator, &launcher.launcher_dir.join("pngs"));
+    let mut badge_renderer = badges::BadgeRenderer::new(&texture_creator);
+    let mut crt_filter = crt::Crt::new(&texture_creator);
+    let mut textures_lost = false; // the renderer lost its device, everything loaded through texture_creator is reloaded
+    let mut render_failures: u32 = 0;
+    let mut verifier = verify::Verifier::new();
+    timer.stage("preparing covers");
+    
+    let options_btn = UIButton::new(
+        (SCREEN_WIDTH / 2 - 75) as i32,
+        593,
+        150,
+        40,
+        "Options"
+    );
+    
+    let launcher_opts_btn = UIButton::new(
+        (SCREEN_WIDTH / 4 * 3 - 75) as i32,
+        593,
+        150,
+        40,
+        "Launcher"
+    );
+    
+    let update_btn = UIButton::new(
+        (SCREEN_WIDTH / 4 - 65) as i32,
+        593,
+        130,
+        40,
+        "Update"
+    );
+    
+    // Left to right, the order Left/Right walks them in
+    let buttons = [
+        (update_btn, MenuAction::OpenUpdates),
+        (options_btn, MenuAction::OpenQuickSettings),
+        (launcher_opts_btn, MenuAction::OpenLauncherOptions),
+    ];
+    
+    println!("Startup finished in {}", startup::seconds(timer.total()));
+    if let Err(e) = timer.save(&launcher.launcher_dir) {
+        eprintln!("Failed to write the startup log: {}", e);
+    }
+    let slow = timer.total() > startup::SLOW_STARTUP;
+    if slow != launcher.launcher_options.slow_startup_warned {
+        if let Some((stage, took)) = timer.slowest().filter(|_| slow) {
+            let total = startup::seconds(timer.total());
+            toasts.push(format!("Startup took {}, mostly {} ({}). Details in logs/startup.log", total, stage, startup::seconds(took)));
+        }
+        launcher.launcher_options.slow_startup_warned = slow;
+        if let Err(e) = launcher.save_launcher_options() {
+            eprintln!("Failed to save launcher options: {}", e);
+        }
+    }
+    
+    println!("\nLauncher ready with grayscale selection!");
+    println!("Controls:");
+    println!("  - Click game box to launch");
+    println!("  - Arrow keys or gamepad D-Pad to navigate");
+    println!("  - Enter or gamepad A/X to launch");
+    println!("  - ESC or gamepad B/Circle to quit");
+    println!("  - Gamepad Start/Guide for the quick menu");
+    
+    let mut should_launch: Option<usize> = None;
+    let mut window_focused = true;
+    let mut window_minimized = false;
+    let mut music_paused_by_focus = false;
+    let mut menu: Option<Menu> = launcher.config_error.as_deref().map(config_error_menu);
+    let mut screen: Option<Box<dyn View + '_>> = None; // the manual keeps textures made by texture_creator
+    let mut quick: Option<QuickSettings> = None;
+    let mut audio_latency_trying = launcher.launcher_options.audio_latency; // preset the mixer runs with right now
+    let mut released_music: Option<Duration> = None; // where the music was when the device was closed for a game
+    let mut shader_previews: HashMap<String, Option<Texture>> = HashMap::new(); // shader -> its screenshot, loaded when first shown
+    let mut launch_unchecked = false;
+    let mut safe_step: Option<usize> = None; // the next launch tries SAFE_STEPS up to here
+    let mut session_overrides: Option<session::Overrides> = None; // set for the next launch by "Launch with options"
+    let mut launch_chosen = false; // the ROM to launch was picked, so a bundle card doesn't ask again
+    let mut touch: Option<TouchState> = None;
+    let mut drag: Option<CardDrag> = None;
+    // Input picked up while waiting idle, handled at the start of the next frame
+    let mut pending_event: Option<Event> = None;
+    let mut pending_actions: Vec<GamepadAction> = Vec::new();
+    let mut bump_played: Option<Instant> = None;
+    let mut nav_played: Option<Instant> = None;
+    let mut return_fade: Option<Instant> = None;
+    let mut back_hold: Option<Instant> = None; // B went down on the grid, quits once held for BACK_HOLD
+    let mut flip: Option<details::CardFlip> = None; // selected card turned over to its details
+    let mut launching: Option<Launching> = None;
+    let mut selected_since: (usize, Instant) = (usize::MAX, Instant::now()); // drives the title marquee and animated covers
+    let mut backdrop: (Option<usize>, Option<usize>, Instant) = (None, None, Instant::now()); // blurred cover shown, the one fading out, since when
+    
+    let (theme_tx, theme_rx) = std::sync::mpsc::channel::<Result<String, String>>();
+    
+    let control = match control::ControlServer::start(&launcher.launcher_dir) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            eprintln!("Control API unavailable: {}", e);
+            None
+        }
+    };
+    if let (Some(port), Some(server), false) = (launcher.launcher_options.remote_port, &control, flags.safe_mode) {
+        match remote::RemoteServer::start(port, server.sender()) {
+            Ok(remote) => launcher.remote = Some(remote),
+            Err(e) => eprintln!("Remote control page unavailable: {}", e),
+        }
+    }
+    
+    // Console-shell mode: launch the last game unless any input arrives during the countdown
+    let mut boot: Option<(String, Instant)> = match launcher.last_played() {
+        Some(session) if launcher.launcher_options.startup == 1 => {
+            println!("Booting {} in {}s, press any key to cancel", session.rom, BOOT_DELAY.as_secs());
+            Some((session.rom.clone(), Instant::now() + BOOT_DELAY))
+        }
+        _ => None,
+    };
+    
+    // Hidden testing aids: `--record <file>` saves all input, `--replay <file>` plays it back and quits
+    let flag_path = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(PathBuf::from);
+    let mut recorder = flag_path("--record").and_then(|path| {
+        replay::Recorder::create(&path)
+            .map_err(|e| eprintln!("Failed to start recording to {}: {}", path.display(), e))
+            .ok()
+    });
+    let mut player = match flag_path("--replay") {
+        Some(path) => Some(replay::Player::load(&path).map_err(|e| format!("Failed to load replay {}: {}", path.display(), e))?),
+        None => None,
+    };
+    
+    'running: loop {
+        let frame_start = std::time::Instant::now();
+        let mut menu_choice: Option<MenuAction> = None;
+        let mut take_screenshot = false;
+        let mut card_animating = false;
+        
+        if let Ok(result) = theme_rx.try_recv() {
+            let message = match &result {
+                Ok(name) => format!("Theme {} installed", name),
+                Err(e) => format!("Theme install failed: {}", e),
+            };
+            notify_if_away(&launcher, canvas.window(), &message);
+            menu = Some(theme_installed_menu(&result));
+        }
+        
+        for request in control.iter().flat_map(|server| server.pending()) {
+            let (reply, action) = control_command(&mut launcher, &mut sfcs, canvas.window_mut(), &request.command);
+            if action.is_some() {
+                menu_choice = action;
+            }
+            request.respond(reply);
+        }
+        
+        if let Some((game, status)) = launcher.poll_game_exit() {
+            launcher.run_post_exit_hook(&game.rom, status);
+            launcher.plugins.on_exit(&game.rom, status.code());
+            for name in launcher.check_achievements() {
+                println!("Achievement unlocked: {}", name);
+                toasts.push(format!("Achievement unlocked: {}", name));
+            }
+            let trial = launcher.safe_trial.take().map(|(_, step)| step);
+            if !status.success() {
+                eprintln!("Game crashed: {} ({})", game.rom, status);
+                menu = Some(match launcher.watchdog_step(&game, status, trial) {
+                    Some(step) => watchdog_menu(&game, status, step),
+                    None => crash_report_menu(&game, status),
+                });
+            }
+            // The game got past startup this time, so these settings are what it needs
+            if let Some(step) = trial.filter(|_| launcher.watchdog_step(&game, status, None).is_none()) {
+                let settings = launcher.keep_safe_settings(&game.rom, step);
+                println!("Keeping safe settings for {}: {}", game.rom, settings);
+                toasts.push(format!("Kept safe settings: {}", settings));
+            }
+            
+            // Back where the player left off: same game selected, music picking up again
+            launcher.reload_after_game();
+            if let Some(idx) = launcher.card_index(&sfcs, &game.rom) {
+                launcher.selected_game = idx;
+                launcher.continue_focused = false;
+            }
+            if let Some(position) = released_music.take() {
+                audio = open_audio(&mut launcher, audio_latency_trying, Some(position));
+                visualizer::set_enabled(launcher.theme.bgtype == theme::BG_VISUALIZER);
+            }
+            return_fade = Some(Instant::now());
+        }
+        
+        if launcher.game_process.is_some() && !launcher.playtime_warned {
+            if let Some(left) = launcher.playtime_left().filter(|&left| left <= playtime::WARN_SECS) {
+                launcher.playtime_warned = true;
+                let body = format!("{} of playtime left today", stats::format_duration(left));
+                if let Err(e) = platform::notify("Playtime almost up", &body) {
+                    eprintln!("Failed to show the playtime notice: {}", e);
+                }
+            }
+        }
+        
+        if let Some(warning) = launcher.poll_pad_battery() {
+            toasts.push(warning);
+        }
+        // Only while nothing else asks for attention, the game may be rewriting it while it runs
+        if launcher.game_process.is_none() && menu.is_none() && screen.is_none() && ini_checked.elapsed() >= INI_CHECK {
+            ini_checked = Instant::now();
+            if launcher.reconcile_game_ini() {
+                menu = Some(ini_conflict_menu(&launcher));
+            }
+        }
+        launcher.plugins.poll();
+        launcher.news.poll();
+        if let Some(message) = launcher.source_build.as_ref().and_then(|b| b.take_outcome()) {
+            notify_if_away(&launcher, canvas.window(), &message);
+            toasts.push(message);
+        }
+        if launcher.poll_hashing() {
+            launcher.refresh_visible(&mut sfcs);
+        }
+        
+        if let Some((rom, at)) = &boot {
+            if Instant::now() >= *at {
+                menu_choice = Some(MenuAction::Launch(rom.clone()));
+                boot = None;
+            }
+        }
+        
+        let mut view_actions: Vec<ViewAction> = Vec::new();
+        if let Some(view) = screen.as_mut() {
+            view_actions.push(view.update());
+        }
+        
+        let mut actions = std::mem::take(&mut pending_actions);
+        actions.extend(launcher.handle_gamepad_input());
+        let mut replayed_events = Vec::new();
+        if let Some(p) = player.as_mut() {
+            if p.finished() {
+                println!("Replay finished");
+                break 'running;
+            }
+            // Live input would make the run differ from the recording
+            let (events, replayed_actions) = p.due();
+            actions = replayed_actions;
+            replayed_events = events;
+        }
+        if let Some(r) = recorder.as_mut() {
+            r.next_frame();
+            for action in actions.iter().filter(|a| !matches!(a, GamepadAction::None)) {
+                r.record_action(action);
+            }
+        }
+        for action in actions {
+            if let GamepadAction::Released(direction) = action {
+                launcher.nav_repeat.release(direction);
+                continue;
+            }
+            if let GamepadAction::BackReleased = action {
+                back_hold = None;
+                continue;
+            }
+            if let GamepadAction::Reconnected = action {
+                toasts.push("Controller reconnected".to_string());
+                continue;
+            }
+            if boot.take().is_some() {
+                println!("Boot cancelled");
+                continue;
+            }
+            
+            if let Some(q) = quick.as_mut().filter(|q| q.is_open()) {
+                if let QuickAction::Change(setting, value) = q.handle_gamepad(&action) {
+                    apply_quick_setting(&mut launcher, audio.as_ref(), setting, value);
+                    q.refresh(launcher.quick_setting_rows());
+                }
+                continue;
+            }
+            
+            if menu.is_none() && matches!(action, GamepadAction::Start) {
+                menu = Some(quick_menu());
+                continue;
+            }
+            
+            if menu.is_none() {
+                if let Some(view) = screen.as_mut() {
+                    view_actions.push(view.handle_gamepad(&action));
+                    continue;
+                }
+            }
+            
+            if let Some(m) = menu.as_mut() {
+                match action {
+                    GamepadAction::Up => m.move_selection(-1),
+                    GamepadAction::Down => m.move_selection(1),
+                    GamepadAction::Confirm => menu_choice = m.selected_action(),
+                    GamepadAction::Back | GamepadAction::Start => menu_choice = Some(MenuAction::Dismiss),
+                    _ => {}
+                }
+            } else if let Some(f) = flip.as_mut().filter(|f| f.is_open()) {
+                // A rescan can take the card away while it's flipped
+                let Some(actions) = sfcs.get(f.card).map(|rom| card_details(&launcher, rom).actions) else {
+                    flip = None;
+                    continue;
+                };
+                match action {
+                    GamepadAction::Up => f.move_selection(-1, actions.len()),
+                    GamepadAction::Down => f.move_selection(1, actions.len()),
+                    GamepadAction::Confirm => {
+                        if let Some((_, chosen)) = actions.get(f.selected).cloned() {
+                            if !matches!(chosen, MenuAction::SetFavorite(..)) {
+                                f.close();
+                            }
+                            menu_choice = Some(chosen);
+                        }
+                    }
+                    GamepadAction::Back | GamepadAction::Details => f.close(),
+                    _ => {}
+                }
+            } else if let Some(focused) = launcher.focused_button {
+                match action {
+                    GamepadAction::Confirm => menu_choice = Some(buttons[focused].1.clone()),
+                    GamepadAction::Left => launcher.focused_button = Some(focused.saturating_sub(1)),
+                    GamepadAction::Right => launcher.focused_button = Some((focused + 1).min(buttons.len() - 1)),
+                    GamepadAction::Up | GamepadAction::Back => launcher.focused_button = None,
+                    _ => {}
+                }
+            } else {
+                match action {
+                    GamepadAction::Confirm if launcher.continue_focused => {
+                        menu_choice = launcher.last_played().map(|s| MenuAction::Launch(s.rom.clone()));
+                    }
+                    GamepadAction::Confirm => {
+                        if !sfcs.is_empty() {
+                            should_launch = Some(launcher.selected_game);
+                        }
+                    }
+                    GamepadAction::Left | GamepadAction::Right | GamepadAction::Down if launcher.continue_focused => {
+                        launcher.continue_focused = false;
+                    }
+                    GamepadAction::Left => {
+                        launcher.move_selection(-1, &sfcs);
+                        launcher.nav_repeat.press(NavDirection::Left);
+                    }
+                    GamepadAction::Right => {
+                        launcher.move_selection(1, &sfcs);
+                        launcher.nav_repeat.press(NavDirection::Right);
+                    }
+                    GamepadAction::Up => {
+                        if let Some(m) = vertical_nav(&mut launcher, &sfcs, -1, buttons.len()) {
+                            menu = Some(m);
+                        }
+                    }
+                    GamepadAction::Down => {
+                        if let Some(m) = vertical_nav(&mut launcher, &sfcs, 1, buttons.len()) {
+                            menu = Some(m);
+                        }
+                    }
+                    GamepadAction::ContextMenu => {
+                        if !sfcs.is_empty() {
+                            menu = Some(game_context_menu(&launcher, &sfcs[launcher.selected_game]));
+                        }
+                    }
+                    GamepadAction::PrevTab => launcher.switch_tab(-1, &mut sfcs),
+                    GamepadAction::NextTab => launcher.switch_tab(1, &mut sfcs),
+                    GamepadAction::PrevPage => launcher.flip_page(-1, sfcs.len()),
+                    GamepadAction::NextPage => launcher.flip_page(1, sfcs.len()),
+                    GamepadAction::QuickSettings => quick = Some(QuickSettings::open(launcher.quick_setting_rows())),
+                    GamepadAction::Details => {
+                        if !sfcs.is_empty() {
+                            flip = Some(details::CardFlip::open(launcher.selected_game, launcher.motion()));
+                        }
+                    }
+                    GamepadAction::Back => match launcher.launcher_options.pad_back_quit {
+                        1 => menu = Some(quit_confirm_menu()),
+                        2 => break 'running,
+                        _ => back_hold = Some(Instant::now()),
+                    },
+                    _ => {}
+                }
+            }
+        }
+        if menu.is_some() || screen.is_some() || quick.is_some() {
+            back_hold = None;
+        }
+        // A flip belongs to the card it was opened on
+        if flip.as_ref().map(|f| f.is_done() || f.card != launcher.selected_game || f.card >= sfcs.len()).unwrap_or(false) {
+            flip = None;
+        }
+        if back_hold.map(|start| start.elapsed() >= BACK_HOLD).unwrap_or(false) {
+            break 'running;
+        }
+        
+        let replaying = player.is_some();
+        let live_events = pending_event.take().into_iter().chain(event_pump.poll_iter());
+        for mut event in live_events.filter(|e| !(replaying && replay::is_user_input(e))).chain(replayed_events) {
+            if let Some(r) = recorder.as_mut() {
+                r.record_event(&event);
+            }
+            if !screen.as_ref().map(|s| s.wants_raw_keys()).unwrap_or(false) {
+                keys::translate(&launcher.launcher_options.launcher_keys, &mut event);
+            }
+            if is_quit_shortcut(&event) {
+                break 'running;
+            }
+            if let Event::KeyDown { keycode: Some(Keycode::F12), repeat: false, .. } = event {
+                take_screenshot = true;
+                continue;
+            }
+            // The disconnect notice takes the first key or click, which only dismisses it
+            if launcher.pad_lost.is_some() && matches!(event, Event::KeyDown { .. } | Event::MouseButtonDown { .. }) {
+                launcher.pad_lost = None;
+                continue;
+            }
+            
+            if let Event::RenderDeviceReset { .. } = event {
+                textures_lost = true;
+                continue;
+            }
+            
+            // Before menus and views take it, so the pointer is current when they close
+            if let Event::MouseMotion { which, x, y, .. } = event {
+                if which != SDL_TOUCH_MOUSEID {
+                    launcher.move_pointer(x, y, window_focused);
+                }
+            }
+            
+            if let Event::Window { win_event, .. } = &event {
+                match win_event {
+                    // The pointer is placed again by the next motion, not where SDL last saw it
+                    WindowEvent::FocusGained => window_focused = true,
+                    WindowEvent::FocusLost => {
+                        window_focused = false;
+                        launcher.release_pointer(&buttons, sfcs.len());
+                        // The button release may go to another window, don't leave a card stuck to the pointer
+                        drag = None;
+                    }
+                    WindowEvent::Leave => launcher.release_pointer(&buttons, sfcs.len()),
+                    WindowEvent::Minimized => window_minimized = true,
+                    WindowEvent::Restored | WindowEvent::Maximized | WindowEvent::Shown => {
+                        window_minimized = false;
+                    }
+                    // Possibly onto another monitor, checked right away
+                    WindowEvent::Moved(..) | WindowEvent::SizeChanged(..) => {
+                        refresh_due = true;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            
+            if let Event::Quit { .. } = event {
+                break 'running;
+            }
+            
+            if let Event::KeyDown { .. } | Event::MouseButtonDown { .. } | Event::FingerDown { .. } = event {
+                launcher.pad_style = None;
+                if boot.take().is_some() {
+                    println!("Boot cancelled");
+                    continue;
+                }
+            }
+            
+            if let Event::DropFile { filename, .. } = &event {
+                if filename.to_lowercase().ends_with(".zip") {
+                    menu = Some(match fs::read(filename) {
+                        Ok(data) if theme::is_pack(&data) => theme_installed_menu(&theme::install_pack(&launcher.launcher_dir, &data)),
+                        Ok(data) => import_cover_pack(&launcher, &mut covers, &data),
+                        Err(e) => theme_installed_menu(&Err(e.to_string())),
+                    });
+                }
+                continue;
+            }
+            
+            if let Some(q) = quick.as_mut().filter(|q| q.is_open()) {
+                if let QuickAction::Change(setting, value) = q.handle_event(&event) {
+                    apply_quick_setting(&mut launcher, audio.as_ref(), setting, value);
+                    q.refresh(launcher.quick_setting_rows());
+                }
+                continue;
+            }
+            
+            if menu.is_none() {
+                if let Some(view) = screen.as_mut() {
+                    view_actions.push(view.handle_event(&event));
+                    continue;
+                }
+            }
+            
+            // An open menu captures all input until it is dismissed
+            if let Some(m) = menu.as_mut() {
+                match event {
+                    Event::Quit { .. } => break 'running,
+                    Event::KeyDown { keycode: Some(Keycode::Up), .. } => m.move_selection(-1),
+                    Event::KeyDown { keycode: Some(Keycode::Down), .. } => m.move_selection(1),
+                    Event::KeyDown { keycode: Some(Keycode::Return), .. } => {
+                        menu_choice = m.selected_action();
+                    }
+                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                        menu_choice = Some(MenuAction::Dismiss);
+                    }
+                    Event::MouseMotion { x, y, .. } => {
+                        if let Some(idx) = m.item_at(x, y) {
+                            m.selected = idx;
+                        }
+                    }
+                    Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. } => {
+                        if let Some(idx) = m.item_at(x, y) {
+                            m.selected = idx;
+                            menu_choice = m.selected_action();
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            
+            // The back of a flipped card takes the keys until it's turned over again
+            if let Some(f) = flip.as_mut().filter(|f| f.is_open()) {
+                if let Event::KeyDown { keycode: Some(key), .. } = event {
+                    let Some(actions) = sfcs.get(f.card).map(|rom| card_details(&launcher, rom).actions) else {
+                        flip = None;
+                        continue;
+                    };
+                    match key {
+                        Keycode::Up => f.move_selection(-1, actions.len()),
+                        Keycode::Down => f.move_selection(1, actions.len()),
+                        Keycode::Return => {
+                            if let Some((_, chosen)) = actions.get(f.selected).cloned() {
+                                if !matches!(chosen, MenuAction::SetFavorite(..)) {
+                                    f.close();
+                                }
+                                menu_choice = Some(chosen);
+                            }
+                        }
+                        Keycode::Escape | Keycode::I => f.close(),
+                        _ => {}
+                    }
+                    continue;
+                }
+            }
+            
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running,
+                Event::KeyDown { keycode: Some(Keycode::V), keymod, .. }
+                    if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD | Mod::LGUIMOD | Mod::RGUIMOD) =>
+                {
+                    menu_choice = Some(MenuAction::InstallThemeFromClipboard);
+                }
+                Event::KeyDown { keycode: Some(Keycode::Tab), .. } => quick = Some(QuickSettings::open(launcher.quick_setting_rows())),
+                // Mouse events synthesized from touch are handled through the Finger events instead
+                Event::MouseButtonDown { which, .. } | Event::MouseMotion { which, .. }
+                    if which == SDL_TOUCH_MOUSEID => {}
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. }
+                    if launcher.current_news().is_some() && news::banner_rect().contains_point((x, y)) =>
+                {
+                    if let Some(id) = launcher.current_news().map(|item| item.id.clone()) {
+                        launcher.dismiss_news(&id);
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. }
+                    if buttons.iter().any(|(b, _)| b.is_hovered(x, y)) =>
+                {
+                    menu_choice = buttons.iter().find(|(b, _)| b.is_hovered(x, y)).map(|(_, action)| action.clone());
+                }
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. }
+                    if launcher.last_played().is_some() && continue_rect().contains_point((x, y)) =>
+                {
+                    menu_choice = launcher.last_played().map(|s| MenuAction::Launch(s.rom.clone()));
+                }
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. }
+                    if y < TAB_BAR_Y + TAB_HEIGHT as i32 =>
+                {
+                    if let Some(tab) = (0..launcher.tab_names().len()).find(|&i| launcher.tab_rect(i).contains_point((x, y))) {
+                        launcher.switch_tab(tab as i32 - launcher.active_tab as i32, &mut sfcs);
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. } => {
+                    // Check if clicked on a game box; launching waits for the release so cards can be dragged
+                    if let Some(idx) = launcher.game_at(x, y, sfcs.len()) {
+                        launcher.selected_game = idx;
+                        drag = Some(CardDrag { from: idx, start: (x, y), dragging: false });
+                    }
+                }
+                Event::MouseButtonUp { mouse_btn: sdl2::mouse::MouseButton::Left, which, x, y, .. }
+                    if which != SDL_TOUCH_MOUSEID =>
+                {
+                    if let Some(d) = drag.take() {
+                        let target = launcher.game_at(x, y, sfcs.len());
+                        if !d.dragging {
+                            if target == Some(d.from) {
+                                should_launch = Some(d.from);
+                            }
+                        } else if let Some(to) = target {
+                            launcher.reorder_game(&mut sfcs, d.from, to);
+                        }
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Right, x, y, .. } => {
+                    if let Some(idx) = launcher.game_at(x, y, sfcs.len()) {
+                        launcher.selected_game = idx;
+                        menu = Some(game_context_menu(&launcher, &sfcs[idx]));
+                    }
+                }
+                Event::FingerDown { finger_id, x, y, .. } => {
+                    if touch.is_none() {
+                        let pos = touch_to_layout(&canvas, x, y);
+                        touch = Some(TouchState {
+                            finger_id,
+                            start: pos,
+                            last: pos,
+                            started: Instant::now(),
+                            long_press_fired: false,
+                        });
+                    }
+                }
+                Event::FingerMotion { finger_id, x, y, .. } => {
+                    if let Some(t) = touch.as_mut().filter(|t| t.finger_id == finger_id) {
+                        t.last = touch_to_layout(&canvas, x, y);
+                    }
+                }
+                Event::FingerUp { finger_id, .. } => {
+                    if let Some(t) = touch.take() {
+                        if t.finger_id != finger_id {
+                            touch = Some(t);
+                        } else if !t.long_press_fired {
+                            let dx = t.last.0 - t.start.0;
+                            let dy = t.last.1 - t.start.1;
+                            if dx.abs() > SWIPE_DISTANCE && dx.abs() > dy.abs() {
+                                // Swipe left for the next page, right for the previous one
+                                launcher.change_page(if dx < 0 { 1 } else { -1 }, sfcs.len());
+                            } else if launcher.last_played().is_some() && continue_rect().contains_point(t.start) {
+                                menu_choice = launcher.last_played().map(|s| MenuAction::Launch(s.rom.clone()));
+                            } else if let Some(idx) = launcher.game_at(t.start.0, t.start.1, sfcs.len()) {
+                                // First tap selects, tapping the selected card launches it
+                                if idx == launcher.selected_game {
+                                    should_launch = Some(idx);
+                                } else {
+                                    launcher.selected_game = idx;
+                                }
+                            }
+                        }
+                    }
+                }
+                Event::MouseMotion { .. } => {
+                    let (x, y) = (launcher.mouse_x, launcher.mouse_y);
+                    if let Some(d) = drag.as_mut() {
+                        if (x - d.start.0).abs() > DRAG_THRESHOLD || (y - d.start.1).abs() > DRAG_THRESHOLD {
+                            d.dragging = true;
+                        }
+                    }
+                    // The handheld preset is navigated with the gamepad only
+                    if !launcher.handheld {
+                        launcher.continue_focused = launcher.last_played().is_some() && continue_rect().contains_point((x, y));
+                        launcher.focused_button = buttons.iter().position(|(b, _)| b.is_hovered(x, y));
+                        launcher.update_selection_from_mouse(&sfcs);
+                    }
+                }
+                // Shift+Left/Right moves the selected card itself
+                Event::KeyDown { keycode: Some(keycode @ (Keycode::Left | Keycode::Right)), keymod, .. }
+                    if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) =>
+                {
+                    let from = launcher.selected_game;
+                    let to = if keycode == Keycode::Left { from.checked_sub(1) } else { Some(from + 1) };
+                    if let Some(to) = to {
+                        launcher.reorder_game(&mut sfcs, from, to);
+                    }
+                }
+                Event::KeyDown { keycode: Some(Keycode::Left | Keycode::Right | Keycode::Down), .. }
+                    if launcher.continue_focused =>
+                {
+                    launcher.continue_focused = false;
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode @ (Keycode::Return | Keycode::Left | Keycode::Right | Keycode::Up | Keycode::Down | Keycode::Escape)),
+                    ..
+                } if launcher.focused_button.is_some() => {
+                    let focused = launcher.focused_button.unwrap_or(0);
+                    match keycode {
+                        Keycode::Return => menu_choice = Some(buttons[focused].1.clone()),
+                        Keycode::Left => launcher.focused_button = Some(focused.saturating_sub(1)),
+                        Keycode::Right => launcher.focused_button = Some((focused + 1).min(buttons.len() - 1)),
+                        Keycode::Up | Keycode::Escape => launcher.focused_button = None,
+                        _ => {}
+                    }
+                }
+                Event::KeyDown { keycode: Some(keycode @ (Keycode::Up | Keycode::Down)), .. } => {
+                    let delta = if keycode == Keycode::Up { -1 } else { 1 };
+                    if let Some(m) = vertical_nav(&mut launcher, &sfcs, delta, buttons.len()) {
+                        menu = Some(m);
+                    }
+                }
+                Event::KeyDown { keycode: Some(Keycode::Menu | Keycode::Application), .. } => {
+                    if !sfcs.is_empty() {
+                        menu = Some(game_context_menu(&launcher, &sfcs[launcher.selected_game]));
+                    }
+                }
+                Event::KeyDown { keycode: Some(Keycode::I), repeat: false, .. } if !launcher.continue_focused => {
+                    if !sfcs.is_empty() {
+                        flip = Some(details::CardFlip::open(launcher.selected_game, launcher.motion()));
+                    }
+                }
+                // OS key repeat is ignored, holding a direction is handled by NavRepeat
+                Event::KeyDown {
+                    keycode: Some(Keycode::Left),
+                    repeat: false,
+                    ..
+                } => {
+                    launcher.move_selection(-1, &sfcs);
+                    launcher.nav_repeat.press(NavDirection::Left);
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Right),
+                    repeat: false,
+                    ..
+                } => {
+                    launcher.move_selection(1, &sfcs);
+                    launcher.nav_repeat.press(NavDirection::Right);
+                }
+                Event::KeyDown { keycode: Some(Keycode::Q), .. } => launcher.switch_tab(-1, &mut sfcs),
+                Event::KeyDown { keycode: Some(Keycode::E), .. } => launcher.switch_tab(1, &mut sfcs),
+                Event::KeyDown { keycode: Some(Keycode::PageUp), .. } => launcher.flip_page(-1, sfcs.len()),
+                Event::KeyDown { keycode: Some(Keycode::PageDown), .. } => launcher.flip_page(1, sfcs.len()),
+                Event::KeyUp { keycode: Some(Keycode::Left), .. } => {
+                    launcher.nav_repeat.release(NavDirection::Left);
+                }
+                Event::KeyUp { keycode: Some(Keycode::Right), .. } => {
+                    launcher.nav_repeat.release(NavDirection::Right);
+                }
+                Event::KeyDown { keycode: Some(Keycode::Return), .. } if launcher.continue_focused => {
+                    menu_choice = launcher.last_played().map(|s| MenuAction::Launch(s.rom.clone()));
+                }
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    ..
+                } => {
+                    if !sfcs.is_empty() {
+                        should_launch = Some(launcher.selected_game);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        // Long press opens the card's context menu without waiting for the finger to lift
+        if let Some(t) = touch.as_mut() {
+            let (dx, dy) = (t.last.0 - t.start.0, t.last.1 - t.start.1);
+            if !t.long_press_fired && menu.is_none() && screen.is_none() && t.started.elapsed() >= LONG_PRESS_TIME
+                && dx.abs() < SWIPE_DISTANCE / 4 && dy.abs() < SWIPE_DISTANCE / 4
+            {
+                t.long_press_fired = true;
+                if let Some(idx) = launcher.game_at(t.start.0, t.start.1, sfcs.len()) {
+                    launcher.selected_game = idx;
+                    menu = Some(game_context_menu(&launcher, &sfcs[idx]));
+                }
+            }
+        }
+        
+        if menu.is_none() && screen.is_none() && quick.is_none() {
+            if let Some(direction) = launcher.nav_repeat.poll() {
+                launcher.move_selection(direction.delta(), &sfcs);
+            }
+        } else {
+            // Releases go to the menu or view, so don't keep repeating once it closes
+            launcher.nav_repeat.clear();
+        }
+        
+        if let Some((at, _)) = launcher.bump {
+            if bump_played != Some(at) {
+                bump_played = Some(at);
+                if let Some(audio) = &audio {
+                    audio.bump_effect();
+                }
+            }
+        }
+        if let Some(at) = launcher.moved {
+            if nav_played != Some(at) {
+                nav_played = Some(at);
+                if let Some(audio) = &audio {
+                    audio.nav_effect();
+                }
+            }
+        }
+        
+        for action in view_actions {
+            match action {
+                ViewAction::None => {}
+                ViewAction::Close => {
+                    screen = None;
+                    // Leaving the theme editor without saving drops the preview
+                    let saved = Launcher::saved_theme(&launcher.launcher_dir, &launcher.launcher_options);
+                    if saved != launcher.theme {
+                        launcher.theme = saved;
+                        font = load_font(ttf_context.as_ref(), &mut launcher);
+                        bg_texture = load_background(&texture_creator, &mut launcher);
+                        ui_atlas = load_atlas(&texture_creator, &launcher);
+                        apply_theme_sounds(&mut audio, &mut launcher, audio_latency_trying);
+                    }
+                }
+                ViewAction::PreviewTheme(preview) => {
+                    let font_changed = preview.font != launcher.theme.font || preview.font_size != launcher.theme.font_size;
+                    let bg_changed = preview.bgtype != launcher.theme.bgtype;
+                    launcher.theme = *preview;
+                    if font_changed {
+                        font = load_font(ttf_context.as_ref(), &mut launcher);
+                    }
+                    if bg_changed {
+                        bg_texture = load_background(&texture_creator, &mut launcher);
+                    }
+                }
+                ViewAction::ThemeSaved(name) => {
+                    launcher.launcher_options.theme = Some(name);
+                    // The editor started from the palette's colors, so they're part of the saved theme now
+                    launcher.launcher_options.palette.clear();
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    // The saved theme may ship its own background, font and sounds
+                    font = load_font(ttf_context.as_ref(), &mut launcher);
+                    bg_texture = load_background(&texture_creator, &mut launcher);
+                    ui_atlas = load_atlas(&texture_creator, &launcher);
+                    apply_theme_sounds(&mut audio, &mut launcher, audio_latency_trying);
+                }
+                ViewAction::LibraryChanged => launcher.refresh_visible(&mut sfcs),
+                ViewAction::HackInstalled(rom, hack_name, exits) => {
+                    notify_if_away(&launcher, canvas.window(), &format!("{} was added to the library", hack_name));
+                    launcher.register_hack(&rom, &hack_name, exits);
+                    launcher.refresh_visible(&mut sfcs);
+                }
+                ViewAction::TaskFinished(message) => {
+                    notify_if_away(&launcher, canvas.window(), &message);
+                    toasts.push(message);
+                }
+                ViewAction::Restored(message) => {
+                    screen = None;
+                    notify_if_away(&launcher, canvas.window(), &message);
+                    toasts.push(message);
+                    launcher.reload_data();
+                    launcher.refresh_visible(&mut sfcs);
+                    font = load_font(ttf_context.as_ref(), &mut launcher);
+                    bg_texture = load_background(&texture_creator, &mut launcher);
+                    ui_atlas = load_atlas(&texture_creator, &launcher);
+                    apply_theme_sounds(&mut audio, &mut launcher, audio_latency_trying);
+                    menu = launcher.config_error.as_deref().map(config_error_menu);
+                }
+                ViewAction::LauncherKeys(bindings) => {
+                    screen = None;
+                    launcher.launcher_options.launcher_keys = bindings;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher keys: {}", e);
+                    }
+                }
+                ViewAction::Choose(action) => menu_choice = Some(*action),
+                ViewAction::PinAccepted(then) => {
+                    screen = None;
+                    launcher.playtime_unlocked = Some(chrono::Local::now().date_naive());
+                    menu_choice = Some(*then);
+                }
+                ViewAction::LaunchWith(rom, overrides) => {
+                    screen = None;
+                    if let Some(idx) = launcher.card_index(&sfcs, &rom) {
+                        launcher.selected_game = idx;
+                        should_launch = Some(idx);
+                        launch_chosen = true;
+                        session_overrides = Some(*overrides);
+                    }
+                }
+                ViewAction::Rename(rom, name, on_disk) => match launcher.rename_game(&rom, &name, on_disk) {
+                    Ok(new_rom) => {
+                        covers.forget(&rom);
+                        launcher.refresh_visible(&mut sfcs);
+                        if let Some(idx) = launcher.card_index(&sfcs, &new_rom) {
+                            launcher.selected_game = idx;
+                        }
+                        screen = None;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to rename {}: {}", rom, e);
+                        toasts.push(format!("Rename failed: {}", e));
+                    }
+                },
+            }
+        }
+        
+        if let Some(choice) = menu_choice.take() {
+            let previous_selection = menu.take().map(|m| m.selected).unwrap_or(0);
+            let mut reopen_menu: Option<Menu> = None;
+            match choice {
+                MenuAction::Dismiss => {}
+                MenuAction::Launch(rom) => match launcher.reveal_game(&mut sfcs, &rom) {
+                    Ok(idx) => {
+                        if sfcs[idx] != rom || launcher.bundles.contains_key(&rom) {
+                            launcher.choose_variant(&sfcs[idx].clone(), &rom);
+                        }
+                        launcher.selected_game = idx;
+                        should_launch = Some(idx);
+                        launch_chosen = true;
+                    }
+                    Err(e) => eprintln!("Cannot launch: {}", e),
+                },
+                MenuAction::ResumeState(rom) => match launcher.resumable_state(&rom) {
+                    Some((state, _)) => {
+                        if let Some(idx) = launcher.card_index(&sfcs, &rom) {
+                            launcher.selected_game = idx;
+                            should_launch = Some(idx);
+                            launch_chosen = true;
+                            session_overrides = Some(session::Overrides { load_state: Some(state), ..Default::default() });
+                        }
+                    }
+                    None => toasts.push("No savestate to resume from".to_string()),
+                },
+                MenuAction::TrustRom(rom, crc) => {
+                    match rom::trust_rom(&launcher.launcher_dir, crc, &rom) {
+                        // Checked again without this warning, the others still get their say
+                        Ok(()) => {
+                            if let Some(idx) = launcher.card_index(&sfcs, &rom) {
+                                should_launch = Some(idx);
+                                launch_chosen = true;
+                            }
+                        }
+                        Err(e) => toasts.push(format!("Failed to remember the ROM: {}", e)),
+                    }
+                }
+                MenuAction::LaunchUnchecked(rom) => {
+                    if let Some(idx) = launcher.card_index(&sfcs, &rom) {
+                        should_launch = Some(idx);
+                        launch_unchecked = true;
+                        launch_chosen = true;
+                    }
+                }
+                MenuAction::MoveGame(rom, delta) => {
+                    if let Some(from) = sfcs.iter().position(|s| *s == rom) {
+                        let to = (from as i32 + delta).clamp(0, sfcs.len() as i32 - 1) as usize;
+                        launcher.reorder_game(&mut sfcs, from, to);
+                    }
+                }
+                MenuAction::SetFavorite(rom, favorite) => {
+                    launcher.set_favorite(&rom, favorite);
+                }
+                MenuAction::SetCoverFromClipboard(rom) => {
+                    let text = video_subsystem.clipboard().clipboard_text().unwrap_or_default();
+                    let path = PathBuf::from(text.trim().trim_matches('"'));
+                    match launcher.set_cover_override(&rom, Some(path)) {
+                        Ok(()) => toasts.push("Cover changed".to_string()),
+                        Err(e) => toasts.push(format!("Cover not changed: {}", e)),
+                    }
+                }
+                MenuAction::ResetCover(rom) => {
+                    if let Err(e) = launcher.set_cover_override(&rom, None) {
+                        eprintln!("Failed to reset cover: {}", e);
+                    }
+                }
+                MenuAction::SetHidden(rom, hidden) => {
+                    launcher.set_hidden(&rom, hidden);
+                    launcher.refresh_visible(&mut sfcs);
+                }
+                MenuAction::ToggleAutoCovers => {
+                    launcher.launcher_options.auto_covers = !launcher.launcher_options.auto_covers;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ToggleTaskNotifications => {
+                    launcher.launcher_options.task_notifications = !launcher.launcher_options.task_notifications;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ToggleFullscreenHandoff => {
+                    launcher.launcher_options.fullscreen_handoff = !launcher.launcher_options.fullscreen_handoff;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ToggleLowLatency => {
+                    launcher.launcher_options.low_latency = !launcher.launcher_options.low_latency;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CyclePadBackQuit(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.pad_back_quit = (options.pad_back_quit as i32 + step).rem_euclid(3) as u8;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleTransitionSpeed(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.transition_speed = step_choice(&anim::SPEEDS, &options.transition_speed, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleEasing(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.easing = (options.easing as i32 + step).rem_euclid(anim::EASINGS.len() as i32) as u8;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CyclePlaytimeLimit(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.playtime_limit_mins = step_choice(&playtime::LIMITS, &options.playtime_limit_mins, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleAgeLimit(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.max_age = step_choice(&rating::LIMITS, &options.max_age, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    launcher.refresh_visible(&mut sfcs);
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::EnterPin(then) => {
+                    let reason = match *then {
+                        MenuAction::Launch(_) => "Today's playtime is used up.",
+                        MenuAction::CycleAgeLimit(_) => "The age limit is locked.",
+                        MenuAction::CycleAgeRating(_) | MenuAction::ToggleDescriptor(..) => "Age ratings are locked.",
+                        _ => "The playtime limit is locked.",
+                    };
+                    screen = Some(Box::new(playtime::PinView::new(&launcher.launcher_options.playtime_pin, reason, *then)));
+                }
+                MenuAction::ResolveIni(use_file) => launcher.resolve_ini_conflicts(use_file),
+                MenuAction::CycleCacheTtl(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.cache_ttl_hours = step_choice(&cache::TTL_HOURS, &options.cache_ttl_hours, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::SetSavesDir(dir) => {
+                    match launcher.set_saves_dir(dir) {
+                        Ok(moved) => toasts.push(format!("Saves folder changed, {} file(s) moved", moved)),
+                        Err(e) => toasts.push(format!("Saves folder not changed: {}", e)),
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::SavesDirFromClipboard => {
+                    let text = video_subsystem.clipboard().clipboard_text().unwrap_or_default();
+                    let dir = PathBuf::from(text.trim().trim_matches('"'));
+                    let result = if dir.is_absolute() {
+                        launcher.set_saves_dir(Some(dir))
+                    } else {
+                        Err("the clipboard holds no folder path".to_string())
+                    };
+                    match result {
+                        Ok(moved) => toasts.push(format!("Saves folder changed, {} file(s) moved", moved)),
+                        Err(e) => toasts.push(format!("Saves folder not changed: {}", e)),
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ClearCache => {
+                    match cache::clear(&launcher.launcher_dir) {
+                        Ok(()) => toasts.push("Download cache cleared".to_string()),
+                        Err(e) => toasts.push(format!("Failed to clear the download cache: {}", e)),
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleBatterySaver(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.battery_saver = step_choice(&BATTERY_SAVER_CHOICES, &options.battery_saver, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleBatteryFps(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.battery_fps = step_choice(&BATTERY_FPS_CHOICES, &options.battery_fps, step);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CyclePalette(step) => {
+                    // The theme's own colors come first, then each palette in turn
+                    let options = &mut launcher.launcher_options;
+                    let names: Vec<&str> = iter::once("").chain(theme::PALETTES.iter().map(|p| p.name)).collect();
+                    options.palette = step_choice(&names, &options.palette.as_str(), step).to_string();
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    launcher.theme = Launcher::saved_theme(&launcher.launcher_dir, &launcher.launcher_options);
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleLanguage(step) => {
+                    let options = &mut launcher.launcher_options;
+                    let codes = TITLE_LANGUAGES.map(|(code, _)| code);
+                    options.language = step_choice(&codes, &options.language.as_str(), step).to_string();
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::CycleSortMode(step) => {
+                    let options = &mut launcher.launcher_options;
+                    options.sort_mode = (options.sort_mode as i32 + step).rem_euclid(2) as u8;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    launcher.refresh_visible(&mut sfcs);
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ToggleShowHidden => {
+                    launcher.launcher_options.show_hidden = !launcher.launcher_options.show_hidden;
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    launcher.refresh_visible(&mut sfcs);
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::OpenCollections(rom) => {
+                    reopen_menu = Some(collections_menu(&launcher, &rom));
+                }
+                MenuAction::ToggleCollection(rom, collection) => {
+                    launcher.toggle_collection(&rom, &collection);
+                    launcher.refresh_visible(&mut sfcs);
+                    reopen_menu = Some(collections_menu(&launcher, &rom));
+                }
+                MenuAction::OpenContent(rom) => {
+                    reopen_menu = Some(content_menu(&launcher, &rom));
+                }
+                MenuAction::CycleAgeRating(rom) => {
+                    launcher.cycle_age_rating(&rom);
+                    launcher.refresh_visible(&mut sfcs);
+                    reopen_menu = Some(content_menu(&launcher, &rom));
+                }
+                MenuAction::ToggleDescriptor(rom, descriptor) => {
+                    launcher.toggle_descriptor(&rom, &descriptor);
+                    reopen_menu = Some(content_menu(&launcher, &rom));
+                }
+                MenuAction::OpenHackBrowser => {
+                    let base_rom = launcher.sfc_dir.join(&launcher.launcher_options.base_rom);
+                    screen = Some(Box::new(hacks::HackBrowser::new(base_rom, launcher.sfc_dir.clone(), launcher.backup_dir(), launcher.cache())));
+                }
+                MenuAction::ToggleStreamerMode => {
+                    let options = &mut launcher.launcher_options;
+                    options.streamer_mode = !options.streamer_mode;
+                    streamer::set_enabled(options.streamer_mode);
+                    if !options.streamer_mode {
+                        let _ = fs::remove_file(launcher.launcher_dir.join("now_playing.txt"));
+                    }
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::ImportCoverPack => {
+                    let text = video_subsystem.clipboard().clipboard_text().unwrap_or_default();
+                    let path = PathBuf::from(text.trim().trim_matches('"'));
+                    reopen_menu = Some(if path.is_file() {
+                        match fs::read(&path) {
+                            Ok(data) => import_cover_pack(&launcher, &mut covers, &data),
+                            Err(e) => cover_pack_menu(&Err(e.to_string())),
+                        }
+                    } else {
+                        Menu::new("Import cover pack")
+                            .line("Copy the path of a cover pack .zip, then choose this again,")
+                            .line("or drop the .zip onto the window.")
+                            .line("Images are matched by ROM file name or library ID.")
+                            .item("Close", MenuAction::Dismiss)
+                    });
+                }
+                MenuAction::ImportOldSettings => {
+                    let text = video_subsystem.clipboard().clipboard_text().unwrap_or_default();
+                    let path = PathBuf::from(text.trim().trim_matches('"'));
+                    let mut result = Menu::new("Import old launcher settings");
+                    if !path.is_dir() {
+                        result = result
+                            .line("Copy the path of the old launcher's folder,")
+                            .line("then choose this again. Its launcher options")
+                            .line("and smw.ini replace the current ones.");
+                    } else {
+                        match launcher.import_old_settings(&path) {
+                            Ok(report) => {
+                                for line in report {
+                                    result = result.line(line);
+                                }
+                                result = result.line("Some settings take effect after a restart");
+                                launcher.theme = Launcher::saved_theme(&launcher.launcher_dir, &launcher.launcher_options);
+                                font = load_font(ttf_context.as_ref(), &mut launcher);
+                                bg_texture = load_background(&texture_creator, &mut launcher);
+                                ui_atlas = load_atlas(&texture_creator, &launcher);
+                                apply_theme_sounds(&mut audio, &mut launcher, audio_latency_trying);
+                            }
+                            Err(e) => {
+                                eprintln!("Failed to import old settings: {}", e);
+                                result = result.line(streamer::scrub(&e.to_string()));
+                            }
+                        }
+                    }
+                    reopen_menu = Some(result.item("Close", MenuAction::Dismiss));
+                }
+                MenuAction::InstallThemeFromClipboard => {
+                    let url = video_subsystem.clipboard().clipboard_text().unwrap_or_default().trim().to_string();
+                    if url.starts_with("http://") || url.starts_with("https://") {
+                        println!("Downloading theme from {}", url);
+                        let launcher_dir = launcher.launcher_dir.clone();
+                        let tx = theme_tx.clone();
+                        std::thread::spawn(move || {
+                            let result = net::download(&url).and_then(|data| theme::install_pack(&launcher_dir, &data));
+                            let _ = tx.send(result);
+                        });
+                    } else {
+                        reopen_menu = Some(
+                            Menu::new("Install theme")
+                                .line("Copy a theme pack URL, then paste it here")
+                                .line("or drop a theme .zip onto the window.")
+                                .item("Close", MenuAction::Dismiss),
+                        );
+                    }
+                }
+                MenuAction::ActivateTheme(name) => {
+                    launcher.launcher_options.theme = Some(name);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    launcher.theme = Launcher::saved_theme(&launcher.launcher_dir, &launcher.launcher_options);
+                    font = load_font(ttf_context.as_ref(), &mut launcher);
+                    bg_texture = load_background(&texture_creator, &mut launcher);
+                    ui_atlas = load_atlas(&texture_creator, &launcher);
+                    apply_theme_sounds(&mut audio, &mut launcher, audio_latency_trying);
+                }
+                MenuAction::DismissNews(id) => launcher.dismiss_news(&id),
+                MenuAction::OpenStats => {
+                    let titles: HashMap<String, String> = launcher
+                        .stats
+                        .sessions
+                        .iter()
+                        .map(|s| (s.rom.clone(), launcher.display_title(&s.rom)))
+                        .collect();
+                    screen = Some(Box::new(stats::StatsScreen::new(&launcher.stats, &titles)));
+                }
+                MenuAction::OpenTrophies => {
+                    screen = Some(Box::new(achievements::TrophyScreen::new(&launcher.achievements)));
+                }
+                MenuAction::ExportStats => {
+                    let mut result = Menu::new("Export statistics");
+                    match stats::export(&launcher.stats, &launcher.launcher_dir.join("exports")) {
+                        Ok(paths) => {
+                            for path in paths {
+                                result = result.line(format!("Wrote {}", streamer::path(&path)));
+                            }
+                            if let Some(url) = launcher.launcher_options.stats_upload_url.clone() {
+                                result = result.line("Uploading in the background");
+                                let body = serde_json::to_value(&launcher.stats).unwrap_or_default();
+                                std::thread::spawn(move || match net::post_json(&url, &body) {
+                                    Ok(()) => println!("Uploaded statistics"),
+                                    Err(e) => eprintln!("Failed to upload statistics: {}", e),
+                                });
+                            }
+                            let exports = launcher.launcher_dir.join("exports");
+                            result = result.item("Open folder", MenuAction::OpenPath(exports));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to export statistics: {}", e);
+                            result = result.line(streamer::scrub(&e));
+                        }
+                    }
+                    reopen_menu = Some(result.item("Close", MenuAction::Dismiss));
+                }
+                MenuAction::ExportFrontends => {
+                    let mut result = Menu::new("Export library");
+                    let exports = launcher.launcher_dir.join("exports");
+                    match launcher.export_frontends(&exports) {
+                        Ok(paths) => {
+                            for path in paths {
+                                result = result.line(format!("Wrote {}", streamer::path(&path)));
+                            }
+                            result = result.line("Copy them to the front-end's folder for this system");
+                            result = result.item("Open folder", MenuAction::OpenPath(exports));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to export the library: {}", e);
+                            result = result.line(streamer::scrub(&e));
+                        }
+                    }
+                    reopen_menu = Some(result.item("Close", MenuAction::Dismiss));
+                }
+                MenuAction::OpenWidgets => {
+                    reopen_menu = Some(widgets_menu(&launcher.launcher_options.widgets));
+                }
+                MenuAction::ToggleWidget(widget) => {
+                    launcher.launcher_options.widgets.toggle(widget);
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    reopen_menu = Some(widgets_menu(&launcher.launcher_options.widgets));
+                }
+                MenuAction::CycleWidgetCorner => {
+                    let widgets = &mut launcher.launcher_options.widgets;
+                    widgets.corner = widgets.corner.next();
+                    if let Err(e) = launcher.save_launcher_options() {
+                        eprintln!("Failed to save launcher options: {}", e);
+                    }
+                    reopen_menu = Some(widgets_menu(&launcher.launcher_options.widgets));
+                }
+                MenuAction::OpenThemeEditor => {
+                    screen = Some(Box::new(theme::ThemeEditor::new(launcher.theme.clone(), launcher.launcher_dir.clone())));
+                }
+                MenuAction::OpenPluginSettings => {
+                    launcher.plugins.on_settings();
+                    reopen_menu = Some(plugin_settings_menu(&launcher));
+                }
+                MenuAction::PluginSetting(plugin, id) => {
+                    launcher.plugins.activate_setting(plugin, &id);
+                    reopen_menu = Some(plugin_settings_menu(&launcher));
+                }
+                MenuAction::OpenPatchManager => {
+                    let patch_dir = launcher.launcher_dir.join("patches");
+                    screen = Some(Box::new(patches::PatchManager::new(
+                        &patch_dir,
+                        launcher.sfc_dir.clone(),
+                        launcher.backup_dir(),
+                        launcher.library.files_by_crc(),
+                    )));
+                }
+                MenuAction::StripHeader(rom) => {
+                    if let Err(e) = launcher.strip_header(&rom) {
+                        eprintln!("Failed to strip header from {}: {}", rom, e);
+                    }
+                }
+                MenuAction::LaunchWithOptions(rom) => {
+                    screen = Some(Box::new(session::OverridesView::new(&launcher.display_title(&rom), &rom)));
+                }
+                MenuAction::RestoreSave(rom) => {
+                    match launcher.restore_save(&rom) {
+                        Ok(from) => {
+                            let from = from.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                            println!("Restored the save for {} from {}", rom, from);
+                            toasts.push(format!("Save restored from {}", from));
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to restore the save for {}: {}", rom, e);
+                            toasts.push(e);
+                        }
+                    }
+                    let issues = launcher.validate_launch(&rom);
+                    if !issues.is_empty() {
+                        reopen_menu = Some(launch_issues_menu(&rom, &issues));
+                    }
+                }
+                MenuAction::OpenBackups => {
+                    reopen_menu = Some(backups_menu(&launcher.backup_dir()));
+                }
+                MenuAction::RestoreBackup(id) => {
+                    match backup::restore(&launcher.backup_dir(), &id) {
+                        Ok(entry) => {
+                            println!("Restored {}", streamer::path(&entry.original));
+                            let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                            if let Some(renamed) = &entry.renamed_to {
+                                let (renamed, original) = (name(renamed), name(&entry.original));
+                                covers.forget(&renamed);
+                                if let Err(e) = launcher.undo_rename(&renamed, &original) {
+                                    eprintln!("Failed to follow the undone rename of {}: {}", original, e);
+                                }
+                            }
+                        }
+                        Err(e) => eprintln!("Failed to restore backup: {}", e),
+                    }
+                    launcher.refresh_visible(&mut sfcs);
+                    reopen_menu = Some(backups_menu(&launcher.backup_dir()));
+                }
+                MenuAction::SelectVariant(card, variant) => {
+                    launcher.choose_variant(&card, &variant);
+                    println!("{} now launches {}", card, variant);
+                }
+                MenuAction::OpenLauncherOptions => {
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::OpenPath(path) => open_in_file_manager(&path),
+                MenuAction::RevealPath(path) => reveal_in_file_manager(&path),
+                MenuAction::OpenDisplayOverrides(rom) => {
+                    let displays = video_subsystem.num_video_displays().unwrap_or(1).max(1) as u32;
+                    show_options(&mut screen, display_page(&launcher, &rom, displays));
+                }
+                MenuAction::CycleIniOverride(rom, key, step) => {
+                    launcher.cycle_ini_override(&rom, &key, step);
+                    let displays = video_subsystem.num_video_displays().unwrap_or(1).max(1) as u32;
+                    show_options(&mut screen, display_page(&launcher, &rom, displays));
+                }
+                MenuAction::CycleWatchdog(rom) => {
+                    launcher.cycle_watchdog(&rom);
+                    reopen_menu = Some(game_context_menu(&launcher, &rom));
+                }
+                MenuAction::CyclePriority(rom) => {
+                    launcher.cycle_priority(&rom);
+                    reopen_menu = Some(game_context_menu(&launcher, &rom));
+                }
+                MenuAction::TogglePerformanceCores(rom) => {
+                    launcher.toggle_performance_cores(&rom);
+                    reopen_menu = Some(game_context_menu(&launcher, &rom));
+                }
+                MenuAction::SafeRelaunch(rom, step) => {
+                    if let Some(idx) = launcher.card_index(&sfcs, &rom) {
+                        if sfcs[idx] != rom {
+                            launcher.choose_variant(&sfcs[idx].clone(), &rom);
+                        }
+                        launcher.selected_game = idx;
+                        should_launch = Some(idx);
+                        launch_chosen = true;
+                        safe_step = Some(step);
+                    }
+                }
+                MenuAction::CycleMonitor(rom, step) => {
+                    let displays = video_subsystem.num_video_displays().unwrap_or(1).max(1) as u32;
+                    launcher.cycle_monitor(&rom, displays, step);
+                    show_options(&mut screen, display_page(&launcher, &rom, displays));
+                }
+                MenuAction::OpenFiles(rom) => {
+                    // These folders only appear once something was written, create them so they can be opened
+                    for dir in [launcher.saves_dir.clone(), launcher.launcher_dir.join("screenshots"), launcher.launcher_dir.join("logs")] {
+                        if let Err(e) = fs::create_dir_all(&dir) {
+                            eprintln!("Failed to create {}: {}", streamer::path(&dir), e);
+                        }
+                    }
+                    reopen_menu = Some(files_menu(&launcher, &rom));
+                }
+                MenuAction::ConfirmDeleteRom(rom) => {
+                    reopen_menu = Some(delete_rom_menu(&rom));
+                }
+                MenuAction::DeleteRom(rom) => match launcher.trash_rom(&rom) {
+                    Ok(()) => {
+                        covers.forget(&rom);
+                        launcher.refresh_visible(&mut sfcs);
+                        toasts.push(format!("Moved {} to the trash", rom));
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to delete {}: {}", rom, e);
+                        toasts.push(format!("Delete failed: {}", e));
+                    }
+                },
+                MenuAction::OpenControllerSetup => {
+                    reopen_menu = Some(controller_menu(&launcher));
+                }
+                MenuAction::OpenQuickSettings => quick = Some(QuickSettings::open(launcher.quick_setting_rows())),
+                MenuAction::OpenAudioLatency => {
+                    audio_latency_trying = launcher.launcher_options.audio_latency;
+                    reopen_menu = Some(audio_latency_menu(&launcher, audio_latency_trying));
+                }
+                MenuAction::TryAudioLatency(preset) => {
+                    if preset != audio_latency_trying {
+                        reopen_audio(&mut audio, &mut launcher, preset);
+                        audio_latency_trying = preset;
+                    }
+                    if let Some(audio) = &audio {
+                        audio.test_effect();
+                    }
+                    reopen_menu = Some(audio_latency_menu(&launcher, preset));
+                }
+                MenuAction::SaveAudioLatency(preset) => {
+                    launcher.set_audio_latency(preset);
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::RevertAudioLatency => {
+                    // Leaving without saving goes back to the saved preset
+                    if audio_latency_trying != launcher.launcher_options.audio_latency {
+                        audio_latency_trying = launcher.launcher_options.audio_latency;
+                        reopen_audio(&mut audio, &mut launcher, audio_latency_trying);
+                    }
+                    show_options(&mut screen, launcher_options_page(&launcher));
+                }
+                MenuAction::OpenLauncherKeys => {
+                    screen = Some(Box::new(keys::KeyBindingsView::new(&launcher.launcher_options.launcher_keys)));
+                }
+                MenuAction::OpenUpdates => match launcher.launcher_options.update_server.clone() {
+                    Some(server) => {
+                        let targets = updates::Targets {
+                            game_executable: launcher.game_executable(),
+                            game_dir: launcher.game_dir(),
+                            launcher_dir: launcher.launcher_dir.clone(),
+                        };
+                        screen = Some(Box::new(updates::UpdateView::new(&server, targets, launcher.game_process.is_some())));
+                    }
+                    None => toasts.push("No update server is set (update_server in the launcher options)".to_string()),
+                },
+                MenuAction::OpenSystemStatus => {
+                    reopen_menu = Some(system_status_menu(&launcher));
+                }
+                MenuAction::Retry(subsystem) => {
+                    match subsystem {
+                        Subsystem::Audio => reopen_audio(&mut audio, &mut launcher, audio_latency_trying),
+                        Subsystem::Gamepad => {
+                            launcher.gamepad_system = Launcher::init_gamepads(&mut launcher.status, &launcher.launcher_dir)
+                        }
+                        Subsystem::Font => font = load_font(ttf_context.as_ref(), &mut launcher),
+                        Subsystem::Background => bg_texture = load_background(&texture_creator, &mut launcher),
+                        Subsystem::Network => {
+                            launcher.status.set(Subsystem::Network, None);
+                            launcher.news = news::News::start(launcher.cache(), launcher.launcher_options.update_server.as_deref());
+                        }
+                        Subsystem::Saves => {
+                            launcher.status.set(Subsystem::Saves, None);
+                            verifier.recheck_saves();
+                        }
+                    }
+                    reopen_menu = Some(system_status_menu(&launcher));
+                }
+                MenuAction::OpenVariants(card) => {
+                    reopen_menu = Some(variants_menu(&launcher, &card));
+                }
+                MenuAction::Quit => break 'running,
+                MenuAction::OpenManual(card) => {
+                    let rom = launcher.resolve_variant(&card);
+                    let pages = manual::pages(&launcher.launcher_dir, &launcher.sfc_dir, &rom);
+                    screen = Some(Box::new(manual::ManualView::new(&launcher.display_title(&rom), pages, &texture_creator)));
+                }
+                MenuAction::PreviewGameIni(rom) => {
+                    reopen_menu = Some(game_ini_menu(&launcher, &rom));
+                }
+                MenuAction::Rename(rom) => {
+                    screen = Some(Box::new(rename::RenameView::new(&rom, &launcher.display_title(&rom))));
+                }
+                MenuAction::BuildFromSource => {
+                    // A build still running is shown again, a second one would share its smw-src
+                    let build = match launcher.source_build.clone().filter(|b| b.running()) {
+                        Some(build) => build,
+                        None => build::SourceBuild::start(
+                            launcher.launcher_options.smw_source_repo.clone(),
+                            launcher.install_dir.join("smw-src"),
+                            launcher.game_executable(),
+                        ),
+                    };
+                    screen = Some(Box::new(build.view()));
+                    launcher.source_build = Some(build);
+                }
+                MenuAction::OpenMaintenance => {
+                    reopen_menu = Some(maintenance_menu(&launcher));
+                }
+                MenuAction::BackupEverything => {
+                    screen = Some(Box::new(archive::ArchiveTask::backup(launcher.archive_dir(), launcher.data_dirs())));
+                }
+                MenuAction::OpenArchives => {
+                    reopen_menu = Some(archives_menu(&launcher.archive_dir()));
+                }
+                MenuAction::RestoreArchive(path) => {
+                    screen = Some(Box::new(archive::ArchiveTask::restore(launcher.archive_dir(), path, launcher.data_dirs())));
+                }
+                MenuAction::ResetConfig => match launcher.reset_launcher_options() {
+                    Ok(backup) => {
+                        println!("Reset launcher options, old file kept as {}", streamer::path(&backup));
+                        reopen_menu = Some(
+                            Menu::new("Launcher options reset")
+                                .line(format!("The old file was kept as {}", streamer::path(&backup)))
+                                .item("Close", MenuAction::Dismiss),
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to reset launcher options: {}", e);
+                        reopen_menu = launcher.config_error.as_deref().map(config_error_menu);
+                    }
+                },
             }
-            Err(e) => {
-                eprintln!("Failed to load background texture: {}", e);
-                None
+            if let Some(mut m) = reopen_menu.take() {
+                m.selected = previous_selection.min(m.items.len().saturating_sub(1));
+                menu = Some(m);
             }
         }
-    } else {
-        None
-    };
-    
-    let pointer_path = launcher.launcher_dir.join("pointer.png");
-    let pointer_texture = if pointer_path.exists() && launcher.launcher_options.selector == 1 {
-        match texture_creator.load_texture(&pointer_path) {
-            Ok(t) => {
-                println!("Loaded pointer texture: {}", pointer_path.display());
-                Some(t)
-            }
-            Err(e) => {
-                eprintln!("Failed to load pointer texture: {}", e);
-                None
+        
+        // Power saving: pause music and throttle or skip rendering while in the background
+        let idle = window_minimized || !window_focused;
+        if let Some(audio) = &audio {
+            if idle && launcher.launcher_options.pause_music_unfocused && audio.has_music() && !music_paused_by_focus {
+                audio.pause_music();
+                music_paused_by_focus = true;
+            } else if !idle && music_paused_by_focus {
+                audio.resume_music();
+                music_paused_by_focus = false;
             }
         }
-    } else {
-        None
-    };
-    
-    let font_path = launcher.launcher_dir.join("smw.ttf");
-    let font = if font_path.exists() {
-        match ttf_context.load_font(&font_path, 24) {
-            Ok(f) => {
-                println!("Loaded font: {}", font_path.display());
-                Some(f)
-            }
-            Err(e) => {
-                eprintln!("Failed to load font: {}", e);
-                None
-            }
+        
+        if window_minimized || (idle && launcher.launcher_options.unfocused_fps == 0) {
+            std::thread::sleep(Duration::from_millis(100));
+            continue 'running;
         }
-    } else {
-        eprintln!("Font not found at: {}", font_path.display());
-        None
-    };
-    
-    let mut event_pump = sdl_context.event_pump()?;
-    let mouse_pressed = false;
-    
-    let sfcs = launcher.scan_sfc_files();
-    
-    if sfcs.is_empty() {
-        println!("\nWARNING: No SFC files found!");
-        println!("Please add .sfc ROM files to: {}", launcher.sfc_dir.display());
-    } else {
-        println!("\nFound {} game(s):", sfcs.len());
-        for (idx, sfc) in sfcs.iter().enumerate() {
-            println!("  {}. {}", idx + 1, sfc);
+        
+        if battery.below(launcher.launcher_options.battery_saver) != battery_saving {
+            battery_saving = !battery_saving;
+            println!("Battery saver {}", if battery_saving { "on" } else { "off" });
+            // The visualizer's audio hook costs power even when nothing is drawn
+            visualizer::set_enabled(!battery_saving && launcher.theme.bgtype == theme::BG_VISUALIZER);
         }
-    }
-    
-    let mut covers: HashMap<String, Texture> = HashMap::new();
-    for sfc in &sfcs {
-        let name = sfc.trim_end_matches(".sfc");
-        let path = launcher.launcher_dir.join("pngs").join(format!("{}.png", name));
-        if path.exists() {
-            if let Ok(mut tex) = texture_creator.load_texture(&path) {
-                tex.set_blend_mode(sdl2::render::BlendMode::Blend);
-                covers.insert(sfc.clone(), tex);
+        
+        // Animations step after this frame's input is in, so a press shows in the frame drawn next
+        launcher.update_color_transitions(sfcs.len());
+        if let Some(q) = quick.as_mut() {
+            if !q.update(launcher.motion()) {
+                quick = None;
             }
         }
-    }
-    
-    let options_btn = UIButton::new(
-        (SCREEN_WIDTH / 2 - 75) as i32,
-        593,
-        150,
-        40,
-        "Options"
-    );
-    
-    let launcher_opts_btn = UIButton::new(
-        (SCREEN_WIDTH / 4 * 3 - 75) as i32,
-        593,
-        150,
-        40,
-        "Launcher"
-    );
-    
-    let update_btn = UIButton::new(
-        (SCREEN_WIDTH / 4 - 65) as i32,
-        593,
-        130,
-        40,
-        "Update"
-    );
-    
-    println!("\nLauncher ready with grayscale selection!");
-    println!("Controls:");
-    println!("  - Click game box to launch");
-    println!("  - Arrow keys or gamepad D-Pad to navigate");
-    println!("  - Enter or gamepad A/X to launch");
-    println!("  - ESC or gamepad B/Circle to quit");
-    
-    let mut should_launch: Option<usize> = None;
-    
-    'running: loop {
-        let frame_start = std::time::Instant::now();
         
-        // Update color transitions for smooth animation
-        launcher.update_color_transitions(sfcs.len());
+        let mouse_state = event_pump.mouse_state();
         
-        if let Some(action) = launcher.handle_gamepad_input() {
-            match action {
-                GamepadAction::Confirm => {
-                    if !sfcs.is_empty() {
-                        should_launch = Some(launcher.selected_game);
+        if std::mem::take(&mut textures_lost) {
+            // Covers and badge icons load again as the grid asks for them
+            bg_texture = load_background(&texture_creator, &mut launcher);
+            ui_atlas = load_atlas(&texture_creator, &launcher);
+            covers = textures::TextureStore::new(&texture_creator, &launcher.launcher_dir.join("pngs"));
+            badge_renderer = badges::BadgeRenderer::new(&texture_creator);
+            crt_filter = crt::Crt::new(&texture_creator);
+            shader_previews.clear();
+        }
+        
+        // A renderer error (device lost, mode switch) costs this frame and the textures, not the launcher
+        let drawn = (|| -> Result<(), Box<dyn std::error::Error>> {
+            ui::set_chrome(launcher.theme.chrome);
+            crt_filter.begin(&mut canvas, launcher.theme.crt)?;
+            if let Some(view) = screen.as_mut() {
+                view.draw(&mut canvas, &texture_creator, Some(&font))?;
+            } else if let Some((rom, at)) = &boot {
+                canvas.set_draw_color(Color::RGB(0, 0, 0));
+                canvas.clear();
+                let secs = at.saturating_duration_since(Instant::now()).as_secs() + 1;
+                let title = format!("Starting {} in {}...", launcher.display_title(rom), secs);
+                let white = Color::RGB(255, 255, 255);
+                ui::draw_text(&mut canvas, &texture_creator, &font, &title, 30, SCREEN_HEIGHT as i32 / 2 - 30, white)?;
+                ui::draw_text(&mut canvas, &texture_creator, &font, "Press any key to cancel", 30, SCREEN_HEIGHT as i32 / 2 + 10, white)?;
+            } else {
+                canvas.set_draw_color(theme::rgb(launcher.theme.background_color));
+                canvas.clear();
+                if let Some(bg) = &bg_texture {
+                    canvas.copy(bg, None, None)?;
+                }
+                if launcher.theme.bgtype == theme::BG_VISUALIZER && !battery_saving {
+                    visualizer.draw(&mut canvas, &launcher.theme)?;
+                }
+
+                covers.set_overrides(launcher.library.cover_overrides());
+                covers.arrange(&sfcs);
+                covers.keep_page(launcher.current_page(), launcher.games_per_page());
+                if let Some(rom) = launcher.cover_capture.poll() {
+                    covers.forget(&rom);
+                }
+                // Verification only uses the time the grid sits there with nothing else going on
+                if launcher.game_process.is_none() && launching.is_none() && menu.is_none() && screen.is_none() && launcher.hashing.pending() == 0 {
+                    if verifier.due() {
+                        verifier.schedule(&launcher.scan_sfc_files());
                     }
+                    verifier.step(&launcher.sfc_dir, &launcher.saves_dir);
                 }
-                GamepadAction::Left => {
-                    if launcher.selected_game > 0 {
-                        launcher.selected_game -= 1;
-                        println!("Selected: {}", sfcs[launcher.selected_game]);
+                match verifier.poll() {
+                    Some(verify::Outcome::Rom(hashed)) => {
+                        if launcher.apply_hashes(vec![hashed]) {
+                            launcher.refresh_visible(&mut sfcs);
+                        }
                     }
+                    Some(verify::Outcome::Saves(problems)) => {
+                        for problem in &problems {
+                            eprintln!("Save check: {}", problem);
+                        }
+                        launcher.status.set(Subsystem::Saves, (!problems.is_empty()).then(|| problems.join(", ")));
+                    }
+                    None => {}
                 }
-                GamepadAction::Right => {
-                    if launcher.selected_game < sfcs.len().saturating_sub(1) {
-                        launcher.selected_game += 1;
-                        println!("Selected: {}", sfcs[launcher.selected_game]);
+                // One hidden run at a time, and never next to a real game
+                if launcher.launcher_options.auto_covers && launcher.game_process.is_none() && launching.is_none() {
+                    if let Some(rom) = launcher.cover_capture.next(covers.uncovered()).map(str::to_string) {
+                        let command = launcher.capture_command(&rom, &launcher.cover_capture.output_path(&rom));
+                        launcher.cover_capture.start(&rom, command);
                     }
                 }
-                GamepadAction::Back => break 'running,
-                _ => {}
-            }
-        }
-        
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => break 'running,
-                Event::MouseButtonDown { mouse_btn: sdl2::mouse::MouseButton::Left, x, y, .. } => {
-                    // Check if clicked on a game box
-                    for (idx, sfc) in sfcs.iter().enumerate().take(3) {
-                        if let Some(rect) = launcher.get_game_box_rect(idx) {
-                            if rect.contains_point((x, y)) {
-                                launcher.selected_game = idx;
-                                should_launch = Some(idx);
-                                break;
-                            }
+                if launcher.theme.bgtype == theme::BG_COVER_BLUR {
+                    let selected = Some(launcher.selected_game).filter(|&idx| idx < sfcs.len());
+                    if selected != backdrop.0 {
+                        backdrop = (selected, backdrop.0, Instant::now());
+                    }
+                    let progress = launcher.motion().progress(backdrop.2, BACKDROP_FADE);
+                    let fade = if progress < 1.0 { launcher.motion().fade(progress) } else { 1.0 };
+                    let screen_rect = Rect::new(0, 0, SCREEN_WIDTH, SCREEN_HEIGHT);
+                    // The old cover stays underneath until the new one has faded in over it
+                    for (idx, alpha) in [(backdrop.1, if fade < 1.0 { 1.0 } else { 0.0 }), (backdrop.0, fade)] {
+                        if let Some(cover) = idx.filter(|_| alpha > 0.0).and_then(|idx| covers.card_mut(idx)) {
+                            let query = cover.backdrop.query();
+                            let (src, dst) = textures::fit(query.width, query.height, screen_rect, true);
+                            cover.backdrop.set_alpha_mod((alpha * 255.0) as u8);
+                            canvas.copy(&cover.backdrop, src, dst)?;
                         }
                     }
+                    card_animating |= fade < 1.0;
                 }
-                Event::MouseMotion { x, y, .. } => {
-                    launcher.mouse_x = x;
-                    launcher.mouse_y = y;
-                    launcher.update_selection_from_mouse(&sfcs);
+                // Tint towards the selected cover's accent color, fading with the card's color transition
+                canvas.set_blend_mode(BlendMode::Blend);
+                for idx in 0..sfcs.len() {
+                    let color_blend = launcher.get_color_blend(idx);
+                    if color_blend <= 0.0 {
+                        continue;
+                    }
+                    if let Some(cover) = covers.card_mut(idx) {
+                        let accent = cover.accent;
+                        canvas.set_draw_color(Color::RGBA(accent.r, accent.g, accent.b, (ACCENT_TINT * color_blend) as u8));
+                        canvas.fill_rect(None)?;
+                    }
                 }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Left),
-                    ..
-                } => {
-                    if launcher.selected_game > 0 {
-                        launcher.selected_game -= 1;
-                        println!("Selected: {}", sfcs[launcher.selected_game]);
+                canvas.set_blend_mode(BlendMode::None);
+                for (idx, sfc) in sfcs.iter().enumerate() {
+                    let rect = match launcher.get_game_box_rect(idx) {
+                        Some(rect) => rect,
+                        None => continue,
+                    };
+                    let color_blend = launcher.get_color_blend(idx);
+                    // Hover zoom, growing and shrinking with the card's color fade
+                    let zoom = HOVER_BOX_SIZE.0 as f32 / BOX_SIZE.0 as f32 - 1.0;
+                    let rect = anim::grow(rect, 1.0 + zoom * color_blend.max(0.0));
+                    let (x, y) = (rect.x(), rect.y());
+                    let is_selected = idx == launcher.selected_game && !launcher.continue_focused && launcher.focused_button.is_none();
+                    if is_selected && selected_since.0 != idx {
+                        selected_since = (idx, Instant::now());
+                    }
+                    if let Some(f) = flip.as_ref().filter(|f| f.card == idx) {
+                        if f.showing_back() {
+                            let details = card_details(&launcher, sfc);
+                            details::draw_back(&mut canvas, &texture_creator, &font, rect, &launcher.theme, &details, f)?;
+                        } else {
+                            let face = f.face(rect);
+                            canvas.set_draw_color(theme::rgb(launcher.theme.card_color));
+                            canvas.fill_rect(face)?;
+                            canvas.set_draw_color(theme::rgb(launcher.theme.card_border_color));
+                            canvas.draw_rect(face)?;
+                            if let Some(cover) = covers.card_mut(idx) {
+                                // Squashed along with the card instead of fitted, that's what sells the turn
+                                let inset = Rect::new(face.x() + 10 * face.width() as i32 / rect.width() as i32, y + 10,
+                                    (face.width() * (rect.width() - 20) / rect.width()).max(1), rect.height() - 70);
+                                canvas.copy(&cover.color, None, inset)?;
+                            }
+                        }
+                        continue;
+                    }
+
+                    canvas.set_draw_color(theme::rgb(launcher.theme.card_color));
+                    canvas.fill_rect(rect)?;
+                    canvas.set_draw_color(theme::rgb(launcher.theme.card_border_color));
+                    canvas.draw_rect(rect)?;
+
+                    if let Some(cover) = covers.card_mut(idx) {
+                        let area = Rect::new(
+                            x + 10,
+                            y + 10,
+                            rect.width() - 20,
+                            rect.height() - 70,
+                        );
+                        let crop = launcher.theme.cover_fit == theme::COVER_CROP;
+                        let (src, dst) = textures::fit(cover.width, cover.height, area, crop);
+                    
+                        // color_blend: 0.0 = grayscale, 1.0 = full color
+                        // Only cards mid-transition draw both, with the color version faded in on top
+                        if color_blend < 1.0 {
+                            canvas.copy(&cover.gray, src, dst)?;
+                        }
+                        if color_blend >= 1.0 {
+                            // Fully selected: animated covers play, everything else stays on its first frame
+                            canvas.copy(cover.frame(selected_since.1.elapsed()), src, dst)?;
+                            card_animating |= cover.is_animated();
+                        } else if color_blend > 0.0 {
+                            cover.color.set_alpha_mod((color_blend * 255.0) as u8);
+                            canvas.copy(&cover.color, src, dst)?;
+                            cover.color.set_alpha_mod(255);
+                        }
+                    }
+
+                    // Slim bar between the cover and the title
+                    if let Some((cleared, total)) = launcher.exit_progress(sfc) {
+                        let bar = Rect::new(x + 10, y + rect.height() as i32 - 57, rect.width() - 20, 4);
+                        canvas.set_draw_color(theme::rgb(launcher.theme.card_border_color));
+                        canvas.fill_rect(bar)?;
+                        let filled = bar.width() * cleared / total;
+                        if filled > 0 {
+                            canvas.set_draw_color(ui::accent_color());
+                            canvas.fill_rect(Rect::new(bar.x(), bar.y(), filled, bar.height()))?;
+                        }
+                    }
+
+                    let card_badges = launcher.card_badges(sfc);
+                    badge_renderer.draw(&mut canvas, &font, &ui_atlas, rect, &launcher.theme, &launcher.launcher_dir, &card_badges)?;
+
+                    let title = match launcher.bundles.get(sfc) {
+                        Some(bundle) => format!("{} [{} games]", bundle.title, bundle.games.len()),
+                        None => launcher.display_title(sfc),
+                    };
+                    let mut label = if launcher.is_hidden(sfc) {
+                        format!("{} (hidden)", title)
+                    } else {
+                        title
+                    };
+                    if let Some(variants) = launcher.variants.get(sfc) {
+                        label = format!("{} [{} versions]", label, variants.len());
+                    }
+                    let color = theme::rgb(launcher.theme.title_color);
+                    let label_y = y + rect.height() as i32 - 50;
+                    let max_width = rect.width() - 20;
+                    let (label_width, _) = font.size_of(&label)?;
+                    if label_width > max_width && is_selected {
+                        // Too long: the selected card scrolls its title, the others cut it short
+                        let offset = ui::marquee_offset(label_width - max_width, selected_since.1.elapsed());
+                        canvas.set_clip_rect(Rect::new(x + 10, label_y, max_width, 50));
+                        ui::draw_text(&mut canvas, &texture_creator, &font, &label, x + 10 - offset, label_y, color)?;
+                        canvas.set_clip_rect(None);
+                        card_animating = true;
+                    } else {
+                        let label = ui::ellipsize(&font, &label, max_width)?;
+                        let (label_width, _) = font.size_of(&label)?;
+                        let label_x = x + (rect.width() as i32 - label_width as i32) / 2;
+                        ui::draw_text(&mut canvas, &texture_creator, &font, &label, label_x, label_y, color)?;
+                    }
+
+                    if is_selected {
+                        // Drop target while dragging a card, regular selector otherwise
+                        let dragging_other = matches!(&drag, Some(d) if d.dragging && d.from != idx);
+                        canvas.set_draw_color(if dragging_other {
+                            Color::RGB(0, 180, 255)
+                        } else {
+                            theme::rgb(launcher.theme.selector_color)
+                        });
+                        let thickness = 3;
+                        let shake = launcher.bump_offset();
+                        for i in 0..thickness {
+                            let thick_rect = Rect::new(
+                                rect.x() + shake - i,
+                                rect.y() - i,
+                                rect.width() + (i * 2) as u32,
+                                rect.height() + (i * 2) as u32
+                            );
+                            canvas.draw_rect(thick_rect)?;
+                        }
                     }
                 }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Right),
-                    ..
-                } => {
-                    if launcher.selected_game < sfcs.len().saturating_sub(1) {
-                        launcher.selected_game += 1;
-                        println!("Selected: {}", sfcs[launcher.selected_game]);
+
+                if launcher.tab_names().len() > 1 {
+                    for (idx, name) in launcher.tab_names().iter().enumerate() {
+                        let rect = launcher.tab_rect(idx);
+                        let active = idx == launcher.active_tab;
+                        canvas.set_draw_color(if active { ui::accent_color() } else { ui::highlight_color() });
+                        canvas.fill_rect(rect)?;
+                        let color = if active { ui::screen_color() } else { ui::text_color() };
+                        ui::draw_text(&mut canvas, &texture_creator, &font, name, rect.x() + 10, rect.y() + 6, color)?;
                     }
                 }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Return),
-                    ..
-                } => {
-                    if !sfcs.is_empty() {
-                        should_launch = Some(launcher.selected_game);
+
+                if let Some(session) = launcher.last_played() {
+                    let rect = continue_rect();
+                    canvas.set_draw_color(theme::rgb(launcher.theme.card_color));
+                    canvas.fill_rect(rect)?;
+                    canvas.set_draw_color(if launcher.continue_focused {
+                        theme::rgb(launcher.theme.selector_color)
+                    } else {
+                        theme::rgb(launcher.theme.card_border_color)
+                    });
+                    let thickness = if launcher.continue_focused { 3 } else { 1 };
+                    for i in 0..thickness {
+                        canvas.draw_rect(Rect::new(rect.x() - i, rect.y() - i, rect.width() + (i * 2) as u32, rect.height() + (i * 2) as u32))?;
+                    }
+                    if let Some(cover) = covers.by_name(&session.rom) {
+                        let crop = launcher.theme.cover_fit == theme::COVER_CROP;
+                        let (src, dst) = textures::fit(cover.width, cover.height, Rect::new(rect.x() + 6, rect.y() + 6, 36, 54), crop);
+                        canvas.copy(&cover.color, src, dst)?;
                     }
+                    let title = format!("Continue: {}", launcher.display_title(&session.rom));
+                    let saved = launcher
+                        .last_save_time()
+                        .map(|t| chrono::DateTime::<chrono::Local>::from(t).format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "never".to_string());
+                    let details = format!("Last save {} - session {}", saved, stats::format_duration(session.secs));
+                    let color = theme::rgb(launcher.theme.title_color);
+                    ui::draw_text(&mut canvas, &texture_creator, &font, &title, rect.x() + 52, rect.y() + 4, color)?;
+                    ui::draw_text(&mut canvas, &texture_creator, &font, &details, rect.x() + 52, rect.y() + 34, color)?;
+                }
+
+                let page_count = (sfcs.len() + launcher.games_per_page() - 1) / launcher.games_per_page();
+                if page_count > 1 {
+                    let label = format!("Page {}/{}", launcher.current_page() + 1, page_count);
+                    ui::draw_text(&mut canvas, &texture_creator, &font, &label, 620, 100, Color::RGB(255, 255, 255))?;
+                }
+                let hashing = launcher.hashing.pending();
+                if hashing > 0 {
+                    let label = format!("Checking {} ROM(s)...", hashing);
+                    ui::draw_text(&mut canvas, &texture_creator, &font, &label, 620, 130, Color::RGB(200, 200, 200))?;
                 }
-                _ => {}
-            }
-        }
-        
-        let mouse_state = event_pump.mouse_state();
-        let (mouse_x, mouse_y) = (mouse_state.x(), mouse_state.y());
-        
-        launcher.mouse_x = mouse_x;
-        launcher.mouse_y = mouse_y;
-        
-        canvas.set_draw_color(Color::RGB(
-            launcher.launcher_options.background_color.0,
-            launcher.launcher_options.background_color.1,
-            launcher.launcher_options.background_color.2,
-        ));
-        canvas.clear();
-
-        for (idx, sfc) in sfcs.iter().enumerate().take(3) {
-            let col = idx;
-            let x = match col { 0 => 30, 1 => 357, _ => 684 };
-            let y = 143;
-
-            let rect = Rect::new(x, y, BOX_SIZE.0, BOX_SIZE.1);
-            let is_selected = idx == launcher.selected_game;
-            let color_blend = launcher.get_color_blend(idx);
-
-            canvas.set_draw_color(Color::RGB(200, 200, 200));
-            canvas.fill_rect(rect)?;
-            canvas.set_draw_color(Color::RGB(100, 100, 100));
-            canvas.draw_rect(rect)?;
-
-            if let Some(tex) = covers.get_mut(sfc) {
-                let dst = Rect::new(
-                    x + 10,
-                    y + 10,
-                    BOX_SIZE.0 - 20,
-                    BOX_SIZE.1 - 70,
-                );
-                
-                // Apply grayscale effect to unselected ROMs
-                // color_blend: 0.0 = grayscale, 1.0 = full color
-                // When selected, color_blend = 1.0 (full color)
-                // When unselected, color_blend = 0.0 (grayscale)
-                
-                // Simple grayscale: average of RGB creates gray tone
-                // We use equal RGB values for true grayscale
-                let gray_intensity = 128; // Brightness for grayscale (0-255)
                 
-                // Interpolate between gray and full color
-                let r_mod = (gray_intensity as f32 + (255.0 - gray_intensity as f32) * color_blend) as u8;
-                let g_mod = (gray_intensity as f32 + (255.0 - gray_intensity as f32) * color_blend) as u8;
-                let b_mod = (gray_intensity as f32 + (255.0 - gray_intensity as f32) * color_blend) as u8;
+                let pressed = launcher.pointer_inside && mouse_state.left();
+                for (idx, (button, _)) in buttons.iter().enumerate() {
+                    let focused = launcher.focused_button == Some(idx);
+                    button.draw_with_text(&mut canvas, &font, &ui_atlas, launcher.hover_point(), pressed, focused, &texture_creator)?;
+                }
                 
-                tex.set_color_mod(r_mod, g_mod, b_mod);
-                tex.set_alpha_mod(255);
+                if let Some(item) = launcher.current_news() {
+                    let banner = news::banner_rect();
+                    canvas.set_draw_color(ui::panel_color());
+                    canvas.fill_rect(banner)?;
+                    canvas.set_draw_color(ui::accent_color());
+                    canvas.draw_rect(banner)?;
+                    let text = format!("{}   (click to dismiss)", item.headline);
+                    ui::draw_text(&mut canvas, &texture_creator, &font, &text, banner.x() + 10, banner.y() + 3, ui::text_color())?;
+                }
                 
-                canvas.copy(tex, None, dst)?;
+                let lines: Vec<&String> = launcher.plugins.overlay_lines().collect();
+                let top = SCREEN_HEIGHT as i32 - 20 - lines.len() as i32 * ui::LIST_ROW_HEIGHT;
+                for (idx, line) in lines.iter().enumerate() {
+                    let y = top + idx as i32 * ui::LIST_ROW_HEIGHT;
+                    ui::draw_text(&mut canvas, &texture_creator, &font, line, 30, y, Color::RGB(255, 255, 255))?;
+                }
                 
-                // Reset color mod for next frame
-                tex.set_color_mod(255, 255, 255);
+                let running = launcher.game_process.as_ref().map(|g| g.started.elapsed().as_secs()).unwrap_or(0);
+                let mut widget_options = launcher.launcher_options.widgets.clone();
+                widget_options.battery |= battery_saving;
+                let lines = widgets::lines(&widget_options, launcher.stats.playtime_today() + running, &mut battery);
+                widgets::draw(&mut canvas, &texture_creator, &font, widget_options.corner, &lines)?;
             }
 
-            if let Some(f) = &font {
-                let surf = f.render(sfc.trim_end_matches(".sfc"))
-                    .blended(Color::RGB(0, 0, 0))?;
-                let tex = texture_creator.create_texture_from_surface(&surf)?;
-                let q = tex.query();
-                let tr = Rect::new(
-                    x + (BOX_SIZE.0 as i32 - q.width as i32) / 2,
-                    y + BOX_SIZE.1 as i32 - 50,
-                    q.width,
-                    q.height,
-                );
-                canvas.copy(&tex, None, tr)?;
+            if let Some(q) = &quick {
+                let mut preview = None;
+                if let QuickSetting::Shader = q.selected() {
+                    let shader = launcher.game_options().shader;
+                    preview = shader_previews
+                        .entry(shader)
+                        .or_insert_with_key(|shader| {
+                            let path = launcher.shader_preview_path(shader)?;
+                            match texture_creator.load_texture(&path) {
+                                Ok(texture) => Some(texture),
+                                Err(e) => {
+                                    eprintln!("Failed to load shader preview {}: {}", streamer::path(&path), e);
+                                    None
+                                }
+                            }
+                        })
+                        .as_ref();
+                }
+                q.draw(&mut canvas, &texture_creator, Some(&font), preview)?;
             }
-
-            if is_selected {
-                canvas.set_draw_color(Color::RGB(255, 220, 0));
-                let thickness = 3;
-                for i in 0..thickness {
-                    let thick_rect = Rect::new(
-                        rect.x() - i,
-                        rect.y() - i,
-                        rect.width() + (i * 2) as u32,
-                        rect.height() + (i * 2) as u32
-                    );
-                    canvas.draw_rect(thick_rect)?;
+            if let Some(m) = &menu {
+                m.draw(&mut canvas, &texture_creator, Some(&font))?;
+            }
+            if let Some(style) = launcher.pad_style {
+                use glyphs::PadButton;
+                let hints: &[(PadButton, &str)] = if menu.is_some() {
+                    &[(PadButton::South, "Select"), (PadButton::East, "Back")]
+                } else if matches!(&quick, Some(q) if q.is_open()) {
+                    &[(PadButton::South, "Change"), (PadButton::East, "Close")]
+                } else if screen.is_some() {
+                    &[(PadButton::East, "Back")]
+                } else {
+                    &[
+                        (PadButton::South, "Launch"),
+                        (PadButton::North, "Quick settings"),
+                        (PadButton::Select, "Game menu"),
+                        (PadButton::Start, "Menu"),
+                    ]
+                };
+                let (right, y) = (SCREEN_WIDTH as i32 - 60, SCREEN_HEIGHT as i32 - 36);
+                let left = glyphs::draw_hints(&mut canvas, &texture_creator, &font, style, hints, right, y)?;
+                if let Some((percent, charging)) = launcher.pad_battery {
+                    glyphs::draw_battery(&mut canvas, &texture_creator, &font, percent, charging, left - 24, y)?;
+                }
+            }
+            toasts.draw(&mut canvas, &texture_creator, Some(&font))?;
+            if launcher.pad_lost.is_some() && launcher.game_process.is_none() {
+                let lines = ["Controller disconnected", "Reconnect it or press any key to go on with the keyboard"];
+                ui::draw_notice(&mut canvas, &texture_creator, &font, &lines)?;
+            }
+            if let Some(start) = back_hold {
+                let progress = start.elapsed().as_secs_f32() / BACK_HOLD.as_secs_f32();
+                let (cx, cy) = (SCREEN_WIDTH as i32 / 2, SCREEN_HEIGHT as i32 - 90);
+                ui::draw_hold_ring(&mut canvas, cx, cy, 24, progress)?;
+                let label = "Keep holding to quit";
+                let (w, _) = font.size_of(label)?;
+                ui::draw_text(&mut canvas, &texture_creator, &font, label, cx - w as i32 / 2, cy + 32, Color::RGB(255, 255, 255))?;
+            }
+            if audio.is_none() {
+                ui::draw_muted_icon(&mut canvas, SCREEN_WIDTH as i32 - 44, SCREEN_HEIGHT as i32 - 40)?;
+            } else if audio.as_ref().map(|a| a.music_loading()).unwrap_or(false) {
+                let label = "Loading music...";
+                let (w, _) = font.size_of(label)?;
+                let (x, y) = (SCREEN_WIDTH as i32 - 60 - w as i32, SCREEN_HEIGHT as i32 - 72);
+                ui::draw_text(&mut canvas, &texture_creator, &font, label, x, y, Color::RGB(200, 200, 200))?;
+            }
+            
+            // Black while a game runs, then fading back in once it exits
+            let shade = if launcher.game_process.is_some() {
+                Some(255)
+            } else if let Some(l) = &launching {
+                match l.phase {
+                    LaunchPhase::Fade => {
+                        let t = launcher.motion().fade(launcher.motion().progress(l.since, LAUNCH_FADE));
+                        Some((t * 255.0) as u8)
+                    }
+                    LaunchPhase::Chime | LaunchPhase::Hook => Some(255),
+                }
+            } else if let Some(at) = return_fade {
+                let t = launcher.motion().progress(at, RETURN_FADE);
+                if t >= 1.0 {
+                    return_fade = None;
+                    None
+                } else {
+                    Some(((1.0 - launcher.motion().fade(t)) * 255.0) as u8)
+                }
+            } else {
+                None
+            };
+            if let Some(alpha) = shade {
+                canvas.set_blend_mode(BlendMode::Blend);
+                canvas.set_draw_color(Color::RGBA(0, 0, 0, alpha));
+                canvas.fill_rect(None)?;
+                canvas.set_blend_mode(BlendMode::None);
+            }
+            if launching.as_ref().map_or(false, |l| matches!(l.phase, LaunchPhase::Hook)) {
+                let label = "Running pre-launch hook...";
+                let (w, h) = font.size_of(label)?;
+                let (x, y) = ((SCREEN_WIDTH as i32 - w as i32) / 2, (SCREEN_HEIGHT as i32 - h as i32) / 2);
+                ui::draw_text(&mut canvas, &texture_creator, &font, label, x, y, Color::RGB(200, 200, 200))?;
+            }
+            crt_filter.finish(&mut canvas, launcher.theme.crt)?;
+            // Over the CRT filter so the pointer stays sharp; the handheld preset is gamepad-only
+            if let Some((x, y)) = launcher.hover_point().filter(|_| !launcher.handheld) {
+                if let Some(sprite) = ui_atlas.sprite(atlas::CURSOR) {
+                    ui_atlas.draw(&mut canvas, atlas::CURSOR, Rect::new(x, y, sprite.width(), sprite.height()))?;
                 }
             }
+            Ok(())
+        })();
+        if let Err(e) = drawn {
+            render_failures += 1;
+            if render_failures > RENDER_RETRIES {
+                return Err(format!("Rendering keeps failing: {}", e).into());
+            }
+            eprintln!("Rendering failed, recreating textures: {}", e);
+            textures_lost = true;
+            std::thread::sleep(Duration::from_millis(100));
+            continue 'running;
         }
+        render_failures = 0;
 
+        // Read back before present, the back buffer is undefined afterwards
+        if take_screenshot {
+            match save_screenshot(&canvas, &launcher.launcher_dir.join("screenshots")) {
+                Ok(path) => println!("Saved screenshot {}", streamer::path(&path)),
+                Err(e) => eprintln!("Failed to save screenshot: {}", e),
+            }
+        }
+        
         canvas.present();
+        crt_filter.presented(target_frame_time);
+        
+        if std::mem::take(&mut refresh_due) || refresh_checked.elapsed() >= REFRESH_CHECK {
+            refresh_checked = Instant::now();
+            // A failed query keeps the current rate rather than falling back to 60Hz again
+            if let Ok(rate) = query_refresh_rate(&video_subsystem, canvas.window()) {
+                if rate > 0 && rate != refresh_rate {
+                    println!("Display refresh rate changed: {}Hz -> {}Hz", refresh_rate, rate);
+                    refresh_rate = rate;
+                    target_frame_time = Duration::from_secs_f64(1.0 / refresh_rate as f64);
+                }
+            }
+        }
+        
+        // The music opens once a frame is up, so the indicator above has been seen
+        if let Some(audio) = &mut audio {
+            audio.load_music();
+        }
         
         // Handle launching after rendering
-        if let Some(game_idx) = should_launch.take() {
-            // Fade out music and play launch sound
-            sdl2::mixer::Music::fade_out(500)?; // 500ms fade out
-            if let Some(ref sound) = launch_sound {
-                sdl2::mixer::Channel::all().play(&sound, 0)?;
+        // A bundle card asks which of its games to start
+        if !std::mem::take(&mut launch_chosen) {
+            if let Some(game_idx) = should_launch.filter(|&idx| launcher.bundles.contains_key(&sfcs[idx])) {
+                should_launch = None;
+                menu = Some(bundle_menu(&launcher, &sfcs[game_idx]));
             }
+        }
+        if let Some(game_idx) = should_launch.take().filter(|_| launching.is_none()) {
+            let rom = launcher.resolve_variant(&sfcs[game_idx]);
+            launcher.safe_trial = safe_step.take().map(|step| (rom.clone(), step));
+            launcher.session = session_overrides.take().map(|overrides| (rom.clone(), overrides));
+            let window = canvas.window();
+            launcher.fullscreen_display =
+                (window.fullscreen_state() != FullscreenType::Off).then(|| window.display_index().ok()).flatten();
+            let issues = if launch_unchecked {
+                Vec::new()
+            } else {
+                launcher.validate_launch(&rom)
+            };
+            launch_unchecked = false;
             
-            // Small delay to let sound play
-            std::thread::sleep(Duration::from_millis(100));
-            
-            if let Err(e) = launcher.launch_game(&sfcs[game_idx]) {
-                eprintln!("Failed to launch game: {}", e);
-            } else if launcher.launcher_options.onload == 1 {
-                break 'running;
+            if !issues.is_empty() {
+                for issue in &issues {
+                    eprintln!("Launch check failed: {}", issue.message);
+                }
+                menu = Some(launch_issues_menu(&rom, &issues));
+            } else {
+                if let Some(audio) = &mut audio {
+                    audio.fade_out_music(launcher.motion().duration(LAUNCH_FADE).as_millis() as i32);
+                }
+                launching = Some(Launching {
+                    rom,
+                    phase: LaunchPhase::Fade,
+                    since: Instant::now(),
+                    hook: None,
+                });
+            }
+        }
+        
+        if let Some(mut l) = launching.take() {
+            // Some once the game was spawned or the launch failed
+            let mut launched: Option<Result<(), Box<dyn std::error::Error>>> = None;
+            match l.phase {
+                LaunchPhase::Fade if l.since.elapsed() >= launcher.motion().duration(LAUNCH_FADE) => {
+                    if let Some(audio) = &audio {
+                        audio.launch_sound();
+                    }
+                    l.phase = LaunchPhase::Chime;
+                    l.since = Instant::now();
+                    launching = Some(l);
+                }
+                LaunchPhase::Chime if l.since.elapsed() >= LAUNCH_CHIME => match launcher.prepare_launch(&l.rom) {
+                    Ok(Some(hook)) => {
+                        l.phase = LaunchPhase::Hook;
+                        l.hook = Some(hook);
+                        launching = Some(l);
+                    }
+                    Ok(None) => launched = Some(launcher.launch_game(&l.rom)),
+                    Err(e) => launched = Some(Err(e)),
+                },
+                LaunchPhase::Hook => match l.hook.as_ref().map(|hook| hook.try_recv()) {
+                    Some(Err(mpsc::TryRecvError::Empty)) => launching = Some(l),
+                    Some(Ok(Ok(()))) => launched = Some(launcher.launch_game(&l.rom)),
+                    Some(Ok(Err(e))) => launched = Some(Err(e.into())),
+                    _ => launched = Some(Err("the pre-launch hook went away".into())),
+                },
+                _ => launching = Some(l),
+            }
+            match launched {
+                Some(Err(e)) => {
+                    eprintln!("Failed to launch game: {}", e);
+                    toasts.push(format!("Launch cancelled: {}", e));
+                    // Nothing started, so come straight back like after a game
+                    if let Some(audio) = &mut audio {
+                        audio.return_effect();
+                    }
+                    return_fade = Some(Instant::now());
+                }
+                Some(Ok(())) if launcher.launcher_options.onload == 1 => break 'running,
+                // Stop everything and close the device while the game runs, some ALSA
+                // setups give the game no sound while the launcher still holds it
+                Some(Ok(())) => released_music = audio.take().map(|a| a.music_position()),
+                None => {}
             }
         }
         
-        std::thread::sleep(Duration::from_millis(16));
+        let animating = launcher.transitions_running(sfcs.len())
+            || boot.is_some()
+            || screen.is_some()
+            || drag.is_some()
+            || touch.is_some()
+            || launcher.nav_repeat.is_held()
+            || launcher.is_bumping()
+            || return_fade.is_some()
+            || back_hold.is_some()
+            || flip.as_ref().map(|f| f.is_turning()).unwrap_or(false)
+            || launching.is_some()
+            || launcher.hashing.pending() > 0
+            || player.is_some()
+            || recorder.is_some()
+            || card_animating
+            || toasts.is_showing()
+            || quick.as_ref().map(|q| q.is_sliding()).unwrap_or(false)
+            || (launcher.theme.bgtype == theme::BG_VISUALIZER && !battery_saving);
+        if idle {
+            std::thread::sleep(Duration::from_millis(1000 / launcher.launcher_options.unfocused_fps as u64));
+        } else if animating {
+            // Only what is left of the frame: a vsync'd present has already waited for the refresh,
+            // and sleeping a whole frame on top of it would leave input sitting a frame longer
+            let mut frame_time = target_frame_time;
+            if battery_saving {
+                frame_time = frame_time.max(Duration::from_millis(1000 / launcher.launcher_options.battery_fps.max(1) as u64));
+            }
+            std::thread::sleep(frame_time.saturating_sub(frame_start.elapsed()));
+        } else {
+            // Nothing on screen is moving: block until input arrives instead of redrawing every frame
+            let deadline = Instant::now() + IDLE_WAKE;
+            let slice = if launcher.has_gamepad() { PAD_POLL_INTERVAL } else { IDLE_WAKE };
+            while pending_event.is_none() && pending_actions.is_empty() && Instant::now() < deadline {
+                pending_event = event_pump.wait_event_timeout(slice.as_millis() as u32);
+                pending_actions = launcher.handle_gamepad_input();
+            }
+        }
     }
+    
+    launcher.save_selection(&sfcs);
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,233 @@
+//! Per-game manuals: controls references, hack readmes and scanned pages, shown one page at a
+//! time. A game's pages are the files in `launcher/manuals/<rom>/` in name order, else a single
+//! `launcher/manuals/<rom>.<ext>`, plus a readme next to the ROM (`sfcs/<rom>.txt` or `.md`),
+//! which is where hack installs leave the one that came with the patch. Text and Markdown are
+//! wrapped to the screen; images (PNG, WebP, JPEG, GIF) are scaled to fit.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::video::{Window, WindowContext};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::font::Font;
+use crate::textures::{self, COVER_EXTENSIONS};
+use crate::ui::{self, View, ViewAction};
+use crate::{GamepadAction, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const TEXT_EXTENSIONS: &[&str] = &["txt", "md"];
+const TEXT_TOP: i32 = 75;
+const TEXT_ROWS: usize = 17;
+
+fn extension(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase()
+}
+
+fn is_page(path: &Path) -> bool {
+    let ext = extension(path);
+    path.is_file() && (TEXT_EXTENSIONS.contains(&ext.as_str()) || COVER_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Files making up a game's manual, empty when it has none.
+pub fn pages(launcher_dir: &Path, sfc_dir: &Path, rom: &str) -> Vec<PathBuf> {
+    let stem = rom.trim_end_matches(".sfc");
+    let manuals = launcher_dir.join("manuals");
+    let mut pages: Vec<PathBuf> = fs::read_dir(manuals.join(stem))
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| is_page(p)).collect())
+        .unwrap_or_default();
+    pages.sort();
+    if pages.is_empty() {
+        pages.extend(
+            TEXT_EXTENSIONS
+                .iter()
+                .chain(COVER_EXTENSIONS)
+                .map(|ext| manuals.join(format!("{}.{}", stem, ext)))
+                .find(|p| p.is_file()),
+        );
+    }
+    pages.extend(TEXT_EXTENSIONS.iter().map(|ext| sfc_dir.join(format!("{}.{}", stem, ext))).filter(|p| p.is_file()));
+    pages
+}
+
+enum Page<'a> {
+    Text { source: String, lines: Vec<(String, bool)> }, // wrapped on first draw: line, is a heading
+    Image { texture: Texture<'a>, width: u32, height: u32 },
+    Broken(String),
+}
+
+fn load_page<'a>(texture_creator: &'a TextureCreator<WindowContext>, path: &Path) -> Page<'a> {
+    if TEXT_EXTENSIONS.contains(&extension(path).as_str()) {
+        return match fs::read(path) {
+            Ok(data) => Page::Text { source: String::from_utf8_lossy(&data).into_owned(), lines: Vec::new() },
+            Err(e) => Page::Broken(e.to_string()),
+        };
+    }
+    let img = match image::open(path) {
+        Ok(img) => img.to_rgba8(),
+        Err(e) => return Page::Broken(e.to_string()),
+    };
+    let (width, height) = img.dimensions();
+    let mut pixels = img.into_raw();
+    let texture = Surface::from_data(&mut pixels, width, height, width * 4, PixelFormatEnum::RGBA32)
+        .and_then(|surface| texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string()));
+    match texture {
+        Ok(texture) => Page::Image { texture, width, height },
+        Err(e) => Page::Broken(e),
+    }
+}
+
+/// Markdown reduced to what reads well as plain lines: headings stand out, emphasis marks go.
+fn plain_line(line: &str) -> (String, bool) {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return (trimmed.trim_start_matches('#').trim().to_string(), true);
+    }
+    let line = match trimmed.strip_prefix("* ").or_else(|| trimmed.strip_prefix("+ ")) {
+        Some(item) => format!("- {}", item),
+        None => line.to_string(),
+    };
+    (line.replace("**", "").replace("__", "").replace('`', ""), false)
+}
+
+/// Splits `text` into lines no wider than `max_width`, breaking between words where it can.
+fn wrap(font: &Font, text: &str, max_width: u32) -> Result<Vec<String>, String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+        if current.is_empty() || font.size_of(&candidate)?.0 <= max_width {
+            current = candidate;
+        } else {
+            lines.push(std::mem::replace(&mut current, word.to_string()));
+        }
+    }
+    // Blank lines are kept, they separate paragraphs
+    lines.push(current);
+    Ok(lines)
+}
+
+/// Pages through a manual: Left/Right or the shoulder buttons turn pages, Up/Down scroll text.
+pub struct ManualView<'a> {
+    title: String,
+    paths: Vec<PathBuf>,
+    pages: Vec<Option<Page<'a>>>, // loaded when first shown, images stay uploaded while the view is open
+    texture_creator: &'a TextureCreator<WindowContext>,
+    page: usize,
+    scroll: usize,
+}
+
+impl<'a> ManualView<'a> {
+    pub fn new(title: &str, paths: Vec<PathBuf>, texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+        ManualView {
+            title: title.to_string(),
+            texture_creator,
+            pages: paths.iter().map(|_| None).collect(),
+            paths,
+            page: 0,
+            scroll: 0,
+        }
+    }
+
+    fn turn(&mut self, delta: i32) {
+        let last = self.paths.len().saturating_sub(1) as i32;
+        let page = (self.page as i32 + delta).clamp(0, last) as usize;
+        if page != self.page {
+            self.page = page;
+            self.scroll = 0;
+        }
+    }
+
+    fn scroll_by(&mut self, delta: i32) {
+        let lines = match self.pages.get(self.page) {
+            Some(Some(Page::Text { lines, .. })) => lines.len(),
+            _ => 0,
+        };
+        let max = lines.saturating_sub(TEXT_ROWS) as i32;
+        self.scroll = (self.scroll as i32 + delta).clamp(0, max) as usize;
+    }
+}
+
+impl View for ManualView<'_> {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        let Event::KeyDown { keycode: Some(key), .. } = event else {
+            return ViewAction::None;
+        };
+        match *key {
+            Keycode::Left | Keycode::PageUp => self.turn(-1),
+            Keycode::Right | Keycode::PageDown => self.turn(1),
+            Keycode::Up => self.scroll_by(-1),
+            Keycode::Down => self.scroll_by(1),
+            Keycode::Escape => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Left | GamepadAction::PrevTab | GamepadAction::PrevPage => self.turn(-1),
+            GamepadAction::Right | GamepadAction::NextTab | GamepadAction::NextPage => self.turn(1),
+            GamepadAction::Up => self.scroll_by(-1),
+            GamepadAction::Down => self.scroll_by(1),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, &self.title)?;
+        let text_color = ui::text_color();
+        let accent = ui::accent_color();
+
+        let Some(path) = self.paths.get(self.page) else {
+            return Ok(());
+        };
+        let creator = self.texture_creator;
+        let page = self.pages[self.page].get_or_insert_with(|| load_page(creator, path));
+        match page {
+            Page::Text { source, lines } => {
+                if let Some(f) = font {
+                    if lines.is_empty() {
+                        for line in source.lines() {
+                            let (line, heading) = plain_line(line);
+                            for wrapped in wrap(f, &line, SCREEN_WIDTH - 80)? {
+                                lines.push((wrapped, heading));
+                            }
+                        }
+                    }
+                    for (row, (line, heading)) in lines.iter().skip(self.scroll).take(TEXT_ROWS).enumerate() {
+                        let color = if *heading { accent } else { text_color };
+                        ui::draw_text(canvas, texture_creator, f, line, 40, TEXT_TOP + row as i32 * ui::LIST_ROW_HEIGHT, color)?;
+                    }
+                }
+            }
+            Page::Image { texture, width, height } => {
+                let area = Rect::new(30, TEXT_TOP, SCREEN_WIDTH - 60, SCREEN_HEIGHT - TEXT_TOP as u32 - 70);
+                let (src, dst) = textures::fit(*width, *height, area, false);
+                canvas.copy(texture, src, dst)?;
+            }
+            Page::Broken(e) => {
+                if let Some(f) = font {
+                    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    ui::draw_text(canvas, texture_creator, f, &format!("Cannot show {}: {}", name, e), 40, TEXT_TOP, text_color)?;
+                }
+            }
+        }
+
+        if let Some(f) = font {
+            let hint = format!("Page {}/{}   Left/Right: turn page   Up/Down: scroll   Esc: back", self.page + 1, self.paths.len());
+            ui::draw_text(canvas, texture_creator, f, &hint, 30, SCREEN_HEIGHT as i32 - 50, text_color)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::Read;
+
+const USER_AGENT: &str = concat!("smas_launcher/", env!("CARGO_PKG_VERSION"));
+
+pub fn get_json(url: &str) -> Result<serde_json::Value, String> {
+    let body = ureq::get(url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+pub fn download(url: &str) -> Result<Vec<u8>, String> {
+    let response = ureq::get(url)
+        .set("User-Agent", USER_AGENT)
+        .call()
+        .map_err(|e| e.to_string())?;
+
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+pub fn post_json(url: &str, body: &serde_json::Value) -> Result<(), String> {
+    ureq::post(url)
+        .set("User-Agent", USER_AGENT)
+        .set("Content-Type", "application/json")
+        .send_string(&body.to_string())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Percent-encodes a query string parameter.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,94 @@
+use sdl2::rect::Rect;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::cache::Cache;
+use crate::SCREEN_WIDTH;
+
+/// One headline from `<update_server>/news.json`, shaped `{"items": [{"id": "...", "headline": "..."}]}`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewsItem {
+    pub id: String,
+    pub headline: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Feed {
+    items: Vec<NewsItem>,
+}
+
+/// Project news, fetched in the background through the download cache so offline sessions
+/// still show the last feed.
+pub struct News {
+    items: Vec<NewsItem>,
+    rx: Option<Receiver<Result<Vec<NewsItem>, String>>>,
+    error: Option<String>, // why the last fetch failed
+}
+
+fn parse(data: &[u8]) -> Result<Vec<NewsItem>, String> {
+    serde_json::from_slice::<Feed>(data).map(|feed| feed.items).map_err(|e| e.to_string())
+}
+
+impl News {
+    pub fn start(cache: Cache, update_server: Option<&str>) -> Self {
+        let url = update_server.map(|server| format!("{}/news.json", server.trim_end_matches('/')));
+        let items = url
+            .as_ref()
+            .and_then(|url| cache.stale(url))
+            .and_then(|data| parse(&data).ok())
+            .unwrap_or_default();
+
+        let rx = url.map(|url| {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let _ = tx.send(cache.fetch(&url).and_then(|data| parse(&data)));
+            });
+            rx
+        });
+
+        News { items, rx, error: None }
+    }
+
+    /// Picks up the fetched feed once it arrives; a failed fetch keeps the cached one.
+    pub fn poll(&mut self) {
+        let result = match self.rx.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(mpsc::TryRecvError::Empty)) | None => return,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => {
+                self.rx = None;
+                return;
+            }
+        };
+        self.rx = None;
+
+        match result {
+            Ok(items) => {
+                self.items = items;
+                self.error = None;
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch news: {}", e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn is_fetching(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Newest headline the user has not dismissed yet.
+    pub fn current(&self, dismissed: &[String]) -> Option<&NewsItem> {
+        self.items.iter().find(|item| !dismissed.contains(&item.id))
+    }
+}
+
+pub fn banner_rect() -> Rect {
+    Rect::new(30, 556, SCREEN_WIDTH - 60, 34)
+}
@@ -0,0 +1,544 @@
+//! Option rows shared by the settings screens, so every toggle, choice and slider behaves the same:
+//! Left/Right (keys or d-pad) step the value, Enter/A flips a toggle, advances a choice or presses
+//! a button, and the mouse clicks toggles and choices or clicks/drags along a slider to set it.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::font::Font;
+use crate::ui::{MenuAction, View, ViewAction};
+use crate::{ui, GamepadAction, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const CONTROL_WIDTH: u32 = 150; // the control sits at the right end of its row
+const VALUE_WIDTH: u32 = 48; // room for the number after a slider's track
+const TOGGLE_WIDTH: u32 = 40;
+const TRACK_HEIGHT: u32 = 6;
+const KNOB_WIDTH: u32 = 8;
+const CHANNEL_STEP: i32 = 5;
+const INFO_LINE_HEIGHT: i32 = 26; // the lines an `OptionsScreen` shows above its rows
+
+pub enum Control {
+    Toggle(bool),
+    /// The names to pick from and the picked one; stepping wraps around.
+    Choice(Vec<String>, usize),
+    /// A number kept between `min` and `max`, moved by `step` per press.
+    Slider { value: i32, min: i32, max: i32, step: i32, unit: &'static str },
+    /// One channel (0 red, 1 green, 2 blue) of `color`, drawn over that channel's gradient.
+    Channel { color: (u8, u8, u8), channel: usize },
+    /// Typed in through the screen that owns the row, shown after the label.
+    Text(String),
+    Button,
+}
+
+pub struct OptionRow {
+    pub label: String,
+    pub control: Control,
+}
+
+impl OptionRow {
+    pub fn toggle(label: &str, on: bool) -> Self {
+        OptionRow { label: label.to_string(), control: Control::Toggle(on) }
+    }
+
+    pub fn choice(label: &str, choices: Vec<String>, current: usize) -> Self {
+        let current = current.min(choices.len().saturating_sub(1));
+        OptionRow { label: label.to_string(), control: Control::Choice(choices, current) }
+    }
+
+    pub fn slider(label: &str, value: i32, min: i32, max: i32, step: i32) -> Self {
+        let control = Control::Slider { value: value.clamp(min, max), min, max, step, unit: "" };
+        OptionRow { label: label.to_string(), control }
+    }
+
+    pub fn channel(label: &str, color: (u8, u8, u8), channel: usize) -> Self {
+        OptionRow { label: label.to_string(), control: Control::Channel { color, channel: channel.min(2) } }
+    }
+
+    pub fn text(label: &str, value: &str) -> Self {
+        OptionRow { label: label.to_string(), control: Control::Text(value.to_string()) }
+    }
+
+    pub fn button(label: &str) -> Self {
+        OptionRow { label: label.to_string(), control: Control::Button }
+    }
+
+    /// Shown after a slider's number, e.g. "%".
+    pub fn unit(mut self, text: &'static str) -> Self {
+        if let Control::Slider { unit, .. } = &mut self.control {
+            *unit = text;
+        }
+        self
+    }
+
+    /// 0/1 for a toggle, the index for a choice, the number for a slider or channel.
+    pub fn value(&self) -> i32 {
+        match &self.control {
+            Control::Toggle(on) => *on as i32,
+            Control::Choice(_, current) => *current as i32,
+            Control::Slider { value, .. } => *value,
+            Control::Channel { color, channel } => [color.0, color.1, color.2][*channel] as i32,
+            Control::Text(_) | Control::Button => 0,
+        }
+    }
+
+    /// Sets the value, clamped or wrapped into range; true when it changed.
+    fn set(&mut self, new: i32) -> bool {
+        let before = self.value();
+        match &mut self.control {
+            Control::Toggle(on) => *on = new != 0,
+            Control::Choice(choices, current) if !choices.is_empty() => {
+                *current = new.rem_euclid(choices.len() as i32) as usize;
+            }
+            Control::Slider { value, min, max, .. } => *value = new.clamp(*min, *max),
+            Control::Channel { color, channel } => {
+                let new = new.clamp(0, 255) as u8;
+                match channel {
+                    0 => color.0 = new,
+                    1 => color.1 = new,
+                    _ => color.2 = new,
+                }
+            }
+            _ => {}
+        }
+        self.value() != before
+    }
+
+    /// Left/Right; toggles flip whichever way.
+    fn step(&mut self, delta: i32) -> bool {
+        let value = self.value();
+        let step = match self.control {
+            Control::Toggle(_) => return self.set(1 - value),
+            Control::Choice(..) => 1,
+            Control::Slider { step, .. } => step,
+            Control::Channel { .. } => CHANNEL_STEP,
+            Control::Text(_) | Control::Button => return false,
+        };
+        self.set(value + delta * step)
+    }
+
+    fn is_slider(&self) -> bool {
+        matches!(self.control, Control::Slider { .. } | Control::Channel { .. })
+    }
+
+    /// The value under `x` on a slider drawn in `control`.
+    fn value_at(&self, control: Rect, x: i32) -> i32 {
+        let track = track_rect(control);
+        let t = ((x - track.x()) as f32 / track.width().max(1) as f32).clamp(0.0, 1.0);
+        match self.control {
+            // Snapped so dragging lands on the same values Left/Right step through
+            Control::Slider { min, max, step, .. } => {
+                let step = step.max(1);
+                min + ((max - min) as f32 * t / step as f32).round() as i32 * step
+            }
+            _ => (255.0 * t).round() as i32,
+        }
+    }
+}
+
+/// What a list of rows asks its screen to do after handling input.
+pub enum RowInput {
+    None,
+    Changed(usize, i32), // row, new value
+    Pressed(usize),      // a button row
+    Back,
+}
+
+/// A scrolling column of option rows in `area`, one row every `row_height` pixels.
+pub struct OptionList {
+    pub rows: Vec<OptionRow>,
+    pub selected: usize,
+    area: Rect,
+    row_height: i32,
+    dragging: bool, // the left button went down on the selected row's slider
+}
+
+impl OptionList {
+    pub fn new(area: Rect, row_height: i32) -> Self {
+        OptionList { rows: Vec::new(), selected: 0, area, row_height, dragging: false }
+    }
+
+    /// Swaps in freshly built rows, e.g. after the settings they show changed elsewhere.
+    pub fn set_rows(&mut self, rows: Vec<OptionRow>) {
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    /// For lists that move, like a panel sliding in.
+    pub fn set_area(&mut self, area: Rect) {
+        self.area = area;
+    }
+
+    fn visible_rows(&self) -> usize {
+        (self.area.height() as i32 / self.row_height).max(1) as usize
+    }
+
+    fn first_visible(&self) -> usize {
+        (self.selected + 1).saturating_sub(self.visible_rows())
+    }
+
+    pub fn move_selection(&mut self, delta: i32) {
+        if !self.rows.is_empty() {
+            self.selected = (self.selected as i32 + delta).rem_euclid(self.rows.len() as i32) as usize;
+        }
+    }
+
+    /// Where row `idx` is drawn, None while it's scrolled out of view.
+    pub fn row_rect(&self, idx: usize) -> Option<Rect> {
+        let slot = idx.checked_sub(self.first_visible()).filter(|&slot| slot < self.visible_rows())?;
+        let y = self.area.y() + slot as i32 * self.row_height;
+        Some(Rect::new(self.area.x(), y, self.area.width(), (self.row_height - 4).max(1) as u32))
+    }
+
+    fn row_at(&self, x: i32, y: i32) -> Option<usize> {
+        (0..self.rows.len()).find(|&idx| self.row_rect(idx).map_or(false, |rect| rect.contains_point((x, y))))
+    }
+
+    /// The bottom edge of the last visible row, for drawing things underneath the list.
+    pub fn bottom(&self) -> i32 {
+        let shown = self.rows.len().min(self.visible_rows()) as i32;
+        self.area.y() + shown * self.row_height
+    }
+
+    fn changed(&self, idx: usize, changed: bool) -> RowInput {
+        if changed {
+            RowInput::Changed(idx, self.rows[idx].value())
+        } else {
+            RowInput::None
+        }
+    }
+
+    fn step(&mut self, delta: i32) -> RowInput {
+        let idx = self.selected;
+        match self.rows.get_mut(idx) {
+            Some(row) => {
+                let changed = row.step(delta);
+                self.changed(idx, changed)
+            }
+            None => RowInput::None,
+        }
+    }
+
+    /// Enter / A: presses buttons, flips toggles and advances choices.
+    fn activate(&mut self) -> RowInput {
+        let idx = self.selected;
+        match self.rows.get(idx).map(|row| &row.control) {
+            Some(Control::Button) => RowInput::Pressed(idx),
+            Some(Control::Toggle(_) | Control::Choice(..)) => self.step(1),
+            _ => RowInput::None,
+        }
+    }
+
+    fn drag_to(&mut self, x: i32) -> RowInput {
+        let idx = self.selected;
+        let Some(rect) = self.row_rect(idx) else {
+            return RowInput::None;
+        };
+        let row = &mut self.rows[idx];
+        let value = row.value_at(control_rect(rect), x);
+        let changed = row.set(value);
+        self.changed(idx, changed)
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> RowInput {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.move_selection(-1),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => self.move_selection(1),
+            Event::KeyDown { keycode: Some(Keycode::Left), .. } => return self.step(-1),
+            Event::KeyDown { keycode: Some(Keycode::Right), .. } => return self.step(1),
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => return self.activate(),
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return RowInput::Back,
+            Event::MouseWheel { y, .. } => self.move_selection(-y.signum()),
+            Event::MouseMotion { x, y, .. } => {
+                if self.dragging {
+                    return self.drag_to(*x);
+                }
+                if let Some(idx) = self.row_at(*x, *y) {
+                    self.selected = idx;
+                }
+            }
+            Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                let Some(idx) = self.row_at(*x, *y) else {
+                    return RowInput::None;
+                };
+                self.selected = idx;
+                let control = self.row_rect(idx).map(control_rect).unwrap_or(self.area);
+                let row = &self.rows[idx];
+                if row.is_slider() && control.contains_point((*x, *y)) {
+                    self.dragging = true;
+                    return self.drag_to(*x);
+                }
+                // A choice steps back from its left half, like the "<" drawn there
+                if let Control::Choice(..) = row.control {
+                    if control.contains_point((*x, *y)) && *x < control.center().x() {
+                        return self.step(-1);
+                    }
+                }
+                return self.activate();
+            }
+            Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } => self.dragging = false,
+            _ => {}
+        }
+        RowInput::None
+    }
+
+    pub fn handle_gamepad(&mut self, action: &GamepadAction) -> RowInput {
+        match action {
+            GamepadAction::Up => self.move_selection(-1),
+            GamepadAction::Down => self.move_selection(1),
+            GamepadAction::Left => return self.step(-1),
+            GamepadAction::Right => return self.step(1),
+            GamepadAction::Confirm => return self.activate(),
+            GamepadAction::Back => return RowInput::Back,
+            _ => {}
+        }
+        RowInput::None
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        for (idx, row) in self.rows.iter().enumerate() {
+            let Some(rect) = self.row_rect(idx) else {
+                continue;
+            };
+            let selected = idx == self.selected;
+            if selected {
+                canvas.set_draw_color(ui::highlight_color());
+                canvas.fill_rect(rect)?;
+            }
+            canvas.set_draw_color(ui::outline_color());
+            canvas.draw_rect(rect)?;
+            draw_control(canvas, texture_creator, font, row, control_rect(rect))?;
+
+            if let Some(f) = font {
+                let label = match &row.control {
+                    Control::Text(value) if selected => format!("{}: {}_", row.label, value),
+                    Control::Text(value) => format!("{}: {}", row.label, value),
+                    _ => row.label.clone(),
+                };
+                let room = match row.control {
+                    Control::Text(_) | Control::Button => rect.width() - 16,
+                    _ => rect.width().saturating_sub(CONTROL_WIDTH + 24),
+                };
+                let label = ui::ellipsize(f, &label, room)?;
+                let y = rect.y() + (rect.height() as i32 - f.size_of("Ag")?.1 as i32) / 2;
+                ui::draw_text(canvas, texture_creator, f, &label, rect.x() + 8, y, ui::text_color())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a row of an `OptionsScreen` runs when it changes or is pressed. Choices pass -1 or 1 for the
+/// way they were stepped, toggles and buttons always pass 1.
+pub type RowAction = Box<dyn Fn(i32) -> MenuAction>;
+
+/// A full-screen page of option rows whose changes go to the main loop as menu actions, which
+/// rebuilds the page from the saved settings afterwards (see `refresh`).
+pub struct OptionsScreen {
+    title: String,
+    lines: Vec<String>, // shown between the title and the rows
+    actions: Vec<RowAction>, // one per row
+    values: Vec<i32>, // each row's value as built, to tell which way a choice was stepped
+    list: OptionList,
+}
+
+impl OptionsScreen {
+    pub fn new(title: &str) -> Self {
+        OptionsScreen {
+            title: title.to_string(),
+            lines: Vec::new(),
+            actions: Vec::new(),
+            values: Vec::new(),
+            list: OptionList::new(list_area(0), ui::LIST_ROW_HEIGHT),
+        }
+    }
+
+    pub fn line(mut self, text: String) -> Self {
+        self.lines.push(text);
+        self.list.set_area(list_area(self.lines.len()));
+        self
+    }
+
+    pub fn row(mut self, row: OptionRow, action: impl Fn(i32) -> MenuAction + 'static) -> Self {
+        self.values.push(row.value());
+        self.list.rows.push(row);
+        self.actions.push(Box::new(action));
+        self
+    }
+
+    /// A row that runs the same action however it's changed or pressed.
+    pub fn item(self, row: OptionRow, action: MenuAction) -> Self {
+        self.row(row, move |_| action.clone())
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Takes over `page`'s lines and rows, keeping the selection where it was.
+    pub fn refresh(&mut self, page: OptionsScreen) {
+        self.lines = page.lines;
+        self.actions = page.actions;
+        self.values = page.values;
+        self.list.set_area(list_area(self.lines.len()));
+        self.list.set_rows(page.list.rows);
+    }
+
+    fn row_input(&mut self, input: RowInput) -> ViewAction {
+        match input {
+            RowInput::Changed(idx, value) => {
+                let before = std::mem::replace(&mut self.values[idx], value);
+                let step = match &self.list.rows[idx].control {
+                    Control::Choice(choices, _) if (before + 1).rem_euclid(choices.len() as i32) != value => -1,
+                    _ => 1,
+                };
+                ViewAction::Choose(Box::new((self.actions[idx])(step)))
+            }
+            RowInput::Pressed(idx) => ViewAction::Choose(Box::new((self.actions[idx])(1))),
+            RowInput::Back => ViewAction::Close,
+            RowInput::None => ViewAction::None,
+        }
+    }
+}
+
+impl View for OptionsScreen {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        let input = self.list.handle_event(event);
+        self.row_input(input)
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        let input = self.list.handle_gamepad(action);
+        self.row_input(input)
+    }
+
+    fn options(&mut self) -> Option<&mut OptionsScreen> {
+        Some(self)
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, &self.title)?;
+        if let Some(f) = font {
+            for (i, line) in self.lines.iter().enumerate() {
+                let line = ui::ellipsize(f, line, SCREEN_WIDTH - 60)?;
+                ui::draw_text(canvas, texture_creator, f, &line, 30, 70 + i as i32 * INFO_LINE_HEIGHT, ui::text_color())?;
+            }
+        }
+        self.list.draw(canvas, texture_creator, font)?;
+        if let Some(f) = font {
+            let hint = "Left/Right: change   Enter: select   Esc: back";
+            ui::draw_text(canvas, texture_creator, f, hint, 30, SCREEN_HEIGHT as i32 - 40, ui::text_color())?;
+        }
+        Ok(())
+    }
+}
+
+/// Where an `OptionsScreen`'s rows go below `lines` info lines, leaving room for the hint.
+fn list_area(lines: usize) -> Rect {
+    let top = 74 + lines as i32 * INFO_LINE_HEIGHT;
+    let rows = ((SCREEN_HEIGHT as i32 - 50 - top) / ui::LIST_ROW_HEIGHT).max(1);
+    Rect::new(30, top, SCREEN_WIDTH - 60, (rows * ui::LIST_ROW_HEIGHT) as u32)
+}
+
+fn control_rect(row: Rect) -> Rect {
+    let x = row.right() - CONTROL_WIDTH as i32 - 8;
+    Rect::new(x, row.y(), CONTROL_WIDTH, row.height())
+}
+
+fn track_rect(control: Rect) -> Rect {
+    let y = control.y() + (control.height() - TRACK_HEIGHT) as i32 / 2;
+    Rect::new(control.x(), y, CONTROL_WIDTH - VALUE_WIDTH, TRACK_HEIGHT)
+}
+
+fn draw_control(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: Option<&Font>,
+    row: &OptionRow,
+    control: Rect,
+) -> Result<(), String> {
+    let value_x = control.right() - VALUE_WIDTH as i32 + 6;
+    let text_y = |f: &Font| -> Result<i32, String> {
+        Ok(control.y() + (control.height() as i32 - f.size_of("Ag")?.1 as i32) / 2)
+    };
+    match &row.control {
+        Control::Toggle(on) => {
+            let height = control.height().saturating_sub(10).max(8);
+            let switch = Rect::new(control.right() - TOGGLE_WIDTH as i32, control.y() + 5, TOGGLE_WIDTH, height);
+            canvas.set_draw_color(if *on { ui::accent_color() } else { ui::panel_color() });
+            canvas.fill_rect(switch)?;
+            canvas.set_draw_color(ui::outline_color());
+            canvas.draw_rect(switch)?;
+            let knob_x = if *on { switch.right() - height as i32 } else { switch.x() };
+            canvas.set_draw_color(ui::text_color());
+            canvas.fill_rect(Rect::new(knob_x + 2, switch.y() + 2, height - 4, height - 4))?;
+        }
+        Control::Choice(choices, current) => {
+            if let (Some(f), Some(name)) = (font, choices.get(*current)) {
+                let y = text_y(f)?;
+                let (arrow, _) = f.size_of("<")?;
+                ui::draw_text(canvas, texture_creator, f, "<", control.x(), y, ui::accent_color())?;
+                ui::draw_text(canvas, texture_creator, f, ">", control.right() - arrow as i32, y, ui::accent_color())?;
+                let name = ui::ellipsize(f, name, control.width().saturating_sub(arrow * 2 + 8))?;
+                let (w, _) = f.size_of(&name)?;
+                let x = control.x() + (control.width() as i32 - w as i32) / 2;
+                ui::draw_text(canvas, texture_creator, f, &name, x, y, ui::text_color())?;
+            }
+        }
+        Control::Slider { value, min, max, unit, .. } => {
+            let track = track_rect(control);
+            let t = (*value - *min) as f32 / (*max - *min).max(1) as f32;
+            canvas.set_draw_color(ui::outline_color());
+            canvas.fill_rect(track)?;
+            canvas.set_draw_color(ui::accent_color());
+            canvas.fill_rect(Rect::new(track.x(), track.y(), (track.width() as f32 * t) as u32, track.height()))?;
+            draw_knob(canvas, track, t, control)?;
+            if let Some(f) = font {
+                ui::draw_text(canvas, texture_creator, f, &format!("{}{}", value, unit), value_x, text_y(f)?, ui::text_color())?;
+            }
+        }
+        Control::Channel { color, channel } => {
+            let track = track_rect(control);
+            let track = Rect::new(track.x(), control.y() + 6, track.width(), control.height().saturating_sub(12).max(4));
+            for dx in 0..track.width() as i32 {
+                let level = (dx * 255 / (track.width() as i32 - 1).max(1)) as u8;
+                let mut shade = [color.0, color.1, color.2];
+                shade[*channel] = level;
+                canvas.set_draw_color(Color::RGB(shade[0], shade[1], shade[2]));
+                canvas.draw_line((track.x() + dx, track.y()), (track.x() + dx, track.bottom() - 1))?;
+            }
+            canvas.set_draw_color(ui::outline_color());
+            canvas.draw_rect(track)?;
+            draw_knob(canvas, track, row.value() as f32 / 255.0, control)?;
+            if let Some(f) = font {
+                ui::draw_text(canvas, texture_creator, f, &row.value().to_string(), value_x, text_y(f)?, ui::text_color())?;
+            }
+        }
+        Control::Text(_) | Control::Button => {}
+    }
+    Ok(())
+}
+
+/// The handle at `t` (0 to 1) along `track`, as tall as the row's control.
+fn draw_knob(canvas: &mut Canvas<Window>, track: Rect, t: f32, control: Rect) -> Result<(), String> {
+    let x = track.x() + ((track.width() - KNOB_WIDTH) as f32 * t.clamp(0.0, 1.0)) as i32;
+    let knob = Rect::new(x, control.y() + 4, KNOB_WIDTH, control.height().saturating_sub(8).max(4));
+    canvas.set_draw_color(ui::text_color());
+    canvas.fill_rect(knob)?;
+    canvas.set_draw_color(ui::outline_color());
+    canvas.draw_rect(knob)
+}
@@ -0,0 +1,234 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::font::Font;
+use crate::hacks::sanitize_file_name;
+use crate::ui::{self, View, ViewAction};
+use crate::{backup, bps, rom, streamer, GamepadAction, SCREEN_WIDTH};
+
+const VISIBLE_ROWS: usize = 11;
+
+struct PatchEntry {
+    path: PathBuf,
+    name: String,
+    source_crc: Option<u32>,
+    base: Option<String>, // ROM in sfcs/ matching the patch's source checksum
+}
+
+enum PatchMsg {
+    Progress(String, f32),
+    Done(Result<String, String>),
+}
+
+/// Lists BPS patches from `launcher/patches/` with the base ROM each one needs, and applies them.
+pub struct PatchManager {
+    patches: Vec<PatchEntry>,
+    selected: usize,
+    status: String,
+    progress: Option<f32>,
+    rx: Option<Receiver<PatchMsg>>,
+    applying: Option<String>,
+    sfc_dir: PathBuf,
+    backup_dir: PathBuf,
+}
+
+impl PatchManager {
+    /// `roms` is the library's headerless CRC32 -> file name, so opening doesn't hash every ROM again.
+    pub fn new(patch_dir: &Path, sfc_dir: PathBuf, backup_dir: PathBuf, roms: HashMap<u32, String>) -> Self {
+        let _ = fs::create_dir_all(patch_dir);
+
+        let mut patches: Vec<PatchEntry> = fs::read_dir(patch_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|e| e.eq_ignore_ascii_case("bps")).unwrap_or(false))
+            .map(|path| {
+                let source_crc = fs::read(&path).ok().and_then(|data| bps::source_crc32(&data));
+                PatchEntry {
+                    name: path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                    base: source_crc.and_then(|crc| roms.get(&crc).cloned()),
+                    source_crc,
+                    path,
+                }
+            })
+            .collect();
+        patches.sort_by_key(|p| p.name.to_lowercase());
+
+        let status = if patches.is_empty() {
+            format!("Put .bps files in {}", streamer::path(patch_dir))
+        } else {
+            String::new()
+        };
+
+        PatchManager {
+            patches,
+            selected: 0,
+            status,
+            progress: None,
+            rx: None,
+            applying: None,
+            sfc_dir,
+            backup_dir,
+        }
+    }
+
+    fn apply_selected(&mut self) {
+        if self.rx.is_some() {
+            return;
+        }
+        let patch = match self.patches.get(self.selected) {
+            Some(patch) => patch,
+            None => return,
+        };
+        let base = match &patch.base {
+            Some(base) => self.sfc_dir.join(base),
+            None => {
+                self.status = "The base ROM for this patch is not in sfcs/".to_string();
+                return;
+            }
+        };
+
+        let patch_path = patch.path.clone();
+        let output = self.sfc_dir.join(format!("{}.sfc", sanitize_file_name(&patch.name)));
+        let backup_dir = self.backup_dir.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = apply_patch(&patch_path, &base, &output, &backup_dir, &tx);
+            let _ = tx.send(PatchMsg::Done(result));
+        });
+
+        self.applying = Some(patch.name.clone());
+        self.rx = Some(rx);
+        self.progress = Some(0.0);
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let max = self.patches.len().saturating_sub(1) as i32;
+        self.selected = (self.selected as i32 + delta).clamp(0, max) as usize;
+    }
+}
+
+impl View for PatchManager {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.move_selection(-1),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => self.move_selection(1),
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => self.apply_selected(),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.move_selection(-1),
+            GamepadAction::Down => self.move_selection(1),
+            GamepadAction::Confirm => self.apply_selected(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn update(&mut self) -> ViewAction {
+        let mut action = ViewAction::None;
+        while let Some(msg) = self.rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            match msg {
+                PatchMsg::Progress(step, fraction) => {
+                    self.status = step;
+                    self.progress = Some(fraction);
+                }
+                PatchMsg::Done(result) => {
+                    self.rx = None;
+                    self.progress = None;
+                    let hack_name = self.applying.take().unwrap_or_default();
+                    match result {
+                        Ok(file_name) => {
+                            println!("Applied patch {} -> {}", hack_name, file_name);
+                            self.status = format!("Added {} to the library", file_name);
+                            action = ViewAction::HackInstalled(file_name, hack_name, None);
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to apply {}: {}", hack_name, e);
+                            self.status = e;
+                        }
+                    }
+                }
+            }
+        }
+        action
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Patch manager")?;
+
+        let rows: Vec<String> = self
+            .patches
+            .iter()
+            .map(|p| {
+                let base = match (&p.base, p.source_crc) {
+                    (Some(base), _) => base.clone(),
+                    (None, Some(crc)) => match rom::known_rom_name(crc) {
+                        Some(name) => format!("missing {}", name),
+                        None => format!("missing ROM {:08X}", crc),
+                    },
+                    (None, None) => "not a BPS patch".to_string(),
+                };
+                format!("{}  ({})", p.name, base)
+            })
+            .collect();
+        ui::draw_list(canvas, texture_creator, font, &rows, self.selected, 80, VISIBLE_ROWS)?;
+
+        if let Some(fraction) = self.progress {
+            let bar = Rect::new(30, 500, SCREEN_WIDTH - 60, 16);
+            canvas.set_draw_color(Color::RGB(60, 60, 90));
+            canvas.fill_rect(bar)?;
+            canvas.set_draw_color(ui::accent_color());
+            canvas.fill_rect(Rect::new(bar.x(), bar.y(), (bar.width() as f32 * fraction) as u32, bar.height()))?;
+        }
+
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            ui::draw_text(canvas, texture_creator, f, &self.status, 30, 540, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, "Enter: apply   Esc: back", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
+
+fn apply_patch(
+    patch_path: &Path,
+    base: &Path,
+    output: &Path,
+    backup_dir: &Path,
+    tx: &Sender<PatchMsg>,
+) -> Result<String, String> {
+    let _ = tx.send(PatchMsg::Progress("Reading base ROM".to_string(), 0.1));
+    let source = fs::read(base).map_err(|e| e.to_string())?;
+    let patch = fs::read(patch_path).map_err(|e| e.to_string())?;
+
+    let _ = tx.send(PatchMsg::Progress("Applying patch".to_string(), 0.4));
+    let patched = bps::apply(rom::strip_header(&source), &patch)?;
+
+    let _ = tx.send(PatchMsg::Progress("Writing ROM".to_string(), 0.8));
+    backup::backup_file(backup_dir, output, "patching")?;
+    fs::write(output, patched).map_err(|e| e.to_string())?;
+
+    Ok(output.file_name().unwrap_or_default().to_string_lossy().into_owned())
+}
@@ -0,0 +1,305 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Steam sets `SteamDeck=1` for games started on the Deck; fall back to the DMI board name
+/// so desktop mode launches are detected too.
+pub fn is_steam_deck() -> bool {
+    if env::var("SteamDeck").map(|v| v == "1").unwrap_or(false) {
+        return true;
+    }
+
+    let vendor = fs::read_to_string("/sys/devices/virtual/dmi/id/board_vendor").unwrap_or_default();
+    let board = fs::read_to_string("/sys/devices/virtual/dmi/id/board_name").unwrap_or_default();
+    vendor.trim() == "Valve" && matches!(board.trim(), "Jupiter" | "Galileo")
+}
+
+/// True when already running inside a gamescope session (Steam Deck game mode).
+pub fn in_gamescope() -> bool {
+    env::var_os("GAMESCOPE_WAYLAND_DISPLAY").is_some()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sandbox {
+    None,
+    Flatpak,
+    AppImage,
+}
+
+pub fn detect_sandbox() -> Sandbox {
+    if env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists() {
+        Sandbox::Flatpak
+    } else if env::var_os("APPIMAGE").is_some() {
+        Sandbox::AppImage
+    } else {
+        Sandbox::None
+    }
+}
+
+/// `$XDG_DATA_HOME/smas_launcher`, falling back to `~/.local/share/smas_launcher`.
+/// Inside a Flatpak this already points at the per-app data folder.
+pub fn xdg_data_dir() -> Option<PathBuf> {
+    Some(xdg_data_home()?.join("smas_launcher"))
+}
+
+/// `$XDG_DATA_HOME`, falling back to `~/.local/share`.
+pub fn xdg_data_home() -> Option<PathBuf> {
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => Some(PathBuf::from(env::var_os("HOME")?).join(".local").join("share")),
+    }
+}
+
+/// Folder containing the .AppImage file itself, where a bundled smw build is looked up.
+pub fn appimage_dir() -> Option<PathBuf> {
+    let appimage = PathBuf::from(env::var_os("APPIMAGE")?);
+    appimage.parent().map(Path::to_path_buf)
+}
+
+/// Registry key an installer can write so shortcuts started from any working directory
+/// (e.g. system32 for Start Menu entries) still find the data folders.
+#[cfg(target_os = "windows")]
+const REGISTRY_KEYS: &[&str] = &[
+    r"HKCU\Software\SMAS Launcher",
+    r"HKLM\Software\SMAS Launcher",
+];
+
+/// Install directory on Windows: next to the executable when it ships with its `launcher` folder,
+/// otherwise whatever the installer recorded in the registry.
+#[cfg(target_os = "windows")]
+pub fn windows_install_dir() -> Option<PathBuf> {
+    let exe_dir = env::current_exe().ok()?.parent()?.to_path_buf();
+    if exe_dir.join("launcher").is_dir() {
+        return Some(exe_dir);
+    }
+
+    REGISTRY_KEYS
+        .iter()
+        .filter_map(|key| registry_string(key, "InstallDir"))
+        .map(PathBuf::from)
+        .find(|dir| dir.is_dir())
+}
+
+#[cfg(target_os = "windows")]
+fn registry_string(key: &str, value: &str) -> Option<String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = Command::new("reg")
+        .args(["query", key, "/v", value])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // "    InstallDir    REG_SZ    C:\Games\SMAS Launcher"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.split_once("REG_SZ").map(|(_, v)| v.trim().to_string()))
+        .filter(|v| !v.is_empty())
+}
+
+/// `Contents` folder of the .app bundle the launcher was started from, if any.
+pub fn macos_bundle_contents() -> Option<PathBuf> {
+    let exe = env::current_exe().ok()?;
+    let macos_dir = exe.parent()?;
+    let contents = macos_dir.parent()?;
+    let bundle = contents.parent()?;
+
+    let in_bundle = macos_dir.file_name()? == "MacOS"
+        && contents.file_name()? == "Contents"
+        && bundle.extension()? == "app";
+    in_bundle.then(|| contents.to_path_buf())
+}
+
+/// App bundles are read-only once signed, so user data lives in Application Support.
+/// The first run seeds it with the assets shipped in `Contents/Resources/launcher`.
+#[cfg(target_os = "macos")]
+pub fn macos_install_dir() -> Option<PathBuf> {
+    let contents = macos_bundle_contents()?;
+    let data_dir = PathBuf::from(env::var_os("HOME")?)
+        .join("Library")
+        .join("Application Support")
+        .join("SMAS Launcher");
+
+    let bundled_assets = contents.join("Resources").join("launcher");
+    let assets = data_dir.join("launcher");
+    if bundled_assets.is_dir() && !assets.exists() {
+        if let Err(e) = copy_dir_all(&bundled_assets, &assets) {
+            eprintln!("Failed to copy bundled launcher assets: {}", e);
+        }
+    }
+    Some(data_dir)
+}
+
+pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves a file to the system trash so a mistaken delete can be undone from the file manager.
+pub fn move_to_trash(path: &Path) -> Result<(), String> {
+    if cfg!(target_os = "windows") {
+        // The Recycle Bin has no plain file system layout, so let the shell do it
+        let script = format!(
+            "Add-Type -AssemblyName Microsoft.VisualBasic; \
+             [Microsoft.VisualBasic.FileIO.FileSystem]::DeleteFile('{}', 'OnlyErrorDialogs', 'SendToRecycleBin')",
+            path.display().to_string().replace('\'', "''")
+        );
+        let status = std::process::Command::new("powershell")
+            .args(["-NoProfile", "-Command", &script])
+            .status()
+            .map_err(|e| e.to_string())?;
+        return if status.success() { Ok(()) } else { Err(format!("powershell exited with {}", status)) };
+    }
+
+    let file_name = path.file_name().ok_or("Not a file")?.to_string_lossy().to_string();
+    if cfg!(target_os = "macos") {
+        let trash = PathBuf::from(env::var_os("HOME").ok_or("HOME is not set")?).join(".Trash");
+        let target = unique_name(&trash, &file_name);
+        return move_file(path, &trash.join(target));
+    }
+
+    // freedesktop.org trash: the file goes to files/, a .trashinfo in info/ remembers where it came from
+    let trash = xdg_data_home().ok_or("HOME is not set")?.join("Trash");
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files).map_err(|e| e.to_string())?;
+    fs::create_dir_all(&info).map_err(|e| e.to_string())?;
+    let target = unique_name(&files, &file_name);
+    let original = fs::canonicalize(path).map_err(|e| e.to_string())?;
+    let trashinfo = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(&original.to_string_lossy()),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    let info_path = info.join(format!("{}.trashinfo", target));
+    fs::write(&info_path, trashinfo).map_err(|e| e.to_string())?;
+    move_file(path, &files.join(&target)).map_err(|e| {
+        let _ = fs::remove_file(&info_path);
+        e
+    })
+}
+
+/// Shows a desktop notification, which stays visible while a fullscreen game has the screen.
+/// Spawned without waiting, a missing notifier only costs the message.
+pub fn notify(title: &str, body: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "windows") {
+        let script = format!(
+            "Add-Type -AssemblyName System.Windows.Forms; \
+             $n = New-Object System.Windows.Forms.NotifyIcon; \
+             $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+             $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); Start-Sleep -Seconds 10; $n.Dispose()",
+            title.replace('\'', "''"),
+            body.replace('\'', "''")
+        );
+        let mut command = std::process::Command::new("powershell");
+        command.args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", &script]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification \"{}\" with title \"{}\"",
+            body.replace('"', "'"),
+            title.replace('"', "'")
+        );
+        let mut command = std::process::Command::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.args(["--app-name=SMAS Launcher", title, body]);
+        command
+    };
+    command.spawn().map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// The user's language from the locale variables, as a code like "en" or "pt-BR". Windows doesn't
+/// set them, so it is "en" there unless the launcher's language option says otherwise.
+pub fn system_language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| value.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+        .filter(|code| !code.is_empty() && code != "C" && code != "POSIX")
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Logical CPUs of the performance cores on a hybrid CPU, as a list `taskset -c` takes
+/// ("0-15"). Only Linux reports them; None there too on CPUs with a single kind of core.
+pub fn performance_cores() -> Option<String> {
+    let cpus = fs::read_to_string("/sys/devices/cpu_core/cpus").ok()?;
+    Some(cpus.trim().to_string()).filter(|cpus| !cpus.is_empty())
+}
+
+/// `nice` value for a process priority step: below normal, normal, above normal, high.
+pub fn nice_value(priority: i8) -> i8 {
+    match priority {
+        i8::MIN..=-1 => 5,
+        0 => 0,
+        1 => -5,
+        _ => -10,
+    }
+}
+
+/// Windows has no `nice` to start a process through, so there the game is created with the
+/// matching priority class. Elsewhere `nice` in front of the command does it.
+pub fn set_priority(command: &mut std::process::Command, priority: i8) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let class: u32 = match priority {
+            i8::MIN..=-1 => 0x0000_4000, // BELOW_NORMAL_PRIORITY_CLASS
+            0 => 0x0000_0020,            // NORMAL_PRIORITY_CLASS
+            1 => 0x0000_8000,            // ABOVE_NORMAL_PRIORITY_CLASS
+            _ => 0x0000_0080,            // HIGH_PRIORITY_CLASS
+        };
+        command.creation_flags(class);
+    }
+    #[cfg(not(windows))]
+    let _ = (command, priority);
+}
+
+/// `name`, or `name (2)`, `name (3)`... whichever is still free in `dir`.
+fn unique_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) => (stem, format!(".{}", ext)),
+        None => (name, String::new()),
+    };
+    (2..)
+        .map(|n| format!("{} ({}){}", stem, n, ext))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Rename, or copy and delete when the trash is on another file system.
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| e.to_string())?;
+    fs::remove_file(from).map_err(|e| e.to_string())
+}
+
+fn percent_encode(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,157 @@
+//! Optional daily playtime limit (`playtime_limit_mins`). Today's sessions from stats.json plus
+//! the running one count against it; ten minutes before the end a desktop notification reaches the
+//! player in-game, and once it is used up games only start after `playtime_pin` is entered, which
+//! lifts the limit for the rest of the day.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::font::Font;
+use crate::ui::{self, MenuAction, View, ViewAction};
+use crate::GamepadAction;
+
+/// Limits the options menu cycles through, in minutes, 0 = no limit.
+pub const LIMITS: [u32; 7] = [0, 30, 60, 90, 120, 180, 240];
+pub const WARN_SECS: u64 = 10 * 60;
+
+/// Seconds left today, None without a limit.
+pub fn remaining(limit_mins: u32, played_secs: u64) -> Option<u64> {
+    (limit_mins > 0).then(|| (limit_mins as u64 * 60).saturating_sub(played_secs))
+}
+
+/// Asks for the PIN, then hands `then` back to the launcher to carry out.
+pub struct PinView {
+    expected: String,
+    reason: String,
+    then: MenuAction,
+    entered: Vec<u8>,
+    cursor: usize, // digit the gamepad is changing
+    typed: bool, // `entered` holds digits the player put there, not just the starting zero
+    wrong: bool,
+}
+
+impl PinView {
+    pub fn new(expected: &str, reason: &str, then: MenuAction) -> Self {
+        PinView {
+            expected: expected.to_string(),
+            reason: reason.to_string(),
+            then,
+            entered: vec![0],
+            cursor: 0,
+            typed: false,
+            wrong: false,
+        }
+    }
+
+    fn submit(&mut self) -> ViewAction {
+        let entered: String = self.entered.iter().map(|d| (b'0' + d) as char).collect();
+        if entered == self.expected {
+            return ViewAction::PinAccepted(Box::new(self.then.clone()));
+        }
+        self.wrong = true;
+        self.entered = vec![0];
+        self.cursor = 0;
+        self.typed = false;
+        ViewAction::None
+    }
+
+    fn type_digit(&mut self, digit: u8) {
+        // Typed digits replace the gamepad's starting zero
+        if self.wrong || !self.typed {
+            self.entered.clear();
+            self.wrong = false;
+        }
+        self.typed = true;
+        self.entered.push(digit);
+        self.cursor = self.entered.len() - 1;
+    }
+
+    fn step_digit(&mut self, delta: i32) {
+        self.wrong = false;
+        self.typed = true;
+        let digit = &mut self.entered[self.cursor];
+        *digit = (*digit as i32 + delta).rem_euclid(10) as u8;
+    }
+
+    fn move_cursor(&mut self, delta: i32) {
+        self.wrong = false;
+        self.typed = true;
+        if delta > 0 && self.cursor + 1 == self.entered.len() {
+            self.entered.push(0);
+        }
+        self.cursor = (self.cursor as i32 + delta).clamp(0, self.entered.len() as i32 - 1) as usize;
+    }
+}
+
+impl View for PinView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => return self.submit(),
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                self.entered.pop();
+                if self.entered.is_empty() {
+                    self.entered.push(0);
+                    self.typed = false;
+                }
+                self.cursor = self.entered.len() - 1;
+            }
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.step_digit(1),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => self.step_digit(-1),
+            Event::KeyDown { keycode: Some(Keycode::Left), .. } => self.move_cursor(-1),
+            Event::KeyDown { keycode: Some(Keycode::Right), .. } => self.move_cursor(1),
+            Event::TextInput { text, .. } => {
+                for digit in text.chars().filter_map(|c| c.to_digit(10)) {
+                    self.type_digit(digit as u8);
+                }
+            }
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.step_digit(1),
+            GamepadAction::Down => self.step_digit(-1),
+            GamepadAction::Left => self.move_cursor(-1),
+            GamepadAction::Right => self.move_cursor(1),
+            GamepadAction::Confirm => return self.submit(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    /// Digits must arrive as typed.
+    fn wants_raw_keys(&self) -> bool {
+        true
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Enter PIN")?;
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            let accent = ui::accent_color();
+            ui::draw_text(canvas, texture_creator, f, &self.reason, 30, 90, text_color)?;
+            // Digits other than the one being changed stay hidden from onlookers
+            let shown: String = (0..self.entered.len())
+                .map(|idx| if idx == self.cursor { format!("[{}]", self.entered[idx]) } else { " * ".to_string() })
+                .collect();
+            ui::draw_text(canvas, texture_creator, f, &shown, 30, 160, accent)?;
+            if self.wrong {
+                ui::draw_text(canvas, texture_creator, f, "Wrong PIN", 30, 210, Color::RGB(255, 110, 110))?;
+            }
+            ui::draw_text(canvas, texture_creator, f, "Digits or Up/Down/Left/Right   Enter: OK   Esc: cancel", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+//! Out-of-process plugins loaded from `launcher/plugins/<name>/plugin.json`.
+//!
+//! A manifest names the command to start, e.g. `{"name": "Tracker", "command": ["python3", "tracker.py"]}`.
+//! The command runs from the plugin's directory and talks to the launcher with one JSON object
+//! per line. The launcher writes events to its stdin:
+//!
+//! - `{"event": "scan", "games": ["smw.sfc", ...]}` whenever the library is rescanned
+//! - `{"event": "launch", "rom": "smw.sfc"}` and `{"event": "exit", "rom": "smw.sfc", "code": 0}`
+//! - `{"event": "settings"}` when the plugin settings menu is opened
+//! - `{"event": "setting", "id": "..."}` when one of the plugin's settings entries is chosen
+//!
+//! and reads messages from its stdout, all fields optional:
+//!
+//! - `{"overlay": ["line", ...]}` text drawn over the game grid, replacing the previous overlay
+//! - `{"titles": {"smw.sfc": "Super Mario World"}}` card titles for games without a hack name
+//! - `{"settings": [{"id": "...", "label": "..."}]}` entries for the plugin settings menu
+
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+use crate::streamer;
+
+/// Events waiting for a plugin to read them; a plugin that falls further behind misses events.
+const EVENT_QUEUE: usize = 64;
+
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    command: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct PluginMessage {
+    overlay: Option<Vec<String>>,
+    titles: Option<HashMap<String, String>>,
+    settings: Option<Vec<SettingEntry>>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SettingEntry {
+    pub id: String,
+    pub label: String,
+}
+
+struct Plugin {
+    name: String,
+    child: Child,
+    events: Option<SyncSender<String>>, // lines for the writer thread, None once the plugin stopped reading
+    rx: Receiver<PluginMessage>,
+    overlay: Vec<String>,
+    settings: Vec<SettingEntry>,
+}
+
+impl Plugin {
+    fn start(dir: &Path) -> Result<Plugin, String> {
+        let manifest_path = dir.join("plugin.json");
+        let text = fs::read_to_string(&manifest_path).map_err(|e| format!("{}: {}", streamer::path(&manifest_path), e))?;
+        let manifest: Manifest = serde_json::from_str(&text).map_err(|e| format!("{}: {}", streamer::path(&manifest_path), e))?;
+        let program = manifest.command.first().ok_or("Plugin command is empty")?;
+
+        let mut child = Command::new(program)
+            .args(&manifest.command[1..])
+            .current_dir(dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Cannot start {}: {}", manifest.name, e))?;
+
+        let stdout = child.stdout.take().ok_or("No plugin stdout")?;
+        let (tx, rx) = mpsc::channel();
+        let name = manifest.name.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(&line) {
+                    Ok(msg) => {
+                        if tx.send(msg).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => eprintln!("Plugin {}: bad message: {}", name, e),
+                }
+            }
+        });
+
+        // A plugin that stops reading would block a write, so writes happen on a thread of their own
+        let mut stdin = child.stdin.take().ok_or("No plugin stdin")?;
+        let (events, queued) = mpsc::sync_channel::<String>(EVENT_QUEUE);
+        thread::spawn(move || {
+            for line in queued {
+                if writeln!(stdin, "{}", line).and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Plugin {
+            name: manifest.name,
+            events: Some(events),
+            child,
+            rx,
+            overlay: Vec::new(),
+            settings: Vec::new(),
+        })
+    }
+
+    fn send(&mut self, event: &serde_json::Value) {
+        let events = match self.events.as_ref() {
+            Some(events) => events,
+            None => return,
+        };
+        match events.try_send(event.to_string()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => eprintln!("Plugin {} is not reading, dropped an event", self.name),
+            Err(TrySendError::Disconnected(_)) => {
+                // The plugin went away, stop talking to it
+                eprintln!("Plugin {} stopped", self.name);
+                self.events = None;
+            }
+        }
+    }
+}
+
+/// All running plugins plus the state they have reported so far.
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<Plugin>,
+    titles: HashMap<String, String>,
+}
+
+impl PluginHost {
+    pub fn load(plugin_dir: &Path) -> Self {
+        let mut dirs: Vec<_> = fs::read_dir(plugin_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join("plugin.json").is_file())
+            .collect();
+        dirs.sort();
+
+        let plugins = dirs
+            .iter()
+            .filter_map(|dir| match Plugin::start(dir) {
+                Ok(plugin) => {
+                    println!("Loaded plugin: {}", plugin.name);
+                    Some(plugin)
+                }
+                Err(e) => {
+                    eprintln!("Failed to load plugin {}: {}", streamer::path(&dir), e);
+                    None
+                }
+            })
+            .collect();
+
+        PluginHost {
+            plugins,
+            titles: HashMap::new(),
+        }
+    }
+
+    fn broadcast(&mut self, event: serde_json::Value) {
+        for plugin in &mut self.plugins {
+            plugin.send(&event);
+        }
+    }
+
+    pub fn on_scan(&mut self, games: &[String]) {
+        self.broadcast(json!({ "event": "scan", "games": games }));
+    }
+
+    pub fn on_launch(&mut self, rom: &str) {
+        self.broadcast(json!({ "event": "launch", "rom": rom }));
+    }
+
+    pub fn on_exit(&mut self, rom: &str, code: Option<i32>) {
+        self.broadcast(json!({ "event": "exit", "rom": rom, "code": code }));
+    }
+
+    pub fn on_settings(&mut self) {
+        self.broadcast(json!({ "event": "settings" }));
+    }
+
+    pub fn activate_setting(&mut self, plugin_idx: usize, id: &str) {
+        if let Some(plugin) = self.plugins.get_mut(plugin_idx) {
+            plugin.send(&json!({ "event": "setting", "id": id }));
+        }
+    }
+
+    /// Picks up messages the plugins sent since the last frame.
+    pub fn poll(&mut self) {
+        for plugin in &mut self.plugins {
+            while let Ok(msg) = plugin.rx.try_recv() {
+                if let Some(overlay) = msg.overlay {
+                    plugin.overlay = overlay;
+                }
+                if let Some(titles) = msg.titles {
+                    self.titles.extend(titles);
+                }
+                if let Some(settings) = msg.settings {
+                    plugin.settings = settings;
+                }
+            }
+        }
+    }
+
+    pub fn title(&self, rom: &str) -> Option<&str> {
+        self.titles.get(rom).map(String::as_str)
+    }
+
+    pub fn overlay_lines(&self) -> impl Iterator<Item = &String> {
+        self.plugins.iter().flat_map(|p| p.overlay.iter())
+    }
+
+    /// Settings entries of every plugin as (plugin index, plugin name, entry).
+    pub fn settings(&self) -> Vec<(usize, &str, &SettingEntry)> {
+        self.plugins
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, p)| p.settings.iter().map(move |s| (idx, p.name.as_str(), s)))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+}
+
+impl Drop for PluginHost {
+    fn drop(&mut self) {
+        for plugin in &mut self.plugins {
+            // Plugins never outlive the launcher
+            plugin.events = None;
+            let _ = plugin.child.kill();
+            let _ = plugin.child.wait();
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Slide-in panel with the handful of settings people change right before launching.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::anim::Motion;
+use crate::font::Font;
+use crate::option_widgets::{OptionList, OptionRow, RowInput};
+use crate::{textures, ui, GamepadAction, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const PANEL_WIDTH: u32 = 360;
+const ROW_HEIGHT: i32 = 40;
+const SLIDE_SPEED: f32 = 0.2; // share of the panel width moved per frame
+const PREVIEW_SIZE: (u32, u32) = (320, 180);
+
+#[derive(Clone, Copy, Debug)]
+pub enum QuickSetting {
+    Fullscreen,
+    Shader,
+    Volume,
+    Autosave,
+}
+
+pub const SETTINGS: [QuickSetting; 4] = [
+    QuickSetting::Fullscreen,
+    QuickSetting::Shader,
+    QuickSetting::Volume,
+    QuickSetting::Autosave,
+];
+
+pub enum QuickAction {
+    None,
+    Change(QuickSetting, i32), // the row's new value, see `OptionRow::value`
+}
+
+pub struct QuickSettings {
+    list: OptionList,
+    shown: f32, // 0 = off screen, 1 = fully slid in
+    closing: bool,
+    motion: Motion, // from the last update, shapes the slide
+}
+
+impl QuickSettings {
+    /// `rows` has one row per entry of `SETTINGS`, in that order.
+    pub fn open(rows: Vec<OptionRow>) -> Self {
+        let mut quick = QuickSettings {
+            list: OptionList::new(Rect::new(0, 0, 1, 1), ROW_HEIGHT),
+            shown: 0.0,
+            closing: false,
+            motion: Motion::default(),
+        };
+        quick.list.set_rows(rows);
+        quick.list.set_area(quick.rows_area());
+        quick
+    }
+
+    /// Picks up the values after a change.
+    pub fn refresh(&mut self, rows: Vec<OptionRow>) {
+        self.list.set_rows(rows);
+    }
+
+    pub fn close(&mut self) {
+        self.closing = true;
+    }
+
+    /// True while the panel should keep input focus, i.e. until closing starts.
+    pub fn is_open(&self) -> bool {
+        !self.closing
+    }
+
+    pub fn is_sliding(&self) -> bool {
+        self.closing || self.shown < 1.0
+    }
+
+    /// Advances the slide animation; returns false once the panel has fully slid out.
+    pub fn update(&mut self, motion: Motion) -> bool {
+        self.motion = motion;
+        let open = if self.closing {
+            self.shown = (self.shown - motion.step(SLIDE_SPEED)).max(0.0);
+            self.shown > 0.0
+        } else {
+            self.shown = (self.shown + motion.step(SLIDE_SPEED)).min(1.0);
+            true
+        };
+        self.list.set_area(self.rows_area());
+        open
+    }
+
+    pub fn selected(&self) -> QuickSetting {
+        SETTINGS[self.list.selected]
+    }
+
+    fn row_input(&mut self, input: RowInput) -> QuickAction {
+        match input {
+            RowInput::Changed(idx, value) => return QuickAction::Change(SETTINGS[idx], value),
+            RowInput::Back => self.close(),
+            RowInput::Pressed(_) | RowInput::None => {}
+        }
+        QuickAction::None
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> QuickAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Tab), .. } => self.close(),
+            Event::MouseButtonDown { x, .. } if *x < self.panel_rect().x() => self.close(),
+            _ => {
+                let input = self.list.handle_event(event);
+                return self.row_input(input);
+            }
+        }
+        QuickAction::None
+    }
+
+    pub fn handle_gamepad(&mut self, action: &GamepadAction) -> QuickAction {
+        if let GamepadAction::QuickSettings = action {
+            self.close();
+            return QuickAction::None;
+        }
+        let input = self.list.handle_gamepad(action);
+        self.row_input(input)
+    }
+
+    fn panel_rect(&self) -> Rect {
+        let x = SCREEN_WIDTH as i32 - (PANEL_WIDTH as f32 * self.motion.ease(self.shown)) as i32;
+        Rect::new(x, 0, PANEL_WIDTH, SCREEN_HEIGHT)
+    }
+
+    fn rows_area(&self) -> Rect {
+        let panel = self.panel_rect();
+        Rect::new(panel.x() + 15, 70, PANEL_WIDTH - 30, (SETTINGS.len() as i32 * ROW_HEIGHT) as u32)
+    }
+
+    /// `shader_preview` is the screenshot for the current shader, shown while the Shader row is selected.
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+        shader_preview: Option<&Texture>,
+    ) -> Result<(), String> {
+        let panel = self.panel_rect();
+        canvas.set_draw_color(ui::panel_color());
+        canvas.fill_rect(panel)?;
+        canvas.set_draw_color(ui::accent_color());
+        canvas.fill_rect(Rect::new(panel.x(), 0, 2, SCREEN_HEIGHT))?;
+        self.list.draw(canvas, texture_creator, font)?;
+
+        let show_preview = matches!(self.selected(), QuickSetting::Shader);
+        let preview_area = Rect::new(panel.x() + 20, self.list.bottom() + 20, PREVIEW_SIZE.0, PREVIEW_SIZE.1);
+        if show_preview {
+            canvas.set_draw_color(Color::RGB(20, 20, 35));
+            canvas.fill_rect(preview_area)?;
+            if let Some(texture) = shader_preview {
+                let query = texture.query();
+                let (src, dst) = textures::fit(query.width, query.height, preview_area, false);
+                canvas.copy(texture, src, dst)?;
+            }
+            canvas.set_draw_color(ui::outline_color());
+            canvas.draw_rect(preview_area)?;
+        }
+
+        if let Some(f) = font {
+            let x = panel.x() + 20;
+            ui::draw_text(canvas, texture_creator, f, "Quick settings", x, 20, ui::accent_color())?;
+            if show_preview && shader_preview.is_none() {
+                let (text_x, text_y) = (preview_area.x() + 10, preview_area.y() + 10);
+                ui::draw_text(canvas, texture_creator, f, "No preview", text_x, text_y, Color::RGB(180, 180, 180))?;
+            }
+            ui::draw_text(canvas, texture_creator, f, "Left/Right: change", x, SCREEN_HEIGHT as i32 - 40, Color::RGB(180, 180, 180))?;
+        }
+        Ok(())
+    }
+}
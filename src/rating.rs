@@ -0,0 +1,33 @@
+//! Age ratings and content descriptors, set per game from its Content menu and kept in
+//! library.json. With `max_age` set, games rated above it are left off the grid; unrated games
+//! stay visible. Lifting or loosening the limit asks for `playtime_pin` when one is set.
+
+/// Ratings the Content menu cycles through, 0 = unrated.
+pub const AGES: [u8; 6] = [0, 3, 7, 12, 16, 18];
+/// Limits the options menu cycles through, each stricter than the last, 0 = no limit.
+pub const LIMITS: [u8; 6] = [0, 18, 16, 12, 7, 3];
+/// Labels a game can be tagged with.
+pub const DESCRIPTORS: [&str; 6] = ["Violence", "Blood", "Scary", "Crude humor", "Language", "Flashing lights"];
+
+/// Whether a game rated `age` is shown under `max_age`.
+pub fn allowed(age: u8, max_age: u8) -> bool {
+    max_age == 0 || age <= max_age
+}
+
+/// Going from limit `from` to `to` shows games that were hidden before.
+pub fn loosens(from: u8, to: u8) -> bool {
+    from != 0 && (to == 0 || to > from)
+}
+
+pub fn label(age: u8) -> String {
+    match age {
+        0 => "Unrated".to_string(),
+        age => format!("{}+", age),
+    }
+}
+
+/// The value after `current` in `choices`, wrapping around.
+pub fn next(choices: &[u8], current: u8) -> u8 {
+    let idx = choices.iter().position(|&c| c == current).map(|i| i + 1).unwrap_or(0);
+    choices[idx % choices.len()]
+}
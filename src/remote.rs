@@ -0,0 +1,254 @@
+//! Phone-friendly remote control page served over plain HTTP on the local network.
+//!
+//! Browsers pair by entering the PIN shown in the launcher options, which sets a session
+//! cookie. Library and launch requests are forwarded to the main loop through the control API.
+
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::control::{self, ControlRequest};
+
+/// Wrong PINs accepted before pairing is locked until the launcher restarts.
+const MAX_PIN_ATTEMPTS: u32 = 10;
+const MAX_BODY: usize = 4096;
+
+struct Pairing {
+    pin: String,
+    tokens: HashSet<String>,
+    failed_attempts: u32,
+}
+
+pub struct RemoteServer {
+    pub url: String,
+    pub port: u16,
+    pub pin: String,
+}
+
+impl RemoteServer {
+    pub fn start(port: u16, control: Sender<ControlRequest>) -> Result<RemoteServer, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Port {}: {}", port, e))?;
+        let pin = format!("{:04}", random_u64() % 10_000);
+        let url = format!("http://{}:{}", local_ip().unwrap_or_else(|| "localhost".to_string()), port);
+        println!("Remote control page at {}", url);
+
+        let pairing = Arc::new(Mutex::new(Pairing {
+            pin: pin.clone(),
+            tokens: HashSet::new(),
+            failed_attempts: 0,
+        }));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let control = control.clone();
+                let pairing = pairing.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &control, &pairing) {
+                        eprintln!("Remote: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(RemoteServer { url, port, pin })
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    cookie: String,
+    body: String,
+}
+
+fn handle_connection(stream: TcpStream, control: &Sender<ControlRequest>, pairing: &Mutex<Pairing>) -> Result<(), String> {
+    let request = read_request(&stream)?;
+    let paired = session_token(&request.cookie)
+        .map(|token| pairing.lock().unwrap().tokens.contains(token))
+        .unwrap_or(false);
+
+    let (status, content_type, body, cookie) = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8", PAGE.to_string(), None),
+        // Plain text for OBS browser sources, no pairing needed since it only reveals the title
+        ("GET", "/now-playing") => {
+            let status = control::request(control, json!({ "cmd": "status" }));
+            let title = status["title"].as_str().unwrap_or("").to_string();
+            ("200 OK", "text/plain; charset=utf-8", title, None)
+        }
+        ("POST", "/api/pair") => {
+            let pin: Value = serde_json::from_str(&request.body).unwrap_or_default();
+            match pair(pairing, pin["pin"].as_str().unwrap_or("")) {
+                Ok(token) => (
+                    "200 OK",
+                    "application/json",
+                    json!({ "ok": true }).to_string(),
+                    Some(format!("smas_session={}; HttpOnly; SameSite=Strict; Path=/", token)),
+                ),
+                Err(e) => ("403 Forbidden", "application/json", control::error(e).to_string(), None),
+            }
+        }
+        (_, path) if path.starts_with("/api/") && !paired => {
+            ("401 Unauthorized", "application/json", control::error("Not paired").to_string(), None)
+        }
+        ("GET", "/api/games") => api(control, json!({ "cmd": "list" })),
+        ("GET", "/api/status") => api(control, json!({ "cmd": "status" })),
+        ("POST", "/api/launch") => {
+            let body: Value = serde_json::from_str(&request.body).unwrap_or_default();
+            api(control, json!({ "cmd": "launch", "rom": body["rom"] }))
+        }
+        _ => ("404 Not Found", "text/plain", "Not found".to_string(), None),
+    };
+
+    let mut stream = stream;
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if let Some(cookie) = cookie {
+        response.push_str(&format!("Set-Cookie: {}\r\n", cookie));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    stream.write_all(response.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn api(control: &Sender<ControlRequest>, command: Value) -> (&'static str, &'static str, String, Option<String>) {
+    let reply = control::request(control, command);
+    let status = if reply["ok"].as_bool().unwrap_or(false) { "200 OK" } else { "400 Bad Request" };
+    (status, "application/json", reply.to_string(), None)
+}
+
+fn pair(pairing: &Mutex<Pairing>, pin: &str) -> Result<String, String> {
+    let mut pairing = pairing.lock().unwrap();
+    if pairing.failed_attempts >= MAX_PIN_ATTEMPTS {
+        return Err("Too many wrong PINs, restart the launcher to pair".to_string());
+    }
+    if pin != pairing.pin {
+        pairing.failed_attempts += 1;
+        return Err("Wrong PIN".to_string());
+    }
+    let token = format!("{:016x}{:016x}", random_u64(), random_u64());
+    pairing.tokens.insert(token.clone());
+    Ok(token)
+}
+
+fn read_request(stream: &TcpStream) -> Result<HttpRequest, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().ok_or("Empty request")?.to_string();
+    let path = parts.next().ok_or("Missing path")?.to_string();
+
+    let mut cookie = String::new();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "cookie" => cookie = value.trim().to_string(),
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.min(MAX_BODY)];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    Ok(HttpRequest {
+        method,
+        path,
+        cookie,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn session_token(cookie: &str) -> Option<&str> {
+    cookie
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == "smas_session")
+        .map(|(_, value)| value)
+}
+
+fn random_u64() -> u64 {
+    // RandomState is seeded from the OS, good enough for a PIN and session tokens
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    hasher.finish()
+}
+
+/// Address other devices on the LAN can reach us at. Connecting a UDP socket sends nothing,
+/// it only makes the OS pick the outgoing interface.
+fn local_ip() -> Option<String> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>SMAS Launcher</title>
+<style>
+body { font-family: sans-serif; background: #1e1e37; color: #fff; margin: 0; padding: 16px; }
+h1 { color: #ffdc00; font-size: 1.4em; }
+button, input { font-size: 1.1em; padding: 12px; border-radius: 8px; border: none; }
+button { background: #646496; color: #fff; width: 100%; margin: 6px 0; text-align: left; }
+#status { color: #ffdc00; margin: 12px 0; }
+</style>
+</head>
+<body>
+<h1>SMAS Launcher</h1>
+<div id="pair" hidden>
+  <p>Enter the PIN shown under Launcher options.</p>
+  <input id="pin" inputmode="numeric" maxlength="4" placeholder="PIN">
+  <button onclick="pair()">Pair</button>
+</div>
+<div id="status"></div>
+<div id="games"></div>
+<script>
+async function call(path, body) {
+  const res = await fetch(path, body === undefined ? {} : { method: "POST", body: JSON.stringify(body) });
+  return { status: res.status, data: await res.json() };
+}
+async function pair() {
+  const res = await call("/api/pair", { pin: document.getElementById("pin").value });
+  if (res.data.ok) { load(); } else { show(res.data.error); }
+}
+function show(text) { document.getElementById("status").textContent = text || ""; }
+async function launch(rom) {
+  const res = await call("/api/launch", { rom: rom });
+  show(res.data.ok ? "Launching " + rom : res.data.error);
+}
+async function load() {
+  const res = await call("/api/games");
+  document.getElementById("pair").hidden = res.status !== 401;
+  if (!res.data.ok) { show(res.status === 401 ? "" : res.data.error); return; }
+  const list = document.getElementById("games");
+  list.innerHTML = "";
+  for (const game of res.data.games.filter(g => !g.hidden)) {
+    const button = document.createElement("button");
+    button.textContent = game.title;
+    button.onclick = () => launch(game.rom);
+    list.appendChild(button);
+  }
+  const status = await call("/api/status");
+  show(status.data.running ? "Playing " + status.data.running : "");
+}
+load();
+</script>
+</body>
+</html>
+"#;
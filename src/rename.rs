@@ -0,0 +1,101 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::GamepadAction;
+
+/// Letters the gamepad cycles through with Up/Down, since there's no keyboard on a couch.
+const PAD_CHARS: &str = " ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_!'&.,()";
+
+/// Rename screen for one library entry. By default it only sets the name shown on the card;
+/// switching to "file on disk" renames the ROM itself.
+pub struct RenameView {
+    rom: String,
+    name: String,
+    on_disk: bool,
+}
+
+impl RenameView {
+    pub fn new(rom: &str, current_title: &str) -> Self {
+        RenameView {
+            rom: rom.to_string(),
+            name: current_title.to_string(),
+            on_disk: false,
+        }
+    }
+
+    fn finish(&self) -> ViewAction {
+        ViewAction::Rename(self.rom.clone(), self.name.trim().to_string(), self.on_disk)
+    }
+
+    /// Steps the last character through `PAD_CHARS`, starting a new one when the name is empty.
+    fn cycle_last(&mut self, delta: i32) {
+        let chars: Vec<char> = PAD_CHARS.chars().collect();
+        let last = self.name.pop().unwrap_or(' ');
+        let idx = chars.iter().position(|&c| c == last).unwrap_or(0) as i32;
+        self.name.push(chars[(idx + delta).rem_euclid(chars.len() as i32) as usize]);
+    }
+}
+
+impl View for RenameView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => return self.finish(),
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => {
+                self.name.pop();
+            }
+            Event::KeyDown { keycode: Some(Keycode::Tab), .. } => self.on_disk = !self.on_disk,
+            Event::TextInput { text, .. } => self.name.push_str(text),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.cycle_last(-1),
+            GamepadAction::Down => self.cycle_last(1),
+            GamepadAction::Right => self.name.push('A'),
+            GamepadAction::Left => {
+                self.name.pop();
+            }
+            GamepadAction::ContextMenu => self.on_disk = !self.on_disk,
+            GamepadAction::Confirm => return self.finish(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Rename")?;
+
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            let accent = ui::accent_color();
+            ui::draw_text(canvas, texture_creator, f, &format!("File: {}", self.rom), 30, 90, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, &format!("New name: {}_", self.name), 30, 150, accent)?;
+            let mode = if self.on_disk {
+                "Renames the file on disk, along with its cover and settings"
+            } else {
+                "Changes the name shown in the launcher, the file stays as it is"
+            };
+            ui::draw_text(canvas, texture_creator, f, mode, 30, 210, text_color)?;
+            if !self.on_disk {
+                ui::draw_text(canvas, texture_creator, f, "Leave empty to go back to the original name", 30, 250, text_color)?;
+            }
+            ui::draw_text(canvas, texture_creator, f, "Enter: save   Tab: name / file on disk   Esc: cancel", 30, 580, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, "Pad: Up/Down letter, Right add, Left delete, Select mode", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
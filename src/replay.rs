@@ -0,0 +1,207 @@
+//! Input recording and playback for testing the UI, started with the hidden `--record <file>` and
+//! `--replay <file>` flags. A recording is one JSON object per line: the main loop frame it came in
+//! on and the input, with keyboard and mouse events stored by value so they can be rebuilt for SDL.
+//! Replays go by frame count rather than the clock, so a slow frame can't shift input onto the wrong one.
+
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{MouseButton, MouseState};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use crate::GamepadAction;
+
+/// Frames a replay lingers after its last input before the launcher quits.
+const REPLAY_TAIL: u64 = 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Input {
+    Key { down: bool, key: String, keymod: u16, repeat: bool },
+    Text(String),
+    MouseMove { x: i32, y: i32 },
+    MouseButton { down: bool, button: u8, x: i32, y: i32, clicks: u8 },
+    Pad(GamepadAction),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Recorded {
+    frame: u64,
+    input: Input,
+}
+
+fn button_number(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::X1 => 4,
+        MouseButton::X2 => 5,
+        MouseButton::Unknown => 0,
+    }
+}
+
+fn button_from_number(number: u8) -> MouseButton {
+    match number {
+        1 => MouseButton::Left,
+        2 => MouseButton::Middle,
+        3 => MouseButton::Right,
+        4 => MouseButton::X1,
+        5 => MouseButton::X2,
+        _ => MouseButton::Unknown,
+    }
+}
+
+/// Keyboard, mouse and touch events are what a replay supplies; window and quit events still come from SDL.
+pub fn is_user_input(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::KeyDown { .. }
+            | Event::KeyUp { .. }
+            | Event::TextInput { .. }
+            | Event::MouseMotion { .. }
+            | Event::MouseButtonDown { .. }
+            | Event::MouseButtonUp { .. }
+            | Event::MouseWheel { .. }
+            | Event::FingerDown { .. }
+            | Event::FingerMotion { .. }
+            | Event::FingerUp { .. }
+    )
+}
+
+pub struct Recorder {
+    file: File,
+    frame: u64, // main loop iterations so far
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        println!("Recording input to {}", path.display());
+        Ok(Recorder { file, frame: 0 })
+    }
+
+    /// Called once at the start of every main loop iteration, before any input of it is recorded.
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    fn write(&mut self, input: Input) {
+        let line = Recorded { frame: self.frame, input };
+        // One line at a time so a crash still leaves a usable recording
+        let result = serde_json::to_string(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|json| writeln!(self.file, "{}", json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Failed to record input: {}", e);
+        }
+    }
+
+    /// Touch and wheel input isn't recorded, the rest of the UI is reachable without it.
+    pub fn record_event(&mut self, event: &Event) {
+        let input = match event {
+            Event::KeyDown { keycode: Some(key), keymod, repeat, .. } | Event::KeyUp { keycode: Some(key), keymod, repeat, .. } => {
+                Input::Key {
+                    down: matches!(event, Event::KeyDown { .. }),
+                    key: key.name(),
+                    keymod: keymod.bits(),
+                    repeat: *repeat,
+                }
+            }
+            Event::TextInput { text, .. } => Input::Text(text.clone()),
+            Event::MouseMotion { x, y, .. } => Input::MouseMove { x: *x, y: *y },
+            Event::MouseButtonDown { mouse_btn, x, y, clicks, .. } | Event::MouseButtonUp { mouse_btn, x, y, clicks, .. } => {
+                Input::MouseButton {
+                    down: matches!(event, Event::MouseButtonDown { .. }),
+                    button: button_number(*mouse_btn),
+                    x: *x,
+                    y: *y,
+                    clicks: *clicks,
+                }
+            }
+            _ => return,
+        };
+        self.write(input);
+    }
+
+    pub fn record_action(&mut self, action: &GamepadAction) {
+        self.write(Input::Pad(action.clone()));
+    }
+}
+
+/// Plays a recording back on the frames it was recorded on.
+pub struct Player {
+    inputs: Vec<Recorded>,
+    next: usize,
+    frame: u64, // main loop iterations so far, counted by `due`
+}
+
+impl Player {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut inputs = Vec::new();
+        for (idx, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let recorded: Recorded = serde_json::from_str(line).map_err(|e| format!("line {}: {}", idx + 1, e))?;
+            inputs.push(recorded);
+        }
+        println!("Replaying {} inputs from {}", inputs.len(), path.display());
+        Ok(Player { inputs, next: 0, frame: 0 })
+    }
+
+    /// Inputs recorded on this frame, as SDL events and gamepad actions. Called once at the start of
+    /// every main loop iteration, like `Recorder::next_frame`.
+    pub fn due(&mut self) -> (Vec<Event>, Vec<GamepadAction>) {
+        self.frame += 1;
+        let (mut events, mut actions) = (Vec::new(), Vec::new());
+        while let Some(recorded) = self.inputs.get(self.next).filter(|r| r.frame <= self.frame) {
+            self.next += 1;
+            match &recorded.input {
+                Input::Pad(action) => actions.push(action.clone()),
+                input => events.extend(to_event(input)),
+            }
+        }
+        (events, actions)
+    }
+
+    /// True once every input was played and the tail has passed.
+    pub fn finished(&self) -> bool {
+        let last = self.inputs.last().map(|r| r.frame).unwrap_or(0);
+        self.next >= self.inputs.len() && self.frame >= last + REPLAY_TAIL
+    }
+}
+
+fn to_event(input: &Input) -> Option<Event> {
+    let event = match input {
+        Input::Key { down, key, keymod, repeat } => {
+            let keycode = Some(Keycode::from_name(key)?);
+            let keymod = Mod::from_bits_truncate(*keymod);
+            if *down {
+                Event::KeyDown { timestamp: 0, window_id: 0, keycode, scancode: None, keymod, repeat: *repeat }
+            } else {
+                Event::KeyUp { timestamp: 0, window_id: 0, keycode, scancode: None, keymod, repeat: *repeat }
+            }
+        }
+        Input::Text(text) => Event::TextInput { timestamp: 0, window_id: 0, text: text.clone() },
+        Input::MouseMove { x, y } => Event::MouseMotion {
+            timestamp: 0,
+            window_id: 0,
+            which: 0,
+            mousestate: MouseState::from_sdl_state(0),
+            x: *x,
+            y: *y,
+            xrel: 0,
+            yrel: 0,
+        },
+        Input::MouseButton { down, button, x, y, clicks } => {
+            let mouse_btn = button_from_number(*button);
+            if *down {
+                Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn, clicks: *clicks, x: *x, y: *y }
+            } else {
+                Event::MouseButtonUp { timestamp: 0, window_id: 0, which: 0, mouse_btn, clicks: *clicks, x: *x, y: *y }
+            }
+        }
+        Input::Pad(_) => return None,
+    };
+    Some(event)
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Headerless CRC32 of ROM dumps the launcher knows how to run.
+const KNOWN_ROMS: &[(u32, &str)] = &[
+    (0xB19E_D489, "Super Mario World (USA)"),
+];
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Drops the 512-byte copier header some dumps carry.
+pub fn strip_header(data: &[u8]) -> &[u8] {
+    if data.len() % 1024 == 512 {
+        &data[512..]
+    } else {
+        data
+    }
+}
+
+pub fn rom_crc32(path: &Path) -> io::Result<u32> {
+    let data = fs::read(path)?;
+    Ok(crc32(strip_header(&data)))
+}
+
+pub fn known_rom_name(crc: u32) -> Option<&'static str> {
+    KNOWN_ROMS.iter().find(|(c, _)| *c == crc).map(|(_, name)| *name)
+}
+
+/// Titles of the games in `KNOWN_ROMS` by language code, for library entries to show on cards.
+/// Every language the title language option offers has one; the European releases kept the English title.
+const KNOWN_TITLES: &[(u32, &[(&str, &str)])] = &[(
+    0xB19E_D489,
+    &[
+        ("en", "Super Mario World"),
+        ("ja", "Super Mario World: Super Mario Bros. 4"),
+        ("de", "Super Mario World"),
+        ("fr", "Super Mario World"),
+        ("es", "Super Mario World"),
+        ("it", "Super Mario World"),
+    ],
+)];
+
+/// The game's title in each language the table above has, empty for ROMs it doesn't list.
+pub fn known_titles(crc: u32) -> HashMap<String, String> {
+    KNOWN_TITLES
+        .iter()
+        .find(|(c, _)| *c == crc)
+        .map(|(_, titles)| titles.iter().map(|(code, title)| (code.to_string(), title.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// ROMs the player vouched for from a launch warning, kept in `launcher/known_roms.json` as
+/// `{"<CRC32 in hex>": "<file name>"}`. Dumps and extractions the table above doesn't list (the
+/// All-Stars games split out of smas.sfc vary with the tool) stop warning once confirmed.
+pub fn trusted_roms(launcher_dir: &Path) -> HashMap<u32, String> {
+    let text = fs::read_to_string(launcher_dir.join("known_roms.json")).unwrap_or_default();
+    let map: HashMap<String, String> = serde_json::from_str(&text).unwrap_or_default();
+    map.into_iter().filter_map(|(crc, name)| u32::from_str_radix(&crc, 16).ok().map(|crc| (crc, name))).collect()
+}
+
+pub fn trust_rom(launcher_dir: &Path, crc: u32, name: &str) -> Result<(), String> {
+    let mut map: HashMap<String, String> =
+        trusted_roms(launcher_dir).into_iter().map(|(crc, name)| (format!("{:08X}", crc), name)).collect();
+    map.insert(format!("{:08X}", crc), name.to_string());
+    let content = serde_json::to_string_pretty(&map).map_err(|e| e.to_string())?;
+    fs::write(launcher_dir.join("known_roms.json"), content).map_err(|e| e.to_string())
+}
+
+/// Offset of the internal SNES header in a headerless ROM, trying LoROM then HiROM and picking
+/// the one whose checksum/complement pair is consistent.
+fn header_base(data: &[u8]) -> Option<usize> {
+    [0x7FC0usize, 0xFFC0].into_iter().find(|&base| {
+        data.len() >= base + 0x20 && {
+            let complement = u16::from_le_bytes([data[base + 0x1C], data[base + 0x1D]]);
+            let checksum = u16::from_le_bytes([data[base + 0x1E], data[base + 0x1F]]);
+            complement ^ checksum == 0xFFFF
+        }
+    })
+}
+
+/// Whether the checksum in the internal header matches the data; None without a readable header.
+/// A mismatch usually means a bad dump or a file damaged after patching.
+pub fn checksum_matches(data: &[u8]) -> Option<bool> {
+    let data = strip_header(data);
+    let base = header_base(data)?;
+    let stored = u16::from_le_bytes([data[base + 0x1E], data[base + 0x1F]]);
+    // Sizes that aren't a power of two have their last part mirrored up to the next one, as on the cart
+    let mut size = 1;
+    while size * 2 <= data.len() {
+        size *= 2;
+    }
+    let sum = |bytes: &[u8]| bytes.iter().fold(0u32, |sum, &b| sum.wrapping_add(b as u32));
+    let mut total = sum(&data[..size]);
+    let rest = &data[size..];
+    if !rest.is_empty() {
+        total = total.wrapping_add(sum(rest).wrapping_mul((size / rest.len()) as u32));
+    }
+    Some(total as u16 == stored)
+}
+
+/// Region from the internal SNES header.
+pub fn header_region(data: &[u8]) -> Option<&'static str> {
+    let data = strip_header(data);
+    let base = header_base(data)?;
+
+    Some(match data[base + 0x19] {
+        0x00 => "Japan",
+        0x01 => "USA",
+        0x02 => "Europe",
+        0x03 => "Sweden",
+        0x06 => "France",
+        0x07 => "Netherlands",
+        0x08 => "Spain",
+        0x09 => "Germany",
+        0x0A => "Italy",
+        0x0D => "Korea",
+        _ => "Other region",
+    })
+}
@@ -0,0 +1,173 @@
+//! "Launch with options": settings for a single run of a game. They go into smw.ini and onto the
+//! command line for that launch only; the next launch writes the usual settings again.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::path::PathBuf;
+
+use crate::compat;
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::GamepadAction;
+
+pub const SAVE_SLOTS: u8 = 4; // the usual save plus three alternates
+const FULLSCREEN_MODES: [&str; 3] = ["Windowed", "Fullscreen", "Fullscreen desktop"]; // smw.ini Fullscreen 0-2
+
+/// One run's changes to how a game starts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overrides {
+    pub fullscreen: Option<u8>, // smw.ini Fullscreen for this run, None = as configured
+    pub save_slot: u8, // 0 = the usual save, else an alternate kept in saves/slots/, see sram.rs
+    pub record_replay: bool, // SavePlaythrough, so the run can be replayed afterwards
+    pub args: String, // extra command line arguments, split on whitespace
+    pub load_state: Option<PathBuf>, // savestate smw starts from, see compat::can_load_state
+}
+
+impl Overrides {
+    /// smw.ini keys and values this run sets on top of the game's settings.
+    pub fn ini_values(&self) -> Vec<(&'static str, String)> {
+        let mut values = Vec::new();
+        if let Some(mode) = self.fullscreen {
+            values.push(("Fullscreen", mode.to_string()));
+        }
+        if self.record_replay {
+            values.push(("SavePlaythrough", "1".to_string()));
+        }
+        values
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        let mut args: Vec<String> = self.args.split_whitespace().map(str::to_string).collect();
+        if let Some(state) = &self.load_state {
+            args.push(compat::LOAD_STATE_FLAG.to_string());
+            args.push(state.to_string_lossy().into_owned());
+        }
+        args
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Fullscreen,
+    SaveSlot,
+    Replay,
+    Args,
+    Launch,
+}
+
+const FIELDS: [Field; 5] = [Field::Fullscreen, Field::SaveSlot, Field::Replay, Field::Args, Field::Launch];
+
+/// The one-off launch dialog for a card.
+pub struct OverridesView {
+    title: String,
+    rom: String,
+    overrides: Overrides,
+    selected: usize,
+}
+
+impl OverridesView {
+    pub fn new(title: &str, rom: &str) -> Self {
+        OverridesView { title: title.to_string(), rom: rom.to_string(), overrides: Overrides::default(), selected: 0 }
+    }
+
+    fn label(&self, field: Field) -> String {
+        let o = &self.overrides;
+        match field {
+            Field::Fullscreen => {
+                let mode = o.fullscreen.map(|m| FULLSCREEN_MODES[m as usize]).unwrap_or("As configured");
+                format!("Display: {}", mode)
+            }
+            Field::SaveSlot => match o.save_slot {
+                0 => "Save: Usual save".to_string(),
+                slot => format!("Save: Alternate slot {}", slot),
+            },
+            Field::Replay => format!("Record replay: {}", if o.record_replay { "On" } else { "Off" }),
+            Field::Args => format!("Extra arguments: {}", o.args),
+            Field::Launch => "Launch".to_string(),
+        }
+    }
+
+    fn adjust(&mut self, delta: i32) {
+        let o = &mut self.overrides;
+        match FIELDS[self.selected] {
+            Field::Fullscreen => {
+                // None, then each mode
+                let current = o.fullscreen.map(|m| m as i32 + 1).unwrap_or(0);
+                let next = (current + delta).rem_euclid(FULLSCREEN_MODES.len() as i32 + 1);
+                o.fullscreen = if next == 0 { None } else { Some(next as u8 - 1) };
+            }
+            Field::SaveSlot => o.save_slot = (o.save_slot as i32 + delta).rem_euclid(SAVE_SLOTS as i32) as u8,
+            Field::Replay => o.record_replay = !o.record_replay,
+            Field::Args | Field::Launch => {}
+        }
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        self.selected = (self.selected as i32 + delta).rem_euclid(FIELDS.len() as i32) as usize;
+    }
+
+    fn launch(&self) -> ViewAction {
+        ViewAction::LaunchWith(self.rom.clone(), Box::new(self.overrides.clone()))
+    }
+}
+
+impl View for OverridesView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        let editing_args = FIELDS[self.selected] == Field::Args;
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => self.move_selection(-1),
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => self.move_selection(1),
+            Event::KeyDown { keycode: Some(Keycode::Left), .. } => self.adjust(-1),
+            Event::KeyDown { keycode: Some(Keycode::Right), .. } => self.adjust(1),
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => return self.launch(),
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } if editing_args => {
+                self.overrides.args.pop();
+            }
+            Event::TextInput { text, .. } if editing_args => self.overrides.args.push_str(text),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Up => self.move_selection(-1),
+            GamepadAction::Down => self.move_selection(1),
+            GamepadAction::Left => self.adjust(-1),
+            GamepadAction::Right => self.adjust(1),
+            GamepadAction::Confirm => return self.launch(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    /// Typed arguments must arrive as typed.
+    fn wants_raw_keys(&self) -> bool {
+        FIELDS[self.selected] == Field::Args
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, &format!("Launch {} with options", self.title))?;
+        let mut rows: Vec<String> = FIELDS.iter().map(|&f| self.label(f)).collect();
+        if FIELDS[self.selected] == Field::Args {
+            rows[self.selected].push('_');
+        }
+        ui::draw_list(canvas, texture_creator, font, &rows, self.selected, 90, FIELDS.len())?;
+
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            ui::draw_text(canvas, texture_creator, f, "Only this launch, the game's settings stay as they are", 30, 280, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, "Left/Right: change   Enter: launch   Esc: cancel", 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+//! Battery save checks. smw keeps a game's save RAM as `saves/<rom>.srm`. Formats are told apart
+//! by size; Super Mario World (and hacks built on it) stores three slots twice, each copy with a
+//! checksum the game uses to pick a good copy. A slot where neither copy passes but one still holds
+//! data is damaged: the game would show it as an empty file and overwrite it on the next save.
+//! Alternate save slots for "Launch with options" live in `saves/slots/<rom>.<slot>.srm` and are
+//! swapped in for one run, the usual save waiting as `saves/slots/<rom>.main<slot>.srm` meanwhile.
+//! Savestates are the `.sav` files smw writes next to the saves, see `latest_state`.
+//! The saves folder itself can move (`saves_dir` in launcher.json), `migrate` carries it along
+//! when the setting changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const SMW_SIZE: usize = 0x800;
+const SMW_SLOT_SIZE: usize = 0x8F; // 0x8D bytes of progress, then the checksum
+const SMW_SLOTS: usize = 3;
+const SMW_BACKUP_OFFSET: usize = 0x1AD; // where the second copy of the three slots starts
+const SMW_CHECKSUM_TOTAL: u16 = 0x5A5A; // bytes plus checksum add up to this
+const SMW_EXITS_OFFSET: usize = 0x8C; // last byte of the progress, the exit count on the file select screen
+
+pub fn save_path(saves_dir: &Path, rom: &str) -> PathBuf {
+    saves_dir.join(format!("{}.srm", stem(rom)))
+}
+
+fn slots_dir(saves_dir: &Path) -> PathBuf {
+    saves_dir.join("slots")
+}
+
+fn stem(rom: &str) -> &str {
+    rom.trim_end_matches(".sfc")
+}
+
+/// Puts alternate save `slot` in place of a game's usual save for one run.
+pub fn use_slot(saves_dir: &Path, rom: &str, slot: u8) -> Result<(), String> {
+    let dir = slots_dir(saves_dir);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let main = save_path(saves_dir, rom);
+    if main.exists() {
+        fs::rename(&main, dir.join(format!("{}.main{}.srm", stem(rom), slot))).map_err(|e| e.to_string())?;
+    }
+    let alternate = dir.join(format!("{}.{}.srm", stem(rom), slot));
+    if alternate.exists() {
+        fs::copy(&alternate, &main).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Undoes `use_slot` once the game has exited: what it saved goes back to the slot and the
+/// usual save returns.
+pub fn end_slot(saves_dir: &Path, rom: &str, slot: u8) -> Result<(), String> {
+    let dir = slots_dir(saves_dir);
+    let main = save_path(saves_dir, rom);
+    if main.exists() {
+        fs::rename(&main, dir.join(format!("{}.{}.srm", stem(rom), slot))).map_err(|e| e.to_string())?;
+    }
+    let parked = dir.join(format!("{}.main{}.srm", stem(rom), slot));
+    if parked.exists() {
+        fs::rename(&parked, &main).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Finishes alternate slot runs the launcher didn't see exit, e.g. after it was killed mid-game.
+pub fn recover_slots(saves_dir: &Path) {
+    for entry in fs::read_dir(slots_dir(saves_dir)).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((rom_stem, slot)) = name.strip_suffix(".srm").and_then(|n| n.rsplit_once(".main")) else {
+            continue;
+        };
+        let Ok(slot) = slot.parse::<u8>() else {
+            continue;
+        };
+        let rom = format!("{}.sfc", rom_stem);
+        match end_slot(saves_dir, &rom, slot) {
+            Ok(()) => println!("Put back the usual save for {} after an unfinished slot {} run", rom, slot),
+            Err(e) => eprintln!("Failed to put back the usual save for {}: {}", rom, e),
+        }
+    }
+}
+
+/// Newest savestate of a game and when it was written. smw names states after the ROM
+/// (`<rom>.<n>.sav`), older builds only `save<n>.sav`, which belong to whatever ran last and so
+/// only count for the main game.
+pub fn latest_state(saves_dir: &Path, rom: &str) -> Option<(PathBuf, SystemTime)> {
+    let prefix = format!("{}.", stem(rom));
+    let shared = rom == "smw.sfc";
+    fs::read_dir(saves_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.ends_with(".sav") && (name.starts_with(&prefix) || (shared && name.starts_with("save")))
+        })
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .max_by_key(|(_, modified)| *modified)
+}
+
+/// Files under `dir`, relative to it, slot folders included.
+fn files(dir: &Path, relative: &Path, found: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir.join(relative)).into_iter().flatten().flatten() {
+        let path = relative.join(entry.file_name());
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => files(dir, &path, found),
+            Ok(kind) if kind.is_file() => found.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// True when the folder holds no files at all, subfolders included.
+pub fn is_empty(dir: &Path) -> bool {
+    let mut found = Vec::new();
+    files(dir, Path::new(""), &mut found);
+    found.is_empty()
+}
+
+/// Moves everything in saves folder `from` to `to` and returns how many files moved. Nothing is
+/// touched when a file already exists in `to`, so two sets of saves never get mixed. Every file is
+/// copied before any original is deleted; if a copy fails, the copies made so far are removed again
+/// and all the saves stay in `from`.
+pub fn migrate(from: &Path, to: &Path) -> Result<usize, String> {
+    let mut found = Vec::new();
+    files(from, Path::new(""), &mut found);
+    let clashes: Vec<String> = found.iter().filter(|f| to.join(f).exists()).map(|f| f.display().to_string()).collect();
+    if !clashes.is_empty() {
+        return Err(format!("{} already has {}", to.display(), clashes.join(", ")));
+    }
+    let mut copied: Vec<PathBuf> = Vec::new();
+    for file in &found {
+        let source = from.join(file);
+        let target = to.join(file);
+        let result = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::copy(&source, &target))
+            .and_then(|bytes| match fs::metadata(&source) {
+                Ok(meta) if meta.len() != bytes => Err(std::io::Error::new(std::io::ErrorKind::Other, "short copy")),
+                Ok(_) => Ok(()),
+                Err(e) => Err(e),
+            });
+        if let Err(e) = result {
+            // The clash check above means none of these existed before
+            let _ = fs::remove_file(&target);
+            for done in &copied {
+                let _ = fs::remove_file(done);
+            }
+            return Err(format!("{}: {}", file.display(), e));
+        }
+        copied.push(target);
+    }
+    for file in &found {
+        // Every save is already safe in `to`, an original that won't go away only takes space
+        if let Err(e) = fs::remove_file(from.join(file)) {
+            eprintln!("Failed to remove the old copy of {}: {}", file.display(), e);
+        }
+    }
+    Ok(found.len())
+}
+
+/// Erased and never-used slots are filled with a single value.
+fn is_blank(copy: &[u8]) -> bool {
+    copy.iter().all(|&b| b == copy[0])
+}
+
+fn smw_copy_ok(copy: &[u8]) -> bool {
+    let (data, stored) = copy.split_at(SMW_SLOT_SIZE - 2);
+    let sum = data.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16));
+    sum.wrapping_add(u16::from_le_bytes([stored[0], stored[1]])) == SMW_CHECKSUM_TOTAL
+}
+
+/// Damaged slots in save RAM `data`, numbered from 1; None for formats that can't be checked.
+pub fn damaged_slots(data: &[u8]) -> Option<Vec<usize>> {
+    if data.len() != SMW_SIZE {
+        return None;
+    }
+    let damaged = (0..SMW_SLOTS)
+        .filter(|slot| {
+            let copies = [slot * SMW_SLOT_SIZE, SMW_BACKUP_OFFSET + slot * SMW_SLOT_SIZE]
+                .map(|start| &data[start..start + SMW_SLOT_SIZE]);
+            !copies.iter().any(|copy| smw_copy_ok(copy)) && !copies.iter().all(|copy| is_blank(copy))
+        })
+        .map(|slot| slot + 1)
+        .collect();
+    Some(damaged)
+}
+
+/// Most exits cleared in any slot of save RAM `data`; None for other formats or without a good slot.
+pub fn exits_cleared(data: &[u8]) -> Option<u32> {
+    if data.len() != SMW_SIZE {
+        return None;
+    }
+    (0..SMW_SLOTS)
+        .filter_map(|slot| {
+            [slot * SMW_SLOT_SIZE, SMW_BACKUP_OFFSET + slot * SMW_SLOT_SIZE]
+                .map(|start| &data[start..start + SMW_SLOT_SIZE])
+                .into_iter()
+                .find(|copy| smw_copy_ok(copy))
+                .map(|copy| copy[SMW_EXITS_OFFSET] as u32)
+        })
+        .max()
+}
+
+/// Exits cleared by ROM file name, for the saves in `saves_dir` that tell.
+pub fn progress(saves_dir: &Path) -> Vec<(String, u32)> {
+    fs::read_dir(saves_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "srm").unwrap_or(false))
+        .filter_map(|path| {
+            let exits = exits_cleared(&fs::read(&path).ok()?)?;
+            let stem = path.file_stem()?.to_string_lossy().into_owned();
+            Some((format!("{}.sfc", stem), exits))
+        })
+        .collect()
+}
+
+/// Damaged saves in `saves_dir` by ROM file name, with their damaged slots.
+pub fn scan(saves_dir: &Path) -> Vec<(String, Vec<usize>)> {
+    let mut found: Vec<(String, Vec<usize>)> = fs::read_dir(saves_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "srm").unwrap_or(false))
+        .filter_map(|path| {
+            let slots = damaged_slots(&fs::read(&path).ok()?)?;
+            let stem = path.file_stem()?.to_string_lossy().into_owned();
+            Some((format!("{}.sfc", stem), slots)).filter(|(_, slots)| !slots.is_empty())
+        })
+        .collect();
+    found.sort();
+    found
+}
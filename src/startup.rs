@@ -0,0 +1,59 @@
+//! Startup timing, for "the launcher takes 20 seconds to open" reports. Each stage is printed as
+//! it ends and the whole run is appended to `launcher/logs/startup.log`; a start slower than
+//! `SLOW_STARTUP` gets a notice naming the stage that took longest.
+
+use chrono::Local;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const SLOW_STARTUP: Duration = Duration::from_secs(8);
+const LOG_LINES: usize = 50; // past startups kept in startup.log
+
+pub struct StartupTimer {
+    started: Instant,
+    stage_started: Instant,
+    stages: Vec<(&'static str, Duration)>,
+}
+
+impl StartupTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        StartupTimer { started: now, stage_started: now, stages: Vec::new() }
+    }
+
+    /// Ends the running stage under `name` and starts the next one.
+    pub fn stage(&mut self, name: &'static str) {
+        let took = self.stage_started.elapsed();
+        println!("Startup: {} took {}", name, seconds(took));
+        self.stages.push((name, took));
+        self.stage_started = Instant::now();
+    }
+
+    pub fn total(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn slowest(&self) -> Option<(&'static str, Duration)> {
+        self.stages.iter().copied().max_by_key(|(_, took)| *took)
+    }
+
+    /// Appends this startup to startup.log, keeping the last `LOG_LINES` runs.
+    pub fn save(&self, launcher_dir: &Path) -> Result<(), String> {
+        let dir = launcher_dir.join("logs");
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join("startup.log");
+        let stages: Vec<String> = self.stages.iter().map(|(name, took)| format!("{} {}", name, seconds(*took))).collect();
+        let line = format!("{} total {}: {}", Local::now().format("%Y-%m-%d %H:%M:%S"), seconds(self.total()), stages.join(", "));
+
+        let old = fs::read_to_string(&path).unwrap_or_default();
+        let mut lines: Vec<&str> = old.lines().collect();
+        lines.push(&line);
+        let keep = lines.len().saturating_sub(LOG_LINES);
+        fs::write(&path, lines[keep..].join("\n") + "\n").map_err(|e| e.to_string())
+    }
+}
+
+pub fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f32())
+}
@@ -0,0 +1,239 @@
+use chrono::{Local, TimeZone};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::GamepadAction;
+
+/// One finished play session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    pub rom: String,
+    pub start: i64, // unix seconds
+    pub secs: u64,
+}
+
+/// Play history, stored as `launcher/stats.json`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub sessions: Vec<Session>,
+}
+
+impl Stats {
+    pub fn load(launcher_dir: &Path) -> Self {
+        let path = launcher_dir.join("stats.json");
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Failed to parse stats.json: {}", e);
+                Stats::default()
+            }),
+            Err(_) => Stats::default(),
+        }
+    }
+
+    pub fn save(&self, launcher_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(launcher_dir.join("stats.json"), content).map_err(|e| e.to_string())
+    }
+
+    pub fn record(&mut self, rom: &str, secs: u64) {
+        self.sessions.push(Session {
+            rom: rom.to_string(),
+            start: Local::now().timestamp() - secs as i64,
+            secs,
+        });
+    }
+
+    /// Seconds played in sessions that started today (local time).
+    pub fn playtime_today(&self) -> u64 {
+        let today = Local::now().date_naive();
+        self.sessions
+            .iter()
+            .filter(|s| {
+                Local
+                    .timestamp_opt(s.start, 0)
+                    .single()
+                    .map(|t| t.date_naive() == today)
+                    .unwrap_or(false)
+            })
+            .map(|s| s.secs)
+            .sum()
+    }
+}
+
+/// "1h 05m", or "12m" under an hour.
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs / 60 % 60);
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// "just now", "5 minutes ago", "2 hours ago", "3 days ago".
+pub fn format_age(secs: u64) -> String {
+    let (amount, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    format!("{} {}{} ago", amount, unit, if amount == 1 { "" } else { "s" })
+}
+
+const WEEKS_SHOWN: usize = 8;
+const GAMES_SHOWN: usize = 8;
+const BAR_HEIGHT: u32 = 24;
+
+/// Dashboard summarizing the play history with simple bar charts.
+pub struct StatsScreen {
+    per_game: Vec<(String, u64)>, // title, seconds, most played first
+    per_week: [usize; WEEKS_SHOWN], // sessions, oldest week first
+    total: u64,
+    session_count: usize,
+    longest: Option<(String, u64)>,
+}
+
+impl StatsScreen {
+    pub fn new(stats: &Stats, titles: &HashMap<String, String>) -> Self {
+        let title = |rom: &str| titles.get(rom).cloned().unwrap_or_else(|| rom.trim_end_matches(".sfc").to_string());
+
+        let mut totals: HashMap<&str, u64> = HashMap::new();
+        for session in &stats.sessions {
+            *totals.entry(session.rom.as_str()).or_default() += session.secs;
+        }
+        let mut per_game: Vec<(String, u64)> = totals.into_iter().map(|(rom, secs)| (title(rom), secs)).collect();
+        per_game.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let now = Local::now().timestamp();
+        let mut per_week = [0; WEEKS_SHOWN];
+        for session in &stats.sessions {
+            let weeks_ago = ((now - session.start).max(0) / (7 * 24 * 3600)) as usize;
+            if weeks_ago < WEEKS_SHOWN {
+                per_week[WEEKS_SHOWN - 1 - weeks_ago] += 1;
+            }
+        }
+
+        StatsScreen {
+            total: per_game.iter().map(|(_, secs)| secs).sum(),
+            session_count: stats.sessions.len(),
+            longest: stats.sessions.iter().max_by_key(|s| s.secs).map(|s| (title(&s.rom), s.secs)),
+            per_game,
+            per_week,
+        }
+    }
+}
+
+impl View for StatsScreen {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => ViewAction::Close,
+            _ => ViewAction::None,
+        }
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Back => ViewAction::Close,
+            _ => ViewAction::None,
+        }
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Statistics")?;
+        let text_color = ui::text_color();
+        let accent = ui::accent_color();
+
+        if let Some(f) = font {
+            let most_played = self.per_game.first().map(|(t, _)| t.as_str()).unwrap_or("-");
+            let longest = match &self.longest {
+                Some((title, secs)) => format!("{} in {}", format_duration(*secs), title),
+                None => "-".to_string(),
+            };
+            ui::draw_text(canvas, texture_creator, f, &format!("Total playtime: {}", format_duration(self.total)), 30, 75, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, &format!("Sessions: {}", self.session_count), 500, 75, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, &format!("Most played: {}", most_played), 30, 105, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, &format!("Longest session: {}", longest), 500, 105, text_color)?;
+            ui::draw_text(canvas, texture_creator, f, "Playtime per game", 30, 150, accent)?;
+            ui::draw_text(canvas, texture_creator, f, "Sessions per week", 600, 150, accent)?;
+            ui::draw_text(canvas, texture_creator, f, "Esc: back", 30, 620, text_color)?;
+        }
+
+        // Per-game playtime as horizontal bars with the title and duration on top
+        let max_secs = self.per_game.first().map(|(_, secs)| *secs).unwrap_or(0).max(1);
+        for (idx, (title, secs)) in self.per_game.iter().take(GAMES_SHOWN).enumerate() {
+            let y = 190 + idx as i32 * (BAR_HEIGHT as i32 + 24);
+            let width = (*secs as f32 / max_secs as f32 * 500.0).max(2.0) as u32;
+            canvas.set_draw_color(ui::highlight_color());
+            canvas.fill_rect(Rect::new(30, y + 22, width, BAR_HEIGHT - 8))?;
+            if let Some(f) = font {
+                let label = format!("{} - {}", title, format_duration(*secs));
+                ui::draw_text(canvas, texture_creator, f, &label, 30, y - 4, text_color)?;
+            }
+        }
+
+        // Sessions per week as vertical bars, oldest on the left
+        let (chart_x, chart_bottom, chart_height) = (600, 560, 340.0);
+        let max_week = self.per_week.iter().copied().max().unwrap_or(0).max(1);
+        for (idx, count) in self.per_week.iter().enumerate() {
+            let x = chart_x + idx as i32 * 44;
+            let height = (*count as f32 / max_week as f32 * chart_height) as u32;
+            canvas.set_draw_color(if idx == WEEKS_SHOWN - 1 { accent } else { ui::highlight_color() });
+            if height > 0 {
+                canvas.fill_rect(Rect::new(x, chart_bottom - height as i32, 34, height))?;
+            }
+            if let Some(f) = font {
+                ui::draw_text(canvas, texture_creator, f, &count.to_string(), x + 6, chart_bottom + 4, text_color)?;
+            }
+        }
+        canvas.set_draw_color(text_color);
+        canvas.draw_line((chart_x - 4, chart_bottom), (chart_x + WEEKS_SHOWN as i32 * 44, chart_bottom))?;
+        Ok(())
+    }
+}
+
+/// Writes the play history to `dir` as stats.csv and stats.json for spreadsheets and scripts.
+pub fn export(stats: &Stats, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+    let mut csv = String::from("rom,start,seconds\n");
+    for session in &stats.sessions {
+        let start = Local
+            .timestamp_opt(session.start, 0)
+            .single()
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        csv.push_str(&format!("{},{},{}\n", csv_field(&session.rom), start, session.secs));
+    }
+    let csv_path = dir.join("stats.csv");
+    fs::write(&csv_path, csv).map_err(|e| e.to_string())?;
+
+    let json_path = dir.join("stats.json");
+    let json = serde_json::to_string_pretty(stats).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json).map_err(|e| e.to_string())?;
+
+    Ok(vec![csv_path, json_path])
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,77 @@
+//! Which optional subsystems came up, so failures the launcher works around (no sound, no
+//! controller, a fallback font) show up in the UI instead of only in the console.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Audio,
+    Gamepad,
+    Font,
+    Background,
+    Network,
+    Saves,
+}
+
+pub const SUBSYSTEMS: [Subsystem; 6] = [
+    Subsystem::Audio,
+    Subsystem::Gamepad,
+    Subsystem::Font,
+    Subsystem::Background,
+    Subsystem::Network,
+    Subsystem::Saves,
+];
+
+impl Subsystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Audio => "Audio",
+            Subsystem::Gamepad => "Gamepad",
+            Subsystem::Font => "Font",
+            Subsystem::Background => "Background",
+            Subsystem::Network => "Network",
+            Subsystem::Saves => "Saves",
+        }
+    }
+}
+
+/// Subsystems skipped with `--no-audio` and `--no-gamepad`, so a launcher crashing in one of them
+/// can still be opened to fix its settings. `--safe-mode` uses the default theme on a plain
+/// background and stays offline.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StartupFlags {
+    pub no_audio: bool,
+    pub no_gamepad: bool,
+    pub safe_mode: bool,
+}
+
+impl StartupFlags {
+    pub fn parse(args: &[String]) -> Self {
+        let has = |flag: &str| args.iter().any(|a| a == flag);
+        StartupFlags {
+            no_audio: has("--no-audio"),
+            no_gamepad: has("--no-gamepad"),
+            safe_mode: has("--safe-mode"),
+        }
+    }
+}
+
+/// Why each subsystem failed or fell back; subsystems without an entry work normally.
+/// Network is only tracked here when it was turned off, otherwise the news fetch knows how that went.
+#[derive(Default)]
+pub struct SystemStatus {
+    problems: HashMap<Subsystem, String>,
+}
+
+impl SystemStatus {
+    pub fn set(&mut self, subsystem: Subsystem, problem: Option<String>) {
+        match problem {
+            Some(problem) => self.problems.insert(subsystem, problem),
+            None => self.problems.remove(&subsystem),
+        };
+    }
+
+    pub fn problem(&self, subsystem: Subsystem) -> Option<&str> {
+        self.problems.get(&subsystem).map(String::as_str)
+    }
+}
@@ -0,0 +1,47 @@
+//! Streamer mode keeps filesystem paths and the user name off screen and out of the logs.
+
+use std::env;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A path for display: only its file name while streaming.
+pub fn path(path: &Path) -> String {
+    if !enabled() {
+        return path.display().to_string();
+    }
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => "(hidden)".to_string(),
+    }
+}
+
+/// Free text for display with the home directory and user name masked while streaming.
+pub fn scrub(text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+    let mut text = text.to_string();
+    if let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        let home = home.to_string_lossy();
+        if !home.is_empty() {
+            text = text.replace(home.as_ref(), "~");
+        }
+    }
+    if let Ok(user) = env::var("USER").or_else(|_| env::var("USERNAME")) {
+        // Very short names would mangle ordinary words
+        if user.len() >= 3 {
+            text = text.replace(&user, "user");
+        }
+    }
+    text
+}
@@ -0,0 +1,395 @@
+//! Cover art for the grid, with the grayscale look of unselected cards baked in at load time.
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::imageops::{self, FilterType};
+use image::{AnimationDecoder, RgbaImage};
+use sdl2::image::LoadSurface;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::video::WindowContext;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::streamer;
+
+/// Cover file extensions, best first: lossless PNG wins over WebP, which usually beats JPEG at the same size.
+pub const COVER_EXTENSIONS: &[&str] = &["png", "webp", "jpg", "jpeg", "gif"];
+
+/// Frames kept per animated cover; longer animations are cut short rather than filling VRAM.
+const MAX_FRAMES: usize = 120;
+/// Shortest frame delay honored, like browsers do for GIFs that ask for 0 ms.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/// Width the blurred backdrop is computed at; the GPU scales it up, which smooths it further.
+const BACKDROP_WIDTH: u32 = 64;
+const BACKDROP_BLUR: f32 = 2.5;
+const BACKDROP_BRIGHTNESS: u32 = 45; // percent
+
+/// Most covers kept on the GPU at once; the rest are reloaded from disk when their page comes back.
+const MAX_RESIDENT: usize = 24;
+
+pub struct Cover<'a> {
+    pub color: Texture<'a>,
+    pub gray: Texture<'a>,
+    pub width: u32,
+    pub height: u32,
+    pub accent: Color, // dominant color, for tinting the screen while the card is selected
+    pub backdrop: Texture<'a>, // small blurred, darkened copy for the blurred cover background
+    frames: Vec<(Texture<'a>, Duration)>, // empty unless the file is an animated GIF or APNG
+}
+
+impl<'a> Cover<'a> {
+    pub fn is_animated(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// The frame to show `elapsed` into a looping animation, or the color cover for still images.
+    pub fn frame(&self, elapsed: Duration) -> &Texture<'a> {
+        let total: Duration = self.frames.iter().map(|(_, delay)| *delay).sum();
+        if total.is_zero() {
+            return &self.color;
+        }
+        let mut t = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+        for (texture, delay) in &self.frames {
+            if t < *delay {
+                return texture;
+            }
+            t -= *delay;
+        }
+        &self.color
+    }
+}
+
+/// Source and destination rects for drawing a `width` x `height` cover into `area`:
+/// scaled to fit with bars on the sides, or with `crop` scaled to fill and the overhang cut off.
+pub fn fit(width: u32, height: u32, area: Rect, crop: bool) -> (Option<Rect>, Rect) {
+    if width == 0 || height == 0 {
+        return (None, area);
+    }
+    let scale_x = area.width() as f32 / width as f32;
+    let scale_y = area.height() as f32 / height as f32;
+    if crop {
+        let scale = scale_x.max(scale_y);
+        let src_width = ((area.width() as f32 / scale) as u32).clamp(1, width);
+        let src_height = ((area.height() as f32 / scale) as u32).clamp(1, height);
+        let src = Rect::new(
+            ((width - src_width) / 2) as i32,
+            ((height - src_height) / 2) as i32,
+            src_width,
+            src_height,
+        );
+        (Some(src), area)
+    } else {
+        let scale = scale_x.min(scale_y);
+        let dst_width = ((width as f32 * scale) as u32).max(1);
+        let dst_height = ((height as f32 * scale) as u32).max(1);
+        let dst = Rect::new(
+            area.x() + (area.width() - dst_width) as i32 / 2,
+            area.y() + (area.height() - dst_height) as i32 / 2,
+            dst_width,
+            dst_height,
+        );
+        (None, dst)
+    }
+}
+
+struct Slot<'a> {
+    path: PathBuf,
+    cover: Option<Cover<'a>>,
+    last_used: u64, // `TextureStore::clock` when the cover was last needed
+    failed: bool,   // not retried until the launcher restarts
+}
+
+/// Covers looked up by card index in the render loop. Only the visible page and its
+/// neighbours are kept resident, least recently shown covers are evicted first.
+pub struct TextureStore<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
+    pngs_dir: PathBuf,
+    slots: Vec<Slot<'a>>,
+    by_name: HashMap<String, Option<usize>>, // ROM -> slot, None when it has no cover file
+    overrides: HashMap<String, PathBuf>, // ROM -> cover picked in the library instead of pngs/
+    order: Vec<String>, // card list the cards below were resolved for
+    cards: Vec<Option<usize>>, // card index -> slot
+    clock: u64,
+}
+
+/// The cover file a ROM shows: its library override, else one in `pngs_dir` named after it, else a
+/// generated one in `pngs_dir/auto`. None when none of them exists.
+pub fn cover_path(pngs_dir: &Path, cover_override: Option<&PathBuf>, rom: &str) -> Option<PathBuf> {
+    let stem = rom.trim_end_matches(".sfc");
+    cover_override
+        .filter(|path| path.exists())
+        .cloned()
+        .or_else(|| COVER_EXTENSIONS.iter().map(|ext| pngs_dir.join(format!("{}.{}", stem, ext))).find(|path| path.exists()))
+        .or_else(|| Some(pngs_dir.join("auto").join(format!("{}.png", stem))).filter(|path| path.exists()))
+}
+
+impl<'a> TextureStore<'a> {
+    /// Covers are `<pngs_dir>/<rom stem>.<png|webp|jpg|jpeg>`, else a generated `<pngs_dir>/auto/<rom stem>.png`;
+    /// nothing is loaded until a page needs it.
+    pub fn new(texture_creator: &'a TextureCreator<WindowContext>, pngs_dir: &Path) -> Self {
+        TextureStore {
+            texture_creator,
+            pngs_dir: pngs_dir.to_path_buf(),
+            slots: Vec::new(),
+            by_name: HashMap::new(),
+            overrides: HashMap::new(),
+            order: Vec::new(),
+            cards: Vec::new(),
+            clock: 0,
+        }
+    }
+
+    fn slot_for(&mut self, rom: &str) -> Option<usize> {
+        if let Some(slot) = self.by_name.get(rom) {
+            return *slot;
+        }
+        let slot = cover_path(&self.pngs_dir, self.overrides.get(rom), rom).map(|path| {
+            self.slots.push(Slot { path, cover: None, last_used: 0, failed: false });
+            self.slots.len() - 1
+        });
+        self.by_name.insert(rom.to_string(), slot);
+        slot
+    }
+
+    /// Drops the cached cover lookup for a ROM whose file or cover was renamed.
+    pub fn forget(&mut self, rom: &str) {
+        self.by_name.remove(rom);
+        self.order.clear();
+    }
+
+    /// Replaces the per-ROM cover overrides, re-resolving the ROMs whose override changed.
+    pub fn set_overrides(&mut self, overrides: HashMap<String, PathBuf>) {
+        if self.overrides == overrides {
+            return;
+        }
+        let changed: Vec<String> = self
+            .overrides
+            .keys()
+            .chain(overrides.keys())
+            .filter(|rom| self.overrides.get(*rom) != overrides.get(*rom))
+            .cloned()
+            .collect();
+        self.overrides = overrides;
+        for rom in changed {
+            self.forget(&rom);
+        }
+    }
+
+    /// Re-resolves the card slots when the visible list changed (tab switch, reorder, hiding).
+    pub fn arrange(&mut self, sfcs: &[String]) {
+        if self.order == sfcs {
+            return;
+        }
+        self.order = sfcs.to_vec();
+        self.cards = sfcs.iter().map(|rom| self.slot_for(rom)).collect();
+    }
+
+    /// Loads the covers of `page` and the pages on either side, then evicts the least
+    /// recently shown ones beyond `MAX_RESIDENT`. Call once per frame after `arrange`.
+    pub fn keep_page(&mut self, page: usize, per_page: usize) {
+        self.clock += 1;
+        let first = page.saturating_sub(1) * per_page;
+        let last = ((page + 2) * per_page).min(self.cards.len());
+        for idx in first..last {
+            if let Some(slot) = self.cards[idx] {
+                self.touch(slot);
+            }
+        }
+        self.evict();
+    }
+
+    fn touch(&mut self, slot: usize) {
+        let texture_creator = self.texture_creator;
+        let entry = &mut self.slots[slot];
+        entry.last_used = self.clock;
+        if entry.cover.is_some() || entry.failed {
+            return;
+        }
+        match load_cover(texture_creator, &entry.path) {
+            Ok(cover) => entry.cover = Some(cover),
+            Err(e) => {
+                eprintln!("Failed to load cover {}: {}", streamer::path(&entry.path), e);
+                entry.failed = true;
+            }
+        }
+    }
+
+    fn evict(&mut self) {
+        let mut resident: Vec<usize> = (0..self.slots.len()).filter(|&slot| self.slots[slot].cover.is_some()).collect();
+        if resident.len() <= MAX_RESIDENT {
+            return;
+        }
+        resident.sort_by_key(|&slot| self.slots[slot].last_used);
+        let excess = resident.len() - MAX_RESIDENT;
+        for &slot in resident.iter().take(excess) {
+            // Never drop a cover that is needed this frame
+            if self.slots[slot].last_used < self.clock {
+                self.slots[slot].cover = None;
+            }
+        }
+    }
+
+    /// Cover of the card at `idx` in the list last passed to `arrange`, if it is resident.
+    pub fn card_mut(&mut self, idx: usize) -> Option<&mut Cover<'a>> {
+        let slot = self.cards.get(idx).copied().flatten()?;
+        self.slots[slot].cover.as_mut()
+    }
+
+    /// Arranged ROMs that have no cover file at all.
+    pub fn uncovered(&self) -> impl Iterator<Item = &str> {
+        self.order.iter().zip(&self.cards).filter(|(_, slot)| slot.is_none()).map(|(rom, _)| rom.as_str())
+    }
+
+    /// Lookup by ROM name for places outside the grid, loading the cover if needed.
+    pub fn by_name(&mut self, rom: &str) -> Option<&Cover<'a>> {
+        let slot = self.slot_for(rom)?;
+        self.touch(slot);
+        self.slots[slot].cover.as_ref()
+    }
+}
+
+fn load_cover<'a>(texture_creator: &'a TextureCreator<WindowContext>, path: &Path) -> Result<Cover<'a>, String> {
+    let surface = Surface::from_file(path)?.convert_format(PixelFormatEnum::RGBA32)?;
+    let mut color = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
+
+    let backdrop = backdrop(texture_creator, &surface)?;
+    let mut gray_surface = surface;
+    let pitch = gray_surface.pitch() as usize;
+    let width = gray_surface.width() as usize;
+    let accent = gray_surface.with_lock(|pixels| dominant_color(pixels, pitch, width));
+    gray_surface.with_lock_mut(|pixels| {
+        for row in pixels.chunks_mut(pitch) {
+            for pixel in row[..width * 4].chunks_exact_mut(4) {
+                let luma = (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000;
+                // Dimmed like the old color-mod look so selected cards still stand out
+                let value = (luma / 2 + 32) as u8;
+                pixel[0] = value;
+                pixel[1] = value;
+                pixel[2] = value;
+            }
+        }
+    });
+    let mut gray = texture_creator.create_texture_from_surface(&gray_surface).map_err(|e| e.to_string())?;
+
+    color.set_blend_mode(BlendMode::Blend);
+    gray.set_blend_mode(BlendMode::Blend);
+    let (width, height) = gray_surface.size();
+    let frames = match load_frames(texture_creator, path) {
+        Ok(frames) => frames,
+        Err(e) => {
+            eprintln!("Failed to decode animation in {}: {}", streamer::path(path), e);
+            Vec::new()
+        }
+    };
+    Ok(Cover { color, gray, width, height, accent, backdrop, frames })
+}
+
+/// The cover shrunk, blurred and darkened, for drawing full screen behind the grid.
+fn backdrop<'a>(texture_creator: &'a TextureCreator<WindowContext>, surface: &Surface) -> Result<Texture<'a>, String> {
+    let (width, height) = surface.size();
+    let pitch = surface.pitch() as usize;
+    let mut packed = Vec::with_capacity((width * height * 4) as usize);
+    surface.with_lock(|pixels| {
+        for row in pixels.chunks(pitch).take(height as usize) {
+            packed.extend_from_slice(&row[..width as usize * 4]);
+        }
+    });
+    let image = RgbaImage::from_raw(width, height, packed).ok_or("cover has no pixels")?;
+    let small_height = (BACKDROP_WIDTH * height / width.max(1)).max(1);
+    let small = imageops::resize(&image, BACKDROP_WIDTH, small_height, FilterType::Triangle);
+    let mut blurred = imageops::blur(&small, BACKDROP_BLUR);
+    for pixel in blurred.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as u32 * BACKDROP_BRIGHTNESS / 100) as u8;
+        }
+        pixel.0[3] = 255;
+    }
+
+    let (small_width, small_height) = blurred.dimensions();
+    let surface = Surface::from_data(&mut blurred, small_width, small_height, small_width * 4, PixelFormatEnum::RGBA32)?;
+    // Linear filtering so the scaled-up texture stays smooth instead of turning into blocks
+    let previous = sdl2::hint::get("SDL_RENDER_SCALE_QUALITY");
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "1");
+    let texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string());
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", previous.as_deref().unwrap_or("0"));
+    let mut texture = texture?;
+    texture.set_blend_mode(BlendMode::Blend);
+    Ok(texture)
+}
+
+/// The most common color in RGBA32 `pixels`, counted in coarse buckets so similar shades add up.
+/// Saturated pixels count more, so a colorful logo beats a gray or black backdrop.
+fn dominant_color(pixels: &[u8], pitch: usize, width: usize) -> Color {
+    const STEP: usize = 3; // sample every third pixel and row, plenty for a cover
+    let mut buckets: HashMap<u16, (u64, [u64; 3])> = HashMap::new();
+    for row in pixels.chunks(pitch).step_by(STEP) {
+        for pixel in row[..width * 4].chunks_exact(4).step_by(STEP) {
+            if pixel[3] < 128 {
+                continue;
+            }
+            let (r, g, b) = (pixel[0], pixel[1], pixel[2]);
+            let saturation = r.max(g).max(b) - r.min(g).min(b);
+            let weight = 1 + saturation as u64 / 16;
+            let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+            let bucket = buckets.entry(key).or_insert((0, [0; 3]));
+            bucket.0 += weight;
+            bucket.1[0] += r as u64 * weight;
+            bucket.1[1] += g as u64 * weight;
+            bucket.1[2] += b as u64 * weight;
+        }
+    }
+    match buckets.values().max_by_key(|(weight, _)| *weight) {
+        Some((weight, sums)) => Color::RGB(
+            (sums[0] / weight) as u8,
+            (sums[1] / weight) as u8,
+            (sums[2] / weight) as u8,
+        ),
+        None => Color::RGB(0, 0, 0),
+    }
+}
+
+/// Every frame of an animated GIF or APNG with its delay; empty for still images and other formats.
+fn load_frames<'a>(
+    texture_creator: &'a TextureCreator<WindowContext>,
+    path: &Path,
+) -> Result<Vec<(Texture<'a>, Duration)>, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    let file = || File::open(path).map(BufReader::new).map_err(|e| e.to_string());
+    let frames = match ext.as_str() {
+        "gif" => GifDecoder::new(file()?).map_err(|e| e.to_string())?.into_frames(),
+        "png" => {
+            let decoder = PngDecoder::new(file()?).map_err(|e| e.to_string())?;
+            if !decoder.is_apng() {
+                return Ok(Vec::new());
+            }
+            decoder.apng().into_frames()
+        }
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut textures = Vec::new();
+    for frame in frames.take(MAX_FRAMES) {
+        let frame = frame.map_err(|e| e.to_string())?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_millis((numer / denom.max(1)) as u64).max(MIN_FRAME_DELAY);
+        // Decoders hand back whole composited frames, so each one can be shown on its own
+        let mut buffer = frame.into_buffer();
+        let (width, height) = buffer.dimensions();
+        let surface = Surface::from_data(&mut buffer, width, height, width * 4, PixelFormatEnum::RGBA32)?;
+        let mut texture = texture_creator.create_texture_from_surface(&surface).map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        textures.push((texture, delay));
+    }
+    // A single frame is just a still image, already covered by `color`
+    if textures.len() < 2 {
+        textures.clear();
+    }
+    Ok(textures)
+}
@@ -0,0 +1,700 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use crate::audio::SoundFiles;
+use crate::badges::BadgeStyle;
+use crate::color_picker::{ColorPicker, PickerInput};
+use crate::crt;
+use crate::font::Font;
+use crate::hacks::sanitize_file_name;
+use crate::option_widgets::{OptionList, OptionRow, RowInput};
+use crate::ui::{self, View, ViewAction};
+use crate::visualizer::Visualizer;
+use crate::{GamepadAction, BOX_SIZE, SCREEN_HEIGHT};
+
+pub const BG_COLOR: u8 = 1;
+pub const BG_IMAGE: u8 = 2;
+pub const BG_VISUALIZER: u8 = 3;
+pub const BG_COVER_BLUR: u8 = 4;
+
+pub const COVER_CONTAIN: u8 = 0;
+pub const COVER_CROP: u8 = 1;
+
+/// Look and sound of the launcher, stored as `launcher/themes/<name>/theme.json`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    pub name: String,
+    pub bgtype: u8, // 1 = solid color, 2 = background.png (or MBG.png), 3 = music visualizer, 4 = blurred selected cover
+    pub background_color: (u8, u8, u8),
+    pub card_color: (u8, u8, u8),
+    pub card_border_color: (u8, u8, u8),
+    pub title_color: (u8, u8, u8),
+    pub selector_color: (u8, u8, u8),
+    pub visualizer_color: (u8, u8, u8),
+    pub visualizer_style: u8, // 0 = bars, 1 = waveform
+    pub card_width: u32,
+    pub card_height: u32,
+    pub card_spacing: i32,
+    pub card_top: i32,
+    pub card_rows: u8, // rows of cards per page, card_spacing apart
+    pub cover_fit: u8, // 0 = whole cover with bars, 1 = crop to fill the card
+    pub font: String, // file in the theme folder or the launcher folder
+    pub font_size: u16,
+    pub music: String, // file in the theme folder or the launcher folder
+    pub launch_sound: String,
+    pub bump_sound: String, // empty for the built-in one
+    pub nav_sound: String, // played when the selection moves, pitched up while moving quickly; empty for the built-in one
+    pub badges: HashMap<String, BadgeStyle>, // by badge kind, see badges.rs
+    pub crt: u8, // 0 = off, 1 = scanlines, 2 = scanlines and screen curvature, see crt.rs
+    pub chrome: Chrome,
+}
+
+/// Colors of the launcher's own screens, menus and buttons, as opposed to the game grid.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Chrome {
+    pub screen: (u8, u8, u8), // full-screen views
+    pub panel: (u8, u8, u8), // menus, toasts, quick settings, news banner
+    pub highlight: (u8, u8, u8), // selected rows, buttons, inactive tabs
+    pub outline: (u8, u8, u8), // row borders, hovered buttons
+    pub text: (u8, u8, u8),
+    pub accent: (u8, u8, u8), // titles, panel borders, the active tab
+}
+
+impl Chrome {
+    pub const CLASSIC: Chrome = Chrome {
+        screen: (30, 30, 55),
+        panel: (40, 40, 70),
+        highlight: (100, 100, 150),
+        outline: (150, 150, 200),
+        text: (255, 255, 255),
+        accent: (255, 220, 0),
+    };
+}
+
+impl Default for Chrome {
+    fn default() -> Self {
+        Chrome::CLASSIC
+    }
+}
+
+/// Built-in color set that restyles a theme, picked in the launcher options.
+pub struct Palette {
+    pub name: &'static str,
+    background: (u8, u8, u8),
+    card: (u8, u8, u8),
+    card_border: (u8, u8, u8),
+    title: (u8, u8, u8),
+    selector: (u8, u8, u8),
+    visualizer: (u8, u8, u8),
+    chrome: Chrome,
+}
+
+pub const PALETTES: [Palette; 4] = [
+    Palette {
+        name: "SMAS blue",
+        background: (66, 113, 183),
+        card: (200, 200, 200),
+        card_border: (100, 100, 100),
+        title: (0, 0, 0),
+        selector: (255, 220, 0),
+        visualizer: (120, 170, 240),
+        chrome: Chrome::CLASSIC,
+    },
+    Palette {
+        name: "Dark",
+        background: (18, 18, 24),
+        card: (48, 48, 58),
+        card_border: (80, 80, 96),
+        title: (230, 230, 235),
+        selector: (120, 180, 255),
+        visualizer: (90, 110, 160),
+        chrome: Chrome {
+            screen: (14, 14, 18),
+            panel: (28, 28, 36),
+            highlight: (60, 60, 80),
+            outline: (90, 90, 110),
+            text: (230, 230, 235),
+            accent: (120, 180, 255),
+        },
+    },
+    Palette {
+        name: "Light",
+        background: (236, 236, 240),
+        card: (255, 255, 255),
+        card_border: (170, 170, 180),
+        title: (30, 30, 40),
+        selector: (40, 110, 220),
+        visualizer: (150, 180, 230),
+        chrome: Chrome {
+            screen: (245, 245, 248),
+            panel: (228, 228, 234),
+            highlight: (190, 205, 235),
+            outline: (160, 160, 175),
+            text: (25, 25, 35),
+            accent: (40, 110, 220),
+        },
+    },
+    Palette {
+        name: "High contrast",
+        background: (0, 0, 0),
+        card: (255, 255, 255),
+        card_border: (255, 255, 255),
+        title: (255, 255, 255),
+        selector: (255, 255, 0),
+        visualizer: (0, 255, 255),
+        chrome: Chrome {
+            screen: (0, 0, 0),
+            panel: (0, 0, 0),
+            highlight: (0, 0, 170),
+            outline: (255, 255, 255),
+            text: (255, 255, 255),
+            accent: (255, 255, 0),
+        },
+    },
+];
+
+pub fn palette(name: &str) -> Option<&'static Palette> {
+    PALETTES.iter().find(|palette| palette.name == name)
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            name: "Default".to_string(),
+            bgtype: BG_COLOR,
+            background_color: (66, 113, 183),
+            card_color: (200, 200, 200),
+            card_border_color: (100, 100, 100),
+            title_color: (0, 0, 0),
+            selector_color: (255, 220, 0),
+            visualizer_color: (120, 170, 240),
+            visualizer_style: 0,
+            card_width: BOX_SIZE.0,
+            card_height: BOX_SIZE.1,
+            card_spacing: 60,
+            card_top: 143,
+            card_rows: 1,
+            cover_fit: COVER_CONTAIN,
+            font: "smw.ttf".to_string(),
+            font_size: 24,
+            music: "smas.wav".to_string(),
+            launch_sound: "pg.wav".to_string(),
+            bump_sound: String::new(),
+            nav_sound: String::new(),
+            badges: HashMap::new(),
+            crt: crt::CRT_OFF,
+            chrome: Chrome::CLASSIC,
+        }
+    }
+}
+
+impl Theme {
+    /// Swaps every color for the palette's; layout, fonts and sounds stay the theme's.
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        self.background_color = palette.background;
+        self.card_color = palette.card;
+        self.card_border_color = palette.card_border;
+        self.title_color = palette.title;
+        self.selector_color = palette.selector;
+        self.visualizer_color = palette.visualizer;
+        self.chrome = palette.chrome;
+    }
+
+    /// Card rectangle for a position on the page (row by row, `columns` per row), centered horizontally.
+    pub fn card_rect(&self, slot: usize, columns: usize) -> Rect {
+        let total = columns as i32 * self.card_width as i32 + (columns as i32 - 1) * self.card_spacing;
+        let left = (crate::SCREEN_WIDTH as i32 - total) / 2;
+        let (row, col) = (slot / columns, slot % columns);
+        Rect::new(
+            left + col as i32 * (self.card_width as i32 + self.card_spacing),
+            self.card_top + row as i32 * (self.card_height as i32 + self.card_spacing),
+            self.card_width,
+            self.card_height,
+        )
+    }
+}
+
+pub fn rgb(color: (u8, u8, u8)) -> Color {
+    Color::RGB(color.0, color.1, color.2)
+}
+
+/// A name that makes a folder of its own under themes/: not empty, not `.` or `..`, no path separators.
+pub fn valid_name(name: &str) -> bool {
+    let folder = sanitize_file_name(name);
+    !folder.is_empty() && folder != "." && folder != ".." && !name.contains(['/', '\\'])
+}
+
+/// Invalid names all land in `themes/_`, never outside themes/.
+pub fn theme_dir(launcher_dir: &Path, name: &str) -> PathBuf {
+    let folder = if valid_name(name) { sanitize_file_name(name) } else { "_".to_string() };
+    launcher_dir.join("themes").join(folder)
+}
+
+/// Loads a named theme; a missing or broken theme falls back to the default look.
+pub fn load(launcher_dir: &Path, name: &str) -> Theme {
+    let path = theme_dir(launcher_dir, name).join("theme.json");
+    match fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| {
+        serde_json::from_str(&text).map_err(|e| e.to_string())
+    }) {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("Failed to load theme {}: {}", name, e);
+            Theme::default()
+        }
+    }
+}
+
+pub fn save(launcher_dir: &Path, theme: &Theme) -> Result<(), String> {
+    let dir = theme_dir(launcher_dir, &theme.name);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(theme).map_err(|e| e.to_string())?;
+    fs::write(dir.join("theme.json"), content).map_err(|e| e.to_string())
+}
+
+/// Theme folder first so themes can ship their own files, then the launcher folder.
+pub fn themed_file(launcher_dir: &Path, theme: &Theme, file: &str) -> PathBuf {
+    let themed = theme_dir(launcher_dir, &theme.name).join(file);
+    if themed.exists() {
+        themed
+    } else {
+        launcher_dir.join(file)
+    }
+}
+
+pub fn font_path(launcher_dir: &Path, theme: &Theme) -> PathBuf {
+    themed_file(launcher_dir, theme, &theme.font)
+}
+
+/// Music and sounds for the launcher, so a theme can sound different as well as look different.
+pub fn sound_files(launcher_dir: &Path, theme: &Theme) -> SoundFiles {
+    SoundFiles {
+        music: themed_file(launcher_dir, theme, &theme.music),
+        launch: themed_file(launcher_dir, theme, &theme.launch_sound),
+        bump: Some(theme.bump_sound.as_str())
+            .filter(|f| !f.is_empty())
+            .map(|f| themed_file(launcher_dir, theme, f)),
+        nav: Some(theme.nav_sound.as_str())
+            .filter(|f| !f.is_empty())
+            .map(|f| themed_file(launcher_dir, theme, f)),
+    }
+}
+
+pub fn background_path(launcher_dir: &Path, theme: &Theme) -> PathBuf {
+    let themed = theme_dir(launcher_dir, &theme.name).join("background.png");
+    if themed.exists() {
+        themed
+    } else {
+        launcher_dir.join("MBG.png")
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Background,
+    VisualizerStyle,
+    Pick(usize),         // color slot, opens the HSV picker
+    Color(usize, usize), // color slot, channel
+    CardWidth,
+    CardHeight,
+    CardSpacing,
+    CardTop,
+    CardRows,
+    CoverFit,
+    Font,
+    FontSize,
+    Crt,
+    Save,
+}
+
+const COLOR_NAMES: [&str; 6] = ["Background", "Card", "Card border", "Title", "Selector", "Visualizer"];
+const CHANNELS: [&str; 3] = ["red", "green", "blue"];
+const PANEL_WIDTH: u32 = 420;
+const VISIBLE_ROWS: usize = 18;
+
+fn fields() -> Vec<Field> {
+    let mut fields = vec![Field::Name, Field::Background, Field::VisualizerStyle];
+    for slot in 0..COLOR_NAMES.len() {
+        fields.push(Field::Pick(slot));
+        for channel in 0..CHANNELS.len() {
+            fields.push(Field::Color(slot, channel));
+        }
+    }
+    fields.extend([
+        Field::CardWidth,
+        Field::CardHeight,
+        Field::CardSpacing,
+        Field::CardTop,
+        Field::CardRows,
+        Field::CoverFit,
+        Field::Font,
+        Field::FontSize,
+        Field::Crt,
+        Field::Save,
+    ]);
+    fields
+}
+
+/// Edits a copy of the active theme; every change is sent back for the main loop to preview.
+pub struct ThemeEditor {
+    theme: Theme,
+    fields: Vec<Field>,
+    list: OptionList, // one row per entry of `fields`
+    picker: Option<(usize, ColorPicker)>, // color slot being picked, takes the input while open
+    fonts: Vec<String>,
+    launcher_dir: PathBuf,
+    status: String,
+    visualizer: Visualizer,
+}
+
+impl ThemeEditor {
+    pub fn new(theme: Theme, launcher_dir: PathBuf) -> Self {
+        let mut fonts: Vec<String> = fs::read_dir(&launcher_dir)
+            .into_iter()
+            .flatten()
+            .chain(fs::read_dir(theme_dir(&launcher_dir, &theme.name)).into_iter().flatten())
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.to_lowercase().ends_with(".ttf"))
+            .collect();
+        fonts.sort();
+        fonts.dedup();
+
+        let area = Rect::new(10, 50, PANEL_WIDTH - 20, (VISIBLE_ROWS as i32 * ui::LIST_ROW_HEIGHT) as u32);
+        let mut editor = ThemeEditor {
+            theme,
+            fields: fields(),
+            list: OptionList::new(area, ui::LIST_ROW_HEIGHT),
+            picker: None,
+            fonts,
+            launcher_dir,
+            status: "Left/Right: change   Enter on Save: save theme   Esc: discard".to_string(),
+            visualizer: Visualizer::new(),
+        };
+        editor.refresh_rows();
+        editor
+    }
+
+    fn color_mut(&mut self, slot: usize) -> &mut (u8, u8, u8) {
+        match slot {
+            0 => &mut self.theme.background_color,
+            1 => &mut self.theme.card_color,
+            2 => &mut self.theme.card_border_color,
+            3 => &mut self.theme.title_color,
+            4 => &mut self.theme.selector_color,
+            _ => &mut self.theme.visualizer_color,
+        }
+    }
+
+    fn color(&self, slot: usize) -> (u8, u8, u8) {
+        match slot {
+            0 => self.theme.background_color,
+            1 => self.theme.card_color,
+            2 => self.theme.card_border_color,
+            3 => self.theme.title_color,
+            4 => self.theme.selector_color,
+            _ => self.theme.visualizer_color,
+        }
+    }
+
+    fn row(&self, field: Field) -> OptionRow {
+        let theme = &self.theme;
+        match field {
+            Field::Name => OptionRow::text("Name", &theme.name),
+            Field::Pick(slot) => OptionRow::button(&format!("{} color: pick...", COLOR_NAMES[slot])),
+            Field::Background => {
+                let modes = ["color", "image", "visualizer", "blurred cover"].map(String::from).to_vec();
+                OptionRow::choice("Background", modes, theme.bgtype.saturating_sub(1) as usize)
+            }
+            Field::VisualizerStyle => {
+                let styles = ["bars", "waveform"].map(String::from).to_vec();
+                OptionRow::choice("Visualizer", styles, theme.visualizer_style as usize)
+            }
+            Field::Color(slot, channel) => {
+                let label = format!("{} {}", COLOR_NAMES[slot], CHANNELS[channel]);
+                OptionRow::channel(&label, self.color(slot), channel)
+            }
+            Field::CardWidth => OptionRow::slider("Card width", theme.card_width as i32, 100, 320, 5),
+            Field::CardHeight => OptionRow::slider("Card height", theme.card_height as i32, 120, 520, 5),
+            Field::CardSpacing => OptionRow::slider("Card spacing", theme.card_spacing, 0, 120, 5),
+            Field::CardTop => OptionRow::slider("Card top", theme.card_top, 70, 400, 5),
+            Field::CardRows => OptionRow::slider("Card rows", theme.card_rows as i32, 1, 3, 1),
+            Field::CoverFit => {
+                let fits = ["fit whole", "crop to fill"].map(String::from).to_vec();
+                OptionRow::choice("Covers", fits, theme.cover_fit as usize)
+            }
+            Field::Font => {
+                let fonts = if self.fonts.is_empty() { vec![theme.font.clone()] } else { self.fonts.clone() };
+                let current = fonts.iter().position(|f| *f == theme.font).unwrap_or(0);
+                OptionRow::choice("Font", fonts, current)
+            }
+            Field::FontSize => OptionRow::slider("Font size", theme.font_size as i32, 12, 48, 2),
+            Field::Crt => {
+                let modes = [crt::CRT_OFF, crt::CRT_SCANLINES, crt::CRT_CURVED].map(|mode| crt::label(mode).to_string());
+                OptionRow::choice("CRT filter", modes.to_vec(), theme.crt as usize)
+            }
+            Field::Save => OptionRow::button("Save theme"),
+        }
+    }
+
+    fn refresh_rows(&mut self) {
+        let rows = self.fields.iter().map(|&field| self.row(field)).collect();
+        self.list.set_rows(rows);
+    }
+
+    /// Sets a field to its row's new value; returns the preview to apply when something changed.
+    fn set(&mut self, field: Field, value: i32) -> ViewAction {
+        let before = self.theme.clone();
+        match field {
+            Field::Name | Field::Pick(_) | Field::Save => {}
+            Field::Background => self.theme.bgtype = value as u8 + 1,
+            Field::VisualizerStyle => self.theme.visualizer_style = value as u8,
+            Field::Color(slot, channel) => {
+                let color = self.color_mut(slot);
+                let component = match channel {
+                    0 => &mut color.0,
+                    1 => &mut color.1,
+                    _ => &mut color.2,
+                };
+                *component = value as u8;
+            }
+            Field::CardWidth => self.theme.card_width = value as u32,
+            Field::CardHeight => self.theme.card_height = value as u32,
+            Field::CardSpacing => self.theme.card_spacing = value,
+            Field::CardTop => self.theme.card_top = value,
+            Field::CardRows => self.theme.card_rows = value as u8,
+            Field::CoverFit => self.theme.cover_fit = value as u8,
+            Field::Font => {
+                if let Some(font) = self.fonts.get(value as usize) {
+                    self.theme.font = font.clone();
+                }
+            }
+            Field::FontSize => self.theme.font_size = value as u16,
+            Field::Crt => self.theme.crt = value as u8,
+        }
+        self.refresh_rows();
+        self.preview(&before)
+    }
+
+    fn preview(&self, before: &Theme) -> ViewAction {
+        if self.theme == *before {
+            ViewAction::None
+        } else {
+            ViewAction::PreviewTheme(Box::new(self.theme.clone()))
+        }
+    }
+
+    fn row_input(&mut self, input: RowInput) -> ViewAction {
+        match input {
+            RowInput::Changed(idx, value) => self.set(self.fields[idx], value),
+            RowInput::Pressed(idx) => match self.fields[idx] {
+                Field::Save => self.save(),
+                Field::Pick(slot) => {
+                    let title = format!("{} color", COLOR_NAMES[slot]);
+                    self.picker = Some((slot, ColorPicker::open(&title, self.color(slot))));
+                    ViewAction::None
+                }
+                _ => ViewAction::None,
+            },
+            RowInput::Back => ViewAction::Close,
+            RowInput::None => ViewAction::None,
+        }
+    }
+
+    /// Applies what the picker did to its color slot; cancelling puts the old color back.
+    fn picker_input(&mut self, input: PickerInput) -> ViewAction {
+        let Some(slot) = self.picker.as_ref().map(|(slot, _)| *slot) else {
+            return ViewAction::None;
+        };
+        let color = match input {
+            PickerInput::None => return ViewAction::None,
+            PickerInput::Done => {
+                self.picker = None;
+                return ViewAction::None;
+            }
+            PickerInput::Changed(color) => color,
+            PickerInput::Cancel(color) => {
+                self.picker = None;
+                color
+            }
+        };
+        let before = self.theme.clone();
+        *self.color_mut(slot) = color;
+        self.refresh_rows();
+        self.preview(&before)
+    }
+
+    fn save(&mut self) -> ViewAction {
+        if !valid_name(&self.theme.name) {
+            self.status = "Give the theme a name first".to_string();
+            return ViewAction::None;
+        }
+        match save(&self.launcher_dir, &self.theme) {
+            Ok(()) => {
+                println!("Saved theme {}", self.theme.name);
+                self.status = format!("Saved {}", self.theme.name);
+                ViewAction::ThemeSaved(self.theme.name.clone())
+            }
+            Err(e) => {
+                eprintln!("Failed to save theme: {}", e);
+                self.status = e;
+                ViewAction::None
+            }
+        }
+    }
+}
+
+impl View for ThemeEditor {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        if let Some((_, picker)) = self.picker.as_mut() {
+            let input = picker.handle_event(event);
+            return self.picker_input(input);
+        }
+        let editing_name = self.fields[self.list.selected] == Field::Name;
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Backspace), .. } if editing_name => {
+                self.theme.name.pop();
+                self.refresh_rows();
+            }
+            Event::TextInput { text, .. } if editing_name => {
+                self.theme.name.push_str(text);
+                self.refresh_rows();
+            }
+            _ => {
+                let input = self.list.handle_event(event);
+                return self.row_input(input);
+            }
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        if let Some((_, picker)) = self.picker.as_mut() {
+            let input = picker.handle_gamepad(action);
+            return self.picker_input(input);
+        }
+        let input = self.list.handle_gamepad(action);
+        self.row_input(input)
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        // Preview: the grid's background and three sample cards. Background images are
+        // drawn by the grid itself, the editor only shows the color underneath.
+        canvas.set_draw_color(rgb(self.theme.background_color));
+        canvas.clear();
+        if self.theme.bgtype == BG_VISUALIZER {
+            self.visualizer.draw(canvas, &self.theme)?;
+        }
+        for col in 0..3 {
+            let rect = self.theme.card_rect(col, 3);
+            canvas.set_draw_color(rgb(self.theme.card_color));
+            canvas.fill_rect(rect)?;
+            canvas.set_draw_color(rgb(self.theme.card_border_color));
+            canvas.draw_rect(rect)?;
+            if col == 1 {
+                canvas.set_draw_color(rgb(self.theme.selector_color));
+                for i in 0..3 {
+                    canvas.draw_rect(Rect::new(
+                        rect.x() - i,
+                        rect.y() - i,
+                        rect.width() + (i * 2) as u32,
+                        rect.height() + (i * 2) as u32,
+                    ))?;
+                }
+            }
+            if let Some(f) = font {
+                let title = format!("Game {}", col + 1);
+                let (w, _) = f.size_of(&title)?;
+                let x = rect.x() + (rect.width() as i32 - w as i32) / 2;
+                ui::draw_text(canvas, texture_creator, f, &title, x, rect.bottom() - 50, rgb(self.theme.title_color))?;
+            }
+        }
+
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 190));
+        canvas.fill_rect(Rect::new(0, 0, PANEL_WIDTH, SCREEN_HEIGHT))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        self.list.draw(canvas, texture_creator, font)?;
+        if let Some((_, picker)) = &self.picker {
+            picker.draw(canvas, texture_creator, font)?;
+        }
+
+        if let Some(f) = font {
+            ui::draw_text(canvas, texture_creator, f, "Theme editor", 10, 10, ui::accent_color())?;
+            let y = SCREEN_HEIGHT as i32 - 40;
+            ui::draw_text(canvas, texture_creator, f, &self.status, 10, y, ui::text_color())?;
+        }
+        Ok(())
+    }
+}
+
+/// True for a zip with a `theme.json` in it, anything else dropped on the window is taken for a cover pack.
+pub fn is_pack(archive: &[u8]) -> bool {
+    zip::ZipArchive::new(Cursor::new(archive))
+        .map(|zip| zip.file_names().any(|name| name == "theme.json" || name.ends_with("/theme.json")))
+        .unwrap_or(false)
+}
+
+/// Installs a theme pack: a zip with `theme.json` at its root or inside a single folder.
+/// Returns the installed theme's name.
+pub fn install_pack(launcher_dir: &Path, archive: &[u8]) -> Result<String, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| format!("Not a zip file: {}", e))?;
+
+    let manifest_name = (0..zip.len())
+        .filter_map(|idx| zip.by_index(idx).ok().map(|f| f.name().to_string()))
+        .filter(|name| name == "theme.json" || name.ends_with("/theme.json"))
+        .min_by_key(|name| name.len())
+        .ok_or("No theme.json in this pack")?;
+    let prefix = manifest_name.trim_end_matches("theme.json").to_string();
+
+    let mut manifest = String::new();
+    zip.by_name(&manifest_name)
+        .map_err(|e| e.to_string())?
+        .read_to_string(&mut manifest)
+        .map_err(|e| e.to_string())?;
+    let theme: Theme = serde_json::from_str(&manifest).map_err(|e| format!("Invalid theme.json: {}", e))?;
+    if !valid_name(&theme.name) {
+        return Err(format!("theme.json has no usable name: {:?}", theme.name));
+    }
+
+    let dir = theme_dir(launcher_dir, &theme.name);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    for idx in 0..zip.len() {
+        let mut file = zip.by_index(idx).map_err(|e| e.to_string())?;
+        // enclosed_name rejects entries that would escape the theme folder
+        let relative = match file.enclosed_name().and_then(|p| p.strip_prefix(&prefix).ok().map(Path::to_path_buf)) {
+            Some(path) if !file.is_dir() && !path.as_os_str().is_empty() => path,
+            _ => continue,
+        };
+        let target = dir.join(relative);
+        if !target.starts_with(&dir) {
+            return Err(format!("{} would land outside the theme folder", file.name()));
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        fs::write(&target, data).map_err(|e| e.to_string())?;
+    }
+
+    println!("Installed theme {}", theme.name);
+    Ok(theme.name)
+}
@@ -0,0 +1,557 @@
+use sdl2::event::Event;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::font::{self, Font};
+use crate::option_widgets::OptionsScreen;
+use crate::session::Overrides;
+use crate::status::Subsystem;
+use crate::theme::{self, Chrome, Theme};
+use crate::widgets::Widget;
+use crate::{streamer, GamepadAction, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const MENU_WIDTH: u32 = 640;
+const LINE_HEIGHT: i32 = 32;
+
+static CHROME: Mutex<Chrome> = Mutex::new(Chrome::CLASSIC);
+
+/// Set each frame from the active theme; every screen and menu draws with these colors.
+pub fn set_chrome(chrome: Chrome) {
+    if let Ok(mut current) = CHROME.lock() {
+        *current = chrome;
+    }
+}
+
+pub fn chrome() -> Chrome {
+    CHROME.lock().map(|chrome| *chrome).unwrap_or(Chrome::CLASSIC)
+}
+
+pub fn screen_color() -> Color {
+    theme::rgb(chrome().screen)
+}
+
+pub fn panel_color() -> Color {
+    theme::rgb(chrome().panel)
+}
+
+pub fn highlight_color() -> Color {
+    theme::rgb(chrome().highlight)
+}
+
+pub fn outline_color() -> Color {
+    theme::rgb(chrome().outline)
+}
+
+pub fn text_color() -> Color {
+    theme::rgb(chrome().text)
+}
+
+pub fn accent_color() -> Color {
+    theme::rgb(chrome().accent)
+}
+
+/// Small crossed-out speaker with its top-left corner at (x, y), shown when there is no audio device.
+pub fn draw_muted_icon(canvas: &mut Canvas<Window>, x: i32, y: i32) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.fill_rect(Rect::new(x, y + 8, 8, 10))?;
+    for i in 0..10 {
+        // The cone widens from the box to the full icon height
+        canvas.draw_line((x + 8 + i, y + 8 - i * 8 / 10), (x + 8 + i, y + 18 + i * 8 / 10))?;
+    }
+    canvas.set_draw_color(Color::RGB(220, 60, 60));
+    for offset in 0..2 {
+        canvas.draw_line((x + 22 + offset, y + 6), (x + 32 + offset, y + 20))?;
+        canvas.draw_line((x + 32 + offset, y + 6), (x + 22 + offset, y + 20))?;
+    }
+    Ok(())
+}
+
+/// Ring centred on (cx, cy) that fills clockwise from the top as `progress` goes from 0 to 1.
+pub fn draw_hold_ring(canvas: &mut Canvas<Window>, cx: i32, cy: i32, radius: i32, progress: f32) -> Result<(), String> {
+    const STEPS: usize = 48;
+    let filled = (progress.clamp(0.0, 1.0) * STEPS as f32).round() as usize;
+    for step in 0..STEPS {
+        let color = if step < filled { Color::RGB(255, 220, 0) } else { Color::RGB(80, 80, 80) };
+        canvas.set_draw_color(color);
+        let a = step as f32 / STEPS as f32 * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+        let b = (step + 1) as f32 / STEPS as f32 * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+        for width in 0..4 {
+            let r = (radius - width) as f32;
+            canvas.draw_line(
+                (cx + (a.cos() * r) as i32, cy + (a.sin() * r) as i32),
+                (cx + (b.cos() * r) as i32, cy + (b.sin() * r) as i32),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Dims the screen and shows `lines` centered in a panel, for notices that wait on the player.
+pub fn draw_notice(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    lines: &[&str],
+) -> Result<(), String> {
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+    canvas.fill_rect(None)?;
+    canvas.set_blend_mode(BlendMode::None);
+
+    let widths = lines.iter().map(|line| font.size_of(line).map(|(w, _)| w)).collect::<Result<Vec<u32>, String>>()?;
+    let width = widths.iter().copied().max().unwrap_or(0) + 60;
+    let height = lines.len() as u32 * LINE_HEIGHT as u32 + 40;
+    let panel = Rect::new(
+        (SCREEN_WIDTH as i32 - width as i32) / 2,
+        (SCREEN_HEIGHT as i32 - height as i32) / 2,
+        width,
+        height,
+    );
+    canvas.set_draw_color(panel_color());
+    canvas.fill_rect(panel)?;
+    canvas.set_draw_color(accent_color());
+    canvas.draw_rect(panel)?;
+    for (idx, (line, w)) in lines.iter().zip(&widths).enumerate() {
+        let color = if idx == 0 { accent_color() } else { text_color() };
+        let x = panel.x() + (width as i32 - *w as i32) / 2;
+        draw_text(canvas, texture_creator, font, line, x, panel.y() + 20 + idx as i32 * LINE_HEIGHT, color)?;
+    }
+    Ok(())
+}
+
+/// Renders a single line of text with its top-left corner at (x, y) and returns the area it covered.
+pub fn draw_text(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    text: &str,
+    x: i32,
+    y: i32,
+    color: Color,
+) -> Result<Rect, String> {
+    if text.is_empty() {
+        return Ok(Rect::new(x, y, 0, 0));
+    }
+
+    let font = match font {
+        Font::Ttf(f) => f,
+        Font::Bitmap { scale } => return font::draw_bitmap_text(canvas, *scale, text, x, y, color),
+    };
+    let surface = font
+        .render(text)
+        .blended(color)
+        .map_err(|e| e.to_string())?;
+    let texture = texture_creator
+        .create_texture_from_surface(&surface)
+        .map_err(|e| e.to_string())?;
+
+    let q = texture.query();
+    let rect = Rect::new(x, y, q.width, q.height);
+    canvas.copy(&texture, None, rect)?;
+    Ok(rect)
+}
+
+const MARQUEE_PAUSE: f32 = 1.0; // seconds spent at each end
+const MARQUEE_SPEED: f32 = 40.0; // pixels per second
+
+/// How far a title `overflow` pixels too wide is scrolled `elapsed` after it started,
+/// going back and forth with a pause at each end.
+pub fn marquee_offset(overflow: u32, elapsed: Duration) -> i32 {
+    let travel = overflow as f32 / MARQUEE_SPEED;
+    let t = elapsed.as_secs_f32() % (2.0 * (MARQUEE_PAUSE + travel));
+    let position = if t < MARQUEE_PAUSE {
+        0.0
+    } else if t < MARQUEE_PAUSE + travel {
+        (t - MARQUEE_PAUSE) / travel
+    } else if t < 2.0 * MARQUEE_PAUSE + travel {
+        1.0
+    } else {
+        1.0 - (t - 2.0 * MARQUEE_PAUSE - travel) / travel
+    };
+    (position * overflow as f32) as i32
+}
+
+/// `text` shortened to fit `max_width`, ending in "..." when anything had to be cut.
+pub fn ellipsize(font: &Font, text: &str, max_width: u32) -> Result<String, String> {
+    if font.size_of(text)?.0 <= max_width {
+        return Ok(text.to_string());
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let shortened = |len: usize| format!("{}...", chars[..len].iter().collect::<String>().trim_end());
+    // Longest prefix that still fits, the width only grows with more characters
+    let (mut low, mut high) = (0, chars.len());
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if font.size_of(&shortened(mid))?.0 <= max_width {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(shortened(low))
+}
+
+pub const LIST_ROW_HEIGHT: i32 = 30;
+
+/// Clears the canvas for a full-screen view and draws its title.
+pub fn draw_screen_frame(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: Option<&Font>,
+    title: &str,
+) -> Result<(), String> {
+    canvas.set_draw_color(screen_color());
+    canvas.clear();
+    canvas.set_draw_color(accent_color());
+    canvas.fill_rect(Rect::new(30, 60, SCREEN_WIDTH - 60, 2))?;
+    if let Some(f) = font {
+        draw_text(canvas, texture_creator, f, title, 30, 20, accent_color())?;
+    }
+    Ok(())
+}
+
+/// Scrolling list of rows starting at `top`, keeping the selected row in view.
+pub fn draw_list(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: Option<&Font>,
+    rows: &[String],
+    selected: usize,
+    top: i32,
+    visible_rows: usize,
+) -> Result<(), String> {
+    let first = (selected + 1).saturating_sub(visible_rows);
+    for (slot, (idx, row)) in rows.iter().enumerate().skip(first).take(visible_rows).enumerate() {
+        let rect = Rect::new(30, top + slot as i32 * LIST_ROW_HEIGHT, SCREEN_WIDTH - 60, (LIST_ROW_HEIGHT - 2) as u32);
+        if idx == selected {
+            canvas.set_draw_color(highlight_color());
+            canvas.fill_rect(rect)?;
+        }
+        if let Some(f) = font {
+            draw_text(canvas, texture_creator, f, row, rect.x() + 8, rect.y(), text_color())?;
+        }
+    }
+    Ok(())
+}
+
+/// What a full-screen view asks the main loop to do after handling input.
+pub enum ViewAction {
+    None,
+    Close,
+    LibraryChanged,
+    HackInstalled(String, String, Option<u32>), // ROM file name, hack name, exit count
+    PreviewTheme(Box<Theme>),
+    ThemeSaved(String),
+    Rename(String, String, bool), // ROM file name, new name, rename the file on disk too
+    LauncherKeys(HashMap<String, String>), // action -> extra key name
+    LaunchWith(String, Box<Overrides>), // card, settings for this launch only
+    PinAccepted(Box<MenuAction>), // the action the PIN was asked for
+    TaskFinished(String), // background work is done, shown as a notification when the window is in the background
+    Restored(String), // an archive was restored over the data, which needs reloading
+    Choose(Box<MenuAction>), // run as if picked from a menu, the view stays open
+}
+
+/// A full-screen view that replaces the game grid until it is closed.
+pub trait View {
+    fn handle_event(&mut self, event: &Event) -> ViewAction;
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction;
+    /// Called once per frame, e.g. to pick up results from background work.
+    fn update(&mut self) -> ViewAction {
+        ViewAction::None
+    }
+    /// True to get key presses as typed, without the `launcher_keys` remapping.
+    fn wants_raw_keys(&self) -> bool {
+        false
+    }
+    /// The settings page this view is, so the main loop can rebuild its rows in place.
+    fn options(&mut self) -> Option<&mut OptionsScreen> {
+        None
+    }
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone)]
+pub enum MenuAction {
+    Dismiss,
+    Launch(String),
+    LaunchUnchecked(String),
+    TrustRom(String, u32), // ROM file, its CRC32
+    MoveGame(String, i32),
+    SetHidden(String, bool),
+    SetFavorite(String, bool),
+    SetCoverFromClipboard(String),
+    ResetCover(String),
+    ToggleShowHidden,
+    ToggleStreamerMode,
+    OpenCollections(String),
+    ToggleCollection(String, String),
+    OpenContent(String),
+    CycleAgeRating(String),
+    ToggleDescriptor(String, String),
+    OpenLauncherOptions,
+    OpenHackBrowser,
+    OpenPatchManager,
+    OpenThemeEditor,
+    OpenWidgets,
+    OpenStats,
+    ExportStats,
+    ExportFrontends,
+    OpenTrophies,
+    DismissNews(String),
+    ToggleWidget(Widget),
+    CycleWidgetCorner,
+    InstallThemeFromClipboard,
+    ImportCoverPack,
+    ImportOldSettings,
+    ActivateTheme(String),
+    OpenPluginSettings,
+    PluginSetting(usize, String), // plugin index, setting id
+
+    StripHeader(String),
+    SelectVariant(String, String),
+    OpenBackups,
+    RestoreBackup(String),
+    OpenPath(PathBuf),
+    RevealPath(PathBuf),
+    OpenFiles(String),
+    ConfirmDeleteRom(String),
+    DeleteRom(String),
+    ResetConfig,
+    OpenMaintenance,
+    BackupEverything,
+    OpenArchives,
+    RestoreArchive(PathBuf),
+    BuildFromSource,
+    PreviewGameIni(String),
+    OpenManual(String),
+    Rename(String),
+    OpenControllerSetup,
+    OpenQuickSettings,
+    OpenAudioLatency,
+    CycleSortMode(i32), // -1 or 1, the way the choice was stepped
+    CyclePalette(i32),
+    CycleLanguage(i32),
+    CyclePadBackQuit(i32),
+    CycleTransitionSpeed(i32),
+    CycleEasing(i32),
+    CycleCacheTtl(i32),
+    ResolveIni(bool), // true = smw.ini's values win
+    CyclePlaytimeLimit(i32),
+    CycleAgeLimit(i32),
+    EnterPin(Box<MenuAction>), // asks for playtime_pin, then carries out the action
+    ClearCache,
+    RestoreSave(String),
+    LaunchWithOptions(String),
+    ResumeState(String),
+    ToggleAutoCovers,
+    ToggleTaskNotifications,
+    ToggleFullscreenHandoff,
+    ToggleLowLatency,
+    CycleBatterySaver(i32),
+    CycleBatteryFps(i32),
+    SetSavesDir(Option<PathBuf>), // None = launcher/saves
+    SavesDirFromClipboard,
+    OpenSystemStatus,
+    OpenUpdates,
+    OpenLauncherKeys,
+    Retry(Subsystem),
+    TryAudioLatency(u8),
+    SaveAudioLatency(u8),
+    RevertAudioLatency,
+    OpenVariants(String),
+    OpenDisplayOverrides(String),
+    CycleIniOverride(String, String, i32), // ROM, smw.ini key, step
+    CycleMonitor(String, i32), // ROM, step
+    CycleWatchdog(String),
+    CyclePriority(String),
+    TogglePerformanceCores(String),
+    SafeRelaunch(String, usize), // ROM, index into SAFE_STEPS
+    Quit,
+}
+
+/// Modal overlay with a title, some message lines and a list of selectable entries.
+/// Used for dialogs and context menus alike.
+pub struct Menu {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub items: Vec<(String, MenuAction)>,
+    pub selected: usize,
+}
+
+impl Menu {
+    pub fn new(title: &str) -> Self {
+        Menu {
+            title: title.to_string(),
+            lines: Vec::new(),
+            items: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn line(mut self, text: impl Into<String>) -> Self {
+        self.lines.push(text.into());
+        self
+    }
+
+    pub fn item(mut self, label: &str, action: MenuAction) -> Self {
+        self.items.push((label.to_string(), action));
+        self
+    }
+
+    pub fn move_selection(&mut self, delta: i32) {
+        if self.items.is_empty() {
+            return;
+        }
+        let len = self.items.len() as i32;
+        self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    pub fn selected_action(&self) -> Option<MenuAction> {
+        self.items.get(self.selected).map(|(_, action)| action.clone())
+    }
+
+    fn panel_rect(&self) -> Rect {
+        // title, message lines, a spacer row, then the entries
+        let rows = 1 + self.lines.len() + 1 + self.items.len();
+        let height = (rows as i32 * LINE_HEIGHT + 20).min(SCREEN_HEIGHT as i32 - 20);
+        Rect::new(
+            (SCREEN_WIDTH as i32 - MENU_WIDTH as i32) / 2,
+            (SCREEN_HEIGHT as i32 - height) / 2,
+            MENU_WIDTH,
+            height as u32,
+        )
+    }
+
+    fn row_y(&self, row: usize) -> i32 {
+        self.panel_rect().y() + 10 + row as i32 * LINE_HEIGHT
+    }
+
+    pub fn item_rect(&self, idx: usize) -> Rect {
+        let panel = self.panel_rect();
+        let row = 2 + self.lines.len() + idx;
+        Rect::new(panel.x() + 20, self.row_y(row), MENU_WIDTH - 40, (LINE_HEIGHT - 4) as u32)
+    }
+
+    pub fn item_at(&self, x: i32, y: i32) -> Option<usize> {
+        (0..self.items.len()).find(|&idx| self.item_rect(idx).contains_point((x, y)))
+    }
+
+    pub fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        canvas.fill_rect(None)?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        let panel = self.panel_rect();
+        canvas.set_draw_color(panel_color());
+        canvas.fill_rect(panel)?;
+        canvas.set_draw_color(accent_color());
+        canvas.draw_rect(panel)?;
+
+        for (idx, _) in self.items.iter().enumerate() {
+            let rect = self.item_rect(idx);
+            if idx == self.selected {
+                canvas.set_draw_color(highlight_color());
+                canvas.fill_rect(rect)?;
+            }
+            canvas.set_draw_color(outline_color());
+            canvas.draw_rect(rect)?;
+        }
+
+        if let Some(f) = font {
+            let x = panel.x() + 20;
+            draw_text(canvas, texture_creator, f, &self.title, x, self.row_y(0), accent_color())?;
+            for (idx, line) in self.lines.iter().enumerate() {
+                draw_text(canvas, texture_creator, f, line, x, self.row_y(1 + idx), text_color())?;
+            }
+            for (idx, (label, _)) in self.items.iter().enumerate() {
+                let rect = self.item_rect(idx);
+                draw_text(canvas, texture_creator, f, label, rect.x() + 10, rect.y(), text_color())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const TOAST_TIME: Duration = Duration::from_secs(4);
+
+/// Short notices shown one at a time at the top of the screen. Suppressed in streamer mode.
+pub struct Toasts {
+    queue: VecDeque<String>,
+    shown_since: Option<Instant>,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Toasts {
+            queue: VecDeque::new(),
+            shown_since: None,
+        }
+    }
+
+    pub fn push(&mut self, text: impl Into<String>) {
+        if streamer::enabled() {
+            return;
+        }
+        self.queue.push_back(text.into());
+    }
+
+    pub fn is_showing(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
+    pub fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        if self.shown_since.map(|t| t.elapsed() >= TOAST_TIME).unwrap_or(false) {
+            self.queue.pop_front();
+            self.shown_since = None;
+        }
+        let (text, f) = match (self.queue.front(), font) {
+            (Some(text), Some(f)) => (text, f),
+            _ => return Ok(()),
+        };
+        let since = *self.shown_since.get_or_insert_with(Instant::now);
+
+        let (width, height) = f.size_of(text)?;
+        let panel = Rect::new(
+            (SCREEN_WIDTH as i32 - width as i32) / 2 - 16,
+            70,
+            width + 32,
+            height + 16,
+        );
+        // Fade out over the last half second
+        let remaining = TOAST_TIME.saturating_sub(since.elapsed()).as_secs_f32();
+        let alpha = (remaining / 0.5).min(1.0);
+        canvas.set_blend_mode(BlendMode::Blend);
+        let chrome = chrome();
+        let (panel_rgb, accent) = (chrome.panel, chrome.accent);
+        canvas.set_draw_color(Color::RGBA(panel_rgb.0, panel_rgb.1, panel_rgb.2, (230.0 * alpha) as u8));
+        canvas.fill_rect(panel)?;
+        canvas.set_draw_color(Color::RGBA(accent.0, accent.1, accent.2, (255.0 * alpha) as u8));
+        canvas.draw_rect(panel)?;
+        canvas.set_blend_mode(BlendMode::None);
+        draw_text(canvas, texture_creator, f, text, panel.x() + 16, panel.y() + 8, text_color())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+//! Component updates from `<update_server>/manifest.json`. The smw build, the shader pack, the
+//! launcher assets and the controller database each carry their own version, so an update only
+//! downloads the parts that changed. Installed versions are kept in `launcher/components.json`.
+//!
+//! The manifest is shaped `{"components": {"shaders": {"version": "3", "url": "packs/shaders.zip", "sha256": "..."}}}`.
+//! Relative URLs are taken from the update server, and `{platform}` becomes `<os>-<arch>` so one
+//! entry can point at every platform's smw build. Downloads must come over https and match their
+//! `sha256` before anything is written; the replaced game binary is kept as `<binary>.previous`
+//! so an update can be rolled back.
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::font::Font;
+use crate::ui::{self, View, ViewAction};
+use crate::{net, streamer, GamepadAction};
+
+/// Manifest keys and display names, in the order the Update screen lists them.
+pub const COMPONENTS: [(&str, &str); 4] = [
+    ("game", "Game binary"),
+    ("shaders", "Shader pack"),
+    ("assets", "Launcher assets"),
+    ("controllers", "Controller database"),
+];
+pub const CONTROLLER_DB: &str = "gamecontrollerdb.txt"; // in the launcher folder
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Remote {
+    pub version: String,
+    pub url: String,
+    #[serde(default)]
+    pub sha256: String, // hex; an entry without one is refused
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Manifest {
+    components: HashMap<String, Remote>,
+}
+
+/// Where each component goes.
+#[derive(Debug, Clone)]
+pub struct Targets {
+    pub game_executable: PathBuf,
+    pub game_dir: PathBuf,
+    pub launcher_dir: PathBuf,
+}
+
+impl Targets {
+    fn versions_path(&self) -> PathBuf {
+        self.launcher_dir.join("components.json")
+    }
+}
+
+pub fn installed_versions(launcher_dir: &Path) -> HashMap<String, String> {
+    fs::read_to_string(launcher_dir.join("components.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_versions(targets: &Targets, versions: &HashMap<String, String>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(versions).map_err(|e| e.to_string())?;
+    fs::write(targets.versions_path(), content).map_err(|e| e.to_string())
+}
+
+fn resolve_url(server: &str, url: &str) -> String {
+    let url = url.replace("{platform}", &format!("{}-{}", env::consts::OS, env::consts::ARCH));
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url
+    } else {
+        format!("{}/{}", server.trim_end_matches('/'), url.trim_start_matches('/'))
+    }
+}
+
+/// Where the game binary an update replaced is kept.
+fn previous_game(targets: &Targets) -> PathBuf {
+    PathBuf::from(format!("{}.previous", targets.game_executable.display()))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Downloads one component, checks it against the manifest and puts it in place.
+fn install(key: &str, url: &str, sha256: &str, targets: &Targets) -> Result<(), String> {
+    if !url.starts_with("https://") {
+        return Err(format!("refusing to download over plain http: {}", url));
+    }
+    if sha256.is_empty() {
+        return Err("the manifest gives no sha256 for it".to_string());
+    }
+    let data = net::download(url)?;
+    let actual = sha256_hex(&data);
+    if !actual.eq_ignore_ascii_case(sha256.trim()) {
+        return Err(format!("download doesn't match its sha256 (got {})", actual));
+    }
+    match key {
+        "game" => {
+            // Written beside the old binary first so a failed download never leaves half a game
+            let partial = targets.game_executable.with_extension("download");
+            fs::write(&partial, data).map_err(|e| e.to_string())?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&partial, fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+            }
+            let previous = previous_game(targets);
+            if targets.game_executable.exists() {
+                fs::rename(&targets.game_executable, &previous).map_err(|e| e.to_string())?;
+            }
+            fs::rename(&partial, &targets.game_executable).map_err(|e| {
+                // Put the old binary back rather than leave no game at all
+                let _ = fs::rename(&previous, &targets.game_executable);
+                e.to_string()
+            })
+        }
+        "shaders" => extract(&data, &targets.game_dir.join("shaders"), "shaders/"),
+        "assets" => extract(&data, &targets.launcher_dir, "launcher/"),
+        "controllers" => fs::write(targets.launcher_dir.join(CONTROLLER_DB), data).map_err(|e| e.to_string()),
+        other => Err(format!("Unknown component {}", other)),
+    }
+}
+
+/// Unpacks a zip over `dir`, dropping a leading `folder` the pack may have been zipped with.
+fn extract(archive: &[u8], dir: &Path, folder: &str) -> Result<(), String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| format!("Not a zip file: {}", e))?;
+    for idx in 0..zip.len() {
+        let mut file = zip.by_index(idx).map_err(|e| e.to_string())?;
+        // enclosed_name rejects entries that would escape the folder
+        let relative = match file.enclosed_name() {
+            Some(path) if !file.is_dir() => path.strip_prefix(folder).map(Path::to_path_buf).unwrap_or(path),
+            _ => continue,
+        };
+        let target = dir.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        fs::write(&target, data).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+enum UpdateMsg {
+    Manifest(Result<HashMap<String, Remote>, String>),
+    Started(&'static str),
+    Installed(&'static str, Result<String, String>), // the new version
+    Finished,
+}
+
+#[derive(Clone, PartialEq)]
+enum State {
+    Unknown,
+    UpToDate,
+    Available,
+    NotOffered, // the manifest has no entry for it
+    Updating,
+    Updated,
+    Failed(String),
+}
+
+/// The Update screen: installed and available version of every component, updating the changed ones.
+pub struct UpdateView {
+    server: String,
+    targets: Targets,
+    installed: HashMap<String, String>,
+    remote: HashMap<String, Remote>,
+    states: Vec<State>, // by COMPONENTS index
+    status: String,
+    rx: Option<Receiver<UpdateMsg>>,
+    game_running: bool, // the game binary can't be replaced while it runs
+}
+
+impl UpdateView {
+    pub fn new(server: &str, targets: Targets, game_running: bool) -> Self {
+        let mut view = UpdateView {
+            server: server.to_string(),
+            installed: installed_versions(&targets.launcher_dir),
+            targets,
+            remote: HashMap::new(),
+            states: vec![State::Unknown; COMPONENTS.len()],
+            status: String::new(),
+            rx: None,
+            game_running,
+        };
+        view.check();
+        view
+    }
+
+    fn check(&mut self) {
+        let url = format!("{}/manifest.json", self.server.trim_end_matches('/'));
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = net::get_json(&url)
+                .and_then(|json| serde_json::from_value::<Manifest>(json).map_err(|e| e.to_string()))
+                .map(|manifest| manifest.components);
+            let _ = tx.send(UpdateMsg::Manifest(result));
+        });
+        self.status = "Checking for updates...".to_string();
+        self.rx = Some(rx);
+    }
+
+    /// Components whose available version differs from the installed one.
+    fn changed(&self) -> Vec<(&'static str, Remote)> {
+        COMPONENTS
+            .iter()
+            .filter_map(|(key, _)| {
+                let remote = self.remote.get(*key)?;
+                (self.installed.get(*key) != Some(&remote.version)).then(|| (*key, remote.clone()))
+            })
+            .filter(|(key, _)| !(self.game_running && *key == "game"))
+            .collect()
+    }
+
+    fn update_changed(&mut self) {
+        if self.rx.is_some() {
+            return;
+        }
+        let changed = self.changed();
+        if changed.is_empty() {
+            self.status = "Nothing to update".to_string();
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        let (server, targets, mut versions) = (self.server.clone(), self.targets.clone(), self.installed.clone());
+        // Versions are saved here rather than by the view, which may be closed before this is done
+        thread::spawn(move || {
+            for (key, remote) in changed {
+                let _ = tx.send(UpdateMsg::Started(key));
+                let result = install(key, &resolve_url(&server, &remote.url), &remote.sha256, &targets).map(|_| remote.version);
+                if let Ok(version) = &result {
+                    versions.insert(key.to_string(), version.clone());
+                    if let Err(e) = save_versions(&targets, &versions) {
+                        eprintln!("Failed to save component versions: {}", e);
+                    }
+                }
+                let _ = tx.send(UpdateMsg::Installed(key, result));
+            }
+            let _ = tx.send(UpdateMsg::Finished);
+        });
+        self.status = "Updating...".to_string();
+        self.rx = Some(rx);
+    }
+
+    /// Swaps the game binary back to the one the last update replaced.
+    fn roll_back_game(&mut self) {
+        if self.rx.is_some() || self.game_running {
+            return;
+        }
+        let previous = previous_game(&self.targets);
+        if !previous.exists() {
+            self.status = "No previous game binary to go back to".to_string();
+            return;
+        }
+        let result = fs::rename(&previous, &self.targets.game_executable).map_err(|e| e.to_string()).and_then(|_| {
+            // The version it was isn't known any more, so the manifest's shows as available again
+            self.installed.remove("game");
+            save_versions(&self.targets, &self.installed)
+        });
+        match result {
+            Ok(()) => {
+                println!("Rolled the game binary back");
+                self.status = "Went back to the previous game binary".to_string();
+                self.refresh_states();
+            }
+            Err(e) => {
+                eprintln!("Failed to roll back the game binary: {}", e);
+                self.status = format!("Could not roll back: {}", streamer::scrub(&e));
+            }
+        }
+    }
+
+    fn set_state(&mut self, key: &str, state: State) {
+        if let Some(idx) = COMPONENTS.iter().position(|(k, _)| *k == key) {
+            self.states[idx] = state;
+        }
+    }
+
+    fn refresh_states(&mut self) {
+        for (idx, (key, _)) in COMPONENTS.iter().enumerate() {
+            self.states[idx] = match self.remote.get(*key) {
+                None => State::NotOffered,
+                Some(remote) if self.installed.get(*key) == Some(&remote.version) => State::UpToDate,
+                Some(_) => State::Available,
+            };
+        }
+    }
+
+    fn row(&self, idx: usize) -> String {
+        let (key, name) = COMPONENTS[idx];
+        let installed = self.installed.get(key).map(String::as_str).unwrap_or("none");
+        let available = self.remote.get(key).map(|r| r.version.as_str());
+        let state = match &self.states[idx] {
+            State::Unknown => "...".to_string(),
+            State::UpToDate => "up to date".to_string(),
+            State::Available if key == "game" && self.game_running => "waiting for the game to exit".to_string(),
+            State::Available => format!("{} available", available.unwrap_or("?")),
+            State::NotOffered => "not offered by this server".to_string(),
+            State::Updating => format!("updating to {}...", available.unwrap_or("?")),
+            State::Updated => "updated".to_string(),
+            State::Failed(e) => format!("failed: {}", streamer::scrub(e)),
+        };
+        format!("{}: {} installed, {}", name, installed, state)
+    }
+}
+
+impl View for UpdateView {
+    fn handle_event(&mut self, event: &Event) -> ViewAction {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return ViewAction::Close,
+            Event::KeyDown { keycode: Some(Keycode::Return), .. } => self.update_changed(),
+            Event::KeyDown { keycode: Some(Keycode::R), .. } if self.rx.is_none() => self.check(),
+            Event::KeyDown { keycode: Some(Keycode::P), .. } => self.roll_back_game(),
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn handle_gamepad(&mut self, action: &GamepadAction) -> ViewAction {
+        match action {
+            GamepadAction::Confirm => self.update_changed(),
+            GamepadAction::ContextMenu => self.roll_back_game(),
+            GamepadAction::Back => return ViewAction::Close,
+            _ => {}
+        }
+        ViewAction::None
+    }
+
+    fn update(&mut self) -> ViewAction {
+        let mut action = ViewAction::None;
+        while let Some(msg) = self.rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            match msg {
+                UpdateMsg::Manifest(Ok(components)) => {
+                    self.remote = components;
+                    self.refresh_states();
+                    self.status = match self.changed().len() {
+                        0 => "Everything is up to date".to_string(),
+                        n => format!("{} update(s) available", n),
+                    };
+                    self.rx = None;
+                }
+                UpdateMsg::Manifest(Err(e)) => {
+                    eprintln!("Failed to fetch the update manifest: {}", e);
+                    self.status = format!("Could not check for updates: {}", streamer::scrub(&e));
+                    self.rx = None;
+                }
+                UpdateMsg::Started(key) => self.set_state(key, State::Updating),
+                UpdateMsg::Installed(key, Ok(version)) => {
+                    println!("Updated {} to {}", key, version);
+                    self.installed.insert(key.to_string(), version);
+                    self.set_state(key, State::Updated);
+                }
+                UpdateMsg::Installed(key, Err(e)) => {
+                    eprintln!("Failed to update {}: {}", key, e);
+                    self.set_state(key, State::Failed(e));
+                }
+                UpdateMsg::Finished => {
+                    self.status = "Done. New assets and controller mappings apply after a restart".to_string();
+                    self.rx = None;
+                    let failed = self.states.iter().filter(|s| matches!(s, State::Failed(_))).count();
+                    action = ViewAction::TaskFinished(match failed {
+                        0 => "Updates downloaded".to_string(),
+                        n => format!("Updates finished, {} failed", n),
+                    });
+                }
+            }
+        }
+        action
+    }
+
+    fn draw(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &TextureCreator<WindowContext>,
+        font: Option<&Font>,
+    ) -> Result<(), String> {
+        ui::draw_screen_frame(canvas, texture_creator, font, "Updates")?;
+        if let Some(f) = font {
+            let text_color = ui::text_color();
+            for idx in 0..COMPONENTS.len() {
+                let color = match self.states[idx] {
+                    State::Available => ui::accent_color(),
+                    State::Failed(_) => Color::RGB(255, 110, 110),
+                    _ => text_color,
+                };
+                ui::draw_text(canvas, texture_creator, f, &self.row(idx), 30, 90 + idx as i32 * 36, color)?;
+            }
+            ui::draw_text(canvas, texture_creator, f, &self.status, 30, 580, ui::accent_color())?;
+            let help = if self.rx.is_some() {
+                "Esc: back"
+            } else {
+                "Enter: update changed   R: check again   P: previous game binary   Esc: back"
+            };
+            ui::draw_text(canvas, texture_creator, f, help, 30, 620, text_color)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! Background verification while the grid sits idle: every ROM is hashed again, even when its
+//! size and date say it hasn't changed, so a damaged file shows up as a checksum badge instead of
+//! as a crash, and the saves folder is checked for files that can't be read or came out empty.
+//! One file at a time on a worker, and a full pass at most once per `PASS_INTERVAL`.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::library::{HashPool, Hashed};
+
+const PASS_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+enum Job {
+    Rom(String),
+    Saves,
+}
+
+pub enum Outcome {
+    Rom(Hashed),
+    Saves(Vec<String>), // one line per damaged save, empty when all are fine
+}
+
+pub struct Verifier {
+    queue: VecDeque<Job>,
+    running: Option<Receiver<Outcome>>,
+    last_pass: Option<Instant>,
+}
+
+impl Verifier {
+    pub fn new() -> Self {
+        Verifier { queue: VecDeque::new(), running: None, last_pass: None }
+    }
+
+    /// True when no pass is queued and the last one is old enough for another.
+    pub fn due(&self) -> bool {
+        self.queue.is_empty() && self.last_pass.map(|t| t.elapsed() >= PASS_INTERVAL).unwrap_or(true)
+    }
+
+    /// Queues a pass over `roms` and the saves.
+    pub fn schedule(&mut self, roms: &[String]) {
+        self.last_pass = Some(Instant::now());
+        self.queue.extend(roms.iter().cloned().map(Job::Rom));
+        self.queue.push_back(Job::Saves);
+    }
+
+    /// Checks the saves again on the next idle frame, e.g. after the user dealt with a problem.
+    pub fn recheck_saves(&mut self) {
+        self.queue.push_front(Job::Saves);
+    }
+
+    /// Starts the next check unless one is still running. Only call while the grid is idle.
+    pub fn step(&mut self, sfc_dir: &Path, saves_dir: &Path) {
+        if self.running.is_some() {
+            return;
+        }
+        let Some(job) = self.queue.pop_front() else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        let (sfc_dir, saves_dir) = (sfc_dir.to_path_buf(), saves_dir.to_path_buf());
+        thread::spawn(move || {
+            let outcome = match job {
+                Job::Rom(file) => Outcome::Rom(HashPool::hash_now(&sfc_dir, &file)),
+                Job::Saves => Outcome::Saves(check_saves(&saves_dir)),
+            };
+            let _ = tx.send(outcome);
+        });
+        self.running = Some(rx);
+    }
+
+    pub fn poll(&mut self) -> Option<Outcome> {
+        let outcome = self.running.as_ref()?.try_recv().ok()?;
+        self.running = None;
+        Some(outcome)
+    }
+}
+
+fn check_saves(dir: &Path) -> Vec<String> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|e| e.path()).filter(|p| p.is_file()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    files.sort();
+    files
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            match fs::read(path) {
+                Ok(data) if data.is_empty() => Some(format!("{} is empty", name)),
+                Ok(_) => None,
+                Err(e) => Some(format!("{} can't be read ({})", name, e)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+//! Background that reacts to the launcher music, fed by an SDL_mixer post-mix callback.
+
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::os::raw::{c_int, c_void};
+use std::sync::Mutex;
+
+use crate::theme::{rgb, Theme};
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+pub const BANDS: usize = 32;
+const WAVE_POINTS: usize = 128;
+const WINDOW: usize = 512; // frames analysed per callback
+const SAMPLE_RATE: f32 = 44_100.0; // matches open_audio in main
+const DECAY: f32 = 0.85; // how fast bars fall back each frame
+
+#[derive(Clone, Copy)]
+struct Capture {
+    bands: [f32; BANDS],
+    wave: [f32; WAVE_POINTS],
+}
+
+static CAPTURE: Mutex<Capture> = Mutex::new(Capture {
+    bands: [0.0; BANDS],
+    wave: [0.0; WAVE_POINTS],
+});
+
+/// Starts or stops analysing the mixer output.
+pub fn set_enabled(enabled: bool) {
+    let callback: Option<unsafe extern "C" fn(*mut c_void, *mut u8, c_int)> =
+        if enabled { Some(postmix) } else { None };
+    unsafe { sdl2::sys::mixer::Mix_SetPostMix(callback, std::ptr::null_mut()) };
+}
+
+/// Runs on the audio thread with the final mix as interleaved signed 16-bit stereo.
+unsafe extern "C" fn postmix(_udata: *mut c_void, stream: *mut u8, len: c_int) {
+    if stream.is_null() || len <= 0 {
+        return;
+    }
+    let samples = std::slice::from_raw_parts(stream as *const i16, len as usize / 2);
+    let mono: Vec<f32> = samples
+        .chunks_exact(2)
+        .take(WINDOW)
+        .map(|frame| (frame[0] as f32 + frame[1] as f32) / (2.0 * i16::MAX as f32))
+        .collect();
+    if mono.is_empty() {
+        return;
+    }
+
+    let mut capture = Capture {
+        bands: [0.0; BANDS],
+        wave: [0.0; WAVE_POINTS],
+    };
+    for (idx, point) in capture.wave.iter_mut().enumerate() {
+        *point = mono[idx * mono.len() / WAVE_POINTS];
+    }
+    // A plain DFT at log-spaced frequencies (60 Hz to 10 kHz) is cheap enough for 32 bands
+    for (band, level) in capture.bands.iter_mut().enumerate() {
+        let freq = 60.0 * (10_000.0f32 / 60.0).powf(band as f32 / (BANDS - 1) as f32);
+        let step = 2.0 * std::f32::consts::PI * freq / SAMPLE_RATE;
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (n, sample) in mono.iter().enumerate() {
+            let angle = step * n as f32;
+            re += sample * angle.cos();
+            im -= sample * angle.sin();
+        }
+        *level = ((re * re + im * im).sqrt() / mono.len() as f32 * 8.0).min(1.0);
+    }
+
+    // Never block the audio thread; a skipped update is invisible
+    if let Ok(mut shared) = CAPTURE.try_lock() {
+        *shared = capture;
+    }
+}
+
+/// Smoothed levels drawn each frame.
+pub struct Visualizer {
+    bars: [f32; BANDS],
+}
+
+impl Visualizer {
+    pub fn new() -> Self {
+        Visualizer { bars: [0.0; BANDS] }
+    }
+
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, theme: &Theme) -> Result<(), String> {
+        let capture = match CAPTURE.lock() {
+            Ok(capture) => *capture,
+            Err(_) => return Ok(()),
+        };
+        for (bar, level) in self.bars.iter_mut().zip(capture.bands) {
+            *bar = level.max(*bar * DECAY);
+        }
+
+        canvas.set_draw_color(rgb(theme.visualizer_color));
+        let (width, height) = (SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32);
+        if theme.visualizer_style == 1 {
+            let points: Vec<Point> = capture
+                .wave
+                .iter()
+                .enumerate()
+                .map(|(idx, sample)| {
+                    let x = idx as i32 * width / (WAVE_POINTS as i32 - 1);
+                    Point::new(x, height / 2 - (sample * height as f32 / 3.0) as i32)
+                })
+                .collect();
+            canvas.draw_lines(points.as_slice())?;
+        } else {
+            let bar_width = width / BANDS as i32;
+            for (idx, level) in self.bars.iter().enumerate() {
+                let bar_height = (level * height as f32 * 0.8) as i32;
+                if bar_height > 0 {
+                    canvas.fill_rect(Rect::new(
+                        idx as i32 * bar_width + 2,
+                        height - bar_height,
+                        (bar_width - 4).max(1) as u32,
+                        bar_height as u32,
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::Local;
+use sdl2::pixels::Color;
+use sdl2::render::{Canvas, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use serde::{Deserialize, Serialize};
+use std::os::raw::c_int;
+use std::time::{Duration, Instant};
+
+use crate::font::Font;
+use crate::stats::format_duration;
+use crate::ui;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const BATTERY_POLL: Duration = Duration::from_secs(30);
+const MARGIN: i32 = 20;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Widget {
+    Clock,
+    PlaytimeToday,
+    Battery,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub fn next(self) -> Corner {
+        match self {
+            Corner::TopLeft => Corner::TopRight,
+            Corner::TopRight => Corner::BottomRight,
+            Corner::BottomRight => Corner::BottomLeft,
+            Corner::BottomLeft => Corner::TopLeft,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top left",
+            Corner::TopRight => "top right",
+            Corner::BottomLeft => "bottom left",
+            Corner::BottomRight => "bottom right",
+        }
+    }
+}
+
+/// Which corner widgets are shown and where.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WidgetOptions {
+    pub clock: bool,
+    pub playtime_today: bool,
+    pub battery: bool,
+    pub corner: Corner,
+}
+
+impl Default for WidgetOptions {
+    fn default() -> Self {
+        WidgetOptions {
+            clock: true,
+            playtime_today: false,
+            battery: true,
+            corner: Corner::TopRight,
+        }
+    }
+}
+
+impl WidgetOptions {
+    pub fn toggle(&mut self, widget: Widget) {
+        match widget {
+            Widget::Clock => self.clock = !self.clock,
+            Widget::PlaytimeToday => self.playtime_today = !self.playtime_today,
+            Widget::Battery => self.battery = !self.battery,
+        }
+    }
+
+    pub fn enabled(&self, widget: Widget) -> bool {
+        match widget {
+            Widget::Clock => self.clock,
+            Widget::PlaytimeToday => self.playtime_today,
+            Widget::Battery => self.battery,
+        }
+    }
+}
+
+/// Battery charge from SDL, polled rarely since some platforms read it from disk.
+pub struct BatteryMonitor {
+    percent: Option<i32>,
+    charging: bool,
+    last_poll: Option<Instant>,
+}
+
+impl BatteryMonitor {
+    pub fn new() -> Self {
+        BatteryMonitor {
+            percent: None,
+            charging: false,
+            last_poll: None,
+        }
+    }
+
+    /// None on machines without a battery.
+    pub fn status(&mut self) -> Option<(i32, bool)> {
+        if self.last_poll.map(|t| t.elapsed() >= BATTERY_POLL).unwrap_or(true) {
+            use sdl2::sys::SDL_PowerState::*;
+            let (mut secs, mut percent): (c_int, c_int) = (-1, -1);
+            let state = unsafe { sdl2::sys::SDL_GetPowerInfo(&mut secs, &mut percent) };
+            self.percent = match state {
+                SDL_POWERSTATE_ON_BATTERY | SDL_POWERSTATE_CHARGING | SDL_POWERSTATE_CHARGED if percent >= 0 => {
+                    Some(percent)
+                }
+                _ => None,
+            };
+            self.charging = matches!(state, SDL_POWERSTATE_CHARGING | SDL_POWERSTATE_CHARGED);
+            self.last_poll = Some(Instant::now());
+        }
+        self.percent.map(|p| (p, self.charging))
+    }
+
+    /// Running on battery with `threshold` percent or less left; a threshold of 0 never counts.
+    pub fn below(&mut self, threshold: u8) -> bool {
+        threshold > 0 && self.status().map(|(percent, charging)| !charging && percent <= threshold as i32).unwrap_or(false)
+    }
+}
+
+pub fn lines(options: &WidgetOptions, playtime_today: u64, battery: &mut BatteryMonitor) -> Vec<String> {
+    let mut lines = Vec::new();
+    if options.clock {
+        lines.push(Local::now().format("%H:%M").to_string());
+    }
+    if options.playtime_today {
+        lines.push(format!("Today: {}", format_duration(playtime_today)));
+    }
+    if options.battery {
+        if let Some((percent, charging)) = battery.status() {
+            lines.push(format!("Battery {}%{}", percent, if charging { " +" } else { "" }));
+        }
+    }
+    lines
+}
+
+/// Stacks the widget lines in the configured corner.
+pub fn draw(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    corner: Corner,
+    lines: &[String],
+) -> Result<(), String> {
+    let height = lines.len() as i32 * ui::LIST_ROW_HEIGHT;
+    let top = match corner {
+        Corner::TopLeft | Corner::TopRight => MARGIN,
+        Corner::BottomLeft | Corner::BottomRight => SCREEN_HEIGHT as i32 - MARGIN - height,
+    };
+    for (idx, line) in lines.iter().enumerate() {
+        let y = top + idx as i32 * ui::LIST_ROW_HEIGHT;
+        let x = match corner {
+            Corner::TopLeft | Corner::BottomLeft => MARGIN,
+            Corner::TopRight | Corner::BottomRight => {
+                let (width, _) = font.size_of(line)?;
+                SCREEN_WIDTH as i32 - MARGIN - width as i32
+            }
+        };
+        ui::draw_text(canvas, texture_creator, font, line, x, y, Color::RGB(255, 255, 255))?;
+    }
+    Ok(())
+}